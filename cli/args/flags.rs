@@ -38,6 +38,39 @@ pub struct FileFlags {
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct AddFlags {
   pub packages: Vec<String>,
+  /// Add the packages as dev-only dependencies, which aren't published.
+  pub dev: bool,
+  /// The file the packages are added to, which is detected when not set.
+  pub target: Option<AddTarget>,
+  /// The range the versions are recorded with, which is the one of the
+  /// configuration file when not set.
+  pub version_range: Option<AddVersionRange>,
+  /// The key the package is added under instead of its name, eg. `react`
+  /// for `npm:preact`.
+  pub alias: Option<String>,
+  /// Select the versions of the jsr packages from their recent versions
+  /// when there is a terminal, instead of adding the latest ones.
+  pub interactive: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AddTarget {
+  /// The `"imports"` of the configuration file.
+  Deno,
+  /// The `"dependencies"` of the `package.json` file.
+  PackageJson,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddVersionRange {
+  /// `^1.2.3`, which allows the compatible versions.
+  #[default]
+  Caret,
+  /// `~1.2.3`, which only allows the patch versions.
+  Tilde,
+  /// `1.2.3`.
+  Exact,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -214,6 +247,14 @@ impl LintFlags {
   }
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OutdatedFlags {
+  /// Update the dependencies instead of listing them, with `deno update`.
+  pub update: bool,
+  /// Update to the latest versions, even outside of the version constraints.
+  pub latest: bool,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReplFlags {
   pub eval_files: Option<Vec<String>>,
@@ -302,12 +343,195 @@ pub struct VendorFlags {
   pub force: bool,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum PublishDiagnosticsFormat {
+  #[default]
+  Pretty,
+  Short,
+}
+
+/// An environment that the public API of the published packages is type
+/// checked in, with `deno publish --check-lib`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum PublishCheckLib {
+  /// Deno's web workers.
+  Worker,
+  /// The globals that Node.js shares with browsers, without the `Deno`
+  /// namespace.
+  Node,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PublishFlags {
   pub token: Option<String>,
   pub dry_run: bool,
   pub allow_slow_types: bool,
   pub no_provenance: bool,
+  pub require_provenance: bool,
+  pub provenance_defer: Option<PathBuf>,
+  pub sbom: Option<PathBuf>,
+  pub auth_info: bool,
+  pub json: bool,
+  pub create: bool,
+  pub wait_for_available: bool,
+  pub trace_registry: bool,
+  pub trace_registry_file: Option<PathBuf>,
+  pub fix: bool,
+  pub explain: Option<String>,
+  pub baseline: Option<PathBuf>,
+  pub write_baseline: bool,
+  pub max_diagnostics: Option<usize>,
+  pub report_file: Option<PathBuf>,
+  pub deny_warnings: bool,
+  pub diagnostics_format: PublishDiagnosticsFormat,
+  /// Bump the versions of the workspace members before publishing.
+  pub bump: Option<VersionIncrement>,
+  pub changed_only: bool,
+  /// The names of the workspace members to publish, all of them when empty.
+  pub filter: Vec<String>,
+  pub include_dependencies: bool,
+  /// The libraries to type check the public API with, besides the ones of
+  /// Deno's main thread.
+  pub check_libs: Vec<PublishCheckLib>,
+  /// Print the fast check modules of the packages, which are their public
+  /// API as users and the documentation see it.
+  pub show_fast_check: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachProvenanceFlags {
+  pub bundle: PathBuf,
+  pub package: String,
+  pub attestations: Option<PathBuf>,
+  pub token: Option<String>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryVerifyFlags {
+  pub package: String,
+  pub path: Option<PathBuf>,
+  pub bundle: Option<PathBuf>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryDeleteFlags {
+  pub package: String,
+  pub token: Option<String>,
+  pub yes: bool,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryInfoFlags {
+  pub package: String,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryOpenFlags {
+  pub package: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryPingFlags {
+  pub token: Option<String>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryProvenanceFlags {
+  pub package: String,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistrySearchFlags {
+  pub query: String,
+  pub limit: Option<usize>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryScopeConfigFlags {
+  pub scope: String,
+  pub token: Option<String>,
+  pub require_publishing_from_ci: Option<bool>,
+  pub verify_github_actions_actor: Option<bool>,
+  pub package: Option<String>,
+  pub github_repository: Option<String>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryScopesFlags {
+  pub token: Option<String>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryAuditLogFlags {
+  pub scope: String,
+  pub token: Option<String>,
+  pub package: Option<String>,
+  pub since: Option<String>,
+  pub until: Option<String>,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryStatsFlags {
+  pub package: String,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryStatusFlags {
+  pub task_id: String,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RegistrySubcommand {
+  AttachProvenance(AttachProvenanceFlags),
+  AuditLog(RegistryAuditLogFlags),
+  Delete(RegistryDeleteFlags),
+  Info(RegistryInfoFlags),
+  Open(RegistryOpenFlags),
+  Ping(RegistryPingFlags),
+  Provenance(RegistryProvenanceFlags),
+  ScopeConfig(RegistryScopeConfigFlags),
+  Scopes(RegistryScopesFlags),
+  Search(RegistrySearchFlags),
+  Stats(RegistryStatsFlags),
+  Status(RegistryStatusFlags),
+  Verify(RegistryVerifyFlags),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VersionIncrement {
+  Patch,
+  Minor,
+  Major,
+  /// The increment of each member according to the conventional commit
+  /// messages that touched it since its last release.
+  Auto,
+  /// A version to set, validated when it's applied.
+  Exact(String),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionBumpFlags {
+  pub increment: VersionIncrement,
+  /// The workspace members to bump, all of them when empty.
+  pub packages: Vec<String>,
+  pub dry_run: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VersionSubcommand {
+  Bump(VersionBumpFlags),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -330,6 +554,7 @@ pub enum DenoSubcommand {
   Uninstall(UninstallFlags),
   Lsp,
   Lint(LintFlags),
+  Outdated(OutdatedFlags),
   Repl(ReplFlags),
   Run(RunFlags),
   Task(TaskFlags),
@@ -338,6 +563,8 @@ pub enum DenoSubcommand {
   Upgrade(UpgradeFlags),
   Vendor(VendorFlags),
   Publish(PublishFlags),
+  Registry(RegistrySubcommand),
+  Version(VersionSubcommand),
 }
 
 impl DenoSubcommand {
@@ -763,12 +990,11 @@ impl Flags {
           .ok()
       }
       Task(_) | Check(_) | Coverage(_) | Cache(_) | Info(_) | Eval(_)
-      | Test(_) | Bench(_) | Repl(_) | Compile(_) | Publish(_) => {
-        std::env::current_dir().ok()
-      }
-      Add(_) | Bundle(_) | Completions(_) | Doc(_) | Fmt(_) | Init(_)
-      | Install(_) | Uninstall(_) | Jupyter(_) | Lsp | Lint(_) | Types
-      | Upgrade(_) | Vendor(_) => None,
+      | Test(_) | Bench(_) | Repl(_) | Compile(_) | Publish(_) | Add(_)
+      | Outdated(_) => std::env::current_dir().ok(),
+      Bundle(_) | Completions(_) | Doc(_) | Fmt(_) | Init(_) | Install(_)
+      | Uninstall(_) | Jupyter(_) | Lsp | Lint(_) | Types | Upgrade(_)
+      | Vendor(_) | Registry(_) | Version(_) => None,
     }
   }
 
@@ -955,6 +1181,10 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
       "upgrade" => upgrade_parse(&mut flags, &mut m),
       "vendor" => vendor_parse(&mut flags, &mut m),
       "publish" => publish_parse(&mut flags, &mut m),
+      "registry" => registry_parse(&mut flags, &mut m),
+      "version" => version_parse(&mut flags, &mut m),
+      "outdated" => outdated_parse(&mut flags, &mut m, false),
+      "update" => outdated_parse(&mut flags, &mut m, true),
       _ => unreachable!(),
     }
   } else {
@@ -1103,13 +1333,17 @@ fn clap_root() -> Command {
         .subcommand(uninstall_subcommand())
         .subcommand(lsp_subcommand())
         .subcommand(lint_subcommand())
+        .subcommand(outdated_subcommand())
         .subcommand(publish_subcommand())
+        .subcommand(registry_subcommand())
         .subcommand(repl_subcommand())
         .subcommand(task_subcommand())
         .subcommand(test_subcommand())
         .subcommand(types_subcommand())
+        .subcommand(update_subcommand())
         .subcommand(upgrade_subcommand())
         .subcommand(vendor_subcommand())
+        .subcommand(version_subcommand())
     })
     .long_about(DENO_HELP)
     .after_help(ENV_VARIABLES_HELP)
@@ -1126,16 +1360,85 @@ fn add_subcommand() -> Command {
 You can add multiple dependencies at once:
 
   deno add @std/path @std/assert
+
+Dependencies that are only used by tests and other local tooling are added
+with --dev. They are resolved like the other imports, but the published
+packages can't import them:
+
+  deno add --dev @std/assert
+
+npm packages are added to the \"dependencies\" of the package.json file when
+there is one, and to the configuration file otherwise. Use --target to choose
+the file:
+
+  deno add --target deno npm:express
+
+The versions are added with a caret range, eg. ^1.2.3. Use --tilde for a tilde
+range, eg. ~1.2.3, or --exact for the exact version, or set the default range
+with \"add.versionRange\" in the configuration file:
+
+  deno add --exact @std/path
+
+A version constraint selects the latest version that it allows, and is added
+as it is. Use --alias to add a package under another name, eg. for the imports
+of a compatible package:
+
+  deno add npm:preact@10 --alias react
+
+Use --interactive to select the versions of the jsr packages that are added
+without a version constraint from their recent versions:
+
+  deno add --interactive @std/path
 ",
     )
     .defer(|cmd| {
-      cmd.arg(
-        Arg::new("packages")
-          .help("List of packages to add")
-          .required(true)
-          .num_args(1..)
-          .action(ArgAction::Append),
-      )
+      cmd
+        .arg(
+          Arg::new("packages")
+            .help("List of packages to add")
+            .required(true)
+            .num_args(1..)
+            .action(ArgAction::Append),
+        )
+        .arg(
+          Arg::new("dev")
+            .long("dev")
+            .short('D')
+            .help("Add the packages as dev-only dependencies, in \"publish.devImports\"")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+          Arg::new("target")
+            .long("target")
+            .help("The file to add the packages to")
+            .value_parser(["deno", "package-json"]),
+        )
+        .arg(
+          Arg::new("exact")
+            .long("exact")
+            .help("Add the exact versions instead of a caret range")
+            .conflicts_with("tilde")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+          Arg::new("tilde")
+            .long("tilde")
+            .help("Add the versions with a tilde range instead of a caret range")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+          Arg::new("alias")
+            .long("alias")
+            .value_name("NAME")
+            .help("Add the package under another name than its own"),
+        )
+        .arg(
+          Arg::new("interactive")
+            .long("interactive")
+            .short('i')
+            .help("Select the versions of the jsr packages to add from their recent versions")
+            .action(ArgAction::SetTrue),
+        )
     })
 }
 
@@ -2435,232 +2738,933 @@ fn publish_subcommand() -> Command {
           .help("Allow publishing with slow types")
           .action(ArgAction::SetTrue),
       )
+      .arg(
+        Arg::new("check-lib")
+          .long("check-lib")
+          .help("Also type check the public API with the libraries of another environment, so its types work for users there, can be repeated")
+          .value_name("LIB")
+          .value_parser(["worker", "node"])
+          .action(ArgAction::Append),
+      )
+      .arg(
+        Arg::new("show-fast-check")
+          .long("show-fast-check")
+          .help("Print the public API of the packages as users and the documentation see it, to review what the fast check types widened or dropped")
+          .conflicts_with("allow-slow-types")
+          .action(ArgAction::SetTrue),
+      )
       .arg(
         Arg::new("no-provenance")
           .long("no-provenance")
           .help("Disable provenance attestation. Enabled by default on Github actions, publicly links the package to where it was built and published from.")
           .action(ArgAction::SetTrue)
       )
+      .arg(
+        Arg::new("require-provenance")
+          .long("require-provenance")
+          .help("Fail when the provenance of a published package can't be generated or submitted, instead of printing a warning")
+          .action(ArgAction::SetTrue)
+          .conflicts_with("no-provenance")
+      )
+      .arg(
+        Arg::new("provenance-defer")
+          .long("provenance-defer")
+          .help("Write the provenance bundles to a directory instead of submitting them, to attach them later with `deno registry attach-provenance`")
+          .value_name("DIR")
+          .num_args(0..=1)
+          .default_missing_value(".")
+          .require_equals(true)
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::DirPath)
+          .conflicts_with("no-provenance")
+      )
+      .arg(
+        Arg::new("sbom")
+          .long("sbom")
+          .help("Write a CycloneDX SBOM of the dependencies of each package to a directory")
+          .value_name("DIR")
+          .num_args(0..=1)
+          .default_missing_value(".")
+          .require_equals(true)
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::DirPath)
+      )
+      .arg(
+        Arg::new("auth-info")
+          .long("auth-info")
+          .help("Print which authentication method and permissions would be used for publishing, without publishing")
+          .action(ArgAction::SetTrue)
+      )
+      .arg(
+        Arg::new("json")
+          .long("json")
+          .help("Print the published packages, their provenance and the counts of publish diagnostics as JSON. The progress is printed to stderr")
+          .action(ArgAction::SetTrue)
+      )
+      .arg(
+        Arg::new("create")
+          .long("create")
+          .help("Create the scopes and packages that don't exist yet instead of asking to create them in the browser. The owner of the token must be an admin of the scopes")
+          .action(ArgAction::SetTrue)
+          .requires("token")
+      )
+      .arg(
+        Arg::new("wait-for-available")
+          .long("wait-for-available")
+          .help("After publishing, wait until the new versions are listed in the package metadata served by the registry, so they can be added or imported right away")
+          .action(ArgAction::SetTrue)
+      )
+      .arg(
+        Arg::new("fix")
+          .long("fix")
+          .help("Instead of publishing, rewrite the imports that publishing would rewrite in the source files, eg. add the extension to sloppy imports, prefix bare Node.js built-ins with `node:`, replace mapped bare specifiers with what they map to and import other workspace members with `jsr:` specifiers")
+          .action(ArgAction::SetTrue)
+          .conflicts_with("dry-run")
+      )
+      .arg(
+        Arg::new("explain")
+          .long("explain")
+          .help("Print what a publish diagnostic code means and how to fix it, eg. `deno publish --explain invalid-external-import`")
+          .value_name("CODE")
+      )
+      .arg(
+        Arg::new("baseline")
+          .long("baseline")
+          .help("Accept the publish diagnostics recorded in a baseline file, so that only new diagnostics fail publishing")
+          .value_name("FILE")
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::FilePath)
+      )
+      .arg(
+        Arg::new("write-baseline")
+          .long("write-baseline")
+          .help("Instead of publishing, record the current publish diagnostics in the file passed to --baseline")
+          .action(ArgAction::SetTrue)
+          .requires("baseline")
+      )
+      .arg(
+        Arg::new("max-diagnostics")
+          .long("max-diagnostics")
+          .help("Only print the first N publish diagnostics, followed by the counts of the others by code and file")
+          .value_name("N")
+          .value_parser(value_parser!(usize))
+      )
+      .arg(
+        Arg::new("report-file")
+          .long("report-file")
+          .help("Write all publish diagnostics to a file as JSON")
+          .value_name("FILE")
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::FilePath)
+      )
+      .arg(
+        Arg::new("diagnostics-format")
+          .long("diagnostics-format")
+          .help("How publish diagnostics are printed. 'short' prints one 'file:line:col level[code] message' line per diagnostic, without code frames")
+          .value_name("FORMAT")
+          .require_equals(true)
+          .value_parser(["pretty", "short"])
+      )
+      .arg(
+        Arg::new("deny-warnings")
+          .long("deny-warnings")
+          .help("Fail publishing when there are publish diagnostics with the warning level too, not only errors")
+          .action(ArgAction::SetTrue)
+      )
+      .arg(
+        Arg::new("bump")
+          .long("bump")
+          .help("Bump the versions of the workspace members before publishing, like 'deno version bump'. 'auto' derives the increment of each member from the conventional commit messages since its last release")
+          .value_name("INCREMENT")
+          .value_parser(["patch", "minor", "major", "auto"])
+      )
+      .arg(
+        Arg::new("changed-only")
+          .long("changed-only")
+          .help("Skip the workspace members whose files are the same as in their latest published version, apart from the configuration file")
+          .action(ArgAction::SetTrue)
+      )
+      .arg(
+        Arg::new("filter")
+          .long("filter")
+          .help("Only publish this workspace member, can be repeated")
+          .value_name("NAME")
+          .action(ArgAction::Append)
+      )
+      .arg(
+        Arg::new("include-dependencies")
+          .long("include-dependencies")
+          .help("Also publish the workspace members that the members selected with --filter depend on, so that the versions they import exist")
+          .action(ArgAction::SetTrue)
+          .requires("filter")
+      )
+      .arg(
+        Arg::new("trace-registry")
+          .long("trace-registry")
+          .help("Print every request to the registry with its status, timing and request ids to stderr, with credentials redacted. Also writes them as JSON lines to a file if one is passed, to attach to bug reports")
+          .value_name("FILE")
+          .num_args(0..=1)
+          .require_equals(true)
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::FilePath)
+      )
       .arg(check_arg(/* type checks by default */ true))
       .arg(no_check_arg())
     })
 }
 
-fn compile_args(app: Command) -> Command {
-  compile_args_without_check_args(app.arg(no_check_arg()))
-}
-
-fn compile_args_without_check_args(app: Command) -> Command {
-  app
-    .arg(import_map_arg())
-    .arg(no_remote_arg())
-    .arg(no_npm_arg())
-    .arg(node_modules_dir_arg())
-    .arg(vendor_arg())
-    .arg(config_arg())
-    .arg(no_config_arg())
-    .arg(reload_arg())
-    .arg(lock_arg())
-    .arg(lock_write_arg())
-    .arg(no_lock_arg())
-    .arg(ca_file_arg())
-}
+fn registry_subcommand() -> Command {
+  Command::new("registry")
+    .hide(true)
+    .about("Unstable preview feature: Interact with the package registry")
+    .subcommand_required(true)
+    .arg_required_else_help(true)
+    .subcommand(
+      Command::new("attach-provenance")
+        .about("Submit a provenance bundle written by `deno publish --provenance-defer`")
+        .long_about(
+          "Submit a provenance bundle written by `deno publish --provenance-defer`
+for an already published package version.
+
+  deno registry attach-provenance foo_bar_1.0.0.provenance.json @foo/bar@1.0.0
+
+Submit the additional attestations that were written along with the bundle:
+
+  deno registry attach-provenance foo_bar_1.0.0.provenance.json @foo/bar@1.0.0 \\
+    --attestations foo_bar_1.0.0.attestations.json",
+        )
+        .arg(
+          Arg::new("bundle")
+            .help("Path to the provenance bundle")
+            .required(true)
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package version the bundle is for, eg. @foo/bar@1.0.0")
+            .required(true),
+        )
+        .arg(
+          Arg::new("attestations")
+            .long("attestations")
+            .help("Path to the attestations written along with the bundle")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token to use when submitting the bundle"),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the result in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("audit-log")
+        .about("Show the recent audit events of a scope")
+        .long_about(
+          "Show the recent audit events of a scope, like published versions, created
+tokens and changed members. Requires a token of a member of the scope.
 
-static ALLOW_READ_HELP: &str = concat!(
-  "Allow file system read access. Optionally specify allowed paths.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-read\n",
-  "  --allow-read=\"/etc,/var/log.txt\""
-);
+  deno registry audit-log @foo --token <token>
 
-static DENY_READ_HELP: &str = concat!(
-  "Deny file system read access. Optionally specify denied paths.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-read\n",
-  "  --deny-read=\"/etc,/var/log.txt\""
-);
+Only show the events of a package in a time range:
 
-static ALLOW_WRITE_HELP: &str = concat!(
-  "Allow file system write access. Optionally specify allowed paths.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-write\n",
-  "  --allow-write=\"/etc,/var/log.txt\""
-);
+  deno registry audit-log @foo --package bar --since 2024-01-01 --until 2024-02-01",
+        )
+        .arg(
+          Arg::new("scope")
+            .help("The scope to show the events of, eg. @foo")
+            .required(true),
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token of a member of the scope"),
+        )
+        .arg(
+          Arg::new("package")
+            .long("package")
+            .help("Only show the events of this package"),
+        )
+        .arg(
+          Arg::new("since")
+            .long("since")
+            .help("Only show the events at or after this date or RFC 3339 timestamp"),
+        )
+        .arg(
+          Arg::new("until")
+            .long("until")
+            .help("Only show the events before this date or RFC 3339 timestamp"),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the events in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("delete")
+        .about("Delete a recently published version")
+        .long_about(
+          "Delete a version of a package, which the registry only allows shortly
+after it was published. Older versions can only be yanked. Asks for
+confirmation unless `--yes` is passed.
+
+  deno registry delete @foo/bar@1.0.0 --token <token>",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package version to delete, eg. @foo/bar@1.0.0")
+            .required(true),
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token of a member of the scope"),
+        )
+        .arg(
+          Arg::new("yes")
+            .long("yes")
+            .short('y')
+            .help("Delete the version without asking for confirmation")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the result in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("info")
+        .about("Show information about a package in the registry")
+        .long_about(
+          "Show the description, versions and dependencies of a package in the registry.
 
-static DENY_WRITE_HELP: &str = concat!(
-  "Deny file system write access. Optionally specify denied paths.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-write\n",
-  "  --deny-write=\"/etc,/var/log.txt\""
-);
+  deno registry info @foo/bar
 
-static ALLOW_NET_HELP: &str = concat!(
-  "Allow network access. Optionally specify allowed IP addresses and host names, with ports as necessary.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-net\n",
-  "  --allow-net=\"localhost:8080,deno.land\""
-);
+Print the information as JSON:
 
-static DENY_NET_HELP: &str = concat!(
-  "Deny network access. Optionally specify denied IP addresses and host names, with ports as necessary.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-net\n",
-  "  --deny-net=\"localhost:8080,deno.land\""
-);
+  deno registry info @foo/bar --json",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package to show, eg. @foo/bar")
+            .required(true),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the information in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("open")
+        .about("Open a package on the registry website")
+        .long_about(
+          "Open the page of a package or of one of its versions on the registry
+website in the default browser.
 
-static ALLOW_ENV_HELP: &str = concat!(
-  "Allow access to system environment information. Optionally specify accessible environment variables.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-env\n",
-  "  --allow-env=\"PORT,HOME,PATH\""
-);
+  deno registry open @foo/bar@1.0.0
 
-static DENY_ENV_HELP: &str = concat!(
-  "Deny access to system environment information. Optionally specify accessible environment variables.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-env\n",
-  "  --deny-env=\"PORT,HOME,PATH\""
-);
+Without a package, opens the package of the configuration file in the
+current directory:
 
-static ALLOW_SYS_HELP: &str = concat!(
-  "Allow access to OS information. Optionally allow specific APIs by function name.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-sys\n",
-  "  --allow-sys=\"systemMemoryInfo,osRelease\""
-);
+  deno registry open",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package to open, eg. @foo/bar or @foo/bar@1.0.0"),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("ping")
+        .about("Check that the registry is reachable")
+        .long_about(
+          "Check that the API and the module endpoints of the registry are
+reachable, and how long they take to respond. Prints the server and rate
+limits the registry advertises.
 
-static DENY_SYS_HELP: &str = concat!(
-  "Deny access to OS information. Optionally deny specific APIs by function name.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-sys\n",
-  "  --deny-sys=\"systemMemoryInfo,osRelease\""
-);
+  deno registry ping
 
-static ALLOW_RUN_HELP: &str = concat!(
-  "Allow running subprocesses. Optionally specify allowed runnable program names.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-run\n",
-  "  --allow-run=\"whoami,ps\""
-);
+Also check that the registry accepts a token:
 
-static DENY_RUN_HELP: &str = concat!(
-  "Deny running subprocesses. Optionally specify denied runnable program names.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-run\n",
-  "  --deny-run=\"whoami,ps\""
-);
+  deno registry ping --token <token>",
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token to check"),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the results in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("provenance")
+        .about("Show the provenance of a published version")
+        .long_about(
+          "Show who built a published version and from which source, as recorded in
+its provenance, and the transparency log entry of the signature. The
+provenance is not verified, use `deno registry verify` for that.
 
-static ALLOW_FFI_HELP: &str = concat!(
-  "(Unstable) Allow loading dynamic libraries. Optionally specify allowed directories or files.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --allow-ffi\n",
-  "  --allow-ffi=\"./libfoo.so\""
-);
+  deno registry provenance @foo/bar@1.0.0
 
-static DENY_FFI_HELP: &str = concat!(
-  "(Unstable) Deny loading dynamic libraries. Optionally specify denied directories or files.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n",
-  "Examples:\n",
-  "  --deny-ffi\n",
-  "  --deny-ffi=\"./libfoo.so\""
-);
+Print the provenance summary as JSON:
 
-static ALLOW_HRTIME_HELP: &str = concat!(
-  "Allow high-resolution time measurement. Note: this can enable timing attacks and fingerprinting.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n"
-);
+  deno registry provenance @foo/bar@1.0.0 --json",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package version to show, eg. @foo/bar@1.0.0")
+            .required(true),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the provenance summary in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("scope-config")
+        .about("Show and change the settings of a scope")
+        .long_about(
+          "Show the settings of a scope, and change them when a setting is passed.
+Requires a token of an admin of the scope to change the settings.
 
-static DENY_HRTIME_HELP: &str = concat!(
-  "Deny high-resolution time measurement. Note: this can prevent timing attacks and fingerprinting.\n",
-  "Docs: https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n"
-);
+  deno registry scope-config @foo --token <token>
 
-static ALLOW_ALL_HELP: &str = concat!(
-  "Allow all permissions. Learn more about permissions in Deno:\n",
-  "https://deno.land/manual@v",
-  env!("CARGO_PKG_VERSION"),
-  "/basics/permissions\n"
-);
+Only allow publishing from CI:
 
-fn permission_args(app: Command) -> Command {
-  app
-    .arg(
-      Arg::new("allow-read")
-        .long("allow-read")
-        .num_args(0..)
-        .use_value_delimiter(true)
-        .require_equals(true)
-        .value_name("PATH")
-        .help(ALLOW_READ_HELP)
-        // todo(dsherret): remove value_parser!(PathBuf) and instead parse as string
-        .value_parser(value_parser!(PathBuf))
-        .value_hint(ValueHint::AnyPath),
+  deno registry scope-config @foo --require-publishing-from-ci=true --token <token>
+
+Link a package of the scope to a GitHub repository, or unlink it with 'none':
+
+  deno registry scope-config @foo --package bar --github-repository foo/bar --token <token>",
+        )
+        .arg(
+          Arg::new("scope")
+            .help("The scope to configure, eg. @foo")
+            .required(true),
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token of a member of the scope"),
+        )
+        .arg(
+          Arg::new("require-publishing-from-ci")
+            .long("require-publishing-from-ci")
+            .help("Only allow publishing the packages of the scope from CI")
+            .value_parser(value_parser!(bool))
+            .require_equals(true),
+        )
+        .arg(
+          Arg::new("verify-github-actions-actor")
+            .long("verify-github-actions-actor")
+            .help("Only allow GitHub Actions runs started by members of the scope to publish")
+            .value_parser(value_parser!(bool))
+            .require_equals(true),
+        )
+        .arg(
+          Arg::new("package")
+            .long("package")
+            .help("The package of the scope to link to a GitHub repository"),
+        )
+        .arg(
+          Arg::new("github-repository")
+            .long("github-repository")
+            .help("The GitHub repository to link the package to, eg. foo/bar, or 'none'")
+            .requires("package"),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the settings in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
     )
-    .arg(
-      Arg::new("deny-read")
-        .long("deny-read")
-        .num_args(0..)
-        .use_value_delimiter(true)
-        .require_equals(true)
-        .value_name("PATH")
-        .help(DENY_READ_HELP)
-        // todo(dsherret): remove value_parser!(PathBuf) and instead parse as string
-        .value_parser(value_parser!(PathBuf))
-        .value_hint(ValueHint::AnyPath),
+    .subcommand(
+      Command::new("scopes")
+        .about("List the scopes the owner of a token is a member of")
+        .long_about(
+          "List the scopes the owner of a token is a member of, and whether they
+are an admin of the scope, which is required to create packages in it.
+
+  deno registry scopes --token <token>",
+        )
+        .arg(
+          Arg::new("token")
+            .long("token")
+            .help("The API token of the user"),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the scopes in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
     )
-    .arg(
-      Arg::new("allow-write")
-        .long("allow-write")
-        .num_args(0..)
-        .use_value_delimiter(true)
-        .require_equals(true)
-        .value_name("PATH")
-        .help(ALLOW_WRITE_HELP)
+    .subcommand(
+      Command::new("search")
+        .about("Search the packages in the registry")
+        .long_about(
+          "Search the packages in the registry by name and description.
+
+  deno registry search path
+
+Show at most 5 packages:
+
+  deno registry search path --limit 5",
+        )
+        .arg(
+          Arg::new("query")
+            .help("The text to search for")
+            .required(true),
+        )
+        .arg(
+          Arg::new("limit")
+            .long("limit")
+            .help("The maximum number of packages to show")
+            .value_parser(value_parser!(usize)),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the packages in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("stats")
+        .about("Show the download counts of a package")
+        .long_about(
+          "Show the download counts of a package in the last 7 days and in total,
+and of its recently downloaded versions.
+
+  deno registry stats @foo/bar",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package to show, eg. @foo/bar")
+            .required(true),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the download counts in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("status")
+        .about("Show the status of a publishing task")
+        .long_about(
+          "Show the status of a publishing task, eg. of a publish that was
+interrupted while waiting for the registry. Fails when the task failed.
+
+  deno registry status <task-id>",
+        )
+        .arg(
+          Arg::new("task-id")
+            .help("The ID of the publishing task")
+            .required(true),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the task in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+    .subcommand(
+      Command::new("verify")
+        .about("Verify the provenance and files of a published package version")
+        .long_about(
+          "Verify the provenance and files of a published package version.
+
+Checks that the provenance bundle attests to the version manifest, that it
+is signed and recorded in the transparency log:
+
+  deno registry verify @foo/bar@1.0.0
+
+Additionally check the files of a tarball of the published files against the
+checksums in the version manifest:
+
+  deno registry verify @foo/bar@1.0.0 --path ./bar.tgz",
+        )
+        .arg(
+          Arg::new("package")
+            .help("The package version to verify, eg. @foo/bar@1.0.0")
+            .required(true),
+        )
+        .arg(
+          Arg::new("path")
+            .long("path")
+            .help("A .tgz tarball of the published files to check")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::AnyPath),
+        )
+        .arg(
+          Arg::new("bundle")
+            .long("bundle")
+            .help("Verify this provenance bundle instead of the one from the registry")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Outputs the verification result in JSON format")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+}
+
+fn version_subcommand() -> Command {
+  Command::new("version")
+    .hide(true)
+    .about("Unstable preview feature: Manage the versions of the packages of a workspace")
+    .subcommand_required(true)
+    .arg_required_else_help(true)
+    .subcommand(
+      Command::new("bump")
+        .about("Bump the versions of the workspace members")
+        .long_about(
+          "Bump the versions of the workspace members and update the constraints
+of the imports between them in the configuration files and import maps.
+
+  deno version bump patch
+  deno version bump minor --package @foo/bar
+  deno version bump 2.0.0-rc.1
+
+With 'auto', the increment of each member is derived from the conventional
+commit messages that touched its directory since its last release tag
+('<name>@<version>' or 'v<version>'): 'feat' bumps the minor version, 'fix'
+and 'perf' the patch version, and breaking changes ('feat!:' or a
+'BREAKING CHANGE:' footer) the major version, or the minor version before
+1.0.0. Members without such commits aren't bumped.",
+        )
+        .arg(
+          Arg::new("increment")
+            .help("'patch', 'minor', 'major', 'auto' or the version to set")
+            .required(true),
+        )
+        .arg(
+          Arg::new("package")
+            .long("package")
+            .help("Only bump this workspace member, can be repeated")
+            .value_name("NAME")
+            .action(ArgAction::Append),
+        )
+        .arg(
+          Arg::new("dry-run")
+            .long("dry-run")
+            .help("Print the new versions without changing any files")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg()),
+    )
+}
+
+fn outdated_subcommand() -> Command {
+  Command::new("outdated")
+    .about("Show the outdated dependencies")
+    .long_about(
+      "Compare the jsr: and npm: dependencies in the configuration file, its import
+map and package.json with the versions in the registries.
+
+  deno outdated
+
+Lists the version in the lockfile, the latest version that the version
+constraint allows and the latest version of each outdated dependency. Update
+them with 'deno update'.",
+    )
+    .defer(|cmd| cmd.arg(config_arg()).arg(no_config_arg()))
+}
+
+fn update_subcommand() -> Command {
+  Command::new("update")
+    .about("Update the outdated dependencies")
+    .long_about(
+      "Update the jsr: and npm: dependencies in the configuration file, its import
+map and package.json to the latest versions that their version constraints
+allow.
+
+  deno update
+
+Update them to their latest versions, which can have breaking changes:
+
+  deno update --latest",
+    )
+    .defer(|cmd| {
+      cmd
+        .arg(
+          Arg::new("latest")
+            .long("latest")
+            .help("Update to the latest versions, even outside of the version constraints")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(config_arg())
+        .arg(no_config_arg())
+    })
+}
+
+fn compile_args(app: Command) -> Command {
+  compile_args_without_check_args(app.arg(no_check_arg()))
+}
+
+fn compile_args_without_check_args(app: Command) -> Command {
+  app
+    .arg(import_map_arg())
+    .arg(no_remote_arg())
+    .arg(no_npm_arg())
+    .arg(node_modules_dir_arg())
+    .arg(vendor_arg())
+    .arg(config_arg())
+    .arg(no_config_arg())
+    .arg(reload_arg())
+    .arg(lock_arg())
+    .arg(lock_write_arg())
+    .arg(no_lock_arg())
+    .arg(ca_file_arg())
+}
+
+static ALLOW_READ_HELP: &str = concat!(
+  "Allow file system read access. Optionally specify allowed paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-read\n",
+  "  --allow-read=\"/etc,/var/log.txt\""
+);
+
+static DENY_READ_HELP: &str = concat!(
+  "Deny file system read access. Optionally specify denied paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-read\n",
+  "  --deny-read=\"/etc,/var/log.txt\""
+);
+
+static ALLOW_WRITE_HELP: &str = concat!(
+  "Allow file system write access. Optionally specify allowed paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-write\n",
+  "  --allow-write=\"/etc,/var/log.txt\""
+);
+
+static DENY_WRITE_HELP: &str = concat!(
+  "Deny file system write access. Optionally specify denied paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-write\n",
+  "  --deny-write=\"/etc,/var/log.txt\""
+);
+
+static ALLOW_NET_HELP: &str = concat!(
+  "Allow network access. Optionally specify allowed IP addresses and host names, with ports as necessary.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-net\n",
+  "  --allow-net=\"localhost:8080,deno.land\""
+);
+
+static DENY_NET_HELP: &str = concat!(
+  "Deny network access. Optionally specify denied IP addresses and host names, with ports as necessary.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-net\n",
+  "  --deny-net=\"localhost:8080,deno.land\""
+);
+
+static ALLOW_ENV_HELP: &str = concat!(
+  "Allow access to system environment information. Optionally specify accessible environment variables.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-env\n",
+  "  --allow-env=\"PORT,HOME,PATH\""
+);
+
+static DENY_ENV_HELP: &str = concat!(
+  "Deny access to system environment information. Optionally specify accessible environment variables.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-env\n",
+  "  --deny-env=\"PORT,HOME,PATH\""
+);
+
+static ALLOW_SYS_HELP: &str = concat!(
+  "Allow access to OS information. Optionally allow specific APIs by function name.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-sys\n",
+  "  --allow-sys=\"systemMemoryInfo,osRelease\""
+);
+
+static DENY_SYS_HELP: &str = concat!(
+  "Deny access to OS information. Optionally deny specific APIs by function name.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-sys\n",
+  "  --deny-sys=\"systemMemoryInfo,osRelease\""
+);
+
+static ALLOW_RUN_HELP: &str = concat!(
+  "Allow running subprocesses. Optionally specify allowed runnable program names.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-run\n",
+  "  --allow-run=\"whoami,ps\""
+);
+
+static DENY_RUN_HELP: &str = concat!(
+  "Deny running subprocesses. Optionally specify denied runnable program names.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-run\n",
+  "  --deny-run=\"whoami,ps\""
+);
+
+static ALLOW_FFI_HELP: &str = concat!(
+  "(Unstable) Allow loading dynamic libraries. Optionally specify allowed directories or files.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-ffi\n",
+  "  --allow-ffi=\"./libfoo.so\""
+);
+
+static DENY_FFI_HELP: &str = concat!(
+  "(Unstable) Deny loading dynamic libraries. Optionally specify denied directories or files.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-ffi\n",
+  "  --deny-ffi=\"./libfoo.so\""
+);
+
+static ALLOW_HRTIME_HELP: &str = concat!(
+  "Allow high-resolution time measurement. Note: this can enable timing attacks and fingerprinting.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n"
+);
+
+static DENY_HRTIME_HELP: &str = concat!(
+  "Deny high-resolution time measurement. Note: this can prevent timing attacks and fingerprinting.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n"
+);
+
+static ALLOW_ALL_HELP: &str = concat!(
+  "Allow all permissions. Learn more about permissions in Deno:\n",
+  "https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n"
+);
+
+fn permission_args(app: Command) -> Command {
+  app
+    .arg(
+      Arg::new("allow-read")
+        .long("allow-read")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(ALLOW_READ_HELP)
+        // todo(dsherret): remove value_parser!(PathBuf) and instead parse as string
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("deny-read")
+        .long("deny-read")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(DENY_READ_HELP)
+        // todo(dsherret): remove value_parser!(PathBuf) and instead parse as string
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("allow-write")
+        .long("allow-write")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(ALLOW_WRITE_HELP)
         // todo(dsherret): remove value_parser!(PathBuf) and instead parse as string
         .value_parser(value_parser!(PathBuf))
         .value_hint(ValueHint::AnyPath),
@@ -3252,7 +4256,25 @@ fn unsafely_ignore_certificate_errors_arg() -> Arg {
 
 fn add_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let packages = matches.remove_many::<String>("packages").unwrap().collect();
-  flags.subcommand = DenoSubcommand::Add(AddFlags { packages });
+  flags.subcommand = DenoSubcommand::Add(AddFlags {
+    packages,
+    dev: matches.get_flag("dev"),
+    target: matches.remove_one::<String>("target").map(|target| {
+      match target.as_str() {
+        "package-json" => AddTarget::PackageJson,
+        _ => AddTarget::Deno,
+      }
+    }),
+    version_range: if matches.get_flag("exact") {
+      Some(AddVersionRange::Exact)
+    } else if matches.get_flag("tilde") {
+      Some(AddVersionRange::Tilde)
+    } else {
+      None
+    },
+    alias: matches.remove_one::<String>("alias"),
+    interactive: matches.get_flag("interactive"),
+  });
 }
 
 fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -3887,506 +4909,1329 @@ fn vendor_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
-fn publish_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.type_check_mode = TypeCheckMode::Local; // local by default
-  no_check_arg_parse(flags, matches);
-  check_arg_parse(flags, matches);
-  config_args_parse(flags, matches);
+fn publish_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.type_check_mode = TypeCheckMode::Local; // local by default
+  no_check_arg_parse(flags, matches);
+  check_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Publish(PublishFlags {
+    token: matches.remove_one("token"),
+    dry_run: matches.get_flag("dry-run"),
+    allow_slow_types: matches.get_flag("allow-slow-types"),
+    no_provenance: matches.get_flag("no-provenance"),
+    require_provenance: matches.get_flag("require-provenance"),
+    provenance_defer: matches.remove_one("provenance-defer"),
+    sbom: matches.remove_one("sbom"),
+    auth_info: matches.get_flag("auth-info"),
+    json: matches.get_flag("json"),
+    create: matches.get_flag("create"),
+    wait_for_available: matches.get_flag("wait-for-available"),
+    trace_registry: matches.contains_id("trace-registry"),
+    trace_registry_file: matches.remove_one("trace-registry"),
+    fix: matches.get_flag("fix"),
+    explain: matches.remove_one("explain"),
+    baseline: matches.remove_one("baseline"),
+    write_baseline: matches.get_flag("write-baseline"),
+    max_diagnostics: matches.remove_one("max-diagnostics"),
+    report_file: matches.remove_one("report-file"),
+    deny_warnings: matches.get_flag("deny-warnings"),
+    diagnostics_format: match matches
+      .remove_one::<String>("diagnostics-format")
+      .as_deref()
+    {
+      Some("short") => PublishDiagnosticsFormat::Short,
+      Some("pretty") | None => PublishDiagnosticsFormat::Pretty,
+      _ => unreachable!(),
+    },
+    bump: matches
+      .remove_one::<String>("bump")
+      .map(|increment| match increment.as_str() {
+        "patch" => VersionIncrement::Patch,
+        "minor" => VersionIncrement::Minor,
+        "major" => VersionIncrement::Major,
+        "auto" => VersionIncrement::Auto,
+        _ => unreachable!(),
+      }),
+    changed_only: matches.get_flag("changed-only"),
+    filter: matches
+      .remove_many::<String>("filter")
+      .map(|filter| filter.collect())
+      .unwrap_or_default(),
+    include_dependencies: matches.get_flag("include-dependencies"),
+    check_libs: matches
+      .remove_many::<String>("check-lib")
+      .map(|libs| {
+        libs
+          .map(|lib| match lib.as_str() {
+            "worker" => PublishCheckLib::Worker,
+            "node" => PublishCheckLib::Node,
+            _ => unreachable!(),
+          })
+          .collect()
+      })
+      .unwrap_or_default(),
+    show_fast_check: matches.get_flag("show-fast-check"),
+  });
+}
+
+fn registry_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let (subcommand, mut matches) = matches.remove_subcommand().unwrap();
+  config_args_parse(flags, &mut matches);
+  let subcommand = match subcommand.as_str() {
+    "attach-provenance" => {
+      RegistrySubcommand::AttachProvenance(AttachProvenanceFlags {
+        bundle: matches.remove_one("bundle").unwrap(),
+        package: matches.remove_one("package").unwrap(),
+        attestations: matches.remove_one("attestations"),
+        token: matches.remove_one("token"),
+        json: matches.get_flag("json"),
+      })
+    }
+    "audit-log" => RegistrySubcommand::AuditLog(RegistryAuditLogFlags {
+      scope: matches.remove_one("scope").unwrap(),
+      token: matches.remove_one("token"),
+      package: matches.remove_one("package"),
+      since: matches.remove_one("since"),
+      until: matches.remove_one("until"),
+      json: matches.get_flag("json"),
+    }),
+    "delete" => RegistrySubcommand::Delete(RegistryDeleteFlags {
+      package: matches.remove_one("package").unwrap(),
+      token: matches.remove_one("token"),
+      yes: matches.get_flag("yes"),
+      json: matches.get_flag("json"),
+    }),
+    "info" => RegistrySubcommand::Info(RegistryInfoFlags {
+      package: matches.remove_one("package").unwrap(),
+      json: matches.get_flag("json"),
+    }),
+    "open" => RegistrySubcommand::Open(RegistryOpenFlags {
+      package: matches.remove_one("package"),
+    }),
+    "ping" => RegistrySubcommand::Ping(RegistryPingFlags {
+      token: matches.remove_one("token"),
+      json: matches.get_flag("json"),
+    }),
+    "provenance" => RegistrySubcommand::Provenance(RegistryProvenanceFlags {
+      package: matches.remove_one("package").unwrap(),
+      json: matches.get_flag("json"),
+    }),
+    "scope-config" => {
+      RegistrySubcommand::ScopeConfig(RegistryScopeConfigFlags {
+        scope: matches.remove_one("scope").unwrap(),
+        token: matches.remove_one("token"),
+        require_publishing_from_ci: matches
+          .remove_one("require-publishing-from-ci"),
+        verify_github_actions_actor: matches
+          .remove_one("verify-github-actions-actor"),
+        package: matches.remove_one("package"),
+        github_repository: matches.remove_one("github-repository"),
+        json: matches.get_flag("json"),
+      })
+    }
+    "scopes" => RegistrySubcommand::Scopes(RegistryScopesFlags {
+      token: matches.remove_one("token"),
+      json: matches.get_flag("json"),
+    }),
+    "search" => RegistrySubcommand::Search(RegistrySearchFlags {
+      query: matches.remove_one("query").unwrap(),
+      limit: matches.remove_one("limit"),
+      json: matches.get_flag("json"),
+    }),
+    "stats" => RegistrySubcommand::Stats(RegistryStatsFlags {
+      package: matches.remove_one("package").unwrap(),
+      json: matches.get_flag("json"),
+    }),
+    "status" => RegistrySubcommand::Status(RegistryStatusFlags {
+      task_id: matches.remove_one("task-id").unwrap(),
+      json: matches.get_flag("json"),
+    }),
+    "verify" => RegistrySubcommand::Verify(RegistryVerifyFlags {
+      package: matches.remove_one("package").unwrap(),
+      path: matches.remove_one("path"),
+      bundle: matches.remove_one("bundle"),
+      json: matches.get_flag("json"),
+    }),
+    _ => unreachable!(),
+  };
+  flags.subcommand = DenoSubcommand::Registry(subcommand);
+}
+
+fn version_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let (subcommand, mut matches) = matches.remove_subcommand().unwrap();
+  config_args_parse(flags, &mut matches);
+  let subcommand = match subcommand.as_str() {
+    "bump" => VersionSubcommand::Bump(VersionBumpFlags {
+      increment: {
+        let increment = matches.remove_one::<String>("increment").unwrap();
+        match increment.as_str() {
+          "patch" => VersionIncrement::Patch,
+          "minor" => VersionIncrement::Minor,
+          "major" => VersionIncrement::Major,
+          "auto" => VersionIncrement::Auto,
+          _ => VersionIncrement::Exact(increment),
+        }
+      },
+      packages: matches
+        .remove_many::<String>("package")
+        .map(|p| p.collect())
+        .unwrap_or_default(),
+      dry_run: matches.get_flag("dry-run"),
+    }),
+    _ => unreachable!(),
+  };
+  flags.subcommand = DenoSubcommand::Version(subcommand);
+}
+
+fn outdated_parse(flags: &mut Flags, matches: &mut ArgMatches, update: bool) {
+  config_args_parse(flags, matches);
+  flags.subcommand = DenoSubcommand::Outdated(OutdatedFlags {
+    update,
+    latest: update && matches.get_flag("latest"),
+  });
+}
+
+fn compile_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  compile_args_without_check_parse(flags, matches);
+  no_check_arg_parse(flags, matches);
+  check_arg_parse(flags, matches);
+}
+
+fn compile_args_without_check_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) {
+  import_map_arg_parse(flags, matches);
+  no_remote_arg_parse(flags, matches);
+  no_npm_arg_parse(flags, matches);
+  node_modules_and_vendor_dir_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
+  reload_arg_parse(flags, matches);
+  lock_args_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+}
+
+fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  unsafely_ignore_certificate_errors_parse(flags, matches);
+  if let Some(read_wl) = matches.remove_many::<PathBuf>("allow-read") {
+    flags.allow_read = Some(read_wl.collect());
+  }
+
+  if let Some(read_wl) = matches.remove_many::<PathBuf>("deny-read") {
+    flags.deny_read = Some(read_wl.collect());
+  }
+
+  if let Some(write_wl) = matches.remove_many::<PathBuf>("allow-write") {
+    flags.allow_write = Some(write_wl.collect());
+  }
+
+  if let Some(write_wl) = matches.remove_many::<PathBuf>("deny-write") {
+    flags.deny_write = Some(write_wl.collect());
+  }
+
+  if let Some(net_wl) = matches.remove_many::<String>("allow-net") {
+    let net_allowlist = flags_net::parse(net_wl.collect()).unwrap();
+    flags.allow_net = Some(net_allowlist);
+  }
+
+  if let Some(net_wl) = matches.remove_many::<String>("deny-net") {
+    let net_denylist = flags_net::parse(net_wl.collect()).unwrap();
+    flags.deny_net = Some(net_denylist);
+  }
+
+  if let Some(env_wl) = matches.remove_many::<String>("allow-env") {
+    flags.allow_env = Some(env_wl.collect());
+    debug!("env allowlist: {:#?}", &flags.allow_env);
+  }
+
+  if let Some(env_wl) = matches.remove_many::<String>("deny-env") {
+    flags.deny_env = Some(env_wl.collect());
+    debug!("env denylist: {:#?}", &flags.deny_env);
+  }
+
+  if let Some(run_wl) = matches.remove_many::<String>("allow-run") {
+    flags.allow_run = Some(run_wl.collect());
+    debug!("run allowlist: {:#?}", &flags.allow_run);
+  }
+
+  if let Some(run_wl) = matches.remove_many::<String>("deny-run") {
+    flags.deny_run = Some(run_wl.collect());
+    debug!("run denylist: {:#?}", &flags.deny_run);
+  }
+
+  if let Some(sys_wl) = matches.remove_many::<String>("allow-sys") {
+    flags.allow_sys = Some(sys_wl.collect());
+    debug!("sys info allowlist: {:#?}", &flags.allow_sys);
+  }
+
+  if let Some(sys_wl) = matches.remove_many::<String>("deny-sys") {
+    flags.deny_sys = Some(sys_wl.collect());
+    debug!("sys info denylist: {:#?}", &flags.deny_sys);
+  }
+
+  if let Some(ffi_wl) = matches.remove_many::<PathBuf>("allow-ffi") {
+    flags.allow_ffi = Some(ffi_wl.collect());
+    debug!("ffi allowlist: {:#?}", &flags.allow_ffi);
+  }
+
+  if let Some(ffi_wl) = matches.remove_many::<PathBuf>("deny-ffi") {
+    flags.deny_ffi = Some(ffi_wl.collect());
+    debug!("ffi denylist: {:#?}", &flags.deny_ffi);
+  }
+
+  if matches.get_flag("allow-hrtime") {
+    flags.allow_hrtime = true;
+  }
+
+  if matches.get_flag("deny-hrtime") {
+    flags.deny_hrtime = true;
+  }
+
+  if matches.get_flag("allow-all") {
+    flags.allow_all = true;
+    flags.allow_read = Some(vec![]);
+    flags.allow_env = Some(vec![]);
+    flags.allow_net = Some(vec![]);
+    flags.allow_run = Some(vec![]);
+    flags.allow_write = Some(vec![]);
+    flags.allow_sys = Some(vec![]);
+    flags.allow_ffi = Some(vec![]);
+    flags.allow_hrtime = true;
+  }
+
+  if matches.get_flag("no-prompt") {
+    flags.no_prompt = true;
+  }
+}
+
+fn unsafely_ignore_certificate_errors_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) {
+  if let Some(ic_wl) =
+    matches.remove_many::<String>("unsafely-ignore-certificate-errors")
+  {
+    let ic_allowlist = flags_net::parse(ic_wl.collect()).unwrap();
+    flags.unsafely_ignore_certificate_errors = Some(ic_allowlist);
+  }
+}
+
+fn runtime_args_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+  include_perms: bool,
+  include_inspector: bool,
+) {
+  compile_args_parse(flags, matches);
+  cached_only_arg_parse(flags, matches);
+  if include_perms {
+    permission_args_parse(flags, matches);
+  }
+  if include_inspector {
+    inspect_arg_parse(flags, matches);
+  }
+  location_arg_parse(flags, matches);
+  v8_flags_arg_parse(flags, matches);
+  seed_arg_parse(flags, matches);
+  enable_testing_features_arg_parse(flags, matches);
+  env_file_arg_parse(flags, matches);
+  strace_ops_parse(flags, matches);
+}
+
+fn inspect_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let default = || "127.0.0.1:9229".parse::<SocketAddr>().unwrap();
+  flags.inspect = if matches.contains_id("inspect") {
+    Some(
+      matches
+        .remove_one::<SocketAddr>("inspect")
+        .unwrap_or_else(default),
+    )
+  } else {
+    None
+  };
+  flags.inspect_brk = if matches.contains_id("inspect-brk") {
+    Some(
+      matches
+        .remove_one::<SocketAddr>("inspect-brk")
+        .unwrap_or_else(default),
+    )
+  } else {
+    None
+  };
+  flags.inspect_wait = if matches.contains_id("inspect-wait") {
+    Some(
+      matches
+        .remove_one::<SocketAddr>("inspect-wait")
+        .unwrap_or_else(default),
+    )
+  } else {
+    None
+  };
+}
+
+fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.import_map_path = matches.remove_one::<String>("import-map");
+}
 
-  flags.subcommand = DenoSubcommand::Publish(PublishFlags {
-    token: matches.remove_one("token"),
-    dry_run: matches.get_flag("dry-run"),
-    allow_slow_types: matches.get_flag("allow-slow-types"),
-    no_provenance: matches.get_flag("no-provenance"),
-  });
+fn env_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.env_file = matches.remove_one::<String>("env");
 }
 
-fn compile_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  compile_args_without_check_parse(flags, matches);
-  no_check_arg_parse(flags, matches);
-  check_arg_parse(flags, matches);
+fn reload_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(cache_bl) = matches.remove_many::<String>("reload") {
+    let raw_cache_blocklist: Vec<String> = cache_bl.collect();
+    if raw_cache_blocklist.is_empty() {
+      flags.reload = true;
+    } else {
+      flags.cache_blocklist = resolve_urls(raw_cache_blocklist);
+      debug!("cache blocklist: {:#?}", &flags.cache_blocklist);
+      flags.reload = false;
+    }
+  }
 }
 
-fn compile_args_without_check_parse(
+fn ca_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.ca_data = matches.remove_one::<String>("cert").map(CaData::File);
+}
+
+fn enable_testing_features_arg_parse(
   flags: &mut Flags,
   matches: &mut ArgMatches,
 ) {
-  import_map_arg_parse(flags, matches);
-  no_remote_arg_parse(flags, matches);
-  no_npm_arg_parse(flags, matches);
-  node_modules_and_vendor_dir_arg_parse(flags, matches);
-  config_args_parse(flags, matches);
-  reload_arg_parse(flags, matches);
-  lock_args_parse(flags, matches);
-  ca_file_arg_parse(flags, matches);
+  if matches.get_flag("enable-testing-features-do-not-use") {
+    flags.enable_testing_features = true
+  }
 }
 
-fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  unsafely_ignore_certificate_errors_parse(flags, matches);
-  if let Some(read_wl) = matches.remove_many::<PathBuf>("allow-read") {
-    flags.allow_read = Some(read_wl.collect());
+fn strace_ops_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(patterns) = matches.remove_many::<String>("strace-ops") {
+    flags.strace_ops = Some(patterns.collect());
   }
+}
 
-  if let Some(read_wl) = matches.remove_many::<PathBuf>("deny-read") {
-    flags.deny_read = Some(read_wl.collect());
+fn cached_only_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if matches.get_flag("cached-only") {
+    flags.cached_only = true;
   }
+}
 
-  if let Some(write_wl) = matches.remove_many::<PathBuf>("allow-write") {
-    flags.allow_write = Some(write_wl.collect());
-  }
+fn ext_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.ext = matches.remove_one::<String>("ext");
+}
 
-  if let Some(write_wl) = matches.remove_many::<PathBuf>("deny-write") {
-    flags.deny_write = Some(write_wl.collect());
+fn location_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.location = matches.remove_one::<Url>("location");
+}
+
+fn v8_flags_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(v8_flags) = matches.remove_many::<String>("v8-flags") {
+    flags.v8_flags = v8_flags.collect();
   }
+}
 
-  if let Some(net_wl) = matches.remove_many::<String>("allow-net") {
-    let net_allowlist = flags_net::parse(net_wl.collect()).unwrap();
-    flags.allow_net = Some(net_allowlist);
+fn seed_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(seed) = matches.remove_one::<u64>("seed") {
+    flags.seed = Some(seed);
+
+    flags.v8_flags.push(format!("--random-seed={seed}"));
   }
+}
 
-  if let Some(net_wl) = matches.remove_many::<String>("deny-net") {
-    let net_denylist = flags_net::parse(net_wl.collect()).unwrap();
-    flags.deny_net = Some(net_denylist);
+fn no_check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(cache_type) = matches.get_one::<String>("no-check") {
+    match cache_type.as_str() {
+      "remote" => flags.type_check_mode = TypeCheckMode::Local,
+      _ => debug!(
+        "invalid value for 'no-check' of '{}' using default",
+        cache_type
+      ),
+    }
+  } else if matches.contains_id("no-check") {
+    flags.type_check_mode = TypeCheckMode::None;
   }
+}
 
-  if let Some(env_wl) = matches.remove_many::<String>("allow-env") {
-    flags.allow_env = Some(env_wl.collect());
-    debug!("env allowlist: {:#?}", &flags.allow_env);
+fn check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(cache_type) = matches.get_one::<String>("check") {
+    match cache_type.as_str() {
+      "all" => flags.type_check_mode = TypeCheckMode::All,
+      _ => debug!(
+        "invalid value for 'check' of '{}' using default",
+        cache_type
+      ),
+    }
+  } else if matches.contains_id("check") {
+    flags.type_check_mode = TypeCheckMode::Local;
   }
+}
 
-  if let Some(env_wl) = matches.remove_many::<String>("deny-env") {
-    flags.deny_env = Some(env_wl.collect());
-    debug!("env denylist: {:#?}", &flags.deny_env);
+fn lock_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  lock_arg_parse(flags, matches);
+  no_lock_arg_parse(flags, matches);
+  if matches.get_flag("lock-write") {
+    flags.lock_write = true;
   }
+}
 
-  if let Some(run_wl) = matches.remove_many::<String>("allow-run") {
-    flags.allow_run = Some(run_wl.collect());
-    debug!("run allowlist: {:#?}", &flags.allow_run);
+fn lock_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if matches.contains_id("lock") {
+    let lockfile = matches
+      .remove_one::<PathBuf>("lock")
+      .unwrap_or_else(|| PathBuf::from("./deno.lock"));
+    flags.lock = Some(lockfile);
   }
+}
 
-  if let Some(run_wl) = matches.remove_many::<String>("deny-run") {
-    flags.deny_run = Some(run_wl.collect());
-    debug!("run denylist: {:#?}", &flags.deny_run);
+fn no_lock_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
+  if matches.get_flag("no-lock") {
+    flags.no_lock = true;
   }
+}
 
-  if let Some(sys_wl) = matches.remove_many::<String>("allow-sys") {
-    flags.allow_sys = Some(sys_wl.collect());
-    debug!("sys info allowlist: {:#?}", &flags.allow_sys);
+fn config_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.config_flag = if matches.get_flag("no-config") {
+    ConfigFlag::Disabled
+  } else if let Some(config) = matches.remove_one::<String>("config") {
+    ConfigFlag::Path(config)
+  } else {
+    ConfigFlag::Discover
+  };
+}
+
+fn no_remote_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if matches.get_flag("no-remote") {
+    flags.no_remote = true;
   }
+}
 
-  if let Some(sys_wl) = matches.remove_many::<String>("deny-sys") {
-    flags.deny_sys = Some(sys_wl.collect());
-    debug!("sys info denylist: {:#?}", &flags.deny_sys);
+fn no_npm_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if matches.get_flag("no-npm") {
+    flags.no_npm = true;
   }
+}
 
-  if let Some(ffi_wl) = matches.remove_many::<PathBuf>("allow-ffi") {
-    flags.allow_ffi = Some(ffi_wl.collect());
-    debug!("ffi allowlist: {:#?}", &flags.allow_ffi);
+fn node_modules_and_vendor_dir_arg_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) {
+  flags.node_modules_dir = matches.remove_one::<bool>("node-modules-dir");
+  flags.vendor = matches.remove_one::<bool>("vendor");
+}
+
+fn reload_arg_validate(urlstr: &str) -> Result<String, String> {
+  if urlstr.is_empty() {
+    return Err(String::from("Missing url. Check for extra commas."));
+  }
+  match Url::from_str(urlstr) {
+    Ok(_) => Ok(urlstr.to_string()),
+    Err(e) => Err(e.to_string()),
   }
+}
 
-  if let Some(ffi_wl) = matches.remove_many::<PathBuf>("deny-ffi") {
-    flags.deny_ffi = Some(ffi_wl.collect());
-    debug!("ffi denylist: {:#?}", &flags.deny_ffi);
+fn watch_arg_parse(matches: &mut ArgMatches) -> Option<WatchFlags> {
+  if matches.get_flag("watch") {
+    Some(WatchFlags {
+      hmr: false,
+      no_clear_screen: matches.get_flag("no-clear-screen"),
+    })
+  } else {
+    None
   }
+}
 
-  if matches.get_flag("allow-hrtime") {
-    flags.allow_hrtime = true;
+fn watch_arg_parse_with_paths(
+  matches: &mut ArgMatches,
+) -> Option<WatchFlagsWithPaths> {
+  if let Some(paths) = matches.remove_many::<PathBuf>("watch") {
+    return Some(WatchFlagsWithPaths {
+      paths: paths.collect(),
+      hmr: false,
+      no_clear_screen: matches.get_flag("no-clear-screen"),
+    });
   }
 
-  if matches.get_flag("deny-hrtime") {
-    flags.deny_hrtime = true;
+  matches
+    .remove_many::<PathBuf>("hmr")
+    .map(|paths| WatchFlagsWithPaths {
+      paths: paths.collect(),
+      hmr: true,
+      no_clear_screen: matches.get_flag("no-clear-screen"),
+    })
+}
+
+// TODO(ry) move this to utility module and add test.
+/// Strips fragment part of URL. Panics on bad URL.
+pub fn resolve_urls(urls: Vec<String>) -> Vec<String> {
+  let mut out: Vec<String> = vec![];
+  for urlstr in urls.iter() {
+    if let Ok(mut url) = Url::from_str(urlstr) {
+      url.set_fragment(None);
+      let mut full_url = String::from(url.as_str());
+      if full_url.len() > 1 && full_url.ends_with('/') {
+        full_url.pop();
+      }
+      out.push(full_url);
+    } else {
+      panic!("Bad Url: {urlstr}");
+    }
   }
+  out
+}
 
-  if matches.get_flag("allow-all") {
-    flags.allow_all = true;
-    flags.allow_read = Some(vec![]);
-    flags.allow_env = Some(vec![]);
-    flags.allow_net = Some(vec![]);
-    flags.allow_run = Some(vec![]);
-    flags.allow_write = Some(vec![]);
-    flags.allow_sys = Some(vec![]);
-    flags.allow_ffi = Some(vec![]);
-    flags.allow_hrtime = true;
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  /// Creates vector of strings, Vec<String>
+  macro_rules! svec {
+    ($($x:expr),* $(,)?) => (vec![$($x.to_string()),*]);
   }
 
-  if matches.get_flag("no-prompt") {
-    flags.no_prompt = true;
+  #[test]
+  fn global_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "--unstable", "--log-level", "debug", "--quiet", "run", "script.ts"]);
+
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string()
+        )),
+        unstable_config: UnstableConfig {
+          legacy_flag_enabled: true,
+          ..Default::default()
+        },
+        log_level: Some(Level::Error),
+        ..Flags::default()
+      }
+    );
+    #[rustfmt::skip]
+    let r2 = flags_from_vec(svec!["deno", "run", "--unstable", "--log-level", "debug", "--quiet", "script.ts"]);
+    let flags2 = r2.unwrap();
+    assert_eq!(flags2, flags);
   }
-}
 
-fn unsafely_ignore_certificate_errors_parse(
-  flags: &mut Flags,
-  matches: &mut ArgMatches,
-) {
-  if let Some(ic_wl) =
-    matches.remove_many::<String>("unsafely-ignore-certificate-errors")
-  {
-    let ic_allowlist = flags_net::parse(ic_wl.collect()).unwrap();
-    flags.unsafely_ignore_certificate_errors = Some(ic_allowlist);
+  #[test]
+  fn upgrade() {
+    let r = flags_from_vec(svec!["deno", "upgrade", "--dry-run", "--force"]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: true,
+          dry_run: true,
+          canary: false,
+          version: None,
+          output: None,
+        }),
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn runtime_args_parse(
-  flags: &mut Flags,
-  matches: &mut ArgMatches,
-  include_perms: bool,
-  include_inspector: bool,
-) {
-  compile_args_parse(flags, matches);
-  cached_only_arg_parse(flags, matches);
-  if include_perms {
-    permission_args_parse(flags, matches);
+  #[test]
+  fn version() {
+    let r = flags_from_vec(svec!["deno", "--version"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::DisplayVersion
+    );
+    let r = flags_from_vec(svec!["deno", "-V"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::DisplayVersion
+    );
   }
-  if include_inspector {
-    inspect_arg_parse(flags, matches);
+
+  #[test]
+  fn run_reload() {
+    let r = flags_from_vec(svec!["deno", "run", "-r", "script.ts"]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string()
+        )),
+        reload: true,
+        ..Flags::default()
+      }
+    );
   }
-  location_arg_parse(flags, matches);
-  v8_flags_arg_parse(flags, matches);
-  seed_arg_parse(flags, matches);
-  enable_testing_features_arg_parse(flags, matches);
-  env_file_arg_parse(flags, matches);
-  strace_ops_parse(flags, matches);
-}
 
-fn inspect_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  let default = || "127.0.0.1:9229".parse::<SocketAddr>().unwrap();
-  flags.inspect = if matches.contains_id("inspect") {
-    Some(
-      matches
-        .remove_one::<SocketAddr>("inspect")
-        .unwrap_or_else(default),
-    )
-  } else {
-    None
-  };
-  flags.inspect_brk = if matches.contains_id("inspect-brk") {
-    Some(
-      matches
-        .remove_one::<SocketAddr>("inspect-brk")
-        .unwrap_or_else(default),
-    )
-  } else {
-    None
-  };
-  flags.inspect_wait = if matches.contains_id("inspect-wait") {
-    Some(
-      matches
-        .remove_one::<SocketAddr>("inspect-wait")
-        .unwrap_or_else(default),
-    )
-  } else {
-    None
-  };
-}
+  #[test]
+  fn run_watch() {
+    let r = flags_from_vec(svec!["deno", "run", "--watch", "script.ts"]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: false,
+            paths: vec![],
+            no_clear_screen: false,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
 
-fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.import_map_path = matches.remove_one::<String>("import-map");
-}
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: false,
+            paths: vec![],
+            no_clear_screen: true,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
 
-fn env_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.env_file = matches.remove_one::<String>("env");
-}
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--unstable-hmr",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: true,
+            paths: vec![],
+            no_clear_screen: true,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
 
-fn reload_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(cache_bl) = matches.remove_many::<String>("reload") {
-    let raw_cache_blocklist: Vec<String> = cache_bl.collect();
-    if raw_cache_blocklist.is_empty() {
-      flags.reload = true;
-    } else {
-      flags.cache_blocklist = resolve_urls(raw_cache_blocklist);
-      debug!("cache blocklist: {:#?}", &flags.cache_blocklist);
-      flags.reload = false;
-    }
-  }
-}
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--unstable-hmr=foo.txt",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: true,
+            paths: vec![PathBuf::from("foo.txt")],
+            no_clear_screen: true,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
 
-fn ca_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.ca_data = matches.remove_one::<String>("cert").map(CaData::File);
-}
+    let r =
+      flags_from_vec(svec!["deno", "run", "--hmr", "--watch", "script.ts"]);
+    assert!(r.is_err());
+  }
 
-fn enable_testing_features_arg_parse(
-  flags: &mut Flags,
-  matches: &mut ArgMatches,
-) {
-  if matches.get_flag("enable-testing-features-do-not-use") {
-    flags.enable_testing_features = true
+  #[test]
+  fn run_watch_with_external() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--watch=file1,file2", "script.ts"]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: false,
+            paths: vec![PathBuf::from("file1"), PathBuf::from("file2")],
+            no_clear_screen: false,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn strace_ops_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(patterns) = matches.remove_many::<String>("strace-ops") {
-    flags.strace_ops = Some(patterns.collect());
+  #[test]
+  fn run_watch_with_no_clear_screen() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: false,
+            paths: vec![],
+            no_clear_screen: true,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn cached_only_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if matches.get_flag("cached-only") {
-    flags.cached_only = true;
+  #[test]
+  fn run_reload_allow_write() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "-r", "--allow-write", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        reload: true,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string()
+        )),
+        allow_write: Some(vec![]),
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn ext_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.ext = matches.remove_one::<String>("ext");
-}
+  #[test]
+  fn run_v8_flags() {
+    let r = flags_from_vec(svec!["deno", "run", "--v8-flags=--help"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default("_".to_string())),
+        v8_flags: svec!["--help"],
+        ..Flags::default()
+      }
+    );
 
-fn location_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.location = matches.remove_one::<Url>("location");
-}
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--v8-flags=--expose-gc,--gc-stats=1",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string()
+        )),
+        v8_flags: svec!["--expose-gc", "--gc-stats=1"],
+        ..Flags::default()
+      }
+    );
 
-fn v8_flags_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(v8_flags) = matches.remove_many::<String>("v8-flags") {
-    flags.v8_flags = v8_flags.collect();
+    let r = flags_from_vec(svec!["deno", "run", "--v8-flags=--expose-gc"]);
+    assert!(r
+      .unwrap_err()
+      .to_string()
+      .contains("[SCRIPT_ARG] may only be omitted with --v8-flags=--help"));
   }
-}
 
-fn seed_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(seed) = matches.remove_one::<u64>("seed") {
-    flags.seed = Some(seed);
+  #[test]
+  fn has_permission() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-read", "x.ts"]);
+    assert_eq!(r.unwrap().has_permission(), true);
 
-    flags.v8_flags.push(format!("--random-seed={seed}"));
-  }
-}
+    let r = flags_from_vec(svec!["deno", "run", "--deny-read", "x.ts"]);
+    assert_eq!(r.unwrap().has_permission(), true);
 
-fn no_check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(cache_type) = matches.get_one::<String>("no-check") {
-    match cache_type.as_str() {
-      "remote" => flags.type_check_mode = TypeCheckMode::Local,
-      _ => debug!(
-        "invalid value for 'no-check' of '{}' using default",
-        cache_type
-      ),
-    }
-  } else if matches.contains_id("no-check") {
-    flags.type_check_mode = TypeCheckMode::None;
+    let r = flags_from_vec(svec!["deno", "run", "x.ts"]);
+    assert_eq!(r.unwrap().has_permission(), false);
   }
-}
 
-fn check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if let Some(cache_type) = matches.get_one::<String>("check") {
-    match cache_type.as_str() {
-      "all" => flags.type_check_mode = TypeCheckMode::All,
-      _ => debug!(
-        "invalid value for 'check' of '{}' using default",
-        cache_type
-      ),
-    }
-  } else if matches.contains_id("check") {
-    flags.type_check_mode = TypeCheckMode::Local;
-  }
-}
+  #[test]
+  fn has_permission_in_argv() {
+    let r = flags_from_vec(svec!["deno", "run", "x.ts", "--allow-read"]);
+    assert_eq!(r.unwrap().has_permission_in_argv(), true);
 
-fn lock_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  lock_arg_parse(flags, matches);
-  no_lock_arg_parse(flags, matches);
-  if matches.get_flag("lock-write") {
-    flags.lock_write = true;
-  }
-}
+    let r = flags_from_vec(svec!["deno", "run", "x.ts", "--deny-read"]);
+    assert_eq!(r.unwrap().has_permission_in_argv(), true);
 
-fn lock_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if matches.contains_id("lock") {
-    let lockfile = matches
-      .remove_one::<PathBuf>("lock")
-      .unwrap_or_else(|| PathBuf::from("./deno.lock"));
-    flags.lock = Some(lockfile);
+    let r = flags_from_vec(svec!["deno", "run", "x.ts"]);
+    assert_eq!(r.unwrap().has_permission_in_argv(), false);
   }
-}
 
-fn no_lock_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
-  if matches.get_flag("no-lock") {
-    flags.no_lock = true;
+  #[test]
+  fn script_args() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-net",
+      "gist.ts",
+      "--title",
+      "X"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string()
+        )),
+        argv: svec!["--title", "X"],
+        allow_net: Some(vec![]),
+        ..Flags::default()
+      }
+    );
   }
-}
-
-fn config_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.config_flag = if matches.get_flag("no-config") {
-    ConfigFlag::Disabled
-  } else if let Some(config) = matches.remove_one::<String>("config") {
-    ConfigFlag::Path(config)
-  } else {
-    ConfigFlag::Discover
-  };
-}
 
-fn no_remote_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if matches.get_flag("no-remote") {
-    flags.no_remote = true;
+  #[test]
+  fn allow_all() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-all", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string()
+        )),
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn no_npm_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  if matches.get_flag("no-npm") {
-    flags.no_npm = true;
+  #[test]
+  fn allow_read() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-read", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string()
+        )),
+        allow_read: Some(vec![]),
+        ..Flags::default()
+      }
+    );
   }
-}
-
-fn node_modules_and_vendor_dir_arg_parse(
-  flags: &mut Flags,
-  matches: &mut ArgMatches,
-) {
-  flags.node_modules_dir = matches.remove_one::<bool>("node-modules-dir");
-  flags.vendor = matches.remove_one::<bool>("vendor");
-}
 
-fn reload_arg_validate(urlstr: &str) -> Result<String, String> {
-  if urlstr.is_empty() {
-    return Err(String::from("Missing url. Check for extra commas."));
+  #[test]
+  fn deny_read() {
+    let r = flags_from_vec(svec!["deno", "run", "--deny-read", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string()
+        )),
+        deny_read: Some(vec![]),
+        ..Flags::default()
+      }
+    );
   }
-  match Url::from_str(urlstr) {
-    Ok(_) => Ok(urlstr.to_string()),
-    Err(e) => Err(e.to_string()),
+
+  #[test]
+  fn allow_hrtime() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-hrtime", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string(),
+        )),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn watch_arg_parse(matches: &mut ArgMatches) -> Option<WatchFlags> {
-  if matches.get_flag("watch") {
-    Some(WatchFlags {
-      hmr: false,
-      no_clear_screen: matches.get_flag("no-clear-screen"),
-    })
-  } else {
-    None
+  #[test]
+  fn deny_hrtime() {
+    let r = flags_from_vec(svec!["deno", "run", "--deny-hrtime", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "gist.ts".to_string(),
+        )),
+        deny_hrtime: true,
+        ..Flags::default()
+      }
+    );
   }
-}
 
-fn watch_arg_parse_with_paths(
-  matches: &mut ArgMatches,
-) -> Option<WatchFlagsWithPaths> {
-  if let Some(paths) = matches.remove_many::<PathBuf>("watch") {
-    return Some(WatchFlagsWithPaths {
-      paths: paths.collect(),
-      hmr: false,
-      no_clear_screen: matches.get_flag("no-clear-screen"),
-    });
+  #[test]
+  fn double_hyphen() {
+    // notice that flags passed after double dash will not
+    // be parsed to Flags but instead forwarded to
+    // script args as Deno.args
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-write",
+      "script.ts",
+      "--",
+      "-D",
+      "--allow-net"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        argv: svec!["--", "-D", "--allow-net"],
+        allow_write: Some(vec![]),
+        ..Flags::default()
+      }
+    );
   }
 
-  matches
-    .remove_many::<PathBuf>("hmr")
-    .map(|paths| WatchFlagsWithPaths {
-      paths: paths.collect(),
-      hmr: true,
-      no_clear_screen: matches.get_flag("no-clear-screen"),
-    })
-}
+  #[test]
+  fn fmt() {
+    let r = flags_from_vec(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
 
-// TODO(ry) move this to utility module and add test.
-/// Strips fragment part of URL. Panics on bad URL.
-pub fn resolve_urls(urls: Vec<String>) -> Vec<String> {
-  let mut out: Vec<String> = vec![];
-  for urlstr in urls.iter() {
-    if let Ok(mut url) = Url::from_str(urlstr) {
-      url.set_fragment(None);
-      let mut full_url = String::from(url.as_str());
-      if full_url.len() > 1 && full_url.ends_with('/') {
-        full_url.pop();
+    let r = flags_from_vec(svec!["deno", "fmt", "--check"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: true,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
       }
-      out.push(full_url);
-    } else {
-      panic!("Bad Url: {urlstr}");
-    }
-  }
-  out
-}
+    );
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use pretty_assertions::assert_eq;
+    let r = flags_from_vec(svec!["deno", "fmt"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
 
-  /// Creates vector of strings, Vec<String>
-  macro_rules! svec {
-    ($($x:expr),* $(,)?) => (vec![$($x.to_string()),*]);
-  }
+    let r = flags_from_vec(svec!["deno", "fmt", "--watch"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Some(Default::default()),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
 
-  #[test]
-  fn global_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "--unstable", "--log-level", "debug", "--quiet", "run", "script.ts"]);
+    let r =
+      flags_from_vec(svec!["deno", "fmt", "--watch", "--no-clear-screen"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Some(WatchFlags {
+            hmr: false,
+            no_clear_screen: true,
+          })
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "fmt",
+      "--check",
+      "--watch",
+      "foo.ts",
+      "--ignore=bar.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: true,
+          files: FileFlags {
+            include: vec!["foo.ts".to_string()],
+            ignore: vec!["bar.js".to_string()],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Some(Default::default()),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
 
-    let flags = r.unwrap();
+    let r = flags_from_vec(svec!["deno", "fmt", "--config", "deno.jsonc"]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string()
-        )),
-        unstable_config: UnstableConfig {
-          legacy_flag_enabled: true,
-          ..Default::default()
-        },
-        log_level: Some(Level::Error),
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
-    #[rustfmt::skip]
-    let r2 = flags_from_vec(svec!["deno", "run", "--unstable", "--log-level", "debug", "--quiet", "script.ts"]);
-    let flags2 = r2.unwrap();
-    assert_eq!(flags2, flags);
-  }
 
-  #[test]
-  fn upgrade() {
-    let r = flags_from_vec(svec!["deno", "upgrade", "--dry-run", "--force"]);
-    let flags = r.unwrap();
+    let r = flags_from_vec(svec![
+      "deno",
+      "fmt",
+      "--config",
+      "deno.jsonc",
+      "--watch",
+      "foo.ts"
+    ]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
-          force: true,
-          dry_run: true,
-          canary: false,
-          version: None,
-          output: None,
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec!["foo.ts".to_string()],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          watch: Some(Default::default()),
         }),
+        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
+        ext: Some("ts".to_string()),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn version() {
-    let r = flags_from_vec(svec!["deno", "--version"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "fmt",
+      "--use-tabs",
+      "--line-width",
+      "60",
+      "--indent-width",
+      "4",
+      "--single-quote",
+      "--prose-wrap",
+      "never",
+      "--no-semicolons",
+    ]);
     assert_eq!(
-      r.unwrap_err().kind(),
-      clap::error::ErrorKind::DisplayVersion
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: Some(true),
+          line_width: Some(NonZeroU32::new(60).unwrap()),
+          indent_width: Some(NonZeroU8::new(4).unwrap()),
+          single_quote: Some(true),
+          prose_wrap: Some("never".to_string()),
+          no_semicolons: Some(true),
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
     );
-    let r = flags_from_vec(svec!["deno", "-V"]);
+
+    // try providing =false to the booleans
+    let r = flags_from_vec(svec![
+      "deno",
+      "fmt",
+      "--use-tabs=false",
+      "--single-quote=false",
+      "--no-semicolons=false",
+    ]);
     assert_eq!(
-      r.unwrap_err().kind(),
-      clap::error::ErrorKind::DisplayVersion
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: Some(false),
+          line_width: None,
+          indent_width: None,
+          single_quote: Some(false),
+          prose_wrap: None,
+          no_semicolons: Some(false),
+          watch: Default::default(),
+        }),
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
     );
   }
 
   #[test]
-  fn run_reload() {
-    let r = flags_from_vec(svec!["deno", "run", "-r", "script.ts"]);
-    let flags = r.unwrap();
+  fn lint() {
+    let r = flags_from_vec(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string()
-        )),
-        reload: true,
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string(),],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Default::default(),
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn run_watch() {
-    let r = flags_from_vec(svec!["deno", "run", "--watch", "script.ts"]);
-    let flags = r.unwrap();
+    let r = flags_from_vec(svec![
+      "deno",
+      "lint",
+      "--watch",
+      "script_1.ts",
+      "script_2.ts"
+    ]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
-            hmr: false,
-            paths: vec![],
-            no_clear_screen: false,
-          }),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Some(Default::default()),
         }),
         ..Flags::default()
       }
@@ -4394,22 +6239,73 @@ mod tests {
 
     let r = flags_from_vec(svec![
       "deno",
-      "run",
+      "lint",
       "--watch",
       "--no-clear-screen",
-      "script.ts"
+      "script_1.ts",
+      "script_2.ts"
     ]);
-    let flags = r.unwrap();
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Some(WatchFlags {
             hmr: false,
-            paths: vec![],
             no_clear_screen: true,
-          }),
+          })
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "lint", "--ignore=script_1.ts,script_2.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--rules"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: true,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Default::default(),
         }),
         ..Flags::default()
       }
@@ -4417,22 +6313,25 @@ mod tests {
 
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--unstable-hmr",
-      "--no-clear-screen",
-      "script.ts"
+      "lint",
+      "--rules",
+      "--rules-tags=recommended"
     ]);
-    let flags = r.unwrap();
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
-            hmr: true,
-            paths: vec![],
-            no_clear_screen: true,
-          }),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: true,
+          maybe_rules_tags: Some(svec!["recommended"]),
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          watch: Default::default(),
         }),
         ..Flags::default()
       }
@@ -4440,1246 +6339,1109 @@ mod tests {
 
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--unstable-hmr=foo.txt",
-      "--no-clear-screen",
-      "script.ts"
+      "lint",
+      "--rules-tags=",
+      "--rules-include=ban-untagged-todo,no-undef",
+      "--rules-exclude=no-const-assign"
     ]);
-    let flags = r.unwrap();
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
-            hmr: true,
-            paths: vec![PathBuf::from("foo.txt")],
-            no_clear_screen: true,
-          }),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: Some(svec![""]),
+          maybe_rules_include: Some(svec!["ban-untagged-todo", "no-undef"]),
+          maybe_rules_exclude: Some(svec!["no-const-assign"]),
+          json: false,
+          compact: false,
+          watch: Default::default(),
         }),
         ..Flags::default()
       }
     );
 
-    let r =
-      flags_from_vec(svec!["deno", "run", "--hmr", "--watch", "script.ts"]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn run_watch_with_external() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--watch=file1,file2", "script.ts"]);
-    let flags = r.unwrap();
+    let r = flags_from_vec(svec!["deno", "lint", "--json", "script_1.ts"]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
-            hmr: false,
-            paths: vec![PathBuf::from("file1"), PathBuf::from("file2")],
-            no_clear_screen: false,
-          }),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string()],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: true,
+          compact: false,
+          watch: Default::default(),
         }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn run_watch_with_no_clear_screen() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--watch",
-      "--no-clear-screen",
-      "script.ts"
+      "lint",
+      "--config",
+      "Deno.jsonc",
+      "--json",
+      "script_1.ts"
     ]);
-
-    let flags = r.unwrap();
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-          watch: Some(WatchFlagsWithPaths {
-            hmr: false,
-            paths: vec![],
-            no_clear_screen: true,
-          }),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string()],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: true,
+          compact: false,
+          watch: Default::default(),
         }),
+        config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn run_reload_allow_write() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "-r", "--allow-write", "script.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "lint",
+      "--config",
+      "Deno.jsonc",
+      "--compact",
+      "script_1.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        reload: true,
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string()
-        )),
-        allow_write: Some(vec![]),
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec!["script_1.ts".to_string()],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: true,
+          watch: Default::default(),
+        }),
+        config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_v8_flags() {
-    let r = flags_from_vec(svec!["deno", "run", "--v8-flags=--help"]);
+  fn types() {
+    let r = flags_from_vec(svec!["deno", "types"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default("_".to_string())),
-        v8_flags: svec!["--help"],
+        subcommand: DenoSubcommand::Types,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--v8-flags=--expose-gc,--gc-stats=1",
-      "script.ts"
-    ]);
+  #[test]
+  fn cache() {
+    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string()
-        )),
-        v8_flags: svec!["--expose-gc", "--gc-stats=1"],
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+        }),
         ..Flags::default()
       }
     );
-
-    let r = flags_from_vec(svec!["deno", "run", "--v8-flags=--expose-gc"]);
-    assert!(r
-      .unwrap_err()
-      .to_string()
-      .contains("[SCRIPT_ARG] may only be omitted with --v8-flags=--help"));
-  }
-
-  #[test]
-  fn has_permission() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-read", "x.ts"]);
-    assert_eq!(r.unwrap().has_permission(), true);
-
-    let r = flags_from_vec(svec!["deno", "run", "--deny-read", "x.ts"]);
-    assert_eq!(r.unwrap().has_permission(), true);
-
-    let r = flags_from_vec(svec!["deno", "run", "x.ts"]);
-    assert_eq!(r.unwrap().has_permission(), false);
-  }
-
-  #[test]
-  fn has_permission_in_argv() {
-    let r = flags_from_vec(svec!["deno", "run", "x.ts", "--allow-read"]);
-    assert_eq!(r.unwrap().has_permission_in_argv(), true);
-
-    let r = flags_from_vec(svec!["deno", "run", "x.ts", "--deny-read"]);
-    assert_eq!(r.unwrap().has_permission_in_argv(), true);
-
-    let r = flags_from_vec(svec!["deno", "run", "x.ts"]);
-    assert_eq!(r.unwrap().has_permission_in_argv(), false);
   }
 
   #[test]
-  fn script_args() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-net",
-      "gist.ts",
-      "--title",
-      "X"
-    ]);
+  fn check() {
+    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string()
-        )),
-        argv: svec!["--title", "X"],
-        allow_net: Some(vec![]),
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+        }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+
+    for all_flag in ["--remote", "--all"] {
+      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
+      assert_eq!(
+        r.unwrap(),
+        Flags {
+          subcommand: DenoSubcommand::Check(CheckFlags {
+            files: svec!["script.ts"],
+          }),
+          type_check_mode: TypeCheckMode::All,
+          ..Flags::default()
+        }
+      );
+
+      let r = flags_from_vec(svec![
+        "deno",
+        "check",
+        all_flag,
+        "--no-remote",
+        "script.ts"
+      ]);
+      assert_eq!(
+        r.unwrap_err().kind(),
+        clap::error::ErrorKind::ArgumentConflict
+      );
+    }
   }
 
   #[test]
-  fn allow_all() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-all", "gist.ts"]);
+  fn info() {
+    let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string()
-        )),
-        allow_all: true,
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn allow_read() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-read", "gist.ts"]);
+    let r = flags_from_vec(svec!["deno", "info", "--reload", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string()
-        )),
-        allow_read: Some(vec![]),
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+        }),
+        reload: true,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn deny_read() {
-    let r = flags_from_vec(svec!["deno", "run", "--deny-read", "gist.ts"]);
+    let r = flags_from_vec(svec!["deno", "info", "--json", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string()
-        )),
-        deny_read: Some(vec![]),
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: true,
+          file: Some("script.ts".to_string()),
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn allow_hrtime() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-hrtime", "gist.ts"]);
+    let r = flags_from_vec(svec!["deno", "info"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string(),
-        )),
-        allow_hrtime: true,
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: None
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn deny_hrtime() {
-    let r = flags_from_vec(svec!["deno", "run", "--deny-hrtime", "gist.ts"]);
+    let r = flags_from_vec(svec!["deno", "info", "--json"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "gist.ts".to_string(),
-        )),
-        deny_hrtime: true,
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: true,
+          file: None
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn double_hyphen() {
-    // notice that flags passed after double dash will not
-    // be parsed to Flags but instead forwarded to
-    // script args as Deno.args
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--allow-write",
-      "script.ts",
-      "--",
-      "-D",
-      "--allow-net"
+      "info",
+      "--no-npm",
+      "--no-remote",
+      "--config",
+      "tsconfig.json"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        argv: svec!["--", "-D", "--allow-net"],
-        allow_write: Some(vec![]),
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: None
+        }),
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        no_npm: true,
+        no_remote: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn fmt() {
-    let r = flags_from_vec(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
+  fn tsconfig() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "-c", "tsconfig.json", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Default::default(),
-        }),
-        ext: Some("ts".to_string()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "fmt", "--check"]);
+  #[test]
+  fn eval() {
+    let r = flags_from_vec(svec!["deno", "eval", "'console.log(\"hello\")'"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: true,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "'console.log(\"hello\")'".to_string(),
         }),
-        ext: Some("ts".to_string()),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "fmt"]);
+  #[test]
+  fn eval_p() {
+    let r = flags_from_vec(svec!["deno", "eval", "-p", "1+2"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: true,
+          code: "1+2".to_string(),
         }),
-        ext: Some("ts".to_string()),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "fmt", "--watch"]);
+  #[test]
+  fn eval_typescript() {
+    let r =
+      flags_from_vec(svec!["deno", "eval", "-T", "'console.log(\"hello\")'"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Some(Default::default()),
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "'console.log(\"hello\")'".to_string(),
         }),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
         ext: Some("ts".to_string()),
         ..Flags::default()
-      }
-    );
-
-    let r =
-      flags_from_vec(svec!["deno", "fmt", "--watch", "--no-clear-screen"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Some(WatchFlags {
-            hmr: false,
-            no_clear_screen: true,
-          })
+      }
+    );
+  }
+
+  #[test]
+  fn eval_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "eval", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--env=.example.env", "42"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "42".to_string(),
         }),
-        ext: Some("ts".to_string()),
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        env_file: Some(".example.env".to_owned()),
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn eval_args() {
     let r = flags_from_vec(svec![
       "deno",
-      "fmt",
-      "--check",
-      "--watch",
-      "foo.ts",
-      "--ignore=bar.js"
+      "eval",
+      "console.log(Deno.args)",
+      "arg1",
+      "arg2"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: true,
-          files: FileFlags {
-            include: vec!["foo.ts".to_string()],
-            ignore: vec!["bar.js".to_string()],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Some(Default::default()),
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "console.log(Deno.args)".to_string(),
         }),
-        ext: Some("ts".to_string()),
+        argv: svec!["arg1", "arg2"],
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "fmt", "--config", "deno.jsonc"]);
+  #[test]
+  fn repl() {
+    let r = flags_from_vec(svec!["deno"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: true,
         }),
-        ext: Some("ts".to_string()),
-        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
+        allow_net: Some(vec![]),
+        unsafely_ignore_certificate_errors: None,
+        allow_env: Some(vec![]),
+        deny_env: None,
+        allow_run: Some(vec![]),
+        deny_run: None,
+        allow_read: Some(vec![]),
+        deny_read: None,
+        allow_sys: Some(vec![]),
+        deny_sys: None,
+        allow_write: Some(vec![]),
+        deny_write: None,
+        allow_ffi: Some(vec![]),
+        deny_ffi: None,
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "fmt",
-      "--config",
-      "deno.jsonc",
-      "--watch",
-      "foo.ts"
-    ]);
+  #[test]
+  fn repl_strace_ops() {
+    // Lightly test this undocumented flag
+    let r = flags_from_vec(svec!["deno", "repl", "--strace-ops"]);
+    assert_eq!(r.unwrap().strace_ops, Some(vec![]));
+    let r =
+      flags_from_vec(svec!["deno", "repl", "--strace-ops=http,websocket"]);
+    assert_eq!(
+      r.unwrap().strace_ops,
+      Some(vec!["http".to_string(), "websocket".to_string()])
+    );
+  }
+
+  #[test]
+  fn repl_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "-A", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--unsafely-ignore-certificate-errors", "--env=.example.env"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec!["foo.ts".to_string()],
-            ignore: vec![],
-          },
-          use_tabs: None,
-          line_width: None,
-          indent_width: None,
-          single_quote: None,
-          prose_wrap: None,
-          no_semicolons: None,
-          watch: Some(Default::default()),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: false,
         }),
-        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
-        ext: Some("ts".to_string()),
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        env_file: Some(".example.env".to_owned()),
+        unsafely_ignore_certificate_errors: Some(vec![]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "fmt",
-      "--use-tabs",
-      "--line-width",
-      "60",
-      "--indent-width",
-      "4",
-      "--single-quote",
-      "--prose-wrap",
-      "never",
-      "--no-semicolons",
-    ]);
+  #[test]
+  fn repl_with_eval_flag() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "--allow-write", "--eval", "console.log('hello');"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: Some(true),
-          line_width: Some(NonZeroU32::new(60).unwrap()),
-          indent_width: Some(NonZeroU8::new(4).unwrap()),
-          single_quote: Some(true),
-          prose_wrap: Some("never".to_string()),
-          no_semicolons: Some(true),
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: Some("console.log('hello');".to_string()),
+          is_default_command: false,
         }),
-        ext: Some("ts".to_string()),
+        allow_write: Some(vec![]),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
+  }
 
-    // try providing =false to the booleans
-    let r = flags_from_vec(svec![
-      "deno",
-      "fmt",
-      "--use-tabs=false",
-      "--single-quote=false",
-      "--no-semicolons=false",
-    ]);
+  #[test]
+  fn repl_with_eval_file_flag() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "--eval-file=./a.js,./b.ts,https://examples.deno.land/hello-world.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt(FmtFlags {
-          check: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          use_tabs: Some(false),
-          line_width: None,
-          indent_width: None,
-          single_quote: Some(false),
-          prose_wrap: None,
-          no_semicolons: Some(false),
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: Some(vec![
+            "./a.js".to_string(),
+            "./b.ts".to_string(),
+            "https://examples.deno.land/hello-world.ts".to_string()
+          ]),
+          eval: None,
+          is_default_command: false,
         }),
-        ext: Some("ts".to_string()),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn lint() {
-    let r = flags_from_vec(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string(),],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Default::default(),
-        }),
-        ..Flags::default()
-      }
-    );
+  fn allow_read_allowlist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
 
     let r = flags_from_vec(svec![
       "deno",
-      "lint",
-      "--watch",
-      "script_1.ts",
-      "script_2.ts"
+      "run",
+      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Some(Default::default()),
-        }),
+        allow_read: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         ..Flags::default()
       }
     );
+  }
+
+  #[test]
+  fn deny_read_denylist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
 
     let r = flags_from_vec(svec![
       "deno",
-      "lint",
-      "--watch",
-      "--no-clear-screen",
-      "script_1.ts",
-      "script_2.ts"
+      "run",
+      format!("--deny-read=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Some(WatchFlags {
-            hmr: false,
-            no_clear_screen: true,
-          })
-        }),
+        deny_read: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         ..Flags::default()
       }
     );
+  }
 
-    let r =
-      flags_from_vec(svec!["deno", "lint", "--ignore=script_1.ts,script_2.ts"]);
+  #[test]
+  fn allow_write_allowlist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec![],
-            ignore: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Default::default(),
-        }),
+        allow_write: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "lint", "--rules"]);
+  #[test]
+  fn deny_write_denylist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      format!("--deny-write=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          rules: true,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Default::default(),
-        }),
+        deny_write: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn allow_net_allowlist() {
     let r = flags_from_vec(svec![
       "deno",
-      "lint",
-      "--rules",
-      "--rules-tags=recommended"
+      "run",
+      "--allow-net=127.0.0.1",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          rules: true,
-          maybe_rules_tags: Some(svec!["recommended"]),
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: false,
-          watch: Default::default(),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_net: Some(svec!["127.0.0.1"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "lint",
-      "--rules-tags=",
-      "--rules-include=ban-untagged-todo,no-undef",
-      "--rules-exclude=no-const-assign"
-    ]);
+  #[test]
+  fn deny_net_denylist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-net=127.0.0.1", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: Some(svec![""]),
-          maybe_rules_include: Some(svec!["ban-untagged-todo", "no-undef"]),
-          maybe_rules_exclude: Some(svec!["no-const-assign"]),
-          json: false,
-          compact: false,
-          watch: Default::default(),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_net: Some(svec!["127.0.0.1"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "lint", "--json", "script_1.ts"]);
+  #[test]
+  fn allow_env_allowlist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string()],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: true,
-          compact: false,
-          watch: Default::default(),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_env: Some(svec!["HOME"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "lint",
-      "--config",
-      "Deno.jsonc",
-      "--json",
-      "script_1.ts"
-    ]);
+  #[test]
+  fn deny_env_denylist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-env=HOME", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string()],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: true,
-          compact: false,
-          watch: Default::default(),
-        }),
-        config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_env: Some(svec!["HOME"]),
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn allow_env_allowlist_multiple() {
     let r = flags_from_vec(svec![
       "deno",
-      "lint",
-      "--config",
-      "Deno.jsonc",
-      "--compact",
-      "script_1.ts"
+      "run",
+      "--allow-env=HOME,PATH",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Lint(LintFlags {
-          files: FileFlags {
-            include: vec!["script_1.ts".to_string()],
-            ignore: vec![],
-          },
-          rules: false,
-          maybe_rules_tags: None,
-          maybe_rules_include: None,
-          maybe_rules_exclude: None,
-          json: false,
-          compact: true,
-          watch: Default::default(),
-        }),
-        config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_env: Some(svec!["HOME", "PATH"]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn types() {
-    let r = flags_from_vec(svec!["deno", "types"]);
+  fn deny_env_denylist_multiple() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-env=HOME,PATH", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Types,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_env: Some(svec!["HOME", "PATH"]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cache() {
-    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts"],
-        }),
-        ..Flags::default()
-      }
-    );
+  fn allow_env_allowlist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
+    assert!(r.is_ok());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=H=ME", "script.ts"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=H\0ME", "script.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn check() {
-    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
+  fn deny_env_denylist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-env=HOME", "script.ts"]);
+    assert!(r.is_ok());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-env=H=ME", "script.ts"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-env=H\0ME", "script.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn allow_sys() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-sys", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Check(CheckFlags {
-          files: svec!["script.ts"],
-        }),
-        type_check_mode: TypeCheckMode::Local,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_sys: Some(vec![]),
         ..Flags::default()
       }
     );
-
-    for all_flag in ["--remote", "--all"] {
-      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
-      assert_eq!(
-        r.unwrap(),
-        Flags {
-          subcommand: DenoSubcommand::Check(CheckFlags {
-            files: svec!["script.ts"],
-          }),
-          type_check_mode: TypeCheckMode::All,
-          ..Flags::default()
-        }
-      );
-
-      let r = flags_from_vec(svec![
-        "deno",
-        "check",
-        all_flag,
-        "--no-remote",
-        "script.ts"
-      ]);
-      assert_eq!(
-        r.unwrap_err().kind(),
-        clap::error::ErrorKind::ArgumentConflict
-      );
-    }
   }
 
   #[test]
-  fn info() {
-    let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
+  fn deny_sys() {
+    let r = flags_from_vec(svec!["deno", "run", "--deny-sys", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("script.ts".to_string()),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_sys: Some(vec![]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "info", "--reload", "script.ts"]);
+  #[test]
+  fn allow_sys_allowlist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("script.ts".to_string()),
-        }),
-        reload: true,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_sys: Some(svec!["hostname"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "info", "--json", "script.ts"]);
+  #[test]
+  fn deny_sys_denylist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-sys=hostname", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: true,
-          file: Some("script.ts".to_string()),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_sys: Some(svec!["hostname"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "info"]);
+  #[test]
+  fn allow_sys_allowlist_multiple() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,osRelease",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: None
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_sys: Some(svec!["hostname", "osRelease"]),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "info", "--json"]);
+  #[test]
+  fn deny_sys_denylist_multiple() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--deny-sys=hostname,osRelease",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: true,
-          file: None
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_sys: Some(svec!["hostname", "osRelease"]),
         ..Flags::default()
       }
     );
+  }
+
+  #[test]
+  fn allow_sys_allowlist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
+    assert!(r.is_ok());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,osRelease",
+      "script.ts"
+    ]);
+    assert!(r.is_ok());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=foo", "script.ts"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,foo",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn deny_sys_denylist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--deny-sys=hostname", "script.ts"]);
+    assert!(r.is_ok());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--deny-sys=hostname,osRelease",
+      "script.ts"
+    ]);
+    assert!(r.is_ok());
+    let r = flags_from_vec(svec!["deno", "run", "--deny-sys=foo", "script.ts"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--deny-sys=hostname,foo",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn reload_validator() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/",
+      "script.ts"
+    ]);
+    assert!(r.is_ok(), "should accept valid urls");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,http://deno.land/b",
+      "script.ts"
+    ]);
+    assert!(r.is_ok(), "should accept accept multiple valid urls");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=./relativeurl/",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject relative urls that start with ./");
 
     let r = flags_from_vec(svec![
       "deno",
-      "info",
-      "--no-npm",
-      "--no-remote",
-      "--config",
-      "tsconfig.json"
+      "run",
+      "--reload=relativeurl/",
+      "script.ts"
     ]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: None
-        }),
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        no_npm: true,
-        no_remote: true,
-        ..Flags::default()
-      }
-    );
-  }
+    assert!(r.is_err(), "Should reject relative urls");
 
-  #[test]
-  fn tsconfig() {
     let r =
-      flags_from_vec(svec!["deno", "run", "-c", "tsconfig.json", "script.ts"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        ..Flags::default()
-      }
-    );
-  }
+      flags_from_vec(svec!["deno", "run", "--reload=/absolute", "script.ts"]);
+    assert!(r.is_err(), "Should reject absolute urls");
 
-  #[test]
-  fn eval() {
-    let r = flags_from_vec(svec!["deno", "eval", "'console.log(\"hello\")'"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "'console.log(\"hello\")'".to_string(),
-        }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        ..Flags::default()
-      }
-    );
-  }
+    let r = flags_from_vec(svec!["deno", "run", "--reload=/", "script.ts"]);
+    assert!(r.is_err(), "Should reject absolute root url");
 
-  #[test]
-  fn eval_p() {
-    let r = flags_from_vec(svec!["deno", "eval", "-p", "1+2"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: true,
-          code: "1+2".to_string(),
-        }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        ..Flags::default()
-      }
-    );
-  }
+    let r = flags_from_vec(svec!["deno", "run", "--reload=", "script.ts"]);
+    assert!(r.is_err(), "Should reject when nothing is provided");
 
-  #[test]
-  fn eval_typescript() {
-    let r =
-      flags_from_vec(svec!["deno", "eval", "-T", "'console.log(\"hello\")'"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "'console.log(\"hello\")'".to_string(),
-        }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        ext: Some("ts".to_string()),
-        ..Flags::default()
-      }
-    );
+    let r = flags_from_vec(svec!["deno", "run", "--reload=,", "script.ts"]);
+    assert!(r.is_err(), "Should reject when a single comma is provided");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=,http://deno.land/a",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject a leading comma");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject a trailing comma");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,,http://deno.land/b",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject adjacent commas");
   }
 
   #[test]
-  fn eval_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "eval", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--env=.example.env", "42"]);
+  fn bundle() {
+    let r = flags_from_vec(svec!["deno", "bundle", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "42".to_string(),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Default::default(),
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        env_file: Some(".example.env".to_owned()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn eval_args() {
+  fn bundle_with_config() {
     let r = flags_from_vec(svec![
       "deno",
-      "eval",
-      "console.log(Deno.args)",
-      "arg1",
-      "arg2"
+      "bundle",
+      "--no-remote",
+      "--config",
+      "tsconfig.json",
+      "source.ts",
+      "bundle.js"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "console.log(Deno.args)".to_string(),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: Some("bundle.js".to_string()),
+          watch: Default::default(),
         }),
-        argv: svec!["arg1", "arg2"],
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
         allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
+        no_remote: true,
+        type_check_mode: TypeCheckMode::Local,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl() {
-    let r = flags_from_vec(svec!["deno"]);
+  fn bundle_with_output() {
+    let r = flags_from_vec(svec!["deno", "bundle", "source.ts", "bundle.js"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: true,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: Some("bundle.js".to_string()),
+          watch: Default::default(),
         }),
-        allow_net: Some(vec![]),
-        unsafely_ignore_certificate_errors: None,
-        allow_env: Some(vec![]),
-        deny_env: None,
-        allow_run: Some(vec![]),
-        deny_run: None,
-        allow_read: Some(vec![]),
-        deny_read: None,
-        allow_sys: Some(vec![]),
-        deny_sys: None,
+        type_check_mode: TypeCheckMode::Local,
         allow_write: Some(vec![]),
-        deny_write: None,
-        allow_ffi: Some(vec![]),
-        deny_ffi: None,
-        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_strace_ops() {
-    // Lightly test this undocumented flag
-    let r = flags_from_vec(svec!["deno", "repl", "--strace-ops"]);
-    assert_eq!(r.unwrap().strace_ops, Some(vec![]));
-    let r =
-      flags_from_vec(svec!["deno", "repl", "--strace-ops=http,websocket"]);
-    assert_eq!(
-      r.unwrap().strace_ops,
-      Some(vec!["http".to_string(), "websocket".to_string()])
-    );
-  }
-
-  #[test]
-  fn repl_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "-A", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--unsafely-ignore-certificate-errors", "--env=.example.env"]);
+  fn bundle_with_lock() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--lock-write",
+      "--lock=lock.json",
+      "source.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Default::default(),
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
+        type_check_mode: TypeCheckMode::Local,
         lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_all: true,
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        env_file: Some(".example.env".to_owned()),
-        unsafely_ignore_certificate_errors: Some(vec![]),
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_with_eval_flag() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "--allow-write", "--eval", "console.log('hello');"]);
+  fn bundle_with_reload() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--reload", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: Some("console.log('hello');".to_string()),
-          is_default_command: false,
+        reload: true,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Default::default(),
         }),
-        allow_write: Some(vec![]),
-        type_check_mode: TypeCheckMode::None,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_with_eval_file_flag() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "--eval-file=./a.js,./b.ts,https://examples.deno.land/hello-world.ts"]);
+  fn bundle_nocheck() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--no-check", "script.ts"])
+      .unwrap();
     assert_eq!(
-      r.unwrap(),
+      r,
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: Some(vec![
-            "./a.js".to_string(),
-            "./b.ts".to_string(),
-            "https://examples.deno.land/hello-world.ts".to_string()
-          ]),
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "script.ts".to_string(),
+          out_file: None,
+          watch: Default::default(),
         }),
         type_check_mode: TypeCheckMode::None,
         ..Flags::default()
@@ -5688,570 +7450,431 @@ mod tests {
   }
 
   #[test]
-  fn allow_read_allowlist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
-      "script.ts"
-    ]);
+  fn bundle_watch() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--watch", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_read: Some(vec![PathBuf::from("."), temp_dir]),
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Some(Default::default()),
+        }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
-    );
+    )
   }
 
   #[test]
-  fn deny_read_denylist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
-
+  fn bundle_watch_with_no_clear_screen() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      format!("--deny-read=.,{}", temp_dir.to_str().unwrap()),
-      "script.ts"
+      "bundle",
+      "--watch",
+      "--no-clear-screen",
+      "source.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        deny_read: Some(vec![PathBuf::from("."), temp_dir]),
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Some(WatchFlags {
+            hmr: false,
+            no_clear_screen: true,
+          }),
+        }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
-    );
+    )
   }
 
   #[test]
-  fn allow_write_allowlist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
-
+  fn run_import_map() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
+      "--import-map=import_map.json",
       "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_write: Some(vec![PathBuf::from("."), temp_dir]),
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_write_denylist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
-
+  fn info_import_map() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      format!("--deny-write=.,{}", temp_dir.to_str().unwrap()),
+      "info",
+      "--import-map=import_map.json",
       "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        deny_write: Some(vec![PathBuf::from("."), temp_dir]),
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          file: Some("script.ts".to_string()),
+          json: false,
+        }),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_allowlist() {
+  fn cache_import_map() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--allow-net=127.0.0.1",
+      "cache",
+      "--import-map=import_map.json",
       "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        allow_net: Some(svec!["127.0.0.1"]),
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+        }),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_net_denylist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-net=127.0.0.1", "script.ts"]);
+  fn doc_import_map() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--import-map=import_map.json",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        deny_net: Some(svec!["127.0.0.1"]),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          source_files: DocSourceFileFlag::Paths(vec!["script.ts".to_owned()]),
+          private: false,
+          json: false,
+          html: None,
+          lint: false,
+          filter: None,
+        }),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_env_allowlist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
+  fn run_env_file_default() {
+    let r = flags_from_vec(svec!["deno", "run", "--env", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        allow_env: Some(svec!["HOME"]),
+        env_file: Some(".env".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_env_denylist() {
+  fn run_env_file_defined() {
     let r =
-      flags_from_vec(svec!["deno", "run", "--deny-env=HOME", "script.ts"]);
+      flags_from_vec(svec!["deno", "run", "--env=.another_env", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        deny_env: Some(svec!["HOME"]),
+        env_file: Some(".another_env".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_env_allowlist_multiple() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-env=HOME,PATH",
-      "script.ts"
-    ]);
+  fn cache_multiple() {
+    let r =
+      flags_from_vec(svec!["deno", "cache", "script.ts", "script_two.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        allow_env: Some(svec!["HOME", "PATH"]),
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts", "script_two.ts"],
+        }),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_env_denylist_multiple() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-env=HOME,PATH", "script.ts"]);
+  fn run_seed() {
+    let r = flags_from_vec(svec!["deno", "run", "--seed", "250", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        deny_env: Some(svec!["HOME", "PATH"]),
+        seed: Some(250_u64),
+        v8_flags: svec!["--random-seed=250"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_env_allowlist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
-    assert!(r.is_ok());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=H=ME", "script.ts"]);
-    assert!(r.is_err());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=H\0ME", "script.ts"]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn deny_env_denylist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-env=HOME", "script.ts"]);
-    assert!(r.is_ok());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-env=H=ME", "script.ts"]);
-    assert!(r.is_err());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-env=H\0ME", "script.ts"]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn allow_sys() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-sys", "script.ts"]);
+  fn run_seed_with_v8_flags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--seed",
+      "250",
+      "--v8-flags=--expose-gc",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        allow_sys: Some(vec![]),
+        seed: Some(250_u64),
+        v8_flags: svec!["--expose-gc", "--random-seed=250"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_sys() {
-    let r = flags_from_vec(svec!["deno", "run", "--deny-sys", "script.ts"]);
+  fn install() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "install",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        deny_sys: Some(vec![]),
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          name: None,
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: vec![],
+          root: None,
+          force: false,
+        }),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_sys_allowlist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
+  fn install_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "install", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--name", "file_server", "--root", "/foo", "--force", "--env=.example.env", "https://deno.land/std/http/file_server.ts", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        allow_sys: Some(svec!["hostname"]),
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          name: Some("file_server".to_string()),
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: svec!["foo", "bar"],
+          root: Some("/foo".to_string()),
+          force: true,
+        }),
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_net: Some(vec![]),
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        allow_read: Some(vec![]),
+        env_file: Some(".example.env".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_sys_denylist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-sys=hostname", "script.ts"]);
+  fn uninstall() {
+    let r = flags_from_vec(svec!["deno", "uninstall", "file_server"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        deny_sys: Some(svec!["hostname"]),
+        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
+          name: "file_server".to_string(),
+          root: None,
+        }),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_sys_allowlist_multiple() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-sys=hostname,osRelease",
-      "script.ts"
-    ]);
+  fn uninstall_with_help_flag() {
+    let r = flags_from_vec(svec!["deno", "uninstall", "--help"]);
+    assert_eq!(r.err().unwrap().kind(), clap::error::ErrorKind::DisplayHelp);
+  }
+
+  #[test]
+  fn log_level() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--log-level=debug", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        allow_sys: Some(svec!["hostname", "osRelease"]),
+        log_level: Some(Level::Debug),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_sys_denylist_multiple() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--deny-sys=hostname,osRelease",
-      "script.ts"
-    ]);
+  fn quiet() {
+    let r = flags_from_vec(svec!["deno", "run", "-q", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        deny_sys: Some(svec!["hostname", "osRelease"]),
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_sys_allowlist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
-    assert!(r.is_ok());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-sys=hostname,osRelease",
-      "script.ts"
-    ]);
-    assert!(r.is_ok());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=foo", "script.ts"]);
-    assert!(r.is_err());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-sys=hostname,foo",
-      "script.ts"
-    ]);
-    assert!(r.is_err());
-  }
+  fn completions() {
+    let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
 
-  #[test]
-  fn deny_sys_denylist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--deny-sys=hostname", "script.ts"]);
-    assert!(r.is_ok());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--deny-sys=hostname,osRelease",
-      "script.ts"
-    ]);
-    assert!(r.is_ok());
-    let r = flags_from_vec(svec!["deno", "run", "--deny-sys=foo", "script.ts"]);
-    assert!(r.is_err());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--deny-sys=hostname,foo",
-      "script.ts"
-    ]);
-    assert!(r.is_err());
+    match r.subcommand {
+      DenoSubcommand::Completions(CompletionsFlags { buf }) => {
+        assert!(!buf.is_empty())
+      }
+      _ => unreachable!(),
+    }
   }
 
   #[test]
-  fn reload_validator() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/",
-      "script.ts"
-    ]);
-    assert!(r.is_ok(), "should accept valid urls");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/a,http://deno.land/b",
-      "script.ts"
-    ]);
-    assert!(r.is_ok(), "should accept accept multiple valid urls");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=./relativeurl/",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject relative urls that start with ./");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=relativeurl/",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject relative urls");
-
-    let r =
-      flags_from_vec(svec!["deno", "run", "--reload=/absolute", "script.ts"]);
-    assert!(r.is_err(), "Should reject absolute urls");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=/", "script.ts"]);
-    assert!(r.is_err(), "Should reject absolute root url");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=", "script.ts"]);
-    assert!(r.is_err(), "Should reject when nothing is provided");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=,", "script.ts"]);
-    assert!(r.is_err(), "Should reject when a single comma is provided");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=,http://deno.land/a",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject a leading comma");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/a,",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject a trailing comma");
-
+  fn run_with_args() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--reload=http://deno.land/a,,http://deno.land/b",
-      "script.ts"
+      "script.ts",
+      "--allow-read",
+      "--allow-net"
     ]);
-    assert!(r.is_err(), "Should reject adjacent commas");
-  }
-
-  #[test]
-  fn bundle() {
-    let r = flags_from_vec(svec!["deno", "bundle", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Default::default(),
-        }),
-        type_check_mode: TypeCheckMode::Local,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        argv: svec!["--allow-read", "--allow-net"],
         ..Flags::default()
       }
     );
-  }
-
-  #[test]
-  fn bundle_with_config() {
     let r = flags_from_vec(svec![
       "deno",
-      "bundle",
-      "--no-remote",
-      "--config",
-      "tsconfig.json",
-      "source.ts",
-      "bundle.js"
+      "run",
+      "--location",
+      "https:foo",
+      "--allow-read",
+      "script.ts",
+      "--allow-net",
+      "-r",
+      "--help",
+      "--foo",
+      "bar"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: Some("bundle.js".to_string()),
-          watch: Default::default(),
-        }),
-        allow_write: Some(vec![]),
-        no_remote: true,
-        type_check_mode: TypeCheckMode::Local,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        location: Some(Url::parse("https://foo/").unwrap()),
+        allow_read: Some(vec![]),
+        argv: svec!["--allow-net", "-r", "--help", "--foo", "bar"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn bundle_with_output() {
-    let r = flags_from_vec(svec!["deno", "bundle", "source.ts", "bundle.js"]);
+    let r = flags_from_vec(svec!["deno", "run", "script.ts", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: Some("bundle.js".to_string()),
-          watch: Default::default(),
-        }),
-        type_check_mode: TypeCheckMode::Local,
-        allow_write: Some(vec![]),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        argv: svec!["foo", "bar"],
         ..Flags::default()
       }
     );
-  }
-
-  #[test]
-  fn bundle_with_lock() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "bundle",
-      "--lock-write",
-      "--lock=lock.json",
-      "source.ts"
-    ]);
+    let r = flags_from_vec(svec!["deno", "run", "script.ts", "-"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Default::default(),
-        }),
-        type_check_mode: TypeCheckMode::Local,
-        lock_write: true,
-        lock: Some(PathBuf::from("lock.json")),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        argv: svec!["-"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn bundle_with_reload() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--reload", "source.ts"]);
+    let r =
+      flags_from_vec(svec!["deno", "run", "script.ts", "-", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        reload: true,
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Default::default(),
-        }),
-        type_check_mode: TypeCheckMode::Local,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        argv: svec!["-", "foo", "bar"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_nocheck() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--no-check", "script.ts"])
-      .unwrap();
+  fn no_check() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-check", "script.ts"]);
     assert_eq!(
-      r,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "script.ts".to_string(),
-          out_file: None,
-          watch: Default::default(),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
@@ -6259,54 +7882,51 @@ mod tests {
   }
 
   #[test]
-  fn bundle_watch() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--watch", "source.ts"]);
+  fn no_check_remote() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--no-check=remote", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Some(Default::default()),
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
-    )
+    );
   }
 
   #[test]
-  fn bundle_watch_with_no_clear_screen() {
+  fn repl_with_unsafely_ignore_certificate_errors() {
     let r = flags_from_vec(svec![
       "deno",
-      "bundle",
-      "--watch",
-      "--no-clear-screen",
-      "source.ts"
+      "repl",
+      "--eval",
+      "console.log('hello');",
+      "--unsafely-ignore-certificate-errors"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Some(WatchFlags {
-            hmr: false,
-            no_clear_screen: true,
-          }),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: Some("console.log('hello');".to_string()),
+          is_default_command: false,
         }),
-        type_check_mode: TypeCheckMode::Local,
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
-    )
+    );
   }
 
   #[test]
-  fn run_import_map() {
+  fn run_with_unsafely_ignore_certificate_errors() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--import-map=import_map.json",
+      "--unsafely-ignore-certificate-errors",
       "script.ts"
     ]);
     assert_eq!(
@@ -6315,148 +7935,178 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        import_map_path: Some("import_map.json".to_owned()),
+        unsafely_ignore_certificate_errors: Some(vec![]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn info_import_map() {
+  fn run_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
     let r = flags_from_vec(svec![
       "deno",
-      "info",
-      "--import-map=import_map.json",
+      "run",
+      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4",
       "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          file: Some("script.ts".to_string()),
-          json: false,
-        }),
-        import_map_path: Some("import_map.json".to_owned()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        unsafely_ignore_certificate_errors: Some(svec![
+          "deno.land",
+          "localhost",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cache_import_map() {
+  fn repl_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
     let r = flags_from_vec(svec![
       "deno",
-      "cache",
-      "--import-map=import_map.json",
-      "script.ts"
-    ]);
+      "repl",
+      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: false,
         }),
-        import_map_path: Some("import_map.json".to_owned()),
+        unsafely_ignore_certificate_errors: Some(svec![
+          "deno.land",
+          "localhost",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4"
+        ]),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn doc_import_map() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--import-map=import_map.json",
-      "script.ts"
-    ]);
+  fn no_remote() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-remote", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          source_files: DocSourceFileFlag::Paths(vec!["script.ts".to_owned()]),
-          private: false,
-          json: false,
-          html: None,
-          lint: false,
-          filter: None,
-        }),
-        import_map_path: Some("import_map.json".to_owned()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        no_remote: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_env_file_default() {
-    let r = flags_from_vec(svec!["deno", "run", "--env", "script.ts"]);
+  fn no_npm() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-npm", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".env".to_owned()),
+        no_npm: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_env_file_defined() {
+  fn local_npm() {
     let r =
-      flags_from_vec(svec!["deno", "run", "--env=.another_env", "script.ts"]);
+      flags_from_vec(svec!["deno", "run", "--node-modules-dir", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".another_env".to_owned()),
+        node_modules_dir: Some(true),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--node-modules-dir=false",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        node_modules_dir: Some(false),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cache_multiple() {
-    let r =
-      flags_from_vec(svec!["deno", "cache", "script.ts", "script_two.ts"]);
+  fn vendor_flag() {
+    let r = flags_from_vec(svec!["deno", "run", "--vendor", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts", "script_two.ts"],
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        vendor: Some(true),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "run", "--vendor=false", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        vendor: Some(false),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_seed() {
-    let r = flags_from_vec(svec!["deno", "run", "--seed", "250", "script.ts"]);
+  fn cached_only() {
+    let r = flags_from_vec(svec!["deno", "run", "--cached-only", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        seed: Some(250_u64),
-        v8_flags: svec!["--random-seed=250"],
+        cached_only: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_seed_with_v8_flags() {
+  fn allow_net_allowlist_with_ports() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--seed",
-      "250",
-      "--v8-flags=--expose-gc",
+      "--allow-net=deno.land,:8000,:4545",
       "script.ts"
     ]);
     assert_eq!(
@@ -6465,142 +8115,149 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        seed: Some(250_u64),
-        v8_flags: svec!["--expose-gc", "--random-seed=250"],
+        allow_net: Some(svec![
+          "deno.land",
+          "0.0.0.0:8000",
+          "127.0.0.1:8000",
+          "localhost:8000",
+          "0.0.0.0:4545",
+          "127.0.0.1:4545",
+          "localhost:4545"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn install() {
+  fn deny_net_denylist_with_ports() {
     let r = flags_from_vec(svec![
       "deno",
-      "install",
-      "https://deno.land/std/http/file_server.ts"
+      "run",
+      "--deny-net=deno.land,:8000,:4545",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install(InstallFlags {
-          name: None,
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_net: Some(svec![
+          "deno.land",
+          "0.0.0.0:8000",
+          "127.0.0.1:8000",
+          "localhost:8000",
+          "0.0.0.0:4545",
+          "127.0.0.1:4545",
+          "localhost:4545"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn install_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "install", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--name", "file_server", "--root", "/foo", "--force", "--env=.example.env", "https://deno.land/std/http/file_server.ts", "foo", "bar"]);
+  fn allow_net_allowlist_with_ipv6_address() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-net=deno.land,deno.land:80,::,127.0.0.1,[::1],1.2.3.4:5678,:5678,[::1]:8080",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install(InstallFlags {
-          name: Some("file_server".to_string()),
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: svec!["foo", "bar"],
-          root: Some("/foo".to_string()),
-          force: true,
-        }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_net: Some(vec![]),
-        unsafely_ignore_certificate_errors: Some(vec![]),
-        allow_read: Some(vec![]),
-        env_file: Some(".example.env".to_owned()),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        allow_net: Some(svec![
+          "deno.land",
+          "deno.land:80",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4:5678",
+          "0.0.0.0:5678",
+          "127.0.0.1:5678",
+          "localhost:5678",
+          "[::1]:8080"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn uninstall() {
-    let r = flags_from_vec(svec!["deno", "uninstall", "file_server"]);
+  fn deny_net_denylist_with_ipv6_address() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--deny-net=deno.land,deno.land:80,::,127.0.0.1,[::1],1.2.3.4:5678,:5678,[::1]:8080",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
-          name: "file_server".to_string(),
-          root: None,
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        deny_net: Some(svec![
+          "deno.land",
+          "deno.land:80",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4:5678",
+          "0.0.0.0:5678",
+          "127.0.0.1:5678",
+          "localhost:5678",
+          "[::1]:8080"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn uninstall_with_help_flag() {
-    let r = flags_from_vec(svec!["deno", "uninstall", "--help"]);
-    assert_eq!(r.err().unwrap().kind(), clap::error::ErrorKind::DisplayHelp);
-  }
-
-  #[test]
-  fn log_level() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--log-level=debug", "script.ts"]);
+  fn lock_write() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--lock-write",
+      "--lock=lock.json",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        log_level: Some(Level::Debug),
+        lock_write: true,
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn quiet() {
-    let r = flags_from_vec(svec!["deno", "run", "-q", "script.ts"]);
+    let r = flags_from_vec(svec!["deno", "run", "--no-lock", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        log_level: Some(Level::Error),
+        no_lock: true,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn completions() {
-    let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
-
-    match r.subcommand {
-      DenoSubcommand::Completions(CompletionsFlags { buf }) => {
-        assert!(!buf.is_empty())
-      }
-      _ => unreachable!(),
-    }
-  }
-
-  #[test]
-  fn run_with_args() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "script.ts",
-      "--allow-read",
-      "--allow-net"
+      "--lock",
+      "--lock-write",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
@@ -6608,22 +8265,19 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        argv: svec!["--allow-read", "--allow-net"],
+        lock_write: true,
+        lock: Some(PathBuf::from("./deno.lock")),
         ..Flags::default()
       }
     );
+
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--location",
-      "https:foo",
-      "--allow-read",
-      "script.ts",
-      "--allow-net",
-      "-r",
-      "--help",
-      "--foo",
-      "bar"
+      "--lock-write",
+      "--lock",
+      "lock.json",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
@@ -6631,1287 +8285,1391 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        location: Some(Url::parse("https://foo/").unwrap()),
-        allow_read: Some(vec![]),
-        argv: svec!["--allow-net", "-r", "--help", "--foo", "bar"],
+        lock_write: true,
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "run", "script.ts", "foo", "bar"]);
+    let r = flags_from_vec(svec!["deno", "run", "--lock-write", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        argv: svec!["foo", "bar"],
+        lock_write: true,
         ..Flags::default()
       }
     );
-    let r = flags_from_vec(svec!["deno", "run", "script.ts", "-"]);
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--lock", "--no-lock", "script.ts"]);
+    assert!(r.is_err(),);
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--lock-write",
+      "--no-lock",
+      "script.ts"
+    ]);
+    assert!(r.is_err(),);
+  }
+
+  #[test]
+  fn test_no_colon_in_value_name() {
+    let app =
+      runtime_args(Command::new("test_inspect_completion_value"), true, true);
+    let inspect_args = app
+      .get_arguments()
+      .filter(|arg| arg.get_id() == "inspect")
+      .collect::<Vec<_>>();
+    // The value_name cannot have a : otherwise it breaks shell completions for zsh.
+    let value_name = "HOST_AND_PORT";
+    let arg = inspect_args
+      .iter()
+      .any(|v| v.get_value_names().unwrap() == [value_name]);
+
+    assert_eq!(arg, true);
+  }
+
+  #[test]
+  fn test_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "test", "--unstable", "--no-npm", "--no-remote", "--trace-leaks", "--no-run", "--filter", "- foo", "--coverage=cov", "--location", "https:foo", "--allow-net", "--allow-none", "dir1/", "dir2/", "--", "arg1", "arg2"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        argv: svec!["-"],
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: true,
+          doc: false,
+          fail_fast: None,
+          filter: Some("- foo".to_string()),
+          allow_none: true,
+          files: FileFlags {
+            include: vec!["dir1/".to_string(), "dir2/".to_string()],
+            ignore: vec![],
+          },
+          shuffle: None,
+          concurrent_jobs: None,
+          trace_leaks: true,
+          coverage_dir: Some("cov".to_string()),
+          watch: Default::default(),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        unstable_config: UnstableConfig {
+          legacy_flag_enabled: true,
+          ..Default::default()
+        },
+        no_prompt: true,
+        no_npm: true,
+        no_remote: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        type_check_mode: TypeCheckMode::Local,
+        allow_net: Some(vec![]),
+        argv: svec!["arg1", "arg2"],
         ..Flags::default()
       }
     );
+  }
 
-    let r =
-      flags_from_vec(svec!["deno", "run", "script.ts", "-", "foo", "bar"]);
+  #[test]
+  fn run_with_cafile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--cert",
+      "example.crt",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        argv: svec!["-", "foo", "bar"],
+        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn no_check() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-check", "script.ts"]);
+  fn run_with_enable_testing_features() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--enable-testing-features-do-not-use",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        type_check_mode: TypeCheckMode::None,
+        enable_testing_features: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn no_check_remote() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--no-check=remote", "script.ts"]);
+  fn test_with_concurrent_jobs() {
+    let r = flags_from_vec(svec!["deno", "test", "--jobs=4"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          reporter: Default::default(),
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Default::default(),
+          junit_path: None,
+        }),
         type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "test", "--jobs=0"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn repl_with_unsafely_ignore_certificate_errors() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "repl",
-      "--eval",
-      "console.log('hello');",
-      "--unsafely-ignore-certificate-errors"
-    ]);
+  fn test_with_fail_fast() {
+    let r = flags_from_vec(svec!["deno", "test", "--fail-fast=3"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: Some("console.log('hello');".to_string()),
-          is_default_command: false,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: Some(NonZeroUsize::new(3).unwrap()),
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Default::default(),
+          reporter: Default::default(),
+          junit_path: None,
         }),
-        unsafely_ignore_certificate_errors: Some(vec![]),
-        type_check_mode: TypeCheckMode::None,
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "test", "--fail-fast=0"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn run_with_unsafely_ignore_certificate_errors() {
+  fn test_with_enable_testing_features() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--unsafely-ignore-certificate-errors",
-      "script.ts"
+      "test",
+      "--enable-testing-features-do-not-use"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        unsafely_ignore_certificate_errors: Some(vec![]),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Default::default(),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        enable_testing_features: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4",
-      "script.ts"
-    ]);
+  fn test_reporter() {
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=pretty"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        unsafely_ignore_certificate_errors: Some(svec![
-          "deno.land",
-          "localhost",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4"
-        ]),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Pretty,
+          ..Default::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn repl_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "repl",
-      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4"]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=dot"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Dot,
+          ..Default::default()
         }),
-        unsafely_ignore_certificate_errors: Some(svec![
-          "deno.land",
-          "localhost",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4"
-        ]),
-        type_check_mode: TypeCheckMode::None,
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn no_remote() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-remote", "script.ts"]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=junit"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        no_remote: true,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Junit,
+          ..Default::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn no_npm() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-npm", "script.ts"]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=tap"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        no_npm: true,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Tap,
+          ..Default::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn local_npm() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--node-modules-dir", "script.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=dot",
+      "--junit-path=report.xml"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        node_modules_dir: Some(true),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Dot,
+          junit_path: Some("report.xml".to_string()),
+          ..Default::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--node-modules-dir=false",
-      "script.ts"
-    ]);
+    let r = flags_from_vec(svec!["deno", "test", "--junit-path"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_shuffle() {
+    let r = flags_from_vec(svec!["deno", "test", "--shuffle=1"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        node_modules_dir: Some(false),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: Some(1),
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Default::default(),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn vendor_flag() {
-    let r = flags_from_vec(svec!["deno", "run", "--vendor", "script.ts"]);
+  fn test_watch() {
+    let r = flags_from_vec(svec!["deno", "test", "--watch"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        vendor: Some(true),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Some(Default::default()),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-
-    let r = flags_from_vec(svec!["deno", "run", "--vendor=false", "script.ts"]);
+  }
+  #[test]
+  fn test_watch_explicit_cwd() {
+    let r = flags_from_vec(svec!["deno", "test", "--watch", "./"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        vendor: Some(false),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec!["./".to_string()],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Some(Default::default()),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cached_only() {
-    let r = flags_from_vec(svec!["deno", "run", "--cached-only", "script.ts"]);
+  fn test_watch_with_no_clear_screen() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--watch", "--no-clear-screen"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        cached_only: true,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_leaks: false,
+          coverage_dir: None,
+          watch: Some(WatchFlags {
+            hmr: false,
+            no_clear_screen: true,
+          }),
+          reporter: Default::default(),
+          junit_path: None,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_allowlist_with_ports() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-net=deno.land,:8000,:4545",
-      "script.ts"
-    ]);
+  fn test_coverage_default_dir() {
+    let r = flags_from_vec(svec!["deno", "test", "--coverage"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        allow_net: Some(svec![
-          "deno.land",
-          "0.0.0.0:8000",
-          "127.0.0.1:8000",
-          "localhost:8000",
-          "0.0.0.0:4545",
-          "127.0.0.1:4545",
-          "localhost:4545"
-        ]),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          coverage_dir: Some("coverage".to_string()),
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_net_denylist_with_ports() {
+  fn bundle_with_cafile() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--deny-net=deno.land,:8000,:4545",
-      "script.ts"
+      "bundle",
+      "--cert",
+      "example.crt",
+      "source.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        deny_net: Some(svec![
-          "deno.land",
-          "0.0.0.0:8000",
-          "127.0.0.1:8000",
-          "localhost:8000",
-          "0.0.0.0:4545",
-          "127.0.0.1:4545",
-          "localhost:4545"
-        ]),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          watch: Default::default(),
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_allowlist_with_ipv6_address() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-net=deno.land,deno.land:80,::,127.0.0.1,[::1],1.2.3.4:5678,:5678,[::1]:8080",
-      "script.ts"
-    ]);
+  fn upgrade_with_ca_file() {
+    let r = flags_from_vec(svec!["deno", "upgrade", "--cert", "example.crt"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        allow_net: Some(svec![
-          "deno.land",
-          "deno.land:80",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4:5678",
-          "0.0.0.0:5678",
-          "127.0.0.1:5678",
-          "localhost:5678",
-          "[::1]:8080"
-        ]),
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: false,
+          dry_run: false,
+          canary: false,
+          version: None,
+          output: None,
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn deny_net_denylist_with_ipv6_address() {
+  fn cache_with_cafile() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--deny-net=deno.land,deno.land:80,::,127.0.0.1,[::1],1.2.3.4:5678,:5678,[::1]:8080",
-      "script.ts"
+      "cache",
+      "--cert",
+      "example.crt",
+      "script.ts",
+      "script_two.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        deny_net: Some(svec![
-          "deno.land",
-          "deno.land:80",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4:5678",
-          "0.0.0.0:5678",
-          "127.0.0.1:5678",
-          "localhost:5678",
-          "[::1]:8080"
-        ]),
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts", "script_two.ts"],
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn lock_write() {
+  fn info_with_cafile() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--lock-write",
-      "--lock=lock.json",
-      "script.ts"
+      "info",
+      "--cert",
+      "example.crt",
+      "https://example.com"
     ]);
     assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        lock_write: true,
-        lock: Some(PathBuf::from("lock.json")),
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("https://example.com".to_string()),
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "run", "--no-lock", "script.ts"]);
+  #[test]
+  fn doc() {
+    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        no_lock: true,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: true,
+          html: None,
+          lint: false,
+          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
+          filter: None,
+        }),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "doc", "--html", "path/to/module.ts"]);
+    assert!(r.is_err());
+
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--lock",
-      "--lock-write",
-      "script.ts"
+      "doc",
+      "--html",
+      "--name=My library",
+      "path/to/module.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        lock_write: true,
-        lock: Some(PathBuf::from("./deno.lock")),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          lint: false,
+          html: Some(DocHtmlFlag {
+            name: "My library".to_string(),
+            output: PathBuf::from("./docs/"),
+          }),
+          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
+          filter: None,
+        }),
         ..Flags::default()
       }
     );
 
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--lock-write",
-      "--lock",
-      "lock.json",
-      "script.ts"
+      "doc",
+      "--html",
+      "--name=My library",
+      "--lint",
+      "--output=./foo",
+      "path/to/module.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        lock_write: true,
-        lock: Some(PathBuf::from("lock.json")),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: Some(DocHtmlFlag {
+            name: "My library".to_string(),
+            output: PathBuf::from("./foo"),
+          }),
+          lint: true,
+          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
+          filter: None,
+        }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "run", "--lock-write", "script.ts"]);
+    let r =
+      flags_from_vec(svec!["deno", "doc", "--html", "--name=My library",]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--filter",
+      "SomeClass.someField",
+      "path/to/module.ts",
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        lock_write: true,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: None,
+          lint: false,
+          source_files: DocSourceFileFlag::Paths(vec![
+            "path/to/module.ts".to_string()
+          ]),
+          filter: Some("SomeClass.someField".to_string()),
+        }),
         ..Flags::default()
       }
     );
 
-    let r =
-      flags_from_vec(svec!["deno", "run", "--lock", "--no-lock", "script.ts"]);
-    assert!(r.is_err(),);
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--lock-write",
-      "--no-lock",
-      "script.ts"
-    ]);
-    assert!(r.is_err(),);
-  }
-
-  #[test]
-  fn test_no_colon_in_value_name() {
-    let app =
-      runtime_args(Command::new("test_inspect_completion_value"), true, true);
-    let inspect_args = app
-      .get_arguments()
-      .filter(|arg| arg.get_id() == "inspect")
-      .collect::<Vec<_>>();
-    // The value_name cannot have a : otherwise it breaks shell completions for zsh.
-    let value_name = "HOST_AND_PORT";
-    let arg = inspect_args
-      .iter()
-      .any(|v| v.get_value_names().unwrap() == [value_name]);
-
-    assert_eq!(arg, true);
-  }
-
-  #[test]
-  fn test_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "test", "--unstable", "--no-npm", "--no-remote", "--trace-leaks", "--no-run", "--filter", "- foo", "--coverage=cov", "--location", "https:foo", "--allow-net", "--allow-none", "dir1/", "dir2/", "--", "arg1", "arg2"]);
+    let r = flags_from_vec(svec!["deno", "doc"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: true,
-          doc: false,
-          fail_fast: None,
-          filter: Some("- foo".to_string()),
-          allow_none: true,
-          files: FileFlags {
-            include: vec!["dir1/".to_string(), "dir2/".to_string()],
-            ignore: vec![],
-          },
-          shuffle: None,
-          concurrent_jobs: None,
-          trace_leaks: true,
-          coverage_dir: Some("cov".to_string()),
-          watch: Default::default(),
-          reporter: Default::default(),
-          junit_path: None,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: None,
+          lint: false,
+          source_files: Default::default(),
+          filter: None,
         }),
-        unstable_config: UnstableConfig {
-          legacy_flag_enabled: true,
-          ..Default::default()
-        },
-        no_prompt: true,
-        no_npm: true,
-        no_remote: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        type_check_mode: TypeCheckMode::Local,
-        allow_net: Some(vec![]),
-        argv: svec!["arg1", "arg2"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn run_with_cafile() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--cert",
-      "example.crt",
-      "script.ts"
+      "doc",
+      "--filter",
+      "Deno.Listener",
+      "--builtin"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          lint: false,
+          json: false,
+          html: None,
+          source_files: DocSourceFileFlag::Builtin,
+          filter: Some("Deno.Listener".to_string()),
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn run_with_enable_testing_features() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--enable-testing-features-do-not-use",
-      "script.ts"
+      "doc",
+      "--no-npm",
+      "--no-remote",
+      "--private",
+      "path/to/module.js"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
-        )),
-        enable_testing_features: true,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: true,
+          lint: false,
+          json: false,
+          html: None,
+          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.js"]),
+          filter: None,
+        }),
+        no_npm: true,
+        no_remote: true,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn test_with_concurrent_jobs() {
-    let r = flags_from_vec(svec!["deno", "test", "--jobs=4"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "path/to/module.js",
+      "path/to/module2.js"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          reporter: Default::default(),
-          doc: false,
-          fail_fast: None,
-          filter: None,
-          allow_none: false,
-          shuffle: None,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Default::default(),
-          junit_path: None,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          lint: false,
+          json: false,
+          html: None,
+          source_files: DocSourceFileFlag::Paths(vec![
+            "path/to/module.js".to_string(),
+            "path/to/module2.js".to_string()
+          ]),
+          filter: None,
         }),
-        type_check_mode: TypeCheckMode::Local,
-        no_prompt: true,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "test", "--jobs=0"]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn test_with_fail_fast() {
-    let r = flags_from_vec(svec!["deno", "test", "--fail-fast=3"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "path/to/module.js",
+      "--builtin",
+      "path/to/module2.js"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: Some(NonZeroUsize::new(3).unwrap()),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: None,
+          lint: false,
+          source_files: DocSourceFileFlag::Paths(vec![
+            "path/to/module.js".to_string(),
+            "path/to/module2.js".to_string()
+          ]),
           filter: None,
-          allow_none: false,
-          shuffle: None,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Default::default(),
-          reporter: Default::default(),
-          junit_path: None,
         }),
-        type_check_mode: TypeCheckMode::Local,
-        no_prompt: true,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "test", "--fail-fast=0"]);
+    let r = flags_from_vec(svec!["deno", "doc", "--lint",]);
     assert!(r.is_err());
-  }
 
-  #[test]
-  fn test_with_enable_testing_features() {
     let r = flags_from_vec(svec![
       "deno",
-      "test",
-      "--enable-testing-features-do-not-use"
+      "doc",
+      "--lint",
+      "path/to/module.js",
+      "path/to/module2.js"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: None,
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          lint: true,
+          json: false,
+          html: None,
+          source_files: DocSourceFileFlag::Paths(vec![
+            "path/to/module.js".to_string(),
+            "path/to/module2.js".to_string()
+          ]),
           filter: None,
-          allow_none: false,
-          shuffle: None,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Default::default(),
-          reporter: Default::default(),
-          junit_path: None,
         }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
-        enable_testing_features: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_reporter() {
-    let r = flags_from_vec(svec!["deno", "test", "--reporter=pretty"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          reporter: TestReporterConfig::Pretty,
-          ..Default::default()
-        }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
-        ..Flags::default()
-      }
-    );
-
-    let r = flags_from_vec(svec!["deno", "test", "--reporter=dot"]);
+  fn inspect_default_host() {
+    let r = flags_from_vec(svec!["deno", "run", "--inspect", "foo.js"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          reporter: TestReporterConfig::Dot,
-          ..Default::default()
-        }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
-        log_level: Some(Level::Error),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "foo.js".to_string(),
+        )),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "test", "--reporter=junit"]);
+  #[test]
+  fn inspect_wait() {
+    let r = flags_from_vec(svec!["deno", "run", "--inspect-wait", "foo.js"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          reporter: TestReporterConfig::Junit,
-          ..Default::default()
-        }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "foo.js".to_string(),
+        )),
+        inspect_wait: Some("127.0.0.1:9229".parse().unwrap()),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "test", "--reporter=tap"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--inspect-wait=127.0.0.1:3567",
+      "foo.js"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          reporter: TestReporterConfig::Tap,
-          ..Default::default()
-        }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
-        log_level: Some(Level::Error),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "foo.js".to_string(),
+        )),
+        inspect_wait: Some("127.0.0.1:3567".parse().unwrap()),
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn compile() {
     let r = flags_from_vec(svec![
       "deno",
-      "test",
-      "--reporter=dot",
-      "--junit-path=report.xml"
+      "compile",
+      "https://examples.deno.land/color-logging.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          reporter: TestReporterConfig::Dot,
-          junit_path: Some("report.xml".to_string()),
-          ..Default::default()
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "https://examples.deno.land/color-logging.ts"
+            .to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          no_terminal: false,
+          include: vec![]
         }),
-        no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
-        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
-
-    let r = flags_from_vec(svec!["deno", "test", "--junit-path"]);
-    assert!(r.is_err());
   }
 
   #[test]
-  fn test_shuffle() {
-    let r = flags_from_vec(svec!["deno", "test", "--shuffle=1"]);
+  fn compile_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "compile", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--no-terminal", "--output", "colors", "--env=.example.env", "https://examples.deno.land/color-logging.ts", "foo", "bar", "-p", "8080"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: None,
-          filter: None,
-          allow_none: false,
-          shuffle: Some(1),
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Default::default(),
-          reporter: Default::default(),
-          junit_path: None,
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "https://examples.deno.land/color-logging.ts"
+            .to_string(),
+          output: Some(PathBuf::from("colors")),
+          args: svec!["foo", "bar", "-p", "8080"],
+          target: None,
+          no_terminal: true,
+          include: vec![]
         }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        allow_read: Some(vec![]),
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        allow_net: Some(vec![]),
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        env_file: Some(".example.env".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_watch() {
-    let r = flags_from_vec(svec!["deno", "test", "--watch"]);
+  fn coverage() {
+    let r = flags_from_vec(svec!["deno", "coverage", "foo.json"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: None,
-          filter: None,
-          allow_none: false,
-          shuffle: None,
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
           files: FileFlags {
-            include: vec![],
+            include: vec!["foo.json".to_string()],
             ignore: vec![],
           },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Some(Default::default()),
-          reporter: Default::default(),
-          junit_path: None,
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          ..CoverageFlags::default()
         }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
+
   #[test]
-  fn test_watch_explicit_cwd() {
-    let r = flags_from_vec(svec!["deno", "test", "--watch", "./"]);
+  fn coverage_with_lcov_and_out_file() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--lcov",
+      "--output=foo.lcov",
+      "foo.json"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: None,
-          filter: None,
-          allow_none: false,
-          shuffle: None,
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
           files: FileFlags {
-            include: vec!["./".to_string()],
+            include: vec!["foo.json".to_string()],
             ignore: vec![],
           },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Some(Default::default()),
-          reporter: Default::default(),
-          junit_path: None,
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          r#type: CoverageType::Lcov,
+          output: Some(PathBuf::from("foo.lcov")),
         }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_watch_with_no_clear_screen() {
-    let r =
-      flags_from_vec(svec!["deno", "test", "--watch", "--no-clear-screen"]);
+  fn coverage_with_default_files() {
+    let r = flags_from_vec(svec!["deno", "coverage",]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          no_run: false,
-          doc: false,
-          fail_fast: None,
-          filter: None,
-          allow_none: false,
-          shuffle: None,
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
           files: FileFlags {
-            include: vec![],
+            include: vec!["coverage".to_string()],
             ignore: vec![],
           },
-          concurrent_jobs: None,
-          trace_leaks: false,
-          coverage_dir: None,
-          watch: Some(WatchFlags {
-            hmr: false,
-            no_clear_screen: true,
-          }),
-          reporter: Default::default(),
-          junit_path: None,
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          ..CoverageFlags::default()
         }),
-        type_check_mode: TypeCheckMode::Local,
-        no_prompt: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_coverage_default_dir() {
-    let r = flags_from_vec(svec!["deno", "test", "--coverage"]);
+  fn location_with_bad_scheme() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "run", "--location", "foo:", "mod.ts"]);
+    assert!(r.is_err());
+    assert!(r
+      .unwrap_err()
+      .to_string()
+      .contains("Expected protocol \"http\" or \"https\""));
+  }
+
+  #[test]
+  fn test_config_path_args() {
+    let flags = flags_from_vec(svec!["deno", "run", "foo.js"]).unwrap();
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(flags.config_path_args(&cwd), Some(vec![cwd.join("foo.js")]));
+
+    let flags =
+      flags_from_vec(svec!["deno", "run", "https://example.com/foo.js"])
+        .unwrap();
+    assert_eq!(flags.config_path_args(&cwd), None);
+
+    let flags =
+      flags_from_vec(svec!["deno", "lint", "dir/a.js", "dir/b.js"]).unwrap();
+    assert_eq!(
+      flags.config_path_args(&cwd),
+      Some(vec![cwd.join("dir/a.js"), cwd.join("dir/b.js")])
+    );
+
+    let flags = flags_from_vec(svec!["deno", "lint"]).unwrap();
+    assert!(flags.config_path_args(&cwd).unwrap().is_empty());
+
+    let flags =
+      flags_from_vec(svec!["deno", "fmt", "dir/a.js", "dir/b.js"]).unwrap();
+    assert_eq!(
+      flags.config_path_args(&cwd),
+      Some(vec![cwd.join("dir/a.js"), cwd.join("dir/b.js")])
+    );
+  }
+
+  #[test]
+  fn test_no_clear_watch_flag_without_watch_flag() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-clear-screen", "foo.js"]);
+    assert!(r.is_err());
+    let error_message = r.unwrap_err().to_string();
+    assert!(&error_message
+      .contains("error: the following required arguments were not provided:"));
+    assert!(&error_message.contains("--watch[=<FILES>...]"));
+  }
+
+  #[test]
+  fn vendor_minimal() {
+    let r = flags_from_vec(svec!["deno", "vendor", "mod.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Test(TestFlags {
-          coverage_dir: Some("coverage".to_string()),
-          ..TestFlags::default()
+        subcommand: DenoSubcommand::Vendor(VendorFlags {
+          specifiers: svec!["mod.ts"],
+          force: false,
+          output_path: None,
         }),
-        type_check_mode: TypeCheckMode::Local,
-        no_prompt: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_with_cafile() {
+  fn vendor_all() {
     let r = flags_from_vec(svec![
       "deno",
-      "bundle",
-      "--cert",
-      "example.crt",
-      "source.ts"
+      "vendor",
+      "--config",
+      "deno.json",
+      "--import-map",
+      "import_map.json",
+      "--lock",
+      "lock.json",
+      "--force",
+      "--output",
+      "out_dir",
+      "--reload",
+      "mod.ts",
+      "deps.test.ts",
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-          watch: Default::default(),
+        subcommand: DenoSubcommand::Vendor(VendorFlags {
+          specifiers: svec!["mod.ts", "deps.test.ts"],
+          force: true,
+          output_path: Some(PathBuf::from("out_dir")),
         }),
-        type_check_mode: TypeCheckMode::Local,
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        config_flag: ConfigFlag::Path("deno.json".to_owned()),
+        import_map_path: Some("import_map.json".to_string()),
+        lock: Some(PathBuf::from("lock.json")),
+        reload: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn upgrade_with_ca_file() {
-    let r = flags_from_vec(svec!["deno", "upgrade", "--cert", "example.crt"]);
+  fn task_subcommand() {
+    let r = flags_from_vec(svec!["deno", "task", "build", "hello", "world",]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
-          force: false,
-          dry_run: false,
-          canary: false,
-          version: None,
-          output: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+        }),
+        argv: svec!["hello", "world"],
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "task", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "task", "--cwd", "foo", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: Some("foo".to_string()),
+          task: Some("build".to_string()),
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cache_with_cafile() {
+  fn task_subcommand_double_hyphen() {
     let r = flags_from_vec(svec![
       "deno",
-      "cache",
-      "--cert",
-      "example.crt",
-      "script.ts",
-      "script_two.ts"
+      "task",
+      "-c",
+      "deno.json",
+      "build",
+      "--",
+      "hello",
+      "world",
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts", "script_two.ts"],
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        argv: svec!["--", "hello", "world"],
+        config_flag: ConfigFlag::Path("deno.json".to_owned()),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn info_with_cafile() {
     let r = flags_from_vec(svec![
-      "deno",
-      "info",
-      "--cert",
-      "example.crt",
-      "https://example.com"
+      "deno", "task", "--cwd", "foo", "build", "--", "hello", "world"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("https://example.com".to_string()),
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: Some("foo".to_string()),
+          task: Some("build".to_string()),
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        argv: svec!["--", "hello", "world"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn doc() {
-    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
+  fn task_subcommand_double_hyphen_only() {
+    // edge case, but it should forward
+    let r = flags_from_vec(svec!["deno", "task", "build", "--"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: true,
-          html: None,
-          lint: false,
-          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
-          filter: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
         }),
+        argv: svec!["--"],
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "doc", "--html", "path/to/module.ts"]);
-    assert!(r.is_err());
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--html",
-      "--name=My library",
-      "path/to/module.ts"
-    ]);
+  #[test]
+  fn task_following_arg() {
+    let r = flags_from_vec(svec!["deno", "task", "build", "-1", "--test"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          lint: false,
-          html: Some(DocHtmlFlag {
-            name: "My library".to_string(),
-            output: PathBuf::from("./docs/"),
-          }),
-          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
-          filter: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
         }),
+        argv: svec!["-1", "--test"],
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--html",
-      "--name=My library",
-      "--lint",
-      "--output=./foo",
-      "path/to/module.ts"
-    ]);
+  #[test]
+  fn task_following_double_hyphen_arg() {
+    let r = flags_from_vec(svec!["deno", "task", "build", "--test"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          html: Some(DocHtmlFlag {
-            name: "My library".to_string(),
-            output: PathBuf::from("./foo"),
-          }),
-          lint: true,
-          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.ts"]),
-          filter: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
         }),
+        argv: svec!["--test"],
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn task_with_global_flags() {
+    // can fail if the custom parser in task_parse() starts at the wrong index
     let r =
-      flags_from_vec(svec!["deno", "doc", "--html", "--name=My library",]);
-    assert!(r.is_err());
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--filter",
-      "SomeClass.someField",
-      "path/to/module.ts",
-    ]);
+      flags_from_vec(svec!["deno", "--quiet", "--unstable", "task", "build"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          html: None,
-          lint: false,
-          source_files: DocSourceFileFlag::Paths(vec![
-            "path/to/module.ts".to_string()
-          ]),
-          filter: Some("SomeClass.someField".to_string()),
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
         }),
+        unstable_config: UnstableConfig {
+          legacy_flag_enabled: true,
+          ..Default::default()
+        },
+        log_level: Some(log::Level::Error),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "doc"]);
+  #[test]
+  fn task_subcommand_empty() {
+    let r = flags_from_vec(svec!["deno", "task"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          html: None,
-          lint: false,
-          source_files: Default::default(),
-          filter: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: None,
         }),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--filter",
-      "Deno.Listener",
-      "--builtin"
-    ]);
+  #[test]
+  fn task_subcommand_config() {
+    let r = flags_from_vec(svec!["deno", "task", "--config", "deno.jsonc"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          lint: false,
-          json: false,
-          html: None,
-          source_files: DocSourceFileFlag::Builtin,
-          filter: Some("Deno.Listener".to_string()),
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: None,
         }),
+        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--no-npm",
-      "--no-remote",
-      "--private",
-      "path/to/module.js"
-    ]);
+  #[test]
+  fn task_subcommand_config_short() {
+    let r = flags_from_vec(svec!["deno", "task", "-c", "deno.jsonc"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: true,
-          lint: false,
-          json: false,
-          html: None,
-          source_files: DocSourceFileFlag::Paths(svec!["path/to/module.js"]),
-          filter: None,
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: None,
         }),
-        no_npm: true,
-        no_remote: true,
+        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
+  }
+
+  #[test]
+  fn task_subcommand_noconfig_invalid() {
+    let r = flags_from_vec(svec!["deno", "task", "--no-config"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::UnknownArgument
+    );
+  }
 
+  #[test]
+  fn bench_with_flags() {
     let r = flags_from_vec(svec![
       "deno",
-      "doc",
-      "path/to/module.js",
-      "path/to/module2.js"
+      "bench",
+      "--json",
+      "--unstable",
+      "--no-npm",
+      "--no-remote",
+      "--no-run",
+      "--filter",
+      "- foo",
+      "--location",
+      "https:foo",
+      "--allow-net",
+      "dir1/",
+      "dir2/",
+      "--",
+      "arg1",
+      "arg2"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          lint: false,
-          json: false,
-          html: None,
-          source_files: DocSourceFileFlag::Paths(vec![
-            "path/to/module.js".to_string(),
-            "path/to/module2.js".to_string()
-          ]),
-          filter: None,
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          filter: Some("- foo".to_string()),
+          json: true,
+          no_run: true,
+          files: FileFlags {
+            include: vec!["dir1/".to_string(), "dir2/".to_string()],
+            ignore: vec![],
+          },
+          watch: Default::default(),
         }),
+        unstable_config: UnstableConfig {
+          legacy_flag_enabled: true,
+          ..Default::default()
+        },
+        no_npm: true,
+        no_remote: true,
+        type_check_mode: TypeCheckMode::Local,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        allow_net: Some(vec![]),
+        no_prompt: true,
+        argv: svec!["arg1", "arg2"],
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "path/to/module.js",
-      "--builtin",
-      "path/to/module2.js"
-    ]);
+  #[test]
+  fn bench_watch() {
+    let r = flags_from_vec(svec!["deno", "bench", "--watch"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          html: None,
-          lint: false,
-          source_files: DocSourceFileFlag::Paths(vec![
-            "path/to/module.js".to_string(),
-            "path/to/module2.js".to_string()
-          ]),
+        subcommand: DenoSubcommand::Bench(BenchFlags {
           filter: None,
+          json: false,
+          no_run: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          watch: Some(Default::default()),
         }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "doc", "--lint",]);
-    assert!(r.is_err());
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--lint",
-      "path/to/module.js",
-      "path/to/module2.js"
-    ]);
+  #[test]
+  fn run_with_check() {
+    let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          lint: true,
-          json: false,
-          html: None,
-          source_files: DocSourceFileFlag::Paths(vec![
-            "path/to/module.js".to_string(),
-            "path/to/module2.js".to_string()
-          ]),
-          filter: None,
-        }),
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn inspect_default_host() {
-    let r = flags_from_vec(svec!["deno", "run", "--inspect", "foo.js"]);
+    let r = flags_from_vec(svec!["deno", "run", "--check=all", "script.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "foo.js".to_string(),
+          "script.ts".to_string(),
         )),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        type_check_mode: TypeCheckMode::All,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn inspect_wait() {
-    let r = flags_from_vec(svec!["deno", "run", "--inspect-wait", "foo.js"]);
+    let r = flags_from_vec(svec!["deno", "run", "--check=foo", "script.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "foo.js".to_string(),
+          "script.ts".to_string(),
         )),
-        inspect_wait: Some("127.0.0.1:9229".parse().unwrap()),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
@@ -7919,661 +9677,1086 @@ mod tests {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--inspect-wait=127.0.0.1:3567",
-      "foo.js"
+      "--no-check",
+      "--check",
+      "script.ts",
     ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn no_config() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-config", "script.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "foo.js".to_string(),
+          "script.ts".to_string(),
         )),
-        inspect_wait: Some("127.0.0.1:3567".parse().unwrap()),
+        config_flag: ConfigFlag::Disabled,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn compile() {
     let r = flags_from_vec(svec![
       "deno",
-      "compile",
-      "https://examples.deno.land/color-logging.ts"
+      "run",
+      "--config",
+      "deno.json",
+      "--no-config",
+      "script.ts",
     ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn init() {
+    let r = flags_from_vec(svec!["deno", "init"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Compile(CompileFlags {
-          source_file: "https://examples.deno.land/color-logging.ts"
-            .to_string(),
-          output: None,
-          args: vec![],
-          target: None,
-          no_terminal: false,
-          include: vec![]
+        subcommand: DenoSubcommand::Init(InitFlags { dir: None }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "init", "foo"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Init(InitFlags {
+          dir: Some(String::from("foo")),
         }),
-        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "init", "--quiet"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Init(InitFlags { dir: None }),
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn compile_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "compile", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--no-terminal", "--output", "colors", "--env=.example.env", "https://examples.deno.land/color-logging.ts", "foo", "bar", "-p", "8080"]);
+  fn jupyter() {
+    let r = flags_from_vec(svec!["deno", "jupyter"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Compile(CompileFlags {
-          source_file: "https://examples.deno.land/color-logging.ts"
-            .to_string(),
-          output: Some(PathBuf::from("colors")),
-          args: svec!["foo", "bar", "-p", "8080"],
-          target: None,
-          no_terminal: true,
-          include: vec![]
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: false,
+          kernel: false,
+          conn_file: None,
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        allow_read: Some(vec![]),
-        unsafely_ignore_certificate_errors: Some(vec![]),
-        allow_net: Some(vec![]),
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        env_file: Some(".example.env".to_owned()),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn coverage() {
-    let r = flags_from_vec(svec!["deno", "coverage", "foo.json"]);
+    let r = flags_from_vec(svec!["deno", "jupyter", "--install"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Coverage(CoverageFlags {
-          files: FileFlags {
-            include: vec!["foo.json".to_string()],
-            ignore: vec![],
-          },
-          include: vec![r"^file:".to_string()],
-          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          ..CoverageFlags::default()
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: true,
+          kernel: false,
+          conn_file: None,
         }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn coverage_with_lcov_and_out_file() {
     let r = flags_from_vec(svec![
       "deno",
-      "coverage",
-      "--lcov",
-      "--output=foo.lcov",
-      "foo.json"
+      "jupyter",
+      "--kernel",
+      "--conn",
+      "path/to/conn/file"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Coverage(CoverageFlags {
-          files: FileFlags {
-            include: vec!["foo.json".to_string()],
-            ignore: vec![],
-          },
-          include: vec![r"^file:".to_string()],
-          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          r#type: CoverageType::Lcov,
-          output: Some(PathBuf::from("foo.lcov")),
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: false,
+          kernel: true,
+          conn_file: Some(PathBuf::from("path/to/conn/file")),
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "jupyter",
+      "--install",
+      "--conn",
+      "path/to/conn/file"
+    ]);
+    r.unwrap_err();
+    let r = flags_from_vec(svec!["deno", "jupyter", "--kernel",]);
+    r.unwrap_err();
+    let r = flags_from_vec(svec!["deno", "jupyter", "--install", "--kernel",]);
+    r.unwrap_err();
   }
 
   #[test]
-  fn coverage_with_default_files() {
-    let r = flags_from_vec(svec!["deno", "coverage",]);
+  fn publish_args() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--no-provenance",
+      "--dry-run",
+      "--allow-slow-types",
+      "--token=asdf",
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Coverage(CoverageFlags {
-          files: FileFlags {
-            include: vec!["coverage".to_string()],
-            ignore: vec![],
-          },
-          include: vec![r"^file:".to_string()],
-          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          ..CoverageFlags::default()
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: Some("asdf".to_string()),
+          dry_run: true,
+          allow_slow_types: true,
+          no_provenance: true,
+          require_provenance: false,
+          provenance_defer: None,
+          sbom: None,
+          auth_info: false,
+          json: false,
+          create: false,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
         }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn location_with_bad_scheme() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "run", "--location", "foo:", "mod.ts"]);
-    assert!(r.is_err());
-    assert!(r
-      .unwrap_err()
-      .to_string()
-      .contains("Expected protocol \"http\" or \"https\""));
-  }
-
-  #[test]
-  fn test_config_path_args() {
-    let flags = flags_from_vec(svec!["deno", "run", "foo.js"]).unwrap();
-    let cwd = std::env::current_dir().unwrap();
-    assert_eq!(flags.config_path_args(&cwd), Some(vec![cwd.join("foo.js")]));
-
-    let flags =
-      flags_from_vec(svec!["deno", "run", "https://example.com/foo.js"])
-        .unwrap();
-    assert_eq!(flags.config_path_args(&cwd), None);
-
-    let flags =
-      flags_from_vec(svec!["deno", "lint", "dir/a.js", "dir/b.js"]).unwrap();
+    let r = flags_from_vec(svec!["deno", "publish", "--auth-info"]);
     assert_eq!(
-      flags.config_path_args(&cwd),
-      Some(vec![cwd.join("dir/a.js"), cwd.join("dir/b.js")])
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: None,
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: false,
+          provenance_defer: None,
+          sbom: None,
+          auth_info: true,
+          json: false,
+          create: false,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
     );
 
-    let flags = flags_from_vec(svec!["deno", "lint"]).unwrap();
-    assert!(flags.config_path_args(&cwd).unwrap().is_empty());
-
-    let flags =
-      flags_from_vec(svec!["deno", "fmt", "dir/a.js", "dir/b.js"]).unwrap();
+    let r = flags_from_vec(svec!["deno", "publish", "--provenance-defer"]);
     assert_eq!(
-      flags.config_path_args(&cwd),
-      Some(vec![cwd.join("dir/a.js"), cwd.join("dir/b.js")])
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: None,
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: false,
+          provenance_defer: Some(PathBuf::from(".")),
+          sbom: None,
+          auth_info: false,
+          json: false,
+          create: false,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
     );
-  }
 
-  #[test]
-  fn test_no_clear_watch_flag_without_watch_flag() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-clear-screen", "foo.js"]);
-    assert!(r.is_err());
-    let error_message = r.unwrap_err().to_string();
-    assert!(&error_message
-      .contains("error: the following required arguments were not provided:"));
-    assert!(&error_message.contains("--watch[=<FILES>...]"));
-  }
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--provenance-defer=bundles",
+      "--no-provenance",
+    ]);
+    r.unwrap_err();
 
-  #[test]
-  fn vendor_minimal() {
-    let r = flags_from_vec(svec!["deno", "vendor", "mod.ts",]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--require-provenance",
+      "--json",
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Vendor(VendorFlags {
-          specifiers: svec!["mod.ts"],
-          force: false,
-          output_path: None,
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: None,
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: true,
+          provenance_defer: None,
+          sbom: None,
+          auth_info: false,
+          json: true,
+          create: false,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
         }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn vendor_all() {
     let r = flags_from_vec(svec![
       "deno",
-      "vendor",
-      "--config",
-      "deno.json",
-      "--import-map",
-      "import_map.json",
-      "--lock",
-      "lock.json",
-      "--force",
-      "--output",
-      "out_dir",
-      "--reload",
-      "mod.ts",
-      "deps.test.ts",
+      "publish",
+      "--require-provenance",
+      "--no-provenance",
     ]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--sbom=sboms"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Vendor(VendorFlags {
-          specifiers: svec!["mod.ts", "deps.test.ts"],
-          force: true,
-          output_path: Some(PathBuf::from("out_dir")),
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: None,
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: false,
+          provenance_defer: None,
+          sbom: Some(PathBuf::from("sboms")),
+          auth_info: false,
+          json: false,
+          create: false,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
         }),
-        config_flag: ConfigFlag::Path("deno.json".to_owned()),
-        import_map_path: Some("import_map.json".to_string()),
-        lock: Some(PathBuf::from("lock.json")),
-        reload: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn task_subcommand() {
-    let r = flags_from_vec(svec!["deno", "task", "build", "hello", "world",]);
+    let r =
+      flags_from_vec(svec!["deno", "publish", "--create", "--token=asdf"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: Some("asdf".to_string()),
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: false,
+          provenance_defer: None,
+          sbom: None,
+          auth_info: false,
+          json: false,
+          create: true,
+          wait_for_available: false,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
         }),
-        argv: svec!["hello", "world"],
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "task", "build"]);
+    // creating scopes and packages requires a token
+    let r = flags_from_vec(svec!["deno", "publish", "--create"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--wait-for-available"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          token: None,
+          dry_run: false,
+          allow_slow_types: false,
+          no_provenance: false,
+          require_provenance: false,
+          provenance_defer: None,
+          sbom: None,
+          auth_info: false,
+          json: false,
+          create: false,
+          wait_for_available: true,
+          trace_registry: false,
+          trace_registry_file: None,
+          fix: false,
+          explain: None,
+          baseline: None,
+          write_baseline: false,
+          max_diagnostics: None,
+          report_file: None,
+          deny_warnings: false,
+          diagnostics_format: PublishDiagnosticsFormat::Pretty,
+          bump: None,
+          changed_only: false,
+          filter: vec![],
+          include_dependencies: false,
+          check_libs: vec![],
+          show_fast_check: false,
         }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "task", "--cwd", "foo", "build"]);
+    let r = flags_from_vec(svec!["deno", "publish", "--trace-registry"]);
+    assert!(matches!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Publish(PublishFlags {
+        trace_registry: true,
+        trace_registry_file: None,
+        ..
+      })
+    ));
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--trace-registry=registry-trace.jsonl"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert!(publish_flags.trace_registry);
+    assert_eq!(
+      publish_flags.trace_registry_file,
+      Some(PathBuf::from("registry-trace.jsonl"))
+    );
+
+    let r = flags_from_vec(svec!["deno", "publish", "--fix"]);
+    assert!(matches!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Publish(PublishFlags { fix: true, .. })
+    ));
+    let r = flags_from_vec(svec!["deno", "publish", "--fix", "--dry-run"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--explain",
+      "invalid-external-import"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(
+      publish_flags.explain.as_deref(),
+      Some("invalid-external-import")
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--baseline",
+      "publish-baseline.json",
+      "--write-baseline"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(
+      publish_flags.baseline,
+      Some(PathBuf::from("publish-baseline.json"))
+    );
+    assert!(publish_flags.write_baseline);
+    let r = flags_from_vec(svec!["deno", "publish", "--write-baseline"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--max-diagnostics",
+      "20",
+      "--report-file",
+      "diagnostics.json"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(publish_flags.max_diagnostics, Some(20));
+    assert_eq!(
+      publish_flags.report_file,
+      Some(PathBuf::from("diagnostics.json"))
+    );
+    let r = flags_from_vec(svec!["deno", "publish", "--max-diagnostics", "-1"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--deny-warnings"]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert!(publish_flags.deny_warnings);
+
+    let r =
+      flags_from_vec(svec!["deno", "publish", "--diagnostics-format=short"]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(
+      publish_flags.diagnostics_format,
+      PublishDiagnosticsFormat::Short
+    );
+    let r =
+      flags_from_vec(svec!["deno", "publish", "--diagnostics-format=json"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--bump", "auto"]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(publish_flags.bump, Some(VersionIncrement::Auto));
+    let r = flags_from_vec(svec!["deno", "publish", "--bump", "1.0.0"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--changed-only"]);
+    assert!(matches!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Publish(PublishFlags {
+        changed_only: true,
+        ..
+      })
+    ));
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--filter",
+      "@foo/bar",
+      "--filter",
+      "@foo/baz",
+      "--include-dependencies"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(publish_flags.filter, svec!["@foo/bar", "@foo/baz"]);
+    assert!(publish_flags.include_dependencies);
+    let r = flags_from_vec(svec!["deno", "publish", "--include-dependencies"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--check-lib=worker",
+      "--check-lib=node"
+    ]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert_eq!(
+      publish_flags.check_libs,
+      vec![PublishCheckLib::Worker, PublishCheckLib::Node]
+    );
+    let r = flags_from_vec(svec!["deno", "publish", "--check-lib=dom"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "publish", "--show-fast-check"]);
+    let DenoSubcommand::Publish(publish_flags) = r.unwrap().subcommand else {
+      unreachable!();
+    };
+    assert!(publish_flags.show_fast_check);
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--show-fast-check",
+      "--allow-slow-types"
+    ]);
+    r.unwrap_err();
+  }
+
+  #[test]
+  fn registry_attach_provenance() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "attach-provenance",
+      "bundle.json",
+      "@foo/bar@1.0.0",
+      "--token=asdf",
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: Some("foo".to_string()),
-          task: Some("build".to_string()),
-        }),
+        subcommand: DenoSubcommand::Registry(
+          RegistrySubcommand::AttachProvenance(AttachProvenanceFlags {
+            bundle: PathBuf::from("bundle.json"),
+            package: "@foo/bar@1.0.0".to_string(),
+            attestations: None,
+            token: Some("asdf".to_string()),
+            json: false,
+          })
+        ),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn task_subcommand_double_hyphen() {
     let r = flags_from_vec(svec![
       "deno",
-      "task",
-      "-c",
-      "deno.json",
-      "build",
-      "--",
-      "hello",
-      "world",
+      "registry",
+      "attach-provenance",
+      "bundle.json",
+      "@foo/bar@1.0.0",
+      "--attestations",
+      "attestations.json",
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
-        }),
-        argv: svec!["--", "hello", "world"],
-        config_flag: ConfigFlag::Path("deno.json".to_owned()),
+        subcommand: DenoSubcommand::Registry(
+          RegistrySubcommand::AttachProvenance(AttachProvenanceFlags {
+            bundle: PathBuf::from("bundle.json"),
+            package: "@foo/bar@1.0.0".to_string(),
+            attestations: Some(PathBuf::from("attestations.json")),
+            token: None,
+            json: false,
+          })
+        ),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "registry"]);
+    r.unwrap_err();
+    let r = flags_from_vec(svec!["deno", "registry", "attach-provenance"]);
+    r.unwrap_err();
+  }
+
+  #[test]
+  fn registry_audit_log() {
     let r = flags_from_vec(svec![
-      "deno", "task", "--cwd", "foo", "build", "--", "hello", "world"
+      "deno",
+      "registry",
+      "audit-log",
+      "@foo",
+      "--package",
+      "bar",
+      "--since",
+      "2024-01-01",
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: Some("foo".to_string()),
-          task: Some("build".to_string()),
-        }),
-        argv: svec!["--", "hello", "world"],
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::AuditLog(
+          RegistryAuditLogFlags {
+            scope: "@foo".to_string(),
+            token: None,
+            package: Some("bar".to_string()),
+            since: Some("2024-01-01".to_string()),
+            until: None,
+            json: false,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_subcommand_double_hyphen_only() {
-    // edge case, but it should forward
-    let r = flags_from_vec(svec!["deno", "task", "build", "--"]);
+  fn registry_delete() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "delete",
+      "@foo/bar@1.0.0",
+      "--token",
+      "asdf",
+      "-y"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
-        }),
-        argv: svec!["--"],
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Delete(
+          RegistryDeleteFlags {
+            package: "@foo/bar@1.0.0".to_string(),
+            token: Some("asdf".to_string()),
+            yes: true,
+            json: false,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_following_arg() {
-    let r = flags_from_vec(svec!["deno", "task", "build", "-1", "--test"]);
+  fn registry_info() {
+    let r =
+      flags_from_vec(svec!["deno", "registry", "info", "@foo/bar", "--json"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
-        }),
-        argv: svec!["-1", "--test"],
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Info(
+          RegistryInfoFlags {
+            package: "@foo/bar".to_string(),
+            json: true,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_following_double_hyphen_arg() {
-    let r = flags_from_vec(svec!["deno", "task", "build", "--test"]);
+  fn registry_scope_config() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "scope-config",
+      "@foo",
+      "--token",
+      "asdf",
+      "--require-publishing-from-ci=true",
+      "--package",
+      "bar",
+      "--github-repository",
+      "foo/bar"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
-        }),
-        argv: svec!["--test"],
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::ScopeConfig(
+          RegistryScopeConfigFlags {
+            scope: "@foo".to_string(),
+            token: Some("asdf".to_string()),
+            require_publishing_from_ci: Some(true),
+            verify_github_actions_actor: None,
+            package: Some("bar".to_string()),
+            github_repository: Some("foo/bar".to_string()),
+            json: false,
+          }
+        )),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "scope-config",
+      "@foo",
+      "--github-repository",
+      "foo/bar"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn task_with_global_flags() {
-    // can fail if the custom parser in task_parse() starts at the wrong index
+  fn registry_scopes() {
     let r =
-      flags_from_vec(svec!["deno", "--quiet", "--unstable", "task", "build"]);
+      flags_from_vec(svec!["deno", "registry", "scopes", "--token", "asdf"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: Some("build".to_string()),
-        }),
-        unstable_config: UnstableConfig {
-          legacy_flag_enabled: true,
-          ..Default::default()
-        },
-        log_level: Some(log::Level::Error),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Scopes(
+          RegistryScopesFlags {
+            token: Some("asdf".to_string()),
+            json: false,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_subcommand_empty() {
-    let r = flags_from_vec(svec!["deno", "task"]);
+  fn registry_open() {
+    let r = flags_from_vec(svec!["deno", "registry", "open", "@foo/bar@1.0.0"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Open(
+          RegistryOpenFlags {
+            package: Some("@foo/bar@1.0.0".to_string()),
+          }
+        )),
+        ..Flags::default()
+      }
+    );
+    let r = flags_from_vec(svec!["deno", "registry", "open"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: None,
-        }),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Open(
+          RegistryOpenFlags { package: None }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_subcommand_config() {
-    let r = flags_from_vec(svec!["deno", "task", "--config", "deno.jsonc"]);
+  fn registry_ping() {
+    let r = flags_from_vec(svec![
+      "deno", "registry", "ping", "--token", "asdf", "--json"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: None,
-        }),
-        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Ping(
+          RegistryPingFlags {
+            token: Some("asdf".to_string()),
+            json: true,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_subcommand_config_short() {
-    let r = flags_from_vec(svec!["deno", "task", "-c", "deno.jsonc"]);
+  fn registry_provenance() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "provenance",
+      "@foo/bar@1.0.0",
+      "--json"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Task(TaskFlags {
-          cwd: None,
-          task: None,
-        }),
-        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Provenance(
+          RegistryProvenanceFlags {
+            package: "@foo/bar@1.0.0".to_string(),
+            json: true,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn task_subcommand_noconfig_invalid() {
-    let r = flags_from_vec(svec!["deno", "task", "--no-config"]);
-    assert_eq!(
-      r.unwrap_err().kind(),
-      clap::error::ErrorKind::UnknownArgument
-    );
-  }
-
-  #[test]
-  fn bench_with_flags() {
+  fn registry_search() {
     let r = flags_from_vec(svec![
-      "deno",
-      "bench",
-      "--json",
-      "--unstable",
-      "--no-npm",
-      "--no-remote",
-      "--no-run",
-      "--filter",
-      "- foo",
-      "--location",
-      "https:foo",
-      "--allow-net",
-      "dir1/",
-      "dir2/",
-      "--",
-      "arg1",
-      "arg2"
+      "deno", "registry", "search", "path", "--limit", "5", "--json"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bench(BenchFlags {
-          filter: Some("- foo".to_string()),
-          json: true,
-          no_run: true,
-          files: FileFlags {
-            include: vec!["dir1/".to_string(), "dir2/".to_string()],
-            ignore: vec![],
-          },
-          watch: Default::default(),
-        }),
-        unstable_config: UnstableConfig {
-          legacy_flag_enabled: true,
-          ..Default::default()
-        },
-        no_npm: true,
-        no_remote: true,
-        type_check_mode: TypeCheckMode::Local,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        allow_net: Some(vec![]),
-        no_prompt: true,
-        argv: svec!["arg1", "arg2"],
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Search(
+          RegistrySearchFlags {
+            query: "path".to_string(),
+            limit: Some(5),
+            json: true,
+          }
+        )),
         ..Flags::default()
       }
     );
+    let r = flags_from_vec(svec!["deno", "registry", "search", "path"]);
+    assert_eq!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Registry(RegistrySubcommand::Search(
+        RegistrySearchFlags {
+          query: "path".to_string(),
+          limit: None,
+          json: false,
+        }
+      ))
+    );
   }
 
   #[test]
-  fn bench_watch() {
-    let r = flags_from_vec(svec!["deno", "bench", "--watch"]);
+  fn registry_stats() {
+    let r = flags_from_vec(svec!["deno", "registry", "stats", "@foo/bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bench(BenchFlags {
-          filter: None,
-          json: false,
-          no_run: false,
-          files: FileFlags {
-            include: vec![],
-            ignore: vec![],
-          },
-          watch: Some(Default::default()),
-        }),
-        no_prompt: true,
-        type_check_mode: TypeCheckMode::Local,
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Stats(
+          RegistryStatsFlags {
+            package: "@foo/bar".to_string(),
+            json: false,
+          }
+        )),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_with_check() {
-    let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
+  fn registry_status() {
+    let r =
+      flags_from_vec(svec!["deno", "registry", "status", "abc", "--json"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Status(
+          RegistryStatusFlags {
+            task_id: "abc".to_string(),
+            json: true,
+          }
         )),
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "run", "--check=all", "script.ts",]);
+  #[test]
+  fn registry_verify() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "registry",
+      "verify",
+      "@foo/bar@1.0.0",
+      "--path",
+      "bar.tgz",
+      "--json",
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
+        subcommand: DenoSubcommand::Registry(RegistrySubcommand::Verify(
+          RegistryVerifyFlags {
+            package: "@foo/bar@1.0.0".to_string(),
+            path: Some(PathBuf::from("bar.tgz")),
+            bundle: None,
+            json: true,
+          }
         )),
-        type_check_mode: TypeCheckMode::All,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "run", "--check=foo", "script.ts",]);
+  #[test]
+  fn version_bump() {
+    let r = flags_from_vec(svec!["deno", "version", "bump", "minor"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
+        subcommand: DenoSubcommand::Version(VersionSubcommand::Bump(
+          VersionBumpFlags {
+            increment: VersionIncrement::Minor,
+            packages: vec![],
+            dry_run: false,
+          }
         )),
-        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
 
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--no-check",
-      "--check",
-      "script.ts",
+      "version",
+      "bump",
+      "2.0.0-rc.1",
+      "--package",
+      "@foo/bar",
+      "--package",
+      "@foo/baz",
+      "--dry-run",
     ]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn no_config() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-config", "script.ts",]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags::new_default(
-          "script.ts".to_string(),
+        subcommand: DenoSubcommand::Version(VersionSubcommand::Bump(
+          VersionBumpFlags {
+            increment: VersionIncrement::Exact("2.0.0-rc.1".to_string()),
+            packages: svec!["@foo/bar", "@foo/baz"],
+            dry_run: true,
+          }
         )),
-        config_flag: ConfigFlag::Disabled,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--config",
-      "deno.json",
-      "--no-config",
-      "script.ts",
-    ]);
-    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "version", "bump", "auto"]);
+    let DenoSubcommand::Version(VersionSubcommand::Bump(bump_flags)) =
+      r.unwrap().subcommand
+    else {
+      unreachable!();
+    };
+    assert_eq!(bump_flags.increment, VersionIncrement::Auto);
+
+    let r = flags_from_vec(svec!["deno", "version", "bump"]);
+    r.unwrap_err();
   }
 
   #[test]
-  fn init() {
-    let r = flags_from_vec(svec!["deno", "init"]);
+  fn outdated_and_update() {
+    let r = flags_from_vec(svec!["deno", "outdated"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Init(InitFlags { dir: None }),
+        subcommand: DenoSubcommand::Outdated(OutdatedFlags {
+          update: false,
+          latest: false,
+        }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "init", "foo"]);
+    let r = flags_from_vec(svec!["deno", "update", "--latest"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Init(InitFlags {
-          dir: Some(String::from("foo")),
+        subcommand: DenoSubcommand::Outdated(OutdatedFlags {
+          update: true,
+          latest: true,
         }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "init", "--quiet"]);
+    let r = flags_from_vec(svec!["deno", "outdated", "--latest"]);
+    r.unwrap_err();
+  }
+
+  #[test]
+  fn add_subcommand() {
+    let r = flags_from_vec(svec!["deno", "add"]);
+    r.unwrap_err();
+
+    let r = flags_from_vec(svec!["deno", "add", "@david/which"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Init(InitFlags { dir: None }),
-        log_level: Some(Level::Error),
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["@david/which"],
+          dev: false,
+          target: None,
+          version_range: None,
+          alias: None,
+          interactive: false,
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn jupyter() {
-    let r = flags_from_vec(svec!["deno", "jupyter"]);
+    let r = flags_from_vec(svec!["deno", "add", "@david/which", "@luca/hello"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
-          install: false,
-          kernel: false,
-          conn_file: None,
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["@david/which", "@luca/hello"],
+          dev: false,
+          target: None,
+          version_range: None,
+          alias: None,
+          interactive: false,
         }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "jupyter", "--install"]);
+    let r = flags_from_vec(svec!["deno", "add", "--dev", "@std/assert"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
-          install: true,
-          kernel: false,
-          conn_file: None,
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["@std/assert"],
+          dev: true,
+          target: None,
+          version_range: None,
+          alias: None,
+          interactive: false,
         }),
         ..Flags::default()
       }
@@ -8581,84 +10764,94 @@ mod tests {
 
     let r = flags_from_vec(svec![
       "deno",
-      "jupyter",
-      "--kernel",
-      "--conn",
-      "path/to/conn/file"
+      "add",
+      "--target",
+      "package-json",
+      "npm:express"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
-          install: false,
-          kernel: true,
-          conn_file: Some(PathBuf::from("path/to/conn/file")),
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["npm:express"],
+          dev: false,
+          target: Some(AddTarget::PackageJson),
+          version_range: None,
+          alias: None,
+          interactive: false,
         }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "jupyter",
-      "--install",
-      "--conn",
-      "path/to/conn/file"
-    ]);
-    r.unwrap_err();
-    let r = flags_from_vec(svec!["deno", "jupyter", "--kernel",]);
-    r.unwrap_err();
-    let r = flags_from_vec(svec!["deno", "jupyter", "--install", "--kernel",]);
+    let r =
+      flags_from_vec(svec!["deno", "add", "--target", "npm", "@std/path"]);
     r.unwrap_err();
-  }
 
-  #[test]
-  fn publish_args() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "publish",
-      "--no-provenance",
-      "--dry-run",
-      "--allow-slow-types",
-      "--token=asdf",
-    ]);
+    let r = flags_from_vec(svec!["deno", "add", "--exact", "@std/path"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Publish(PublishFlags {
-          token: Some("asdf".to_string()),
-          dry_run: true,
-          allow_slow_types: true,
-          no_provenance: true,
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["@std/path"],
+          dev: false,
+          target: None,
+          version_range: Some(AddVersionRange::Exact),
+          alias: None,
+          interactive: false,
         }),
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn add_subcommand() {
-    let r = flags_from_vec(svec!["deno", "add"]);
+    let r = flags_from_vec(svec!["deno", "add", "--tilde", "@std/path"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["@std/path"],
+          dev: false,
+          target: None,
+          version_range: Some(AddVersionRange::Tilde),
+          alias: None,
+          interactive: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "add", "--exact", "--tilde", "@std/path"]);
     r.unwrap_err();
 
-    let r = flags_from_vec(svec!["deno", "add", "@david/which"]);
+    let r =
+      flags_from_vec(svec!["deno", "add", "npm:preact@10", "--alias", "react"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Add(AddFlags {
-          packages: svec!["@david/which"],
+          packages: svec!["npm:preact@10"],
+          dev: false,
+          target: None,
+          version_range: None,
+          alias: Some("react".to_string()),
+          interactive: false,
         }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "add", "@david/which", "@luca/hello"]);
+    let r = flags_from_vec(svec!["deno", "add", "-i", "@std/path"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Add(AddFlags {
-          packages: svec!["@david/which", "@luca/hello"],
+          packages: svec!["@std/path"],
+          dev: false,
+          target: None,
+          version_range: None,
+          alias: None,
+          interactive: true,
         }),
         ..Flags::default()
       }
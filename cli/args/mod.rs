@@ -28,6 +28,7 @@ pub use deno_config::TsConfigForEmit;
 pub use deno_config::TsConfigType;
 pub use deno_config::TsTypeLib;
 pub use deno_config::WorkspaceConfig;
+pub use deno_config::WorkspaceMemberConfig;
 pub use flags::*;
 pub use lockfile::Lockfile;
 pub use lockfile::LockfileError;
@@ -485,6 +486,71 @@ fn resolve_lint_rules_options(
   }
 }
 
+/// Gets the workspace of a configuration file, including the members of the
+/// `"workspaces"` of a package.json next to it that have a deno.json or
+/// jsr.json, for repositories that are npm workspaces too.
+fn resolve_workspace_config(
+  config_file: &ConfigFile,
+) -> Result<Option<WorkspaceConfig>, AnyError> {
+  let maybe_workspace_config = config_file.to_workspace_config()?;
+  if config_file.specifier.scheme() != "file" {
+    return Ok(maybe_workspace_config);
+  }
+  let config_path = config_file.specifier.to_file_path().unwrap();
+  let root_dir = config_path.parent().unwrap();
+  let member_dirs =
+    package_json::workspace_member_dirs(&root_dir.join("package.json"))?;
+  if member_dirs.is_empty() {
+    return Ok(maybe_workspace_config);
+  }
+
+  let mut members = maybe_workspace_config
+    .map(|workspace_config| workspace_config.members)
+    .unwrap_or_default();
+  for dir in member_dirs {
+    if dir == root_dir || members.iter().any(|member| member.dir_path == dir) {
+      continue;
+    }
+    let Some(member_config_path) =
+      ["deno.json", "deno.jsonc", "jsr.json", "jsr.jsonc"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    else {
+      continue;
+    };
+    let member_config = ConfigFile::read(&member_config_path)?;
+    let (Some(package_name), Some(package_version)) = (
+      member_config.json.name.clone(),
+      member_config.json.version.clone(),
+    ) else {
+      log::debug!(
+        "Skipping package.json workspace member '{}', which isn't a package",
+        dir.display()
+      );
+      continue;
+    };
+    let member_name = dir
+      .strip_prefix(root_dir)
+      .unwrap_or(&dir)
+      .components()
+      .map(|component| component.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/");
+    members.push(WorkspaceMemberConfig {
+      member_name,
+      dir_path: dir,
+      package_name,
+      package_version,
+      config_file: member_config,
+    });
+  }
+  if members.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(WorkspaceConfig { members }))
+}
+
 /// Discover `package.json` file. If `maybe_stop_at` is provided, we will stop
 /// crawling up the directory tree at that path.
 fn discover_package_json(
@@ -724,7 +790,7 @@ impl CliOptions {
     };
     let maybe_workspace_config =
       if let Some(config_file) = maybe_config_file.as_ref() {
-        config_file.to_workspace_config()?
+        resolve_workspace_config(config_file)?
       } else {
         None
       };
@@ -1205,6 +1271,18 @@ impl CliOptions {
     &self.maybe_workspace_config
   }
 
+  /// Gets the members of the workspace, or the package of the configuration
+  /// file when it isn't a workspace.
+  pub fn workspace_members(
+    &self,
+  ) -> Result<Vec<WorkspaceMemberConfig>, AnyError> {
+    match (&self.maybe_workspace_config, &self.maybe_config_file) {
+      (Some(workspace_config), _) => Ok(workspace_config.members.clone()),
+      (None, Some(config_file)) => config_file.to_workspace_members(),
+      (None, None) => Ok(Vec::new()),
+    }
+  }
+
   pub fn maybe_package_json(&self) -> &Option<PackageJson> {
     &self.maybe_package_json
   }
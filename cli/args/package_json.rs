@@ -1,10 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::path::PathBuf;
 
 use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
+use deno_core::serde_json;
 use deno_npm::registry::parse_dep_entry_name_and_raw_version;
 use deno_runtime::deno_node::PackageJson;
 use deno_semver::package::PackageReq;
@@ -147,6 +150,69 @@ pub fn discover_from(
   Ok(None)
 }
 
+/// Gets the directories of the members of the npm workspace of a
+/// package.json, from the globs in its `"workspaces"`. Patterns starting
+/// with `!` exclude directories.
+pub fn workspace_member_dirs(
+  package_json_path: &Path,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let text = match std::fs::read_to_string(package_json_path) {
+    Ok(text) => text,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+      return Ok(Vec::new());
+    }
+    Err(err) => bail!(
+      "Error loading package.json at {}. {:#}",
+      package_json_path.display(),
+      err
+    ),
+  };
+  let value =
+    serde_json::from_str::<serde_json::Value>(&text).with_context(|| {
+      format!("Failed parsing '{}'", package_json_path.display())
+    })?;
+  let root_dir = package_json_path.parent().unwrap();
+  let mut dirs = BTreeSet::new();
+  let mut excluded_dirs = BTreeSet::new();
+  for pattern in workspace_patterns(&value) {
+    let (pattern, matched_dirs) = match pattern.strip_prefix('!') {
+      Some(pattern) => (pattern, &mut excluded_dirs),
+      None => (pattern, &mut dirs),
+    };
+    let full_pattern = root_dir.join(pattern.trim_start_matches("./"));
+    let paths =
+      glob::glob(&full_pattern.to_string_lossy()).with_context(|| {
+        format!(
+          "Invalid pattern '{}' in the \"workspaces\" of '{}'",
+          pattern,
+          package_json_path.display()
+        )
+      })?;
+    for path in paths.flatten() {
+      if path.is_dir() {
+        matched_dirs.insert(path);
+      }
+    }
+  }
+  Ok(dirs.difference(&excluded_dirs).cloned().collect())
+}
+
+/// Gets the patterns of `"workspaces": [...]`, or of the Yarn form
+/// `"workspaces": { "packages": [...] }`.
+fn workspace_patterns(package_json: &serde_json::Value) -> Vec<&str> {
+  let workspaces = match package_json.get("workspaces") {
+    Some(serde_json::Value::Object(obj)) => obj.get("packages"),
+    workspaces => workspaces,
+  };
+  match workspaces {
+    Some(serde_json::Value::Array(patterns)) => patterns
+      .iter()
+      .filter_map(|pattern| pattern.as_str())
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
 #[cfg(test)]
 mod test {
   use pretty_assertions::assert_eq;
@@ -269,4 +335,23 @@ mod test {
       ])
     );
   }
+
+  #[test]
+  fn test_workspace_patterns() {
+    assert_eq!(
+      workspace_patterns(&serde_json::json!({
+        "workspaces": ["packages/*", "!packages/internal"]
+      })),
+      vec!["packages/*", "!packages/internal"]
+    );
+    assert_eq!(
+      workspace_patterns(&serde_json::json!({
+        "workspaces": { "packages": ["libs/*"], "nohoist": ["**/react"] }
+      })),
+      vec!["libs/*"]
+    );
+    assert!(
+      workspace_patterns(&serde_json::json!({ "name": "app" })).is_empty()
+    );
+  }
 }
@@ -116,11 +116,22 @@ impl DenoDir {
     self.root.join("check_cache_v1")
   }
 
+  /// Path for the cache used for the checks of `deno publish`.
+  pub fn publish_check_cache_db_file_path(&self) -> PathBuf {
+    // bump this version name to invalidate the entire cache
+    self.root.join("publish_check_cache_v1")
+  }
+
   /// Path to the registries cache, used for the lps.
   pub fn registries_folder_path(&self) -> PathBuf {
     self.root.join("registries")
   }
 
+  /// Path to the cache of the metadata responses of the registry API.
+  pub fn registry_api_folder_path(&self) -> PathBuf {
+    self.root.join("registry_api")
+  }
+
   /// Path to the dependencies cache folder.
   pub fn deps_folder_path(&self) -> PathBuf {
     self.root.join("deps")
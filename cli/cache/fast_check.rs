@@ -3,6 +3,7 @@
 use deno_core::error::AnyError;
 use deno_graph::FastCheckCacheItem;
 use deno_graph::FastCheckCacheKey;
+use deno_graph::FastCheckCacheModuleItem;
 use deno_runtime::deno_webstorage::rusqlite::params;
 
 use super::cache_db::CacheDB;
@@ -22,15 +23,25 @@ pub static FAST_CHECK_CACHE_DB: CacheDBConfiguration = CacheDBConfiguration {
 #[derive(Clone)]
 pub struct FastCheckCache {
   inner: FastCheckCacheInner,
+  only_passed: bool,
 }
 
 impl FastCheckCache {
   pub fn new(db: CacheDB) -> Self {
     Self {
       inner: FastCheckCacheInner::new(db),
+      only_passed: false,
     }
   }
 
+  /// Only uses the cached results of packages without slow types. The cache
+  /// doesn't keep the diagnostics, which are needed when checking the
+  /// packages of the workspace, so those packages are analyzed again.
+  pub fn with_only_passed(mut self) -> Self {
+    self.only_passed = true;
+    self
+  }
+
   fn ensure_ok<T: Default>(res: Result<T, AnyError>) -> T {
     match res {
       Ok(x) => x,
@@ -51,7 +62,11 @@ impl FastCheckCache {
 
 impl deno_graph::FastCheckCache for FastCheckCache {
   fn get(&self, key: FastCheckCacheKey) -> Option<FastCheckCacheItem> {
-    Self::ensure_ok(self.inner.get(key))
+    let item = Self::ensure_ok(self.inner.get(key))?;
+    if self.only_passed && has_diagnostics(&item) {
+      return None;
+    }
+    Some(item)
   }
 
   fn set(&self, key: FastCheckCacheKey, value: FastCheckCacheItem) {
@@ -59,6 +74,12 @@ impl deno_graph::FastCheckCache for FastCheckCache {
   }
 }
 
+fn has_diagnostics(item: &FastCheckCacheItem) -> bool {
+  item.modules.iter().any(|(_, module)| {
+    matches!(module, FastCheckCacheModuleItem::Diagnostic(_))
+  })
+}
+
 #[derive(Clone)]
 struct FastCheckCacheInner {
   conn: CacheDB,
@@ -114,8 +135,8 @@ mod test {
   use std::collections::BTreeSet;
 
   use deno_ast::ModuleSpecifier;
-  use deno_graph::FastCheckCacheModuleItem;
   use deno_graph::FastCheckCacheModuleItemDiagnostic;
+  use deno_graph::FastCheckCacheModuleItemInfo;
   use deno_semver::package::PackageNv;
 
   use super::*;
@@ -159,4 +180,50 @@ mod test {
     let cache = FastCheckCacheInner::new(conn);
     assert!(cache.get(key).unwrap().is_none());
   }
+
+  #[test]
+  pub fn cache_only_passed() {
+    let conn = CacheDB::in_memory(&FAST_CHECK_CACHE_DB, "1.0.0");
+    let cache = FastCheckCache::new(conn.clone());
+    let only_passed_cache = FastCheckCache::new(conn).with_only_passed();
+
+    let specifier = ModuleSpecifier::parse("file:///a/mod.ts").unwrap();
+    let key = FastCheckCacheKey::build(
+      &PackageNv::from_str("@scope/a@1.0.0").unwrap(),
+      &BTreeSet::from([specifier.clone()]),
+    );
+    let failed = FastCheckCacheItem {
+      dependencies: BTreeSet::new(),
+      modules: vec![(
+        specifier.clone(),
+        FastCheckCacheModuleItem::Diagnostic(
+          FastCheckCacheModuleItemDiagnostic { source_hash: 123 },
+        ),
+      )],
+    };
+    deno_graph::FastCheckCache::set(&cache, key, failed.clone());
+    assert_eq!(deno_graph::FastCheckCache::get(&cache, key), Some(failed));
+    assert_eq!(
+      deno_graph::FastCheckCache::get(&only_passed_cache, key),
+      None
+    );
+
+    let passed = FastCheckCacheItem {
+      dependencies: BTreeSet::new(),
+      modules: vec![(
+        specifier,
+        FastCheckCacheModuleItem::Info(FastCheckCacheModuleItemInfo {
+          source_hash: 123,
+          module_info: "{}".to_string(),
+          text: "export {};".into(),
+          source_map: Vec::<u8>::new().into(),
+        }),
+      )],
+    };
+    deno_graph::FastCheckCache::set(&only_passed_cache, key, passed.clone());
+    assert_eq!(
+      deno_graph::FastCheckCache::get(&only_passed_cache, key),
+      Some(passed)
+    );
+  }
 }
@@ -34,6 +34,7 @@ mod incremental;
 mod module_info;
 mod node;
 mod parsed_source;
+mod publish_check;
 
 pub use caches::Caches;
 pub use check::TypeCheckCache;
@@ -48,6 +49,7 @@ pub use module_info::ModuleInfoCache;
 pub use node::NodeAnalysisCache;
 pub use parsed_source::LazyGraphSourceParser;
 pub use parsed_source::ParsedSourceCache;
+pub use publish_check::PublishCheckCache;
 
 /// Permissions used to save a file in the disk caches.
 pub const CACHE_PERM: u32 = 0o644;
@@ -0,0 +1,105 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use super::cache_db::CacheDB;
+use super::cache_db::CacheDBConfiguration;
+use super::cache_db::CacheFailure;
+use deno_core::error::AnyError;
+use deno_runtime::deno_webstorage::rusqlite::params;
+
+pub static PUBLISH_CHECK_CACHE_DB: CacheDBConfiguration =
+  CacheDBConfiguration {
+    table_initializer: "CREATE TABLE IF NOT EXISTS publishcheck (
+      check_hash TEXT PRIMARY KEY
+    );",
+    on_version_change: "DELETE FROM publishcheck;",
+    preheat_queries: &[],
+    // If the cache fails, just ignore all caching attempts
+    on_failure: CacheFailure::Blackhole,
+  };
+
+/// The cache used to tell whether the module graph of the packages being
+/// published was already checked, eg. by `deno publish --dry-run` before
+/// publishing.
+///
+/// This stores a hash of the inputs of each graph that passed the slow types
+/// and type checks and only clears them out when changing CLI versions.
+pub struct PublishCheckCache(CacheDB);
+
+impl PublishCheckCache {
+  pub fn new(db: CacheDB) -> Self {
+    Self(db)
+  }
+
+  pub fn has_check_hash(&self, hash: u64) -> bool {
+    match self.has_check_hash_result(hash) {
+      Ok(val) => val,
+      Err(err) => {
+        if cfg!(debug_assertions) {
+          panic!("Error retrieving hash: {err}");
+        } else {
+          log::debug!("Error retrieving hash: {}", err);
+          // fail silently when not debugging
+          false
+        }
+      }
+    }
+  }
+
+  fn has_check_hash_result(&self, hash: u64) -> Result<bool, AnyError> {
+    self.0.exists(
+      "SELECT * FROM publishcheck WHERE check_hash=?1 LIMIT 1",
+      params![hash.to_string()],
+    )
+  }
+
+  pub fn add_check_hash(&self, check_hash: u64) {
+    if let Err(err) = self.add_check_hash_result(check_hash) {
+      if cfg!(debug_assertions) {
+        panic!("Error saving check hash: {err}");
+      } else {
+        log::debug!("Error saving check hash: {}", err);
+      }
+    }
+  }
+
+  fn add_check_hash_result(&self, check_hash: u64) -> Result<(), AnyError> {
+    let sql = "
+    INSERT OR REPLACE INTO
+      publishcheck (check_hash)
+    VALUES
+      (?1)";
+    self.0.execute(sql, params![&check_hash.to_string(),])?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  pub fn publish_check_cache_general_use() {
+    let conn = CacheDB::in_memory(&PUBLISH_CHECK_CACHE_DB, "1.0.0");
+    let cache = PublishCheckCache::new(conn);
+
+    assert!(!cache.has_check_hash(1));
+    cache.add_check_hash(1);
+    assert!(cache.has_check_hash(1));
+    assert!(!cache.has_check_hash(2));
+
+    // try changing the cli version (should clear)
+    let conn = cache.0.recreate_with_version("2.0.0");
+    let cache = PublishCheckCache::new(conn);
+    assert!(!cache.has_check_hash(1));
+    cache.add_check_hash(1);
+
+    // recreating the cache should not remove the data because the CLI version is the same
+    let conn = cache.0.recreate_with_version("2.0.0");
+    let cache = PublishCheckCache::new(conn);
+    assert!(cache.has_check_hash(1));
+
+    // adding when already exists should not cause issue
+    cache.add_check_hash(1);
+    assert!(cache.has_check_hash(1));
+  }
+}
@@ -16,6 +16,9 @@ use crate::resolver::CliGraphResolver;
 use crate::resolver::SloppyImportsResolver;
 use crate::tools::check;
 use crate::tools::check::TypeChecker;
+use crate::tools::registry::transpiles_jsx;
+use crate::tools::registry::AssetLoaders;
+use crate::tools::registry::AssetShimLoader;
 use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::fs::canonicalize_path;
 use crate::util::path::specifier_to_file_path;
@@ -274,12 +277,24 @@ impl ModuleGraphCreator {
     for package in packages {
       roots.extend(package.config_file.resolve_export_value_urls()?);
     }
+    // files like `.svelte` components are analyzed with the loader shims
+    // configured in "publish.loaders"
+    let asset_loaders = AssetLoaders::from_members(packages)?;
+    let mut default_loader;
+    let mut shim_loader;
+    let loader: Option<&mut dyn Loader> = if asset_loaders.is_empty() {
+      None
+    } else {
+      default_loader = self.module_graph_builder.create_graph_loader();
+      shim_loader = AssetShimLoader::new(&mut default_loader, asset_loaders);
+      Some(&mut shim_loader)
+    };
     let mut graph = self
       .create_graph_with_options(CreateGraphOptions {
         is_dynamic: false,
         graph_kind: deno_graph::GraphKind::All,
         roots,
-        loader: None,
+        loader,
       })
       .await?;
     if self.options.type_check_mode().is_true() {
@@ -289,6 +304,9 @@ impl ModuleGraphCreator {
       &mut graph,
       BuildFastCheckGraphOptions {
         workspace_fast_check: true,
+        // the type declarations of transpiled TSX modules are generated
+        // from their fast check modules
+        fast_check_dts: transpiles_jsx(packages)?,
       },
     )?;
     Ok(graph)
@@ -359,6 +377,7 @@ impl ModuleGraphCreator {
         check::CheckOptions {
           build_fast_check_graph: true,
           lib: self.options.ts_type_lib_window(),
+          lib_override: None,
           log_ignored_options: true,
           reload: self.options.reload_flag(),
           type_check_mode: self.options.type_check_mode(),
@@ -372,6 +391,8 @@ pub struct BuildFastCheckGraphOptions {
   /// Whether to do fast check on workspace members. This
   /// is mostly only useful when publishing.
   pub workspace_fast_check: bool,
+  /// Whether to generate the type declarations of the fast check modules.
+  pub fast_check_dts: bool,
 }
 
 pub struct ModuleGraphBuilder {
@@ -605,10 +626,14 @@ impl ModuleGraphBuilder {
     }
 
     log::debug!("Building fast check graph");
-    let fast_check_cache = if !options.workspace_fast_check {
-      Some(cache::FastCheckCache::new(self.caches.fast_check_db()))
+    let fast_check_cache =
+      cache::FastCheckCache::new(self.caches.fast_check_db());
+    // the members of the workspace are checked for slow types, so their
+    // results are only reused when they had none
+    let fast_check_cache = if options.workspace_fast_check {
+      fast_check_cache.with_only_passed()
     } else {
-      None
+      fast_check_cache
     };
     let parser = self.parsed_source_cache.as_capturing_parser();
     let cli_resolver = &self.resolver;
@@ -623,8 +648,13 @@ impl ModuleGraphBuilder {
     graph.build_fast_check_type_graph(
       deno_graph::BuildFastCheckTypeGraphOptions {
         jsr_url_provider: Some(&CliJsrUrlProvider),
-        fast_check_cache: fast_check_cache.as_ref().map(|c| c as _),
-        fast_check_dts: false,
+        // the cached results don't have the type declarations
+        fast_check_cache: if options.fast_check_dts {
+          None
+        } else {
+          Some(&fast_check_cache)
+        },
+        fast_check_dts: options.fast_check_dts,
         module_parser: Some(&parser),
         resolver: Some(graph_resolver),
         npm_resolver: Some(graph_npm_resolver),
@@ -248,6 +248,28 @@ impl HttpClient {
     }
   }
 
+  /// Trusts the provided PEM encoded certificates in addition to the ones
+  /// from the root cert store.
+  pub fn with_ca_certs(mut self, ca_certs: Vec<Vec<u8>>) -> Self {
+    self.options.ca_certs = ca_certs;
+    self
+  }
+
+  /// Keeps idle connections in the pool for the provided duration and pings
+  /// idle HTTP/2 connections at the provided interval, so that later
+  /// requests can be multiplexed over them instead of opening new ones.
+  pub fn with_persistent_connections(
+    mut self,
+    idle_timeout: Duration,
+    keep_alive_interval: Duration,
+  ) -> Self {
+    self.options.pool_idle_timeout =
+      Some(Some(idle_timeout.as_millis() as u64));
+    self.options.http2_keep_alive_interval =
+      Some(keep_alive_interval.as_millis() as u64);
+    self
+  }
+
   #[cfg(test)]
   pub fn from_client(client: reqwest::Client) -> Self {
     let result = Self {
@@ -3,6 +3,7 @@
 use crate::args::jsr_api_url;
 use crate::args::jsr_url;
 use crate::file_fetcher::FileFetcher;
+use crate::tools::registry::api;
 use dashmap::DashMap;
 use deno_cache_dir::HttpCache;
 use deno_core::anyhow::anyhow;
@@ -18,7 +19,6 @@ use deno_semver::jsr::JsrPackageReqReference;
 use deno_semver::package::PackageNv;
 use deno_semver::package::PackageReq;
 use deno_semver::Version;
-use serde::Deserialize;
 use std::borrow::Cow;
 use std::sync::Arc;
 
@@ -224,13 +224,7 @@ impl PackageSearchApi for CliJsrSearchApi {
     if let Some(names) = self.search_cache.get(query) {
       return Ok(names.clone());
     }
-    let mut search_url = jsr_api_url().clone();
-    search_url
-      .path_segments_mut()
-      .map_err(|_| anyhow!("Custom jsr URL cannot be a base."))?
-      .pop_if_empty()
-      .push("packages");
-    search_url.query_pairs_mut().append_pair("query", query);
+    let search_url = api::search_packages_url(jsr_api_url().as_str(), query)?;
     let file = self
       .file_fetcher
       .fetch(&search_url, PermissionsContainer::allow_all())
@@ -338,19 +332,7 @@ fn partial_jsr_package_version_info_from_slice(
 }
 
 fn parse_jsr_search_response(source: &str) -> Result<Vec<String>, AnyError> {
-  #[derive(Debug, Deserialize)]
-  #[serde(rename_all = "camelCase")]
-  struct Item {
-    scope: String,
-    name: String,
-    version_count: usize,
-  }
-  #[derive(Debug, Deserialize)]
-  #[serde(rename_all = "camelCase")]
-  struct Response {
-    items: Vec<Item>,
-  }
-  let items = serde_json::from_str::<Response>(source)?.items;
+  let items = serde_json::from_str::<api::List<api::Package>>(source)?.items;
   Ok(
     items
       .into_iter()
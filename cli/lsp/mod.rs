@@ -25,7 +25,7 @@ pub mod jsr;
 pub mod language_server;
 mod logging;
 mod lsp_custom;
-mod npm;
+pub mod npm;
 mod parent_process_checker;
 mod path_to_regex;
 mod performance;
@@ -162,6 +162,9 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
         tools::lint::lint(flags, lint_flags).await
       }
     }),
+    DenoSubcommand::Outdated(outdated_flags) => spawn_subcommand(async {
+      tools::registry::outdated(flags, outdated_flags).await
+    }),
     DenoSubcommand::Repl(repl_flags) => {
       spawn_subcommand(async move { tools::repl::run(flags, repl_flags).await })
     }
@@ -220,6 +223,12 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
     DenoSubcommand::Publish(publish_flags) => spawn_subcommand(async {
       tools::registry::publish(flags, publish_flags).await
     }),
+    DenoSubcommand::Registry(registry_subcommand) => spawn_subcommand(async {
+      tools::registry::registry(flags, registry_subcommand).await
+    }),
+    DenoSubcommand::Version(version_subcommand) => spawn_subcommand(async {
+      tools::registry::version(flags, version_subcommand).await
+    }),
   };
 
   handle.await?
@@ -166,6 +166,7 @@ impl ModuleLoadPreparer {
           check::CheckOptions {
             build_fast_check_graph: true,
             lib,
+            lib_override: None,
             log_ignored_options: false,
             reload: self.options.reload_flag()
               && !roots.iter().all(|r| reload_exclusions.contains(r)),
@@ -39,6 +39,9 @@ pub struct CheckOptions {
   pub build_fast_check_graph: bool,
   /// Default type library to type check with.
   pub lib: TsTypeLib,
+  /// The `lib` compiler option to type check with instead of the libraries
+  /// of `lib` and the configuration file, eg. for another runtime than Deno.
+  pub lib_override: Option<Vec<String>>,
   /// Whether to log about any ignored compiler options.
   pub log_ignored_options: bool,
   /// If true, valid `.tsbuildinfo` files will be ignored and type checking
@@ -124,7 +127,12 @@ impl TypeChecker {
     }
 
     let type_check_mode = options.type_check_mode;
-    let ts_config = ts_config_result.ts_config;
+    let mut ts_config = ts_config_result.ts_config;
+    if let Some(lib_override) = options.lib_override {
+      if let Some(compiler_options) = ts_config.0.as_object_mut() {
+        compiler_options.insert("lib".to_string(), lib_override.into());
+      }
+    }
     let maybe_check_hash = match self.npm_resolver.check_state_hash() {
       Some(npm_check_hash) => {
         match get_check_hash(
@@ -181,6 +189,7 @@ impl TypeChecker {
         &mut graph,
         BuildFastCheckGraphOptions {
           workspace_fast_check: false,
+          fast_check_dts: false,
         },
       )?;
     }
@@ -3,6 +3,7 @@
 //! This module provides file linting utilities using
 //! [`deno_lint`](https://github.com/denoland/deno_lint).
 use deno_ast::diagnostics::Diagnostic;
+use deno_ast::diagnostics::DiagnosticLocation;
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
 use deno_ast::ParsedSource;
@@ -42,6 +43,8 @@ use crate::cache::IncrementalCache;
 use crate::colors;
 use crate::factory::CliFactory;
 use crate::tools::fmt::run_parallelized;
+use crate::tools::registry;
+use crate::tools::registry::PublishDiagnostic;
 use crate::util::file_watcher;
 use crate::util::fs::canonicalize_path;
 use crate::util::fs::specifier_from_file_path;
@@ -173,12 +176,16 @@ async fn lint_files(
   let has_error = Arc::new(AtomicFlag::default());
 
   let mut futures = Vec::with_capacity(2);
-  if lint_rules.no_slow_types {
+  if lint_rules.no_slow_types || !lint_rules.jsr_rules.is_empty() {
     if let Some(config_file) = maybe_config_file {
       let members = config_file.to_workspace_members()?;
       let has_error = has_error.clone();
       let reporter_lock = reporter_lock.clone();
       let module_graph_creator = factory.module_graph_creator().await?.clone();
+      let parsed_source_cache = factory.parsed_source_cache().clone();
+      let maybe_lockfile = factory.maybe_lockfile().clone();
+      let no_slow_types = lint_rules.no_slow_types;
+      let jsr_rules = lint_rules.jsr_rules.clone();
       let path_urls = paths
         .iter()
         .filter_map(|p| ModuleSpecifier::from_file_path(p).ok())
@@ -189,15 +196,24 @@ async fn lint_files(
         // setup to handle workspaces. Iterating over the workspace members
         // should be done at a higher level because it also needs to take into
         // account the config per workspace member.
+        let mut linted_members = Vec::with_capacity(members.len());
         for member in &members {
           let export_urls = member.config_file.resolve_export_value_urls()?;
           if !export_urls.iter().any(|url| path_urls.contains(url)) {
             continue; // entrypoint is not specified, so skip
           }
-          let diagnostics = no_slow_types::collect_no_slow_type_diagnostics(
+          linted_members.push(member.clone());
+          if !no_slow_types {
+            continue;
+          }
+          let allowlist = registry::SlowTypesAllowlist::from_config_file(
+            &member.config_file,
+          )?;
+          let mut diagnostics = no_slow_types::collect_no_slow_type_diagnostics(
             &export_urls,
             &graph,
           );
+          diagnostics.retain(|diagnostic| !allowlist.allows(diagnostic));
           if !diagnostics.is_empty() {
             has_error.raise();
             let mut reporter = reporter_lock.lock();
@@ -207,6 +223,25 @@ async fn lint_files(
             }
           }
         }
+        if !jsr_rules.is_empty() && !linted_members.is_empty() {
+          let lockfile =
+            maybe_lockfile.as_ref().map(|lockfile| lockfile.lock());
+          let diagnostics = registry::collect_jsr_lint_diagnostics(
+            &graph,
+            &parsed_source_cache,
+            lockfile.as_deref(),
+            &linted_members,
+            &jsr_rules,
+          )?;
+          if !diagnostics.is_empty() {
+            has_error.raise();
+            let mut reporter = reporter_lock.lock();
+            for diagnostic in &diagnostics {
+              reporter
+                .visit_diagnostic(LintOrCliDiagnostic::Publish(diagnostic));
+            }
+          }
+        }
         Ok(())
       }));
     }
@@ -390,13 +425,25 @@ fn handle_lint_result(
 pub enum LintOrCliDiagnostic<'a> {
   Lint(&'a LintDiagnostic),
   FastCheck(&'a FastCheckDiagnostic),
+  Publish(&'a PublishDiagnostic),
 }
 
 impl<'a> LintOrCliDiagnostic<'a> {
-  pub fn specifier(&self) -> &ModuleSpecifier {
+  pub fn specifier(&self) -> Cow<'_, str> {
     match self {
-      LintOrCliDiagnostic::Lint(d) => &d.specifier,
-      LintOrCliDiagnostic::FastCheck(d) => d.specifier(),
+      LintOrCliDiagnostic::Lint(d) => Cow::Borrowed(d.specifier.as_str()),
+      LintOrCliDiagnostic::FastCheck(d) => {
+        Cow::Borrowed(d.specifier().as_str())
+      }
+      LintOrCliDiagnostic::Publish(d) => match d.location() {
+        DiagnosticLocation::Path { path } => {
+          Cow::Owned(path.display().to_string())
+        }
+        DiagnosticLocation::Module { specifier }
+        | DiagnosticLocation::ModulePosition { specifier, .. } => {
+          Cow::Owned(specifier.to_string())
+        }
+      },
     }
   }
 
@@ -406,6 +453,7 @@ impl<'a> LintOrCliDiagnostic<'a> {
       LintOrCliDiagnostic::FastCheck(d) => {
         d.range().map(|r| (&r.text_info, r.range))
       }
+      LintOrCliDiagnostic::Publish(d) => d.range(),
     }
   }
 }
@@ -415,6 +463,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.level(),
       LintOrCliDiagnostic::FastCheck(d) => d.level(),
+      LintOrCliDiagnostic::Publish(d) => d.level(),
     }
   }
 
@@ -422,6 +471,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.code(),
       LintOrCliDiagnostic::FastCheck(_) => Cow::Borrowed("no-slow-types"),
+      LintOrCliDiagnostic::Publish(d) => d.code(),
     }
   }
 
@@ -429,6 +479,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.message(),
       LintOrCliDiagnostic::FastCheck(d) => d.message(),
+      LintOrCliDiagnostic::Publish(d) => d.message(),
     }
   }
 
@@ -436,6 +487,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.location(),
       LintOrCliDiagnostic::FastCheck(d) => d.location(),
+      LintOrCliDiagnostic::Publish(d) => d.location(),
     }
   }
 
@@ -443,6 +495,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.snippet(),
       LintOrCliDiagnostic::FastCheck(d) => d.snippet(),
+      LintOrCliDiagnostic::Publish(d) => d.snippet(),
     }
   }
 
@@ -450,6 +503,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.hint(),
       LintOrCliDiagnostic::FastCheck(d) => d.hint(),
+      LintOrCliDiagnostic::Publish(d) => d.hint(),
     }
   }
 
@@ -459,6 +513,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.snippet_fixed(),
       LintOrCliDiagnostic::FastCheck(d) => d.snippet_fixed(),
+      LintOrCliDiagnostic::Publish(d) => d.snippet_fixed(),
     }
   }
 
@@ -466,6 +521,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.info(),
       LintOrCliDiagnostic::FastCheck(d) => d.info(),
+      LintOrCliDiagnostic::Publish(d) => d.info(),
     }
   }
 
@@ -473,6 +529,7 @@ impl<'a> deno_ast::diagnostics::Diagnostic for LintOrCliDiagnostic<'a> {
     match self {
       LintOrCliDiagnostic::Lint(d) => d.docs_url(),
       LintOrCliDiagnostic::FastCheck(d) => d.docs_url(),
+      LintOrCliDiagnostic::Publish(d) => d.docs_url(),
     }
   }
 }
@@ -697,7 +754,7 @@ fn get_config_rules_err_empty(
   maybe_config_file: Option<&deno_config::ConfigFile>,
 ) -> Result<ConfiguredRules, AnyError> {
   let lint_rules = get_configured_rules(rules, maybe_config_file);
-  if lint_rules.rules.is_empty() {
+  if lint_rules.rules.is_empty() && lint_rules.jsr_rules.is_empty() {
     bail!("No rules have been configured")
   }
   Ok(lint_rules)
@@ -708,6 +765,8 @@ pub struct ConfiguredRules {
   pub rules: Vec<&'static dyn LintRule>,
   // cli specific rules
   pub no_slow_types: bool,
+  /// The checks of `deno publish` that are enabled as lint rules.
+  pub jsr_rules: Vec<&'static str>,
 }
 
 impl ConfiguredRules {
@@ -719,6 +778,7 @@ impl ConfiguredRules {
     if self.no_slow_types {
       names.push("no-slow-types");
     }
+    names.extend(self.jsr_rules.iter().copied());
     names
   }
 }
@@ -731,13 +791,17 @@ pub fn get_configured_rules(
   let implicit_no_slow_types = maybe_config_file
     .map(|c| c.is_package() || !c.json.workspaces.is_empty())
     .unwrap_or(false);
+  let is_cli_rule =
+    |c: &str| c == NO_SLOW_TYPES_NAME || registry::JSR_LINT_RULES.contains(&c);
   if rules.tags.is_none() && rules.include.is_none() && rules.exclude.is_none()
   {
     ConfiguredRules {
       rules: rules::get_recommended_rules(),
       no_slow_types: implicit_no_slow_types,
+      jsr_rules: Vec::new(),
     }
   } else {
+    let jsr_rules = get_configured_jsr_rules(&rules);
     let no_slow_types = implicit_no_slow_types
       && !rules
         .exclude
@@ -747,25 +811,40 @@ pub fn get_configured_rules(
     let rules = rules::get_filtered_rules(
       rules.tags.or_else(|| Some(vec!["recommended".to_string()])),
       rules.exclude.map(|exclude| {
-        exclude
-          .into_iter()
-          .filter(|c| c != NO_SLOW_TYPES_NAME)
-          .collect()
+        exclude.into_iter().filter(|c| !is_cli_rule(c)).collect()
       }),
       rules.include.map(|include| {
-        include
-          .into_iter()
-          .filter(|c| c != NO_SLOW_TYPES_NAME)
-          .collect()
+        include.into_iter().filter(|c| !is_cli_rule(c)).collect()
       }),
     );
     ConfiguredRules {
       rules,
       no_slow_types,
+      jsr_rules,
     }
   }
 }
 
+/// The checks of `deno publish` that are enabled, either all of them with
+/// the `jsr` tag or by their names.
+fn get_configured_jsr_rules(rules: &LintRulesConfig) -> Vec<&'static str> {
+  let contains = |names: &Option<Vec<String>>, name: &str| {
+    names
+      .as_ref()
+      .map(|names| names.iter().any(|n| n == name))
+      .unwrap_or(false)
+  };
+  let has_jsr_tag = contains(&rules.tags, registry::JSR_LINT_TAG);
+  registry::JSR_LINT_RULES
+    .iter()
+    .copied()
+    .filter(|code| {
+      (has_jsr_tag || contains(&rules.include, code))
+        && !contains(&rules.exclude, code)
+    })
+    .collect()
+}
+
 #[cfg(test)]
 mod test {
   use deno_lint::rules::get_recommended_rules;
@@ -795,4 +874,31 @@ mod test {
     recommended_rule_names.sort();
     assert_eq!(rule_names, recommended_rule_names);
   }
+
+  #[test]
+  fn jsr_rules_from_tag_and_include() {
+    let rules_config = LintRulesConfig {
+      exclude: Some(vec!["deno-only-api".to_string()]),
+      include: None,
+      tags: Some(vec!["recommended".to_string(), "jsr".to_string()]),
+    };
+    let rules = get_configured_rules(rules_config, None);
+    assert_eq!(
+      rules.jsr_rules,
+      vec![
+        "invalid-external-import",
+        "unpinned-dependency",
+        "conflicting-exports"
+      ]
+    );
+
+    let rules_config = LintRulesConfig {
+      exclude: None,
+      include: Some(vec!["deno-only-api".to_string()]),
+      tags: None,
+    };
+    let rules = get_configured_rules(rules_config, None);
+    assert_eq!(rules.jsr_rules, vec!["deno-only-api"]);
+    assert!(!rules.rules.is_empty());
+  }
 }
@@ -1,9 +1,29 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
 use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::url::Url;
 use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
+use deno_terminal::colors;
 use serde::de::DeserializeOwned;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+
+use super::auth::AuthHeader;
+use super::trace;
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -21,31 +41,203 @@ pub struct ExchangeAuthorizationResponse {
   pub user: User,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
   pub name: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+  pub scope: String,
+}
+
+/// The settings of a scope, which only members of it can see.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeSettings {
+  pub scope: String,
+  #[serde(default, rename = "requirePublishingFromCI")]
+  pub require_publishing_from_ci: Option<bool>,
+  #[serde(default)]
+  pub gh_actions_verify_actor: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeMember {
+  pub scope: String,
+  #[serde(default)]
+  pub is_admin: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubRepository {
+  pub owner: String,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+  pub scope: String,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  /// `None` when the registry doesn't report the linked repository and
+  /// `Some(None)` when the package isn't linked to one.
+  #[serde(default, deserialize_with = "deserialize_some")]
+  pub github_repository: Option<Option<GithubRepository>>,
+  #[serde(default)]
+  pub latest_version: Option<String>,
+  #[serde(default)]
+  pub version_count: usize,
+  /// The documentation score in percent.
+  #[serde(default)]
+  pub score: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersion {
+  pub scope: String,
+  pub package: String,
+  pub version: String,
+  #[serde(default)]
+  pub yanked: bool,
+  #[serde(default)]
+  pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyKind {
+  Jsr,
+  Npm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersionDependency {
+  pub kind: DependencyKind,
+  pub name: String,
+  pub constraint: String,
+  #[serde(default)]
+  pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogEntry {
+  /// Eg. `package_version_publish`.
+  pub action: String,
+  /// Not set for actions of the registry itself.
+  #[serde(default)]
+  pub user: Option<User>,
+  /// The name of the package the action affected, if any.
+  #[serde(default)]
+  pub package: Option<String>,
+  #[serde(default)]
+  pub meta: serde_json::Value,
+  pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The number of downloads in a time bucket of a day.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadDataPoint {
+  pub time_bucket: chrono::DateTime<chrono::Utc>,
+  pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDownloads {
+  pub version: String,
+  pub downloads: Vec<DownloadDataPoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDownloads {
+  pub total: Vec<DownloadDataPoint>,
+  /// The downloads of the versions that were downloaded recently.
+  #[serde(default)]
+  pub recent_versions: Vec<VersionDownloads>,
+}
+
+/// A page of the results of a list endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct List<T> {
+  pub items: Vec<T>,
+  #[serde(default)]
+  pub total: usize,
+}
+
+/// The response of a list endpoint, which is either a page of the results
+/// or, for endpoints that aren't paginated, all of them.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ListResponse<T> {
+  Page(List<T>),
+  All(Vec<T>),
+}
+
+/// The number of items requested per page from list endpoints.
+const PAGE_LIMIT: usize = 100;
+/// The number of pages that are followed at most.
+const MAX_PAGES: usize = 100;
+/// The number of packages searched for when no limit is passed.
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  T: Deserialize<'de>,
+  D: Deserializer<'de>,
+{
+  T::deserialize(deserializer).map(Some)
+}
+
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OidcTokenResponse {
   pub value: String,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PublishingTaskError {
   pub code: String,
   pub message: String,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PublishingTask {
   pub id: String,
   pub status: String,
   pub error: Option<PublishingTaskError>,
+  #[serde(default)]
+  pub package_scope: Option<String>,
+  #[serde(default)]
+  pub package_name: Option<String>,
+  #[serde(default)]
+  pub package_version: Option<String>,
+}
+
+/// A chunked upload of the tarball of a package version.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSession {
+  pub id: String,
+  /// The number of bytes the registry has received.
+  pub offset: u64,
+  /// The size of the chunks the registry accepts, if it limits it.
+  #[serde(default)]
+  pub chunk_size: Option<u64>,
 }
 
 #[derive(serde::Deserialize)]
@@ -57,6 +249,57 @@ pub struct ApiError {
   pub data: serde_json::Value,
   #[serde(skip)]
   pub x_deno_ray: Option<String>,
+  /// The status of the response, `None` when the error didn't come from a
+  /// response.
+  #[serde(skip)]
+  pub status: Option<StatusCode>,
+  /// The error as one the CLI handles, interpreted from the code and data.
+  #[serde(skip)]
+  pub kind: ApiErrorKind,
+}
+
+/// The error codes of the registry that the CLI handles.
+#[derive(Default)]
+pub enum ApiErrorKind {
+  /// The version was already uploaded, by the publishing task.
+  DuplicateVersionPublish(Box<PublishingTask>),
+  /// The user didn't approve the interactive authorization yet.
+  AuthorizationPending,
+  /// The credentials aren't allowed to do what was requested.
+  MissingPermission,
+  /// The owner of the credentials is not a member of the scope.
+  ActorNotScopeMember,
+  /// The version was published too long ago to be deleted.
+  VersionDeletionPeriodPassed,
+  #[default]
+  Other,
+}
+
+impl ApiErrorKind {
+  fn from_error(code: &str, data: &mut serde_json::Value) -> Self {
+    match code {
+      "duplicateVersionPublish" => {
+        let task = data
+          .get_mut("task")
+          .map(serde_json::Value::take)
+          .and_then(|task| serde_json::from_value(task).ok());
+        match task {
+          Some(task) => Self::DuplicateVersionPublish(Box::new(task)),
+          None => {
+            log::debug!(
+              "Missing publishing task in duplicateVersionPublish error"
+            );
+            Self::Other
+          }
+        }
+      }
+      "authorizationPending" => Self::AuthorizationPending,
+      "missingPermission" | "actorNotAuthorized" => Self::MissingPermission,
+      "actorNotScopeMember" => Self::ActorNotScopeMember,
+      "versionDeletionPeriodPassed" => Self::VersionDeletionPeriodPassed,
+      _ => Self::Other,
+    }
+  }
 }
 
 impl std::fmt::Display for ApiError {
@@ -77,68 +320,1457 @@ impl std::fmt::Debug for ApiError {
 
 impl std::error::Error for ApiError {}
 
+/// The credentials used for publishing a package were valid, but not
+/// allowed to publish it.
+#[derive(Debug, thiserror::Error)]
+pub enum PublishPermissionError {
+  #[error(
+    "The token does not have permission to publish @{scope}/{package}. It may be read-only or restricted to other scopes or packages. Create a token that can publish it at {create_token_url}"
+  )]
+  MissingPermission {
+    scope: String,
+    package: String,
+    create_token_url: String,
+    #[source]
+    source: ApiError,
+  },
+  #[error(
+    "The owner of the token is not a member of @{scope}, so it can't publish @{scope}/{package}. Ask an admin of the scope to invite them at {scope_members_url}"
+  )]
+  NotScopeMember {
+    scope: String,
+    package: String,
+    scope_members_url: String,
+    #[source]
+    source: ApiError,
+  },
+}
+
+/// Interprets an error response of the registry to a publish request, for
+/// errors caused by the permissions of the credentials used.
+pub fn publish_permission_error(
+  err: ApiError,
+  registry_url: &str,
+  scope: &str,
+  package: &str,
+) -> Result<PublishPermissionError, ApiError> {
+  match err.kind {
+    ApiErrorKind::MissingPermission => {
+      Ok(PublishPermissionError::MissingPermission {
+        scope: scope.to_string(),
+        package: package.to_string(),
+        create_token_url: format!("{}account/tokens/create", registry_url),
+        source: err,
+      })
+    }
+    ApiErrorKind::ActorNotScopeMember => {
+      Ok(PublishPermissionError::NotScopeMember {
+        scope: scope.to_string(),
+        package: package.to_string(),
+        scope_members_url: format!("{}@{}/~/members", registry_url, scope),
+        source: err,
+      })
+    }
+    _ => Err(err),
+  }
+}
+
+/// The number of bytes of a response body that are included in errors and
+/// debug logs.
+const MAX_RESPONSE_BODY_DISPLAY_LEN: usize = 2048;
+
 pub async fn parse_response<T: DeserializeOwned>(
   response: reqwest::Response,
 ) -> Result<T, ApiError> {
   let status = response.status();
-  let x_deno_ray = response
-    .headers()
+  let url = response.url().clone();
+  let headers = response.headers().clone();
+  let x_deno_ray = headers
     .get("x-deno-ray")
     .and_then(|value| value.to_str().ok())
     .map(|s| s.to_string());
-  let text = response.text().await.unwrap();
+  let unknown_error = |message: String| ApiError {
+    code: "unknown".to_string(),
+    message,
+    data: serde_json::json!({}),
+    x_deno_ray: x_deno_ray.clone(),
+    status: Some(status),
+    kind: ApiErrorKind::Other,
+  };
+  let text = match response.text().await {
+    Ok(text) => text,
+    Err(err) => {
+      return Err(unknown_error(format!("Failed to read response: {}", err)))
+    }
+  };
+  let log_response = |reason: &str| {
+    log::debug!(
+      "{} from {}: status {}, headers {:?}, body '{}'",
+      reason,
+      url,
+      status,
+      headers,
+      truncate_body(&text)
+    );
+  };
 
   if !status.is_success() {
     match serde_json::from_str::<ApiError>(&text) {
       Ok(mut err) => {
+        log::debug!("Registry error from {}: {}", url, err);
         err.x_deno_ray = x_deno_ray;
+        err.status = Some(status);
+        err.kind = ApiErrorKind::from_error(&err.code, &mut err.data);
         return Err(err);
       }
       Err(_) => {
-        let err = ApiError {
-          code: "unknown".to_string(),
-          message: format!("{}: {}", status, text),
-          x_deno_ray,
-          data: serde_json::json!({}),
-        };
-        return Err(err);
+        log_response("Unexpected error response");
+        return Err(unknown_error(format!(
+          "{}: {}",
+          status,
+          truncate_body(&text)
+        )));
       }
     }
   }
 
-  serde_json::from_str(&text).map_err(|err| ApiError {
-    code: "unknown".to_string(),
-    message: format!("Failed to parse response: {}, response: '{}'", err, text),
-    x_deno_ray,
-    data: serde_json::json!({}),
+  serde_json::from_str(&text).map_err(|err| {
+    log_response("Unexpected response");
+    unknown_error(format!(
+      "Failed to parse response: {}, response: '{}'",
+      err,
+      truncate_body(&text)
+    ))
   })
 }
 
-pub async fn get_scope(
-  client: &reqwest::Client,
-  registry_api_url: &str,
-  scope: &str,
-) -> Result<reqwest::Response, AnyError> {
-  let scope_url = format!("{}scopes/{}", registry_api_url, scope);
-  let response = client.get(&scope_url).send().await?;
-  Ok(response)
+/// Truncates a response body to a length that can be displayed.
+fn truncate_body(text: &str) -> std::borrow::Cow<str> {
+  if text.len() <= MAX_RESPONSE_BODY_DISPLAY_LEN {
+    return std::borrow::Cow::Borrowed(text);
+  }
+  let mut end = MAX_RESPONSE_BODY_DISPLAY_LEN;
+  while !text.is_char_boundary(end) {
+    end -= 1;
+  }
+  std::borrow::Cow::Owned(format!(
+    "{}... ({} more bytes)",
+    &text[..end],
+    text.len() - end
+  ))
 }
 
-pub fn get_package_api_url(
-  registry_api_url: &str,
-  scope: &str,
-  package: &str,
-) -> String {
-  format!("{}scopes/{}/packages/{}", registry_api_url, scope, package)
+/// Gets the registry error an error was caused by, if any.
+pub fn as_api_error(err: &AnyError) -> Option<&ApiError> {
+  err.downcast_ref::<ApiError>()
 }
 
-pub async fn get_package(
-  client: &reqwest::Client,
-  registry_api_url: &str,
-  scope: &str,
-  package: &str,
-) -> Result<reqwest::Response, AnyError> {
-  let package_url = get_package_api_url(registry_api_url, scope, package);
-  let response = client.get(&package_url).send().await?;
-  Ok(response)
+/// A client for the API of a JSR registry, eg. `https://api.jsr.io/`.
+///
+/// Errors returned by the registry are [`ApiError`]s, which can be
+/// retrieved from the returned errors with [`as_api_error`].
+#[derive(Clone)]
+pub struct JsrApiClient {
+  client: reqwest::Client,
+  api_url: String,
+  cache: Option<Arc<ApiResponseCache>>,
+  /// Whether the warning about showing cached responses because the
+  /// registry can't be reached was printed.
+  offline_warned: Arc<AtomicBool>,
+  /// The `Accept-Encoding` header of the last response that had one, which
+  /// advertises the encodings the registry accepts for uploads.
+  accept_encoding: Arc<Mutex<Option<String>>>,
+  /// The base URLs of the mirrors of the registry, eg. `https://jsr.io/`,
+  /// that reads fail over to.
+  mirror_urls: Vec<Url>,
+  /// Whether to try chunked uploads, `false` when the registry advertises
+  /// that it doesn't support them.
+  chunked_uploads: bool,
+}
+
+impl JsrApiClient {
+  pub fn new(client: reqwest::Client, api_url: &Url) -> Self {
+    Self {
+      client,
+      api_url: api_url.to_string(),
+      cache: None,
+      offline_warned: Default::default(),
+      accept_encoding: Default::default(),
+      mirror_urls: Vec::new(),
+      chunked_uploads: true,
+    }
+  }
+
+  pub fn with_chunked_uploads(mut self, chunked_uploads: bool) -> Self {
+    self.chunked_uploads = chunked_uploads;
+    self
+  }
+
+  /// Fails over to the provided mirrors of the registry, in order, when
+  /// reads can't reach the registry. Writes always go to the registry.
+  pub fn with_mirrors(mut self, mirror_urls: Vec<Url>) -> Self {
+    self.mirror_urls = mirror_urls;
+    self
+  }
+
+  /// Caches the responses of the unauthenticated metadata requests, which
+  /// are then revalidated instead of downloaded again and used when the
+  /// registry can't be reached.
+  pub fn with_cache(mut self, cache: ApiResponseCache) -> Self {
+    self.cache = Some(Arc::new(cache));
+    self
+  }
+
+  pub fn scope_url(&self, scope: &str) -> String {
+    format!("{}scopes/{}", self.api_url, scope)
+  }
+
+  pub fn package_url(&self, scope: &str, package: &str) -> String {
+    format!("{}/packages/{}", self.scope_url(scope), package)
+  }
+
+  pub fn package_version_url(
+    &self,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> String {
+    format!("{}/versions/{}", self.package_url(scope, package), version)
+  }
+
+  pub fn provenance_url(
+    &self,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> String {
+    format!(
+      "{}/provenance",
+      self.package_version_url(scope, package, version)
+    )
+  }
+
+  /// Sends a request to the registry. When the registry rate limits the
+  /// request, it is retried after the time the `Retry-After` header of the
+  /// response asks for, so that polling loops slow down instead of failing.
+  async fn send(
+    &self,
+    request: reqwest::RequestBuilder,
+  ) -> Result<reqwest::Response, AnyError> {
+    let mut retries = 0;
+    loop {
+      // requests with a streamed body can't be retried
+      let Some(attempt) = request.try_clone() else {
+        return Ok(trace::send(&self.client, request).await?);
+      };
+      let response = trace::send(&self.client, attempt).await?;
+      if let Some(accept_encoding) = response
+        .headers()
+        .get(reqwest::header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+      {
+        *self.accept_encoding.lock() = Some(accept_encoding.to_string());
+      }
+      if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(response);
+      }
+      let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+          parse_retry_after(value, crate::util::time::utc_now())
+        })
+        .unwrap_or(DEFAULT_RETRY_AFTER);
+      if retries >= MAX_RATE_LIMIT_RETRIES || retry_after > MAX_RETRY_AFTER {
+        bail!(
+          "The registry is rate limiting the requests of this client. Try again in {} seconds.",
+          retry_after.as_secs().max(1)
+        );
+      }
+      retries += 1;
+      log::warn!(
+        "{} Rate limited by the registry, retrying in {} seconds",
+        colors::yellow("Warning"),
+        retry_after.as_secs().max(1)
+      );
+      tokio::time::sleep(retry_after).await;
+    }
+  }
+
+  /// The encoding to upload tarballs with, based on the encodings the
+  /// registry advertised in the responses so far.
+  pub fn upload_encoding(&self) -> UploadEncoding {
+    negotiate_upload_encoding(self.accept_encoding.lock().as_deref())
+  }
+
+  /// The URL followed by the same URL at each mirror. `base_url` is the
+  /// base of `url`, which `mirror_base_url` gets for a mirror.
+  fn failover_urls(
+    &self,
+    url: &str,
+    base_url: &str,
+    mirror_base_url: impl Fn(&Url) -> String,
+  ) -> Vec<String> {
+    let mut urls = vec![url.to_string()];
+    if let Some(path) = url.strip_prefix(base_url) {
+      urls.extend(
+        self
+          .mirror_urls
+          .iter()
+          .map(|mirror_url| format!("{}{}", mirror_base_url(mirror_url), path)),
+      );
+    }
+    urls
+  }
+
+  /// Sends a request to each of the URLs in order until one can be reached.
+  async fn send_with_failover(
+    &self,
+    urls: Vec<String>,
+    request: impl Fn(&str) -> reqwest::RequestBuilder,
+  ) -> Result<reqwest::Response, AnyError> {
+    let mut urls = urls.into_iter().peekable();
+    loop {
+      let url = urls.next().unwrap();
+      match self.send(request(&url)).await {
+        Err(err) if is_connection_error(&err) && urls.peek().is_some() => {
+          log::warn!(
+            "{} Failed to reach {}, trying the mirror {}: {:#}",
+            colors::yellow("Warning"),
+            url,
+            urls.peek().unwrap(),
+            err
+          );
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Gets a file of the registry, eg. `@foo/bar/meta.json` at
+  /// `https://jsr.io/`, from a mirror when the registry can't be reached.
+  pub async fn get_registry_file(
+    &self,
+    registry_url: &Url,
+    path: &str,
+  ) -> Result<reqwest::Response, AnyError> {
+    let url = registry_url.join(path)?;
+    let urls =
+      self.failover_urls(url.as_str(), registry_url.as_str(), Url::to_string);
+    self
+      .send_with_failover(urls, |url| self.client.get(url))
+      .await
+  }
+
+  /// Gets a resource, `None` when it doesn't exist.
+  async fn get_optional<T: DeserializeOwned>(
+    &self,
+    url: String,
+  ) -> Result<Option<T>, AnyError> {
+    let cached = self.cache.as_ref().and_then(|cache| cache.get(&url));
+    let urls = self.failover_urls(&url, &self.api_url, |mirror_url| {
+      let mut api_url = mirror_url.clone();
+      api_url.set_path("api/");
+      api_url.to_string()
+    });
+    let result = self
+      .send_with_failover(urls, |url| {
+        let request = self.client.get(url);
+        match cached.as_ref().and_then(|cached| cached.etag.as_ref()) {
+          Some(etag) => request.header(reqwest::header::IF_NONE_MATCH, etag),
+          None => request,
+        }
+      })
+      .await;
+    let response = match (result, cached) {
+      (Ok(response), cached) => match (response.status(), cached) {
+        (StatusCode::NOT_FOUND, _) => return Ok(None),
+        (StatusCode::NOT_MODIFIED, Some(cached)) => {
+          let value = serde_json::from_str(&cached.body)?;
+          if let Some(cache) = &self.cache {
+            // the cached response is as fresh as a downloaded one
+            cache.set(
+              &url,
+              CachedResponse {
+                cached_at: crate::util::time::utc_now().timestamp(),
+                ..cached
+              },
+            );
+          }
+          return Ok(Some(value));
+        }
+        _ => response,
+      },
+      (Err(err), Some(cached)) if is_connection_error(&err) => {
+        self.warn_offline(&cached, &err);
+        return Ok(Some(serde_json::from_str(&cached.body)?));
+      }
+      (Err(err), _) => return Err(err),
+    };
+    match &self.cache {
+      Some(cache) if response.status().is_success() => {
+        let etag = response
+          .headers()
+          .get(reqwest::header::ETAG)
+          .and_then(|value| value.to_str().ok())
+          .map(|value| value.to_string());
+        let body = response.text().await?;
+        let value = serde_json::from_str(&body)?;
+        cache.set(
+          &url,
+          CachedResponse {
+            etag,
+            body,
+            cached_at: crate::util::time::utc_now().timestamp(),
+          },
+        );
+        Ok(Some(value))
+      }
+      _ => Ok(Some(parse_response(response).await?)),
+    }
+  }
+
+  /// Warns once that cached responses are used because the registry
+  /// can't be reached, and how old they are.
+  fn warn_offline(&self, cached: &CachedResponse, err: &AnyError) {
+    if self.offline_warned.swap(true, Ordering::Relaxed) {
+      return;
+    }
+    let age = crate::util::time::utc_now().timestamp() - cached.cached_at;
+    log::warn!(
+      "{} The registry can't be reached, showing data cached {} ago that may be out of date: {:#}",
+      colors::yellow("Warning"),
+      format_age(age),
+      err
+    );
+  }
+
+  /// Gets the items of a list endpoint, following its pages until all or
+  /// `max_items` of them were received. `None` when the endpoint doesn't
+  /// exist and no authorization is passed.
+  async fn get_list<T: DeserializeOwned>(
+    &self,
+    url: Url,
+    authorization: Option<&AuthHeader>,
+    max_items: Option<usize>,
+  ) -> Result<Option<Vec<T>>, AnyError> {
+    let max_items = max_items.unwrap_or(usize::MAX);
+    let mut items = Vec::new();
+    for page in 1..=MAX_PAGES {
+      let limit = (max_items - items.len()).min(PAGE_LIMIT);
+      let mut page_url = url.clone();
+      page_url
+        .query_pairs_mut()
+        .append_pair("page", &page.to_string())
+        .append_pair("limit", &limit.to_string());
+      let response = match authorization {
+        Some(authorization) => {
+          let request = self
+            .client
+            .get(page_url)
+            .header(authorization.name.clone(), &*authorization.value);
+          let response = self.send(request).await?;
+          Some(parse_response(response).await?)
+        }
+        None => self.get_optional(page_url.to_string()).await?,
+      };
+      let list = match response {
+        Some(ListResponse::Page(list)) => list,
+        Some(ListResponse::All(all)) => {
+          items.extend(all);
+          break;
+        }
+        None if page == 1 => return Ok(None),
+        None => break,
+      };
+      let page_len = list.items.len();
+      items.extend(list.items);
+      // registries may return shorter pages than requested, so the total
+      // decides whether there are more
+      let is_last_page = if list.total > 0 {
+        items.len() >= list.total
+      } else {
+        page_len < limit
+      };
+      if page_len == 0 || is_last_page || items.len() >= max_items {
+        break;
+      }
+    }
+    items.truncate(max_items);
+    Ok(Some(items))
+  }
+
+  pub async fn get_scope(
+    &self,
+    scope: &str,
+  ) -> Result<Option<Scope>, AnyError> {
+    self.get_optional(self.scope_url(scope)).await
+  }
+
+  pub async fn get_package(
+    &self,
+    scope: &str,
+    package: &str,
+  ) -> Result<Option<Package>, AnyError> {
+    self.get_optional(self.package_url(scope, package)).await
+  }
+
+  pub async fn get_package_version(
+    &self,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> Result<Option<PackageVersion>, AnyError> {
+    self
+      .get_optional(self.package_version_url(scope, package, version))
+      .await
+  }
+
+  /// Searches the packages of the registry by name and description.
+  pub async fn search_packages(
+    &self,
+    query: &str,
+    limit: Option<usize>,
+  ) -> Result<Vec<Package>, AnyError> {
+    let url = search_packages_url(&self.api_url, query)?;
+    let packages = self
+      .get_list(url, None, Some(limit.unwrap_or(DEFAULT_SEARCH_LIMIT)))
+      .await?;
+    Ok(packages.unwrap_or_default())
+  }
+
+  pub async fn create_scope(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+  ) -> Result<Scope, AnyError> {
+    let request = self
+      .client
+      .post(format!("{}scopes", self.api_url))
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(&json!({ "scope": scope }));
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  pub async fn create_package(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+  ) -> Result<Package, AnyError> {
+    let request = self
+      .client
+      .post(format!("{}/packages", self.scope_url(scope)))
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(&json!({ "package": package }));
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  pub async fn get_scope_settings(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+  ) -> Result<ScopeSettings, AnyError> {
+    let request = self
+      .client
+      .get(self.scope_url(scope))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Updates a setting of a scope, eg. `{ "requirePublishingFromCI": true }`.
+  pub async fn update_scope_settings(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    update: &serde_json::Value,
+  ) -> Result<ScopeSettings, AnyError> {
+    let request = self
+      .client
+      .patch(self.scope_url(scope))
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(update);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Links a package to a GitHub repository, or unlinks it with `None`.
+  pub async fn update_package_github_repository(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+    repository: Option<&GithubRepository>,
+  ) -> Result<(), AnyError> {
+    let github_repository = repository.map(|repository| {
+      json!({ "owner": repository.owner, "repo": repository.name })
+    });
+    let request = self
+      .client
+      .patch(self.package_url(scope, package))
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(&json!({ "githubRepository": github_repository }));
+    let response = self.send(request).await?;
+    if !response.status().is_success() {
+      parse_response::<IgnoredAny>(response).await?;
+    }
+    Ok(())
+  }
+
+  /// Deletes a version, which the registry only allows shortly after it
+  /// was published.
+  pub async fn delete_package_version(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> Result<(), AnyError> {
+    let request = self
+      .client
+      .delete(self.package_version_url(scope, package, version))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    if !response.status().is_success() {
+      parse_response::<IgnoredAny>(response).await?;
+    }
+    Ok(())
+  }
+
+  /// Gets the recent audit events of a scope, newest first.
+  pub async fn get_scope_audit_log(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+  ) -> Result<Vec<AuditLogEntry>, AnyError> {
+    let url = Url::parse(&format!("{}/audit_log", self.scope_url(scope)))?;
+    let entries = self.get_list(url, Some(authorization), None).await?;
+    Ok(entries.unwrap_or_default())
+  }
+
+  /// Gets the daily download counts of a package, `None` when the package
+  /// doesn't exist.
+  pub async fn get_package_downloads(
+    &self,
+    scope: &str,
+    package: &str,
+  ) -> Result<Option<PackageDownloads>, AnyError> {
+    self
+      .get_optional(format!("{}/downloads", self.package_url(scope, package)))
+      .await
+  }
+
+  /// Gets all the versions of a package, `None` when the package doesn't
+  /// exist.
+  pub async fn get_package_versions(
+    &self,
+    scope: &str,
+    package: &str,
+  ) -> Result<Option<Vec<PackageVersion>>, AnyError> {
+    let url =
+      Url::parse(&format!("{}/versions", self.package_url(scope, package)))?;
+    self.get_list(url, None, None).await
+  }
+
+  /// Gets the dependencies of a package version, `None` when the version
+  /// doesn't exist.
+  pub async fn get_package_version_dependencies(
+    &self,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> Result<Option<Vec<PackageVersionDependency>>, AnyError> {
+    let url = format!(
+      "{}/dependencies",
+      self.package_version_url(scope, package, version)
+    );
+    self.get_optional(url).await
+  }
+
+  /// Gets the authenticated user.
+  pub async fn get_user(
+    &self,
+    authorization: &AuthHeader,
+  ) -> Result<User, AnyError> {
+    let request = self
+      .client
+      .get(format!("{}user", self.api_url))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Gets the scopes the authenticated user is a member of.
+  pub async fn get_user_scopes(
+    &self,
+    authorization: &AuthHeader,
+  ) -> Result<Vec<Scope>, AnyError> {
+    let url = Url::parse(&format!("{}user/scopes", self.api_url))?;
+    let scopes = self.get_list(url, Some(authorization), None).await?;
+    Ok(scopes.unwrap_or_default())
+  }
+
+  /// Gets the membership of the authenticated user in the provided scope.
+  pub async fn get_user_scope_member(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+  ) -> Result<ScopeMember, AnyError> {
+    let request = self
+      .client
+      .get(format!("{}user/member/{}", self.api_url, scope))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Creates an authorization for the provided permissions that the user
+  /// approves in the browser.
+  pub async fn create_authorization<P: Serialize>(
+    &self,
+    challenge: &str,
+    permissions: &P,
+  ) -> Result<CreateAuthorizationResponse, AnyError> {
+    let request = self
+      .client
+      .post(format!("{}authorizations", self.api_url))
+      .json(&json!({
+        "challenge": challenge,
+        "permissions": permissions,
+      }));
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Exchanges an approved authorization for a token. Fails with the
+  /// `authorizationPending` error code while it isn't approved yet.
+  pub async fn exchange_authorization(
+    &self,
+    exchange_token: &str,
+    verifier: &str,
+  ) -> Result<ExchangeAuthorizationResponse, AnyError> {
+    let request = self
+      .client
+      .post(format!("{}authorizations/exchange", self.api_url))
+      .json(&json!({
+        "exchangeToken": exchange_token,
+        "verifier": verifier,
+      }));
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Uploads the compressed tarball of a package version, which the
+  /// registry publishes in the returned task.
+  pub async fn publish_version(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+    version: &str,
+    config: &str,
+    tarball: EncodedTarball,
+  ) -> Result<PublishingTask, AnyError> {
+    let url = format!(
+      "{}?config=/{}",
+      self.package_version_url(scope, package, version),
+      config
+    );
+    let request = self
+      .client
+      .post(url)
+      .header(authorization.name.clone(), &*authorization.value)
+      .header(reqwest::header::CONTENT_ENCODING, tarball.encoding.as_str())
+      .body(tarball.bytes);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Starts a chunked upload of the compressed tarball of a package
+  /// version. Returns `None` when the registry doesn't support chunked
+  /// uploads.
+  pub async fn create_upload(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+    version: &str,
+    config: &str,
+    tarball: &EncodedTarball,
+  ) -> Result<Option<UploadSession>, AnyError> {
+    if !self.chunked_uploads {
+      return Ok(None);
+    }
+    let url = format!(
+      "{}/uploads?config=/{}",
+      self.package_version_url(scope, package, version),
+      config
+    );
+    let request = self
+      .client
+      .post(url)
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(&json!({
+        "size": tarball.bytes.len(),
+        "encoding": tarball.encoding.as_str(),
+      }));
+    let response = self.send(request).await?;
+    if matches!(
+      response.status(),
+      StatusCode::NOT_FOUND
+        | StatusCode::METHOD_NOT_ALLOWED
+        | StatusCode::NOT_IMPLEMENTED
+    ) {
+      return Ok(None);
+    }
+    Ok(Some(parse_response(response).await?))
+  }
+
+  /// Gets how much of a chunked upload the registry has received.
+  pub async fn get_upload(
+    &self,
+    authorization: &AuthHeader,
+    id: &str,
+  ) -> Result<UploadSession, AnyError> {
+    let request = self
+      .client
+      .get(format!("{}uploads/{}", self.api_url, id))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Uploads the chunk of a tarball that starts at `offset`.
+  pub async fn upload_chunk(
+    &self,
+    authorization: &AuthHeader,
+    id: &str,
+    offset: u64,
+    chunk: Bytes,
+  ) -> Result<UploadSession, AnyError> {
+    let request = self
+      .client
+      .patch(format!("{}uploads/{}", self.api_url, id))
+      .header(authorization.name.clone(), &*authorization.value)
+      .header("upload-offset", offset.to_string())
+      .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+      .body(chunk);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Completes a chunked upload, after which the registry publishes the
+  /// version in the returned task.
+  pub async fn complete_upload(
+    &self,
+    authorization: &AuthHeader,
+    id: &str,
+  ) -> Result<PublishingTask, AnyError> {
+    let request = self
+      .client
+      .post(format!("{}uploads/{}/complete", self.api_url, id))
+      .header(authorization.name.clone(), &*authorization.value);
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  pub async fn get_publishing_task(
+    &self,
+    authorization: Option<&AuthHeader>,
+    id: &str,
+  ) -> Result<PublishingTask, AnyError> {
+    let mut request = self
+      .client
+      .get(format!("{}publish_status/{}", self.api_url, id));
+    if let Some(authorization) = authorization {
+      request =
+        request.header(authorization.name.clone(), &*authorization.value);
+    }
+    let response = self.send(request).await?;
+    Ok(parse_response(response).await?)
+  }
+
+  /// Gets the provenance bundle of a package version, `None` when it was
+  /// published without provenance.
+  pub async fn get_provenance(
+    &self,
+    scope: &str,
+    package: &str,
+    version: &str,
+  ) -> Result<Option<serde_json::Value>, AnyError> {
+    let body: Option<serde_json::Value> = self
+      .get_optional(self.provenance_url(scope, package, version))
+      .await?;
+    // the bundle is returned in the shape it was submitted in
+    Ok(body.map(|mut body| match body.get_mut("bundle") {
+      Some(bundle) => bundle.take(),
+      None => body,
+    }))
+  }
+
+  /// Submits a provenance bundle, and the bundles of any additional
+  /// attestations, for an already published package version.
+  pub async fn submit_provenance<T: Serialize>(
+    &self,
+    authorization: &AuthHeader,
+    scope: &str,
+    package: &str,
+    version: &str,
+    bundle: &T,
+    attestations: &[T],
+  ) -> Result<(), AnyError> {
+    let body = if attestations.is_empty() {
+      json!({ "bundle": bundle })
+    } else {
+      json!({ "bundle": bundle, "attestations": attestations })
+    };
+    let request = self
+      .client
+      .post(self.provenance_url(scope, package, version))
+      .header(authorization.name.clone(), &*authorization.value)
+      .json(&body);
+    let response = self.send(request).await?;
+    if !response.status().is_success() {
+      parse_response::<IgnoredAny>(response).await?;
+    }
+    Ok(())
+  }
+}
+
+/// The compression of an uploaded tarball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadEncoding {
+  Gzip,
+  Zstd,
+}
+
+impl UploadEncoding {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      UploadEncoding::Gzip => "gzip",
+      UploadEncoding::Zstd => "zstd",
+    }
+  }
+}
+
+/// A tarball to upload, compressed with `encoding`.
+pub struct EncodedTarball {
+  pub bytes: Bytes,
+  pub encoding: UploadEncoding,
+}
+
+/// Picks the encoding of uploads from the `Accept-Encoding` response header
+/// of the registry (RFC 7694). Every registry accepts gzip.
+fn negotiate_upload_encoding(accept_encoding: Option<&str>) -> UploadEncoding {
+  let accepts_zstd = accept_encoding
+    .into_iter()
+    .flat_map(|value| value.split(','))
+    .any(|item| {
+      let mut parts = item.split(';').map(str::trim);
+      let coding = parts.next().unwrap_or_default();
+      // a quality of 0 means not acceptable
+      let rejected = parts.any(|param| {
+        param
+          .strip_prefix("q=")
+          .and_then(|q| q.parse::<f32>().ok())
+          .is_some_and(|q| q == 0.0)
+      });
+      coding.eq_ignore_ascii_case("zstd") && !rejected
+    });
+  if accepts_zstd {
+    UploadEncoding::Zstd
+  } else {
+    UploadEncoding::Gzip
+  }
+}
+
+/// An on-disk cache of the successful responses of the registry API,
+/// keyed by URL.
+pub struct ApiResponseCache {
+  dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+  /// The `ETag` to revalidate the response with, if the registry sent one.
+  etag: Option<String>,
+  body: String,
+  /// When the response was downloaded or last revalidated, in seconds
+  /// since the Unix epoch. Zero for responses cached by older versions.
+  #[serde(default)]
+  cached_at: i64,
+}
+
+impl ApiResponseCache {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  fn path(&self, url: &str) -> PathBuf {
+    self.dir.join(crate::util::checksum::gen(&[url.as_bytes()]))
+  }
+
+  fn get(&self, url: &str) -> Option<CachedResponse> {
+    let text = std::fs::read_to_string(self.path(url)).ok()?;
+    serde_json::from_str(&text).ok()
+  }
+
+  /// Failing to write the cache only makes the next request slower, so
+  /// errors are ignored.
+  fn set(&self, url: &str, response: CachedResponse) {
+    let Ok(text) = serde_json::to_string(&response) else {
+      return;
+    };
+    if let Err(err) = std::fs::create_dir_all(&self.dir)
+      .and_then(|_| std::fs::write(self.path(url), text))
+    {
+      log::debug!("Failed caching the response of {}: {}", url, err);
+    }
+  }
+}
+
+/// Formats the age of a cached response, eg. `3 hours`.
+fn format_age(seconds: i64) -> String {
+  let (count, unit) = match seconds.max(0) {
+    seconds if seconds < 60 => return "less than a minute".to_string(),
+    seconds if seconds < 60 * 60 => (seconds / 60, "minute"),
+    seconds if seconds < 24 * 60 * 60 => (seconds / (60 * 60), "hour"),
+    seconds => (seconds / (24 * 60 * 60), "day"),
+  };
+  if count == 1 {
+    format!("1 {}", unit)
+  } else {
+    format!("{} {}s", count, unit)
+  }
+}
+
+/// Whether the request failed without getting a response from the server.
+fn is_connection_error(err: &AnyError) -> bool {
+  err
+    .downcast_ref::<reqwest::Error>()
+    .is_some_and(|err| err.is_connect() || err.is_timeout())
+}
+
+/// How often a rate limited request is retried.
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+/// The wait when a rate limited response doesn't say how long to wait.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+/// Longer waits fail instead of leaving the user waiting.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+/// Parses the `Retry-After` header, which is either a number of seconds or
+/// an HTTP date.
+fn parse_retry_after(
+  value: &str,
+  now: chrono::DateTime<chrono::Utc>,
+) -> Option<Duration> {
+  let value = value.trim();
+  if let Ok(seconds) = value.parse::<u64>() {
+    return Some(Duration::from_secs(seconds));
+  }
+  let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+  Some(
+    (date.with_timezone(&chrono::Utc) - now)
+      .to_std()
+      .unwrap_or_default(),
+  )
+}
+
+/// The URL of a package search, which returns a [`List`] of [`Package`]s,
+/// eg. `https://api.jsr.io/packages?query=fs`.
+pub fn search_packages_url(
+  api_url: &str,
+  query: &str,
+) -> Result<Url, AnyError> {
+  let mut url = Url::parse(api_url)?;
+  url
+    .path_segments_mut()
+    .map_err(|_| {
+      deno_core::anyhow::anyhow!("Custom jsr URL cannot be a base.")
+    })?
+    .pop_if_empty()
+    .push("packages");
+  url.query_pairs_mut().append_pair("query", query);
+  Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+
+  use deno_core::serde_json::json;
+  use deno_runtime::deno_tls::rustls;
+  use deno_runtime::deno_tls::rustls_pemfile;
+  use deno_runtime::deno_tls::rustls_tokio_stream::TlsStream;
+  use hyper_v014::server::conn::Http;
+  use hyper_v014::service::service_fn;
+  use hyper_v014::Body;
+  use hyper_v014::Request;
+  use test_util::TempDir;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn requests_reuse_connection() {
+    // a minimal HTTP/1.1 server that counts the connections it accepts
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let server_connections = connections.clone();
+    tokio::spawn(async move {
+      loop {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        server_connections.fetch_add(1, Ordering::SeqCst);
+        tokio::spawn(async move {
+          let mut request = Vec::new();
+          let mut buf = [0; 1024];
+          loop {
+            let read = stream.read(&mut buf).await.unwrap_or(0);
+            if read == 0 {
+              return;
+            }
+            request.extend_from_slice(&buf[..read]);
+            // the requests don't have a body, so they end with an empty line
+            while let Some(end) =
+              request.windows(4).position(|window| window == b"\r\n\r\n")
+            {
+              request.drain(..end + 4);
+              let body = r#"{"scope":"foo"}"#;
+              let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+              );
+              stream.write_all(response.as_bytes()).await.unwrap();
+            }
+          }
+        });
+      }
+    });
+
+    let http_client = crate::http_util::HttpClient::new(None, None);
+    let api_client = JsrApiClient::new(
+      http_client.client().unwrap().clone(),
+      &Url::parse(&format!("http://{}/api/", addr)).unwrap(),
+    );
+    for _ in 0..5 {
+      let scope = api_client.get_scope("foo").await.unwrap();
+      assert_eq!(scope.unwrap().scope, "foo");
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+  }
+
+  #[tokio::test]
+  async fn concurrent_uploads_share_connection() {
+    // a TLS server that negotiates HTTP/2 like the registry and counts the
+    // connections it accepts
+    let tls_config = {
+      let read_pem = |name: &str| {
+        std::fs::read(test_util::testdata_path().join("tls").join(name))
+          .unwrap()
+      };
+      let certs =
+        rustls_pemfile::certs(&mut read_pem("localhost.crt").as_slice())
+          .unwrap()
+          .into_iter()
+          .map(rustls::Certificate)
+          .collect();
+      let key = rustls_pemfile::pkcs8_private_keys(
+        &mut read_pem("localhost.key").as_slice(),
+      )
+      .unwrap()
+      .remove(0);
+      let mut config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, rustls::PrivateKey(key))
+        .unwrap();
+      config.alpn_protocols = vec!["h2".into(), "http/1.1".into()];
+      Arc::new(config)
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let uploads = Arc::new(AtomicUsize::new(0));
+    let server_connections = connections.clone();
+    let server_uploads = uploads.clone();
+    tokio::spawn(async move {
+      loop {
+        let (stream, _) = listener.accept().await.unwrap();
+        server_connections.fetch_add(1, Ordering::SeqCst);
+        let stream =
+          TlsStream::new_server_side(stream, tls_config.clone(), None);
+        let uploads = server_uploads.clone();
+        let service = service_fn(move |request: Request<Body>| {
+          let uploads = uploads.clone();
+          async move {
+            let body = if request.method() == hyper_v014::Method::POST {
+              let tarball = hyper_v014::body::to_bytes(request.into_body())
+                .await
+                .unwrap();
+              assert_eq!(tarball.len(), 64 * 1024);
+              uploads.fetch_add(1, Ordering::SeqCst);
+              // keep the uploads in flight at the same time
+              tokio::time::sleep(Duration::from_millis(100)).await;
+              r#"{"id":"task","status":"pending","error":null}"#
+            } else {
+              r#"{"scope":"foo"}"#
+            };
+            Ok::<_, std::convert::Infallible>(
+              hyper_v014::Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+            )
+          }
+        });
+        tokio::spawn(Http::new().serve_connection(stream, service));
+      }
+    });
+
+    let root_ca =
+      std::fs::read(test_util::testdata_path().join("tls/RootCA.pem")).unwrap();
+    let http_client = crate::http_util::HttpClient::new(None, None)
+      .with_ca_certs(vec![root_ca])
+      .with_persistent_connections(
+        super::super::REGISTRY_POOL_IDLE_TIMEOUT,
+        super::super::REGISTRY_HTTP2_KEEP_ALIVE_INTERVAL,
+      );
+    let api_client = JsrApiClient::new(
+      http_client.client().unwrap().clone(),
+      &Url::parse(&format!("https://localhost:{}/api/", port)).unwrap(),
+    );
+    let authorization = AuthHeader {
+      name: reqwest::header::AUTHORIZATION,
+      value: "Bearer token".into(),
+    };
+
+    // the checks before publishing open the connection to the registry
+    let scope = api_client.get_scope("foo").await.unwrap();
+    assert_eq!(scope.unwrap().scope, "foo");
+    let packages = (0..8).map(|i| format!("pkg{}", i)).collect::<Vec<_>>();
+    let publish_results =
+      deno_core::futures::future::join_all(packages.iter().map(|package| {
+        api_client.publish_version(
+          &authorization,
+          "foo",
+          package,
+          "1.0.0",
+          "deno.json",
+          EncodedTarball {
+            bytes: Bytes::from(vec![0; 64 * 1024]),
+            encoding: UploadEncoding::Gzip,
+          },
+        )
+      }))
+      .await;
+    for result in publish_results {
+      assert_eq!(result.unwrap().status, "pending");
+    }
+    assert_eq!(uploads.load(Ordering::SeqCst), 8);
+    // all the uploads were multiplexed over the same connection
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn api_response_cache() {
+    let temp_dir = TempDir::new();
+    let cache =
+      ApiResponseCache::new(temp_dir.path().join("cache").to_path_buf());
+    let url = "https://api.jsr.io/scopes/foo";
+    assert!(cache.get(url).is_none());
+    cache.set(
+      url,
+      CachedResponse {
+        etag: Some("\"abc\"".to_string()),
+        body: "{\"scope\":\"foo\"}".to_string(),
+        cached_at: 1700000000,
+      },
+    );
+    let cached = cache.get(url).unwrap();
+    assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+    assert_eq!(cached.cached_at, 1700000000);
+    assert_eq!(cached.body, "{\"scope\":\"foo\"}");
+    assert!(cache.get("https://api.jsr.io/scopes/bar").is_none());
+  }
+
+  #[test]
+  fn format_cache_age() {
+    assert_eq!(format_age(-5), "less than a minute");
+    assert_eq!(format_age(59), "less than a minute");
+    assert_eq!(format_age(60), "1 minute");
+    assert_eq!(format_age(3 * 60 * 60 + 5), "3 hours");
+    assert_eq!(format_age(24 * 60 * 60), "1 day");
+    assert_eq!(format_age(40 * 24 * 60 * 60), "40 days");
+  }
+
+  #[test]
+  fn mirror_failover_urls() {
+    let client = JsrApiClient::new(
+      reqwest::Client::new(),
+      &Url::parse("https://jsr.io/api/").unwrap(),
+    )
+    .with_mirrors(vec![
+      Url::parse("https://mirror1.example.com/").unwrap(),
+      Url::parse("https://mirror2.example.com/").unwrap(),
+    ]);
+    assert_eq!(
+      client.failover_urls(
+        "https://jsr.io/@foo/bar/meta.json",
+        "https://jsr.io/",
+        Url::to_string
+      ),
+      vec![
+        "https://jsr.io/@foo/bar/meta.json",
+        "https://mirror1.example.com/@foo/bar/meta.json",
+        "https://mirror2.example.com/@foo/bar/meta.json",
+      ]
+    );
+    assert_eq!(
+      client.failover_urls(
+        "https://example.com/other",
+        "https://jsr.io/",
+        Url::to_string
+      ),
+      vec!["https://example.com/other"]
+    );
+  }
+
+  #[test]
+  fn negotiate_upload_encoding_header() {
+    assert_eq!(negotiate_upload_encoding(None), UploadEncoding::Gzip);
+    assert_eq!(
+      negotiate_upload_encoding(Some("gzip, br")),
+      UploadEncoding::Gzip
+    );
+    assert_eq!(
+      negotiate_upload_encoding(Some("gzip, zstd")),
+      UploadEncoding::Zstd
+    );
+    assert_eq!(
+      negotiate_upload_encoding(Some("gzip;q=1.0, ZSTD;q=0.5")),
+      UploadEncoding::Zstd
+    );
+    assert_eq!(
+      negotiate_upload_encoding(Some("gzip, zstd;q=0")),
+      UploadEncoding::Gzip
+    );
+  }
+
+  #[test]
+  fn api_error_kind_from_code() {
+    let mut data = json!({
+      "task": { "id": "abc", "status": "processing", "error": null },
+    });
+    let ApiErrorKind::DuplicateVersionPublish(task) =
+      ApiErrorKind::from_error("duplicateVersionPublish", &mut data)
+    else {
+      panic!("expected a duplicate version error");
+    };
+    assert_eq!(task.id, "abc");
+    assert!(matches!(
+      ApiErrorKind::from_error("duplicateVersionPublish", &mut json!({})),
+      ApiErrorKind::Other
+    ));
+    assert!(matches!(
+      ApiErrorKind::from_error("actorNotAuthorized", &mut json!({})),
+      ApiErrorKind::MissingPermission
+    ));
+    assert!(matches!(
+      ApiErrorKind::from_error("somethingElse", &mut json!({})),
+      ApiErrorKind::Other
+    ));
+  }
+
+  #[test]
+  fn truncate_response_body() {
+    assert_eq!(truncate_body("short"), "short");
+    let long = "é".repeat(MAX_RESPONSE_BODY_DISPLAY_LEN);
+    let truncated = truncate_body(&long);
+    assert!(
+      truncated.starts_with(&"é".repeat(MAX_RESPONSE_BODY_DISPLAY_LEN / 2))
+    );
+    assert!(truncated.ends_with(&format!(
+      "... ({} more bytes)",
+      MAX_RESPONSE_BODY_DISPLAY_LEN
+    )));
+  }
+
+  #[test]
+  fn parse_retry_after_formats() {
+    let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+      .unwrap()
+      .with_timezone(&chrono::Utc);
+    assert_eq!(
+      parse_retry_after("120", now),
+      Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+      parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+      Some(Duration::from_secs(30))
+    );
+    // dates in the past don't wait
+    assert_eq!(
+      parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+      Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+  }
+
+  #[test]
+  fn package_github_repository() {
+    let package = serde_json::from_value::<Package>(json!({
+      "scope": "foo",
+      "name": "bar",
+      "githubRepository": { "owner": "denoland", "name": "deno" },
+      "versionCount": 2,
+    }))
+    .unwrap();
+    assert_eq!(
+      package.github_repository,
+      Some(Some(GithubRepository {
+        owner: "denoland".to_string(),
+        name: "deno".to_string(),
+      }))
+    );
+    assert_eq!(package.version_count, 2);
+    let package = serde_json::from_value::<Package>(json!({
+      "scope": "foo",
+      "name": "bar",
+      "githubRepository": null,
+    }))
+    .unwrap();
+    assert_eq!(package.github_repository, Some(None));
+    let package = serde_json::from_value::<Package>(json!({
+      "scope": "foo",
+      "name": "bar",
+    }))
+    .unwrap();
+    assert_eq!(package.github_repository, None);
+  }
+
+  #[test]
+  fn api_urls() {
+    let client = JsrApiClient::new(
+      reqwest::Client::new(),
+      &Url::parse("https://api.jsr.io/").unwrap(),
+    );
+    assert_eq!(
+      client.provenance_url("foo", "bar", "1.0.0"),
+      "https://api.jsr.io/scopes/foo/packages/bar/versions/1.0.0/provenance"
+    );
+    assert_eq!(
+      search_packages_url("https://api.jsr.io/", "@std/fs")
+        .unwrap()
+        .as_str(),
+      "https://api.jsr.io/packages?query=%40std%2Ffs"
+    );
+  }
 }
@@ -0,0 +1,287 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Loader shims for the files that packages export or import besides
+//! JavaScript and TypeScript modules, eg. `.svelte` components, configured
+//! with `"publish.loaders"`. The graph sees a shim in place of the file,
+//! while the file itself is published as it is.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use deno_ast::MediaType;
+use deno_config::WorkspaceMemberConfig;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::FutureExt;
+use deno_core::ModuleSpecifier;
+use deno_graph::source::CacheInfo;
+use deno_graph::source::LoadFuture;
+use deno_graph::source::LoadOptions;
+use deno_graph::source::LoadResponse;
+use deno_graph::source::Loader;
+use deno_graph::ModuleInfo;
+use serde::Deserialize;
+
+use super::config::CliPublishConfig;
+
+/// How a file with a configured extension is analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetLoader {
+  /// Analyze the `<script>` blocks of the file, like in Svelte and Vue
+  /// components, and ignore the rest of it.
+  Script,
+  /// The file doesn't import anything, like a stylesheet.
+  Asset,
+}
+
+impl AssetLoader {
+  /// The source of the module that the graph analyzes instead of the file.
+  /// Everything that isn't analyzed is replaced with spaces, so the
+  /// positions in the shim are the same as in the file, and the imports are
+  /// rewritten in the file itself when publishing.
+  pub fn shim(&self, text: &str) -> (String, MediaType) {
+    match self {
+      AssetLoader::Script => {
+        let mut shim = String::with_capacity(text.len());
+        let mut media_type = MediaType::JavaScript;
+        let mut rest = text;
+        while let Some((tag, script, after)) = next_script_block(rest) {
+          if is_typescript_tag(tag) {
+            media_type = MediaType::TypeScript;
+          }
+          let start = rest.len() - tag.len() - script.len() - after.len();
+          blank(&mut shim, &rest[..start + tag.len()]);
+          shim.push_str(script);
+          rest = after;
+        }
+        blank(&mut shim, rest);
+        (shim, media_type)
+      }
+      AssetLoader::Asset => {
+        let mut shim = String::with_capacity(text.len());
+        blank(&mut shim, text);
+        (shim, MediaType::JavaScript)
+      }
+    }
+  }
+}
+
+/// Finds the next `<script>` block, returning its opening tag, its content
+/// and the text after the content.
+fn next_script_block(text: &str) -> Option<(&str, &str, &str)> {
+  let lowercase = text.to_ascii_lowercase();
+  let tag_start = lowercase.find("<script")?;
+  let tag_end = tag_start + lowercase[tag_start..].find('>')? + 1;
+  let script_end = lowercase[tag_end..]
+    .find("</script")
+    .map(|index| tag_end + index)
+    .unwrap_or(text.len());
+  Some((
+    &text[tag_start..tag_end],
+    &text[tag_end..script_end],
+    &text[script_end..],
+  ))
+}
+
+fn is_typescript_tag(tag: &str) -> bool {
+  let tag = tag.to_ascii_lowercase().replace('\'', "\"");
+  tag.contains("lang=\"ts\"") || tag.contains("lang=\"typescript\"")
+}
+
+/// Appends spaces with the same byte length as the text, keeping its line
+/// breaks.
+fn blank(shim: &mut String, text: &str) {
+  for c in text.chars() {
+    match c {
+      '\n' | '\r' => shim.push(c),
+      _ => shim.extend(std::iter::repeat(' ').take(c.len_utf8())),
+    }
+  }
+}
+
+/// The loader shims of the packages, by the directory of the package.
+#[derive(Debug, Default)]
+pub struct AssetLoaders {
+  packages: Vec<(ModuleSpecifier, HashMap<String, AssetLoader>)>,
+}
+
+impl AssetLoaders {
+  pub fn from_members(
+    members: &[WorkspaceMemberConfig],
+  ) -> Result<Self, AnyError> {
+    let mut packages = Vec::new();
+    for member in members {
+      let publish_config =
+        CliPublishConfig::from_config_file(&member.config_file)?;
+      if publish_config.loaders.is_empty() {
+        continue;
+      }
+      let dir_url = ModuleSpecifier::from_directory_path(&member.dir_path)
+        .ok()
+        .with_context(|| {
+          format!("Invalid package directory '{}'", member.dir_path.display())
+        })?;
+      let loaders = publish_config
+        .loaders
+        .into_iter()
+        .map(|(extension, loader)| {
+          (
+            extension.trim_start_matches('.').to_ascii_lowercase(),
+            loader,
+          )
+        })
+        .collect();
+      packages.push((dir_url, loaders));
+    }
+    Ok(Self { packages })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.packages.is_empty()
+  }
+
+  /// The loader of a file of one of the packages, when it's not a module
+  /// the graph can analyze by itself.
+  pub fn get(&self, specifier: &ModuleSpecifier) -> Option<AssetLoader> {
+    if specifier.scheme() != "file"
+      || MediaType::from_specifier(specifier) != MediaType::Unknown
+    {
+      return None;
+    }
+    let (_, extension) = specifier.path().rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    self
+      .packages
+      .iter()
+      .filter(|(dir_url, _)| specifier.as_str().starts_with(dir_url.as_str()))
+      // the innermost package, for nested packages
+      .max_by_key(|(dir_url, _)| dir_url.as_str().len())
+      .and_then(|(_, loaders)| loaders.get(&extension).copied())
+  }
+}
+
+/// A loader that loads the shims of the files with configured extensions,
+/// and everything else with the wrapped loader.
+pub struct AssetShimLoader<'a> {
+  inner: &'a mut dyn Loader,
+  loaders: AssetLoaders,
+}
+
+impl<'a> AssetShimLoader<'a> {
+  pub fn new(inner: &'a mut dyn Loader, loaders: AssetLoaders) -> Self {
+    Self { inner, loaders }
+  }
+}
+
+impl<'a> Loader for AssetShimLoader<'a> {
+  fn get_cache_info(&self, specifier: &ModuleSpecifier) -> Option<CacheInfo> {
+    self.inner.get_cache_info(specifier)
+  }
+
+  fn load(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    options: LoadOptions,
+  ) -> LoadFuture {
+    let load = self.inner.load(specifier, options);
+    let Some(loader) = self.loaders.get(specifier) else {
+      return load;
+    };
+    async move {
+      let (content, specifier) = match load.await? {
+        Some(LoadResponse::Module {
+          content, specifier, ..
+        }) => (content, specifier),
+        response => return Ok(response),
+      };
+      let text = std::str::from_utf8(&content)
+        .with_context(|| format!("Failed decoding '{}' as UTF-8", specifier))?;
+      let (shim, media_type) = loader.shim(text);
+      let content_type = match media_type {
+        MediaType::TypeScript => "application/typescript",
+        _ => "application/javascript",
+      };
+      Ok(Some(LoadResponse::Module {
+        content: shim.into_bytes().into(),
+        specifier,
+        maybe_headers: Some(HashMap::from([(
+          "content-type".to_string(),
+          content_type.to_string(),
+        )])),
+      }))
+    }
+    .boxed_local()
+  }
+
+  fn cache_module_info(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    source: &Arc<[u8]>,
+    module_info: &ModuleInfo,
+  ) {
+    self.inner.cache_module_info(specifier, source, module_info)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn script_shim_keeps_positions() {
+    let text = "<script lang=\"ts\">\nimport { a } from \"./a.ts\";\n</script>\n\n<p>caf\u{e9} {a}</p>\n";
+    let (shim, media_type) = AssetLoader::Script.shim(text);
+    assert_eq!(media_type, MediaType::TypeScript);
+    assert_eq!(shim.len(), text.len());
+    assert_eq!(
+      shim.lines().collect::<Vec<_>>(),
+      vec![
+        "                  ",
+        "import { a } from \"./a.ts\";",
+        "         ",
+        "",
+        "                ",
+      ]
+    );
+    let start = text.find("\"./a.ts\"").unwrap();
+    assert_eq!(&shim[start..start + 8], "\"./a.ts\"");
+
+    let (shim, media_type) = AssetLoader::Script
+      .shim("<script context=\"module\">export const b = 1;</script><div />");
+    assert_eq!(media_type, MediaType::JavaScript);
+    assert_eq!(shim.trim(), "export const b = 1;");
+
+    let (shim, _) = AssetLoader::Asset.shim("@import \"./b.css\";\n");
+    assert_eq!(shim, "                  \n");
+  }
+
+  #[test]
+  fn asset_loaders_by_package() {
+    let loaders = AssetLoaders {
+      packages: vec![
+        (
+          ModuleSpecifier::parse("file:///repo/").unwrap(),
+          HashMap::from([("css".to_string(), AssetLoader::Asset)]),
+        ),
+        (
+          ModuleSpecifier::parse("file:///repo/ui/").unwrap(),
+          HashMap::from([("svelte".to_string(), AssetLoader::Script)]),
+        ),
+      ],
+    };
+    let get = |specifier: &str| {
+      loaders.get(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert_eq!(
+      get("file:///repo/ui/Button.svelte"),
+      Some(AssetLoader::Script)
+    );
+    assert_eq!(get("file:///repo/style.CSS"), Some(AssetLoader::Asset));
+    // the nested package doesn't configure a loader for stylesheets
+    assert_eq!(get("file:///repo/ui/style.css"), None);
+    assert_eq!(get("file:///repo/Button.svelte"), None);
+    assert_eq!(get("file:///repo/ui/mod.ts"), None);
+    assert_eq!(get("https://example.com/ui/Button.svelte"), None);
+  }
+}
@@ -0,0 +1,199 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::Utc;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::serde_json::Value;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryAuditLogFlags;
+
+use super::api::AuditLogEntry;
+use super::auth::AuthHeaderTemplate;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLogEntryJson {
+  action: String,
+  user: Option<String>,
+  package: Option<String>,
+  meta: Value,
+  created_at: DateTime<Utc>,
+}
+
+/// Which audit events are shown.
+#[derive(Debug, Default)]
+struct AuditLogFilter {
+  package: Option<String>,
+  since: Option<DateTime<Utc>>,
+  until: Option<DateTime<Utc>>,
+}
+
+impl AuditLogFilter {
+  fn matches(&self, entry: &AuditLogEntry) -> bool {
+    if let Some(package) = &self.package {
+      if entry.package.as_ref() != Some(package) {
+        return false;
+      }
+    }
+    if self.since.is_some_and(|since| entry.created_at < since) {
+      return false;
+    }
+    if self.until.is_some_and(|until| entry.created_at >= until) {
+      return false;
+    }
+    true
+  }
+}
+
+pub async fn audit_log(
+  flags: Flags,
+  audit_log_flags: RegistryAuditLogFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let scope = audit_log_flags.scope.trim_start_matches('@').to_string();
+  if scope.is_empty() || scope.contains('/') {
+    bail!(
+      "Invalid scope '{}', use '@<scope_name>' format",
+      audit_log_flags.scope
+    );
+  }
+  let filter = AuditLogFilter {
+    package: audit_log_flags.package.map(|package| {
+      // the package can be passed with or without the scope
+      match package.split_once('/') {
+        Some((_, name)) => name.to_string(),
+        None => package,
+      }
+    }),
+    since: audit_log_flags
+      .since
+      .as_deref()
+      .map(parse_time)
+      .transpose()?,
+    until: audit_log_flags
+      .until
+      .as_deref()
+      .map(parse_time)
+      .transpose()?,
+  };
+  let Some(token) = audit_log_flags.token else {
+    bail!("No means to authenticate. Pass a token to `--token`.");
+  };
+  let authorization =
+    AuthHeaderTemplate::from_config(context.registry_config())?.token(&token);
+
+  let entries = context
+    .api_client()?
+    .get_scope_audit_log(&authorization, &scope)
+    .await
+    .map_err(|err| {
+      err.context(format!("Failed to get the audit log of @{}", scope))
+    })?
+    .into_iter()
+    .filter(|entry| filter.matches(entry))
+    .map(|entry| AuditLogEntryJson {
+      action: entry.action,
+      user: entry.user.map(|user| user.name),
+      package: entry.package,
+      meta: entry.meta,
+      created_at: entry.created_at,
+    })
+    .collect::<Vec<_>>();
+
+  if audit_log_flags.json {
+    print_json(&entries)?;
+    return Ok(());
+  }
+  if entries.is_empty() {
+    println!("No audit events found for @{}", scope);
+    return Ok(());
+  }
+  for entry in &entries {
+    let mut line = format!(
+      "{} {}",
+      colors::gray(entry.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+      entry.action
+    );
+    if let Some(package) = &entry.package {
+      line.push_str(&format!(" @{}/{}", scope, package));
+    }
+    if let Some(user) = &entry.user {
+      line.push_str(&format!(" {}", colors::gray(format!("by {}", user))));
+    }
+    println!("{}", line);
+  }
+  Ok(())
+}
+
+/// Parses a date, eg. `2024-01-31`, or an RFC 3339 timestamp.
+fn parse_time(text: &str) -> Result<DateTime<Utc>, AnyError> {
+  if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+    return Ok(time.with_timezone(&Utc));
+  }
+  match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+    Ok(date) => Ok(DateTime::from_naive_utc_and_offset(
+      date.and_hms_opt(0, 0, 0).unwrap(),
+      Utc,
+    )),
+    Err(_) => bail!(
+      "Invalid time '{}', use a date like 2024-01-31 or an RFC 3339 timestamp",
+      text
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json;
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  fn entry(package: Option<&str>, created_at: &str) -> AuditLogEntry {
+    serde_json::from_value(json!({
+      "action": "package_version_publish",
+      "package": package,
+      "createdAt": created_at,
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn parse_time_formats() {
+    assert_eq!(
+      parse_time("2024-01-31").unwrap().to_rfc3339(),
+      "2024-01-31T00:00:00+00:00"
+    );
+    assert_eq!(
+      parse_time("2024-01-31T12:00:00+02:00")
+        .unwrap()
+        .to_rfc3339(),
+      "2024-01-31T10:00:00+00:00"
+    );
+    assert!(parse_time("31.01.2024").is_err());
+  }
+
+  #[test]
+  fn audit_log_filter_matches() {
+    let filter = AuditLogFilter {
+      package: Some("bar".to_string()),
+      since: Some(parse_time("2024-01-01").unwrap()),
+      until: Some(parse_time("2024-02-01").unwrap()),
+    };
+    assert!(filter.matches(&entry(Some("bar"), "2024-01-01T00:00:00Z")));
+    assert!(!filter.matches(&entry(Some("baz"), "2024-01-15T00:00:00Z")));
+    assert!(!filter.matches(&entry(None, "2024-01-15T00:00:00Z")));
+    assert!(!filter.matches(&entry(Some("bar"), "2023-12-31T23:59:59Z")));
+    assert!(!filter.matches(&entry(Some("bar"), "2024-02-01T00:00:00Z")));
+    assert!(
+      AuditLogFilter::default().matches(&entry(None, "2024-01-15T00:00:00Z"))
+    );
+  }
+}
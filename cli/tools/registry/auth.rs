@@ -1,10 +1,26 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::io::IsTerminal;
+use std::ops::Range;
+use std::rc::Rc;
 
 use deno_core::anyhow;
 use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_runtime::deno_fetch::reqwest::header::HeaderName;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
+use deno_terminal::colors;
+use lsp_types::Url;
+use serde::Serialize;
+
+use super::api;
+use super::api::ApiError;
+use super::api::JsrApiClient;
+use super::config::RegistryConfig;
+use super::provenance::CiProvider;
 
 pub enum AuthMethod {
   Interactive,
@@ -12,9 +28,198 @@ pub enum AuthMethod {
   Oidc(OidcConfig),
 }
 
+impl AuthMethod {
+  /// Whether new credentials can be obtained without user input other than
+  /// what was already required for the initial authorization.
+  pub fn can_reauthenticate(&self) -> bool {
+    match self {
+      AuthMethod::Interactive | AuthMethod::Oidc(_) => true,
+      AuthMethod::Token(_) => false,
+    }
+  }
+}
+
 pub struct OidcConfig {
   pub url: String,
   pub token: String,
+  pub limits: OidcLimits,
+}
+
+/// Limits of the OIDC token endpoint on the audience, which encodes the
+/// requested permissions. Permissions are split over several tokens to stay
+/// within them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OidcLimits {
+  pub max_permissions: usize,
+  pub max_audience_length: usize,
+}
+
+impl Default for OidcLimits {
+  fn default() -> Self {
+    Self {
+      max_permissions: 16,
+      max_audience_length: 4096,
+    }
+  }
+}
+
+impl OidcLimits {
+  pub fn from_config(
+    config: Option<&RegistryConfig>,
+  ) -> Result<OidcLimits, AnyError> {
+    let mut limits = OidcLimits::default();
+    let Some(config) = config else {
+      return Ok(limits);
+    };
+    if let Some(max_permissions) = config.oidc_max_permissions {
+      if max_permissions == 0 {
+        bail!("\"oidcMaxPermissions\" must be greater than 0");
+      }
+      limits.max_permissions = max_permissions;
+    }
+    if let Some(max_audience_length) = config.oidc_max_audience_length {
+      if max_audience_length == 0 {
+        bail!("\"oidcMaxAudienceLength\" must be greater than 0");
+      }
+      limits.max_audience_length = max_audience_length;
+    }
+    Ok(limits)
+  }
+}
+
+/// Encodes the permissions as the audience of an OIDC token request.
+pub fn encode_oidc_audience<T: Serialize>(permissions: &[T]) -> String {
+  let audience = serde_json::json!({ "permissions": permissions }).to_string();
+  percent_encoding::percent_encode(
+    audience.as_bytes(),
+    percent_encoding::NON_ALPHANUMERIC,
+  )
+  .to_string()
+}
+
+/// Splits the permissions into ranges that can each be requested in a single
+/// OIDC token. A permission that exceeds the audience length on its own gets
+/// a range of its own and is left to the token endpoint to reject.
+pub fn chunk_oidc_permissions<T: Serialize>(
+  permissions: &[T],
+  limits: &OidcLimits,
+) -> Vec<Range<usize>> {
+  // percent encoding is per character, so the length of the encoded
+  // audience is the sum of the lengths of its encoded parts
+  let empty_len = encode_oidc_audience::<T>(&[]).len();
+  let separator_len = encode_oidc_audience(&[(), ()]).len() + empty_len
+    - 2 * encode_oidc_audience(&[()]).len();
+
+  let mut chunks = Vec::new();
+  let mut start = 0;
+  let mut audience_len = empty_len;
+  for (i, permission) in permissions.iter().enumerate() {
+    let permission_len =
+      encode_oidc_audience(std::slice::from_ref(permission)).len() - empty_len;
+    let count = i - start;
+    let added_len = if count == 0 {
+      permission_len
+    } else {
+      separator_len + permission_len
+    };
+    if count > 0
+      && (count >= limits.max_permissions
+        || audience_len + added_len > limits.max_audience_length)
+    {
+      chunks.push(start..i);
+      start = i;
+      audience_len = empty_len + permission_len;
+    } else {
+      audience_len += added_len;
+    }
+  }
+  if start < permissions.len() {
+    chunks.push(start..permissions.len());
+  }
+  chunks
+}
+
+/// The registry rejected the credentials of a request, for example because
+/// a short-lived token expired during a long running publish.
+#[derive(Debug, thiserror::Error)]
+#[error("The registry rejected the authorization: {0}")]
+pub struct UnauthorizedError(pub ApiError);
+
+/// The header a credential is sent to the registry in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthHeader {
+  pub name: HeaderName,
+  pub value: Rc<str>,
+}
+
+const TOKEN_PLACEHOLDER: &str = "{token}";
+
+/// Describes how credentials are turned into a header for a registry,
+/// which defaults to `Authorization: Bearer <token>`.
+#[derive(Clone, Debug)]
+pub struct AuthHeaderTemplate {
+  name: HeaderName,
+  token_value: String,
+  oidc_value: String,
+}
+
+impl Default for AuthHeaderTemplate {
+  fn default() -> Self {
+    Self {
+      name: AUTHORIZATION,
+      token_value: format!("Bearer {TOKEN_PLACEHOLDER}"),
+      oidc_value: format!("githuboidc {TOKEN_PLACEHOLDER}"),
+    }
+  }
+}
+
+impl AuthHeaderTemplate {
+  pub fn from_config(
+    config: Option<&RegistryConfig>,
+  ) -> Result<Self, AnyError> {
+    let mut template = Self::default();
+    let Some(config) = config else {
+      return Ok(template);
+    };
+    if let Some(name) = &config.auth_header_name {
+      template.name = HeaderName::from_bytes(name.as_bytes())
+        .with_context(|| format!("Invalid auth header name '{}'", name))?;
+    }
+    if let Some(value) = &config.auth_header_value {
+      template.token_value = validate_template(value)?;
+    }
+    if let Some(value) = &config.oidc_auth_header_value {
+      template.oidc_value = validate_template(value)?;
+    }
+    Ok(template)
+  }
+
+  /// Header for a token obtained interactively or provided by the user.
+  pub fn token(&self, token: &str) -> AuthHeader {
+    AuthHeader {
+      name: self.name.clone(),
+      value: self.token_value.replace(TOKEN_PLACEHOLDER, token).into(),
+    }
+  }
+
+  /// Header for a token obtained from the CI provider's OIDC endpoint.
+  pub fn oidc(&self, token: &str) -> AuthHeader {
+    AuthHeader {
+      name: self.name.clone(),
+      value: self.oidc_value.replace(TOKEN_PLACEHOLDER, token).into(),
+    }
+  }
+}
+
+fn validate_template(value: &str) -> Result<String, AnyError> {
+  if !value.contains(TOKEN_PLACEHOLDER) {
+    bail!(
+      "Invalid auth header value '{}', it must contain the '{}' placeholder",
+      value,
+      TOKEN_PLACEHOLDER
+    );
+  }
+  Ok(value.to_string())
 }
 
 pub(crate) fn is_gha() -> bool {
@@ -49,7 +254,11 @@ pub fn get_auth_method(
   }
 
   match get_gh_oidc_env_vars() {
-    Some(Ok((url, token))) => Ok(AuthMethod::Oidc(OidcConfig { url, token })),
+    Some(Ok((url, token))) => Ok(AuthMethod::Oidc(OidcConfig {
+      url,
+      token,
+      limits: OidcLimits::default(),
+    })),
     Some(Err(err)) => Err(err),
     None if std::io::stdin().is_terminal() => Ok(AuthMethod::Interactive),
     None => {
@@ -57,3 +266,315 @@ pub fn get_auth_method(
     }
   }
 }
+
+/// Redacts a secret for display, keeping a short prefix so that different
+/// tokens can still be told apart.
+pub fn redact_token(token: &str) -> String {
+  const VISIBLE_PREFIX_LEN: usize = 4;
+  let len = token.chars().count();
+  // don't reveal anything of short secrets
+  if len < VISIBLE_PREFIX_LEN * 3 {
+    return "*".repeat(8);
+  }
+  let prefix = token.chars().take(VISIBLE_PREFIX_LEN).collect::<String>();
+  format!("{}{}", prefix, "*".repeat(8))
+}
+
+/// Prints how `deno publish` would authenticate, for `--auth-info`.
+pub fn print_auth_info(
+  auth_method: &AuthMethod,
+  auth_header_template: &AuthHeaderTemplate,
+  registry_url: &Url,
+  package_names: &[String],
+) {
+  println!("{} {}", colors::bold("Registry:"), registry_url);
+  let header = match auth_method {
+    AuthMethod::Interactive => {
+      println!(
+        "{} interactive {}",
+        colors::bold("Auth method:"),
+        colors::gray("(authorize in the browser when publishing)")
+      );
+      auth_header_template.token("<token from interactive authorization>")
+    }
+    AuthMethod::Token(token) => {
+      println!(
+        "{} token {}",
+        colors::bold("Auth method:"),
+        colors::gray("(from --token)")
+      );
+      println!("{} {}", colors::bold("Token:"), redact_token(token));
+      auth_header_template.token(&redact_token(token))
+    }
+    AuthMethod::Oidc(oidc_config) => {
+      let ci_provider = CiProvider::detect()
+        .map(|ci_provider| ci_provider.name())
+        .unwrap_or("unknown CI provider");
+      println!(
+        "{} OIDC {}",
+        colors::bold("Auth method:"),
+        colors::gray(format!("({})", ci_provider))
+      );
+      let token_endpoint = Url::parse(&oidc_config.url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .unwrap_or_else(|| "<invalid url>".to_string());
+      println!(
+        "{} {}",
+        colors::bold("OIDC token endpoint:"),
+        token_endpoint
+      );
+      println!(
+        "{} {}",
+        colors::bold("OIDC request token:"),
+        redact_token(&oidc_config.token)
+      );
+      println!(
+        "{} at least {} {}",
+        colors::bold("OIDC token requests:"),
+        package_names
+          .len()
+          .div_ceil(oidc_config.limits.max_permissions),
+        colors::gray(format!(
+          "(up to {} permissions and {} encoded audience bytes each)",
+          oidc_config.limits.max_permissions,
+          oidc_config.limits.max_audience_length
+        ))
+      );
+      auth_header_template.oidc("<token from OIDC provider>")
+    }
+  };
+  println!(
+    "{} {}: {}",
+    colors::bold("Header:"),
+    header.name,
+    header.value
+  );
+  println!("{}", colors::bold("Requests permission to publish:"));
+  for package_name in package_names {
+    println!(" - {}", package_name);
+  }
+}
+
+/// Verifies that the provided credentials are allowed to publish the
+/// packages before any time is spent on building and packaging them.
+///
+/// Only failures that are certain result in an error. Scopes and packages
+/// that don't exist yet are skipped, because they are created on publish.
+pub async fn check_publish_permissions(
+  api_client: &JsrApiClient,
+  auth_method: &AuthMethod,
+  auth_header_template: &AuthHeaderTemplate,
+  packages: &[(String, String)],
+) -> Result<(), AnyError> {
+  let missing = match auth_method {
+    // permissions are granted by the user in the browser
+    AuthMethod::Interactive => return Ok(()),
+    AuthMethod::Token(token) => {
+      let authorization = auth_header_template.token(token);
+      check_token_scopes(api_client, &authorization, packages).await?
+    }
+    AuthMethod::Oidc(_) => {
+      let Ok(repository) = std::env::var("GITHUB_REPOSITORY") else {
+        return Ok(());
+      };
+      check_oidc_packages(api_client, &repository, packages).await?
+    }
+  };
+
+  if missing.is_empty() {
+    return Ok(());
+  }
+  let mut message =
+    "The provided credentials are not allowed to publish:".to_string();
+  for item in missing {
+    message.push_str(&format!("\n - {}", item));
+  }
+  bail!("{}", message)
+}
+
+async fn check_token_scopes(
+  api_client: &JsrApiClient,
+  authorization: &AuthHeader,
+  packages: &[(String, String)],
+) -> Result<Vec<String>, AnyError> {
+  let scopes = packages
+    .iter()
+    .map(|(scope, _)| scope.as_str())
+    .collect::<BTreeSet<_>>();
+  let mut missing = Vec::new();
+  for scope in scopes {
+    if api_client.get_scope(scope).await?.is_none() {
+      continue;
+    }
+    let result = api_client.get_user_scope_member(authorization, scope).await;
+    let err = match result {
+      Ok(_) => continue,
+      Err(err) => err,
+    };
+    let Some(status) = api::as_api_error(&err).and_then(|err| err.status)
+    else {
+      return Err(err);
+    };
+    match status.as_u16() {
+      401 => bail!(
+        "The provided token was rejected by the registry. It may be invalid or expired."
+      ),
+      403 | 404 => {
+        missing.push(format!("@{} (token owner is not a member)", scope))
+      }
+      status => log::debug!(
+        "Unexpected status {} checking membership of @{}",
+        status,
+        scope
+      ),
+    }
+  }
+  Ok(missing)
+}
+
+async fn check_oidc_packages(
+  api_client: &JsrApiClient,
+  repository: &str,
+  packages: &[(String, String)],
+) -> Result<Vec<String>, AnyError> {
+  let mut missing = Vec::new();
+  for (scope, package) in packages {
+    let package_info = match api_client.get_package(scope, package).await {
+      Ok(Some(package_info)) => package_info,
+      Ok(None) => continue,
+      Err(err) if api::as_api_error(&err).is_some() => {
+        log::debug!("Failed to get package @{}/{}: {:#}", scope, package, err);
+        continue;
+      }
+      Err(err) => return Err(err),
+    };
+    match package_info.github_repository {
+      Some(None) => missing.push(format!(
+        "@{}/{} (not linked to a GitHub repository)",
+        scope, package
+      )),
+      Some(Some(linked)) => {
+        let linked = format!("{}/{}", linked.owner, linked.name);
+        if !linked.eq_ignore_ascii_case(repository) {
+          missing.push(format!(
+            "@{}/{} (linked to {}, not {})",
+            scope, package, linked, repository
+          ));
+        }
+      }
+      None => {}
+    }
+  }
+  Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn auth_header_template_default() {
+    let template = AuthHeaderTemplate::from_config(None).unwrap();
+    let header = template.token("abc");
+    assert_eq!(header.name, AUTHORIZATION);
+    assert_eq!(&*header.value, "Bearer abc");
+    assert_eq!(&*template.oidc("abc").value, "githuboidc abc");
+  }
+
+  #[test]
+  fn auth_header_template_custom() {
+    let template = AuthHeaderTemplate::from_config(Some(&RegistryConfig {
+      auth_header_name: Some("X-Api-Key".to_string()),
+      auth_header_value: Some("{token}".to_string()),
+      oidc_auth_header_value: Some("Oidc {token}".to_string()),
+      ..Default::default()
+    }))
+    .unwrap();
+    let header = template.token("abc");
+    assert_eq!(header.name.as_str(), "x-api-key");
+    assert_eq!(&*header.value, "abc");
+    assert_eq!(&*template.oidc("abc").value, "Oidc abc");
+  }
+
+  #[test]
+  fn redact_token_hides_secret() {
+    assert_eq!(redact_token("short"), "********");
+    assert_eq!(redact_token("ddp_abcdefghijkl"), "ddp_********");
+  }
+
+  #[test]
+  fn auth_header_template_invalid() {
+    assert!(AuthHeaderTemplate::from_config(Some(&RegistryConfig {
+      auth_header_name: Some("X Api Key".to_string()),
+      ..Default::default()
+    }))
+    .is_err());
+    assert!(AuthHeaderTemplate::from_config(Some(&RegistryConfig {
+      auth_header_value: Some("Token".to_string()),
+      ..Default::default()
+    }))
+    .is_err());
+  }
+
+  #[test]
+  fn chunk_oidc_permissions_audience_length() {
+    let permissions = vec!["aa"; 5];
+    let two_len = encode_oidc_audience(&permissions[..2]).len();
+    let limits = |max_audience_length| OidcLimits {
+      max_permissions: 16,
+      max_audience_length,
+    };
+
+    // exactly at the boundary
+    let chunks = chunk_oidc_permissions(&permissions, &limits(two_len));
+    assert_eq!(chunks, vec![0..2, 2..4, 4..5]);
+    for chunk in chunks {
+      assert!(encode_oidc_audience(&permissions[chunk]).len() <= two_len);
+    }
+
+    // one byte below the boundary
+    let chunks = chunk_oidc_permissions(&permissions, &limits(two_len - 1));
+    assert_eq!(chunks, vec![0..1, 1..2, 2..3, 3..4, 4..5]);
+
+    // a single permission that is too large on its own is still requested
+    let chunks = chunk_oidc_permissions(&permissions, &limits(1));
+    assert_eq!(chunks.len(), 5);
+
+    let all_len = encode_oidc_audience(&permissions).len();
+    let chunks = chunk_oidc_permissions(&permissions, &limits(all_len));
+    assert_eq!(chunks, vec![0..5]);
+  }
+
+  #[test]
+  fn chunk_oidc_permissions_max_permissions() {
+    let permissions = vec!["aa"; 5];
+    let limits = OidcLimits {
+      max_permissions: 2,
+      ..Default::default()
+    };
+    let chunks = chunk_oidc_permissions(&permissions, &limits);
+    assert_eq!(chunks, vec![0..2, 2..4, 4..5]);
+    assert!(chunk_oidc_permissions::<&str>(&[], &limits).is_empty());
+  }
+
+  #[test]
+  fn oidc_limits_from_config() {
+    assert_eq!(
+      OidcLimits::from_config(None).unwrap(),
+      OidcLimits::default()
+    );
+    let limits = OidcLimits::from_config(Some(&RegistryConfig {
+      oidc_max_audience_length: Some(1024),
+      ..Default::default()
+    }))
+    .unwrap();
+    assert_eq!(limits.max_permissions, 16);
+    assert_eq!(limits.max_audience_length, 1024);
+    assert!(OidcLimits::from_config(Some(&RegistryConfig {
+      oidc_max_permissions: Some(0),
+      ..Default::default()
+    }))
+    .is_err());
+  }
+}
@@ -0,0 +1,178 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Baselines of accepted publish diagnostics, written with
+//! `deno publish --baseline <file> --write-baseline`, so that only new
+//! diagnostics fail publishing.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use serde::Deserialize;
+use serde::Serialize;
+
+const BASELINE_VERSION: u32 = 1;
+
+#[derive(
+  Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+struct BaselineEntry {
+  /// The file of the diagnostic, relative to the baseline file.
+  file: String,
+  code: String,
+  message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+  version: u32,
+  diagnostics: Vec<BaselineEntry>,
+}
+
+/// The accepted diagnostics. Their positions aren't recorded, so that
+/// editing a file doesn't invalidate its entries, which are counted instead.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+  dir: PathBuf,
+  counts: BTreeMap<BaselineEntry, usize>,
+}
+
+impl Baseline {
+  /// Creates an empty baseline that is written to `path`.
+  pub fn new(path: &Path) -> Self {
+    Self {
+      dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+      counts: BTreeMap::new(),
+    }
+  }
+
+  pub fn read(path: &Path) -> Result<Self, AnyError> {
+    let text = std::fs::read_to_string(path).with_context(|| {
+      format!("Failed reading baseline '{}'", path.display())
+    })?;
+    let file =
+      serde_json::from_str::<BaselineFile>(&text).with_context(|| {
+        format!("Failed parsing baseline '{}'", path.display())
+      })?;
+    if file.version != BASELINE_VERSION {
+      bail!(
+        "Unsupported version {} of baseline '{}', write it again with --write-baseline",
+        file.version,
+        path.display()
+      );
+    }
+    let mut baseline = Self::new(path);
+    for entry in file.diagnostics {
+      *baseline.counts.entry(entry).or_insert(0) += 1;
+    }
+    Ok(baseline)
+  }
+
+  pub fn write(&self, path: &Path) -> Result<(), AnyError> {
+    let diagnostics = self
+      .counts
+      .iter()
+      .flat_map(|(entry, count)| std::iter::repeat(entry.clone()).take(*count))
+      .collect();
+    let file = BaselineFile {
+      version: BASELINE_VERSION,
+      diagnostics,
+    };
+    let text = format!("{}\n", serde_json::to_string_pretty(&file)?);
+    std::fs::write(path, text)
+      .with_context(|| format!("Failed writing baseline '{}'", path.display()))
+  }
+
+  /// Records a diagnostic of a file, which is either a URL or a path.
+  pub fn add(&mut self, file: &str, code: &str, message: &str) {
+    let entry = self.entry(file, code, message);
+    *self.counts.entry(entry).or_insert(0) += 1;
+  }
+
+  /// Whether a diagnostic is accepted, which uses up one of the recorded
+  /// occurrences of it.
+  pub fn accept(&mut self, file: &str, code: &str, message: &str) -> bool {
+    let entry = self.entry(file, code, message);
+    match self.counts.get_mut(&entry) {
+      Some(count) if *count > 0 => {
+        *count -= 1;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// How many recorded diagnostics haven't been accepted, eg. because they
+  /// were fixed since the baseline was written.
+  pub fn remaining(&self) -> usize {
+    self.counts.values().sum()
+  }
+
+  fn entry(&self, file: &str, code: &str, message: &str) -> BaselineEntry {
+    let path = match Url::parse(file) {
+      Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+      _ => Some(PathBuf::from(file)),
+    };
+    let file = match path
+      .as_ref()
+      .and_then(|path| path.strip_prefix(&self.dir).ok())
+    {
+      Some(relative) => relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"),
+      None => file.to_string(),
+    };
+    BaselineEntry {
+      file,
+      code: code.to_string(),
+      message: message.to_string(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn baseline_accepts_recorded_diagnostics() {
+    let dir = std::env::temp_dir().join("pkg");
+    let file = Url::from_file_path(dir.join("src").join("mod.ts"))
+      .unwrap()
+      .to_string();
+    let mut baseline = Baseline::new(&dir.join("baseline.json"));
+    baseline.add(&file, "invalid-external-import", "invalid import");
+    baseline.add(&file, "invalid-external-import", "invalid import");
+    assert_eq!(baseline.remaining(), 2);
+    assert_eq!(
+      baseline.counts.keys().next().unwrap().file,
+      "src/mod.ts".to_string()
+    );
+
+    assert!(baseline.accept(
+      &file,
+      "invalid-external-import",
+      "invalid import"
+    ));
+    assert!(!baseline.accept(&file, "unsupported-jsx-tsx", "invalid import"));
+    assert_eq!(baseline.remaining(), 1);
+    assert!(baseline.accept(
+      &file,
+      "invalid-external-import",
+      "invalid import"
+    ));
+    assert!(!baseline.accept(
+      &file,
+      "invalid-external-import",
+      "invalid import"
+    ));
+    assert_eq!(baseline.remaining(), 0);
+  }
+}
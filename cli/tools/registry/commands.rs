@@ -0,0 +1,518 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use std::sync::Arc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+use deno_semver::Version;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::jsr_url;
+use crate::args::AttachProvenanceFlags;
+use crate::args::Flags;
+use crate::args::RegistryProvenanceFlags;
+use crate::args::RegistryStatusFlags;
+use crate::args::RegistrySubcommand;
+use crate::factory::CliFactory;
+use crate::http_util::HttpClient;
+
+use super::api::ApiResponseCache;
+use super::api::JsrApiClient;
+use super::audit_log::audit_log;
+use super::auth::AuthHeaderTemplate;
+use super::config::CliPublishConfig;
+use super::config::RegistryConfig;
+use super::create_registry_http_client;
+use super::delete::delete;
+use super::discovery::discover_registry;
+use super::discovery::RegistryEndpoints;
+use super::info::info;
+use super::open_package::open_package;
+use super::output::print_json;
+use super::ping::ping;
+use super::provenance::summarize_provenance;
+use super::provenance::SigstoreConfig;
+use super::scope_config::scope_config;
+use super::scopes::scopes;
+use super::search::search;
+use super::stats::stats;
+use super::verify::verify;
+
+pub async fn registry(
+  flags: Flags,
+  subcommand: RegistrySubcommand,
+) -> Result<(), AnyError> {
+  match subcommand {
+    RegistrySubcommand::AttachProvenance(attach_flags) => {
+      attach_provenance(flags, attach_flags).await
+    }
+    RegistrySubcommand::AuditLog(audit_log_flags) => {
+      audit_log(flags, audit_log_flags).await
+    }
+    RegistrySubcommand::Delete(delete_flags) => {
+      delete(flags, delete_flags).await
+    }
+    RegistrySubcommand::Info(info_flags) => info(flags, info_flags).await,
+    RegistrySubcommand::Open(open_flags) => {
+      open_package(flags, open_flags).await
+    }
+    RegistrySubcommand::Ping(ping_flags) => ping(flags, ping_flags).await,
+    RegistrySubcommand::Provenance(provenance_flags) => {
+      show_provenance(flags, provenance_flags).await
+    }
+    RegistrySubcommand::ScopeConfig(scope_config_flags) => {
+      scope_config(flags, scope_config_flags).await
+    }
+    RegistrySubcommand::Scopes(scopes_flags) => {
+      scopes(flags, scopes_flags).await
+    }
+    RegistrySubcommand::Search(search_flags) => {
+      search(flags, search_flags).await
+    }
+    RegistrySubcommand::Stats(stats_flags) => stats(flags, stats_flags).await,
+    RegistrySubcommand::Status(status_flags) => {
+      status(flags, status_flags).await
+    }
+    RegistrySubcommand::Verify(verify_flags) => {
+      verify(flags, verify_flags).await
+    }
+  }
+}
+
+/// The registry settings shared by the `deno registry` subcommands.
+pub(super) struct RegistryContext {
+  pub cli_factory: CliFactory,
+  pub publish_config: CliPublishConfig,
+  pub http_client: Arc<HttpClient>,
+  pub endpoints: RegistryEndpoints,
+}
+
+impl RegistryContext {
+  pub async fn new(flags: Flags) -> Result<RegistryContext, AnyError> {
+    let cli_factory = CliFactory::from_flags(flags).await?;
+    let maybe_config_file = cli_factory.cli_options().maybe_config_file();
+    let publish_config = match maybe_config_file {
+      Some(config_file) => CliPublishConfig::from_config_file(config_file)?,
+      None => CliPublishConfig::default(),
+    };
+    let http_client = create_registry_http_client(
+      &cli_factory,
+      maybe_config_file.as_ref(),
+      publish_config.registry(jsr_url()),
+    )?;
+    let endpoints = discover_registry(http_client.client()?, jsr_url()).await?;
+    Ok(RegistryContext {
+      cli_factory,
+      publish_config,
+      http_client,
+      endpoints,
+    })
+  }
+
+  pub fn registry_config(&self) -> Option<&RegistryConfig> {
+    self.publish_config.registry(jsr_url())
+  }
+
+  pub fn api_client(&self) -> Result<JsrApiClient, AnyError> {
+    let cache_dir = self.cli_factory.deno_dir()?.registry_api_folder_path();
+    let mirror_urls = match self.registry_config() {
+      Some(registry_config) => registry_config.mirror_urls()?,
+      None => Vec::new(),
+    };
+    Ok(
+      JsrApiClient::new(
+        self.http_client.client()?.clone(),
+        &self.endpoints.api_url,
+      )
+      .with_cache(ApiResponseCache::new(cache_dir))
+      .with_mirrors(mirror_urls)
+      .with_chunked_uploads(self.endpoints.supports("chunkedUploads")),
+    )
+  }
+}
+
+/// A package as passed on the command line, eg. `@foo/bar`.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct PackageArg {
+  pub scope: String,
+  pub package: String,
+}
+
+impl std::fmt::Display for PackageArg {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "@{}/{}", self.scope, self.package)
+  }
+}
+
+pub(super) fn parse_package_arg(text: &str) -> Result<PackageArg, AnyError> {
+  let parsed = text
+    .strip_prefix("jsr:")
+    .unwrap_or(text)
+    .strip_prefix('@')
+    .and_then(|text| text.split_once('/'))
+    .filter(|(scope, package)| {
+      !scope.is_empty() && !package.is_empty() && !package.contains(['/', '@'])
+    });
+  let Some((scope, package)) = parsed else {
+    bail!(
+      "Invalid package '{}', use '@<scope_name>/<package_name>' format",
+      text
+    );
+  };
+  Ok(PackageArg {
+    scope: scope.to_string(),
+    package: package.to_string(),
+  })
+}
+
+/// A package version as passed on the command line, eg. `@foo/bar@1.0.0`.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct PackageVersionArg {
+  pub scope: String,
+  pub package: String,
+  pub version: Version,
+}
+
+impl std::fmt::Display for PackageVersionArg {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "@{}/{}@{}", self.scope, self.package, self.version)
+  }
+}
+
+pub(super) fn parse_package_version_arg(
+  text: &str,
+) -> Result<PackageVersionArg, AnyError> {
+  let parsed = text
+    .strip_prefix('@')
+    .and_then(|text| text.split_once('/'))
+    .and_then(|(scope, rest)| {
+      let (package, version) = rest.split_once('@')?;
+      Some((scope, package, version))
+    });
+  let Some((scope, package, version)) = parsed else {
+    bail!(
+      "Invalid package version '{}', use '@<scope_name>/<package_name>@<version>' format",
+      text
+    );
+  };
+  let version = Version::parse_standard(version)
+    .with_context(|| format!("Invalid version in '{}'", text))?;
+  Ok(PackageVersionArg {
+    scope: scope.to_string(),
+    package: package.to_string(),
+    version,
+  })
+}
+
+/// Gets the name of the subject a provenance bundle attests to.
+fn provenance_bundle_subject(bundle: &Value) -> Option<String> {
+  let payload = bundle
+    .get("content")?
+    .get("dsseEnvelope")?
+    .get("payload")?
+    .as_str()?;
+  let statement: Value =
+    serde_json::from_slice(&BASE64_STANDARD.decode(payload).ok()?).ok()?;
+  let subject = statement.get("subject")?;
+  // a statement can have one or more subjects
+  let subject = match subject {
+    Value::Array(subjects) => subjects.first()?,
+    subject => subject,
+  };
+  Some(subject.get("name")?.as_str()?.to_string())
+}
+
+#[derive(Debug, Serialize)]
+struct AttachedProvenanceJson {
+  package: String,
+  subject: String,
+}
+
+async fn attach_provenance(
+  flags: Flags,
+  attach_flags: AttachProvenanceFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let cli_options = context.cli_factory.cli_options();
+  let package = parse_package_version_arg(&attach_flags.package)?;
+
+  let Some(token) = attach_flags.token else {
+    bail!("No means to authenticate. Pass a token to `--token`.");
+  };
+
+  let bundle_path = cli_options.initial_cwd().join(&attach_flags.bundle);
+  let bundle_text =
+    std::fs::read_to_string(&bundle_path).with_context(|| {
+      format!(
+        "Failed reading provenance bundle '{}'",
+        bundle_path.display()
+      )
+    })?;
+  let bundle: Value =
+    serde_json::from_str(&bundle_text).with_context(|| {
+      format!(
+        "Failed parsing provenance bundle '{}'",
+        bundle_path.display()
+      )
+    })?;
+  let expected_subject = format!("pkg:jsr/{}", package);
+  match provenance_bundle_subject(&bundle) {
+    Some(subject) if subject == expected_subject => {}
+    Some(subject) => bail!(
+      "The provenance bundle '{}' is for '{}', not '{}'",
+      bundle_path.display(),
+      subject,
+      expected_subject
+    ),
+    None => bail!("'{}' is not a provenance bundle", bundle_path.display()),
+  }
+
+  let attestations = match &attach_flags.attestations {
+    Some(attestations_path) => {
+      let attestations_path = cli_options.initial_cwd().join(attestations_path);
+      let attestations_text = std::fs::read_to_string(&attestations_path)
+        .with_context(|| {
+          format!(
+            "Failed reading attestations '{}'",
+            attestations_path.display()
+          )
+        })?;
+      let attestations: Vec<Value> = serde_json::from_str(&attestations_text)
+        .with_context(|| {
+        format!(
+          "Failed parsing attestations '{}'",
+          attestations_path.display()
+        )
+      })?;
+      let all_for_package = attestations.iter().all(|attestation| {
+        provenance_bundle_subject(attestation).as_deref()
+          == Some(expected_subject.as_str())
+      });
+      if !all_for_package {
+        bail!(
+          "The attestations '{}' are not all for '{}'",
+          attestations_path.display(),
+          expected_subject
+        );
+      }
+      attestations
+    }
+    None => vec![],
+  };
+
+  let auth_header_template =
+    AuthHeaderTemplate::from_config(context.registry_config())?;
+
+  context
+    .api_client()?
+    .submit_provenance(
+      &auth_header_template.token(&token),
+      &package.scope,
+      &package.package,
+      &package.version.to_string(),
+      &bundle,
+      &attestations,
+    )
+    .await
+    .with_context(|| format!("Failed to submit provenance for {}", package))?;
+
+  if attach_flags.json {
+    print_json(&AttachedProvenanceJson {
+      package: package.to_string(),
+      subject: expected_subject,
+    })?;
+  } else {
+    println!("{} {}", colors::green("Attached provenance to"), package);
+  }
+  Ok(())
+}
+
+async fn show_provenance(
+  flags: Flags,
+  provenance_flags: RegistryProvenanceFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let package = parse_package_version_arg(&provenance_flags.package)?;
+  let bundle = context
+    .api_client()?
+    .get_provenance(
+      &package.scope,
+      &package.package,
+      &package.version.to_string(),
+    )
+    .await
+    .with_context(|| format!("Failed to get the provenance of {}", package))?;
+  let Some(bundle) = bundle else {
+    bail!("{} was published without provenance", package);
+  };
+  let summary = summarize_provenance(&bundle)
+    .with_context(|| format!("Failed to read the provenance of {}", package))?;
+
+  if provenance_flags.json {
+    return print_json(&summary);
+  }
+  println!("{} {}", colors::bold("Provenance of"), package);
+  let fields = [
+    ("Builder:", &summary.builder_id),
+    ("Source repository:", &summary.source_repository),
+    ("Source ref:", &summary.source_ref),
+    ("Source commit:", &summary.source_commit),
+    ("Workflow:", &summary.workflow_path),
+    ("Build:", &summary.invocation_id),
+    ("Signed by:", &summary.signer),
+  ];
+  for (name, value) in fields {
+    if let Some(value) = value {
+      println!("  {} {}", colors::gray(name), value);
+    }
+  }
+  if let Some(log_index) = summary.log_index {
+    let sigstore_config = SigstoreConfig::resolve(context.registry_config())?;
+    println!(
+      "  {} {} ({})",
+      colors::gray("Transparency log:"),
+      log_index,
+      sigstore_config.log_entry_url(log_index)
+    );
+  }
+  Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishingTaskJson {
+  id: String,
+  status: String,
+  error: Option<PublishingTaskErrorJson>,
+  package_scope: Option<String>,
+  package_name: Option<String>,
+  package_version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PublishingTaskErrorJson {
+  code: String,
+  message: String,
+}
+
+async fn status(
+  flags: Flags,
+  status_flags: RegistryStatusFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let task = context
+    .api_client()?
+    .get_publishing_task(None, &status_flags.task_id)
+    .await
+    .with_context(|| {
+      format!(
+        "Failed to get the publishing task '{}'",
+        status_flags.task_id
+      )
+    })?;
+
+  if status_flags.json {
+    print_json(&PublishingTaskJson {
+      id: task.id.clone(),
+      status: task.status.clone(),
+      error: task.error.as_ref().map(|error| PublishingTaskErrorJson {
+        code: error.code.clone(),
+        message: error.message.clone(),
+      }),
+      package_scope: task.package_scope.clone(),
+      package_name: task.package_name.clone(),
+      package_version: task.package_version.clone(),
+    })?;
+  } else {
+    let package = match (
+      &task.package_scope,
+      &task.package_name,
+      &task.package_version,
+    ) {
+      (Some(scope), Some(name), Some(version)) => {
+        format!(" of @{}/{}@{}", scope, name, version)
+      }
+      _ => String::new(),
+    };
+    let status = match task.status.as_str() {
+      "success" => colors::green(&task.status),
+      "failure" => colors::red(&task.status),
+      _ => colors::yellow(&task.status),
+    };
+    println!("Publishing task {}{}: {}", task.id, package, status);
+  }
+  if let Some(error) = &task.error {
+    bail!(
+      "Publishing task {} failed: {} ({})",
+      task.id,
+      error.message,
+      error.code
+    );
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn parse_package_version_arg_valid() {
+    assert_eq!(
+      parse_package_version_arg("@foo/bar@1.0.0").unwrap(),
+      PackageVersionArg {
+        scope: "foo".to_string(),
+        package: "bar".to_string(),
+        version: Version::parse_standard("1.0.0").unwrap(),
+      }
+    );
+  }
+
+  #[test]
+  fn parse_package_version_arg_invalid() {
+    assert!(parse_package_version_arg("@foo/bar").is_err());
+    assert!(parse_package_version_arg("foo/bar@1.0.0").is_err());
+    assert!(parse_package_version_arg("@foo/bar@^1.0.0").is_err());
+  }
+
+  #[test]
+  fn parse_package_arg_valid() {
+    let expected = PackageArg {
+      scope: "foo".to_string(),
+      package: "bar".to_string(),
+    };
+    assert_eq!(parse_package_arg("@foo/bar").unwrap(), expected);
+    assert_eq!(parse_package_arg("jsr:@foo/bar").unwrap(), expected);
+    assert!(parse_package_arg("foo/bar").is_err());
+    assert!(parse_package_arg("@foo/bar@1.0.0").is_err());
+    assert!(parse_package_arg("@foo/").is_err());
+  }
+
+  #[test]
+  fn provenance_bundle_subject_from_payload() {
+    let statement = json!({
+      "_type": "https://in-toto.io/Statement/v1",
+      "subject": { "name": "pkg:jsr/@foo/bar@1.0.0" },
+    });
+    let bundle = json!({
+      "content": {
+        "dsseEnvelope": {
+          "payload": BASE64_STANDARD.encode(statement.to_string()),
+        },
+      },
+    });
+    assert_eq!(
+      provenance_bundle_subject(&bundle).as_deref(),
+      Some("pkg:jsr/@foo/bar@1.0.0")
+    );
+    assert_eq!(provenance_bundle_subject(&json!({})), None);
+  }
+}
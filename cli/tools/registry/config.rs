@@ -0,0 +1,693 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+
+use deno_config::ConfigFile;
+use deno_config::PublishConfig;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+use deno_core::url::Url;
+use serde::Deserialize;
+
+use crate::args::AddVersionRange;
+
+use super::assets::AssetLoader;
+use super::diagnostics::PUBLISH_DIAGNOSTIC_CODES;
+
+/// Keys of the `publish` object in the configuration file that are handled
+/// by the CLI rather than by `deno_config`.
+const CLI_PUBLISH_KEYS: &[&str] = &[
+  "registries",
+  "scopes",
+  "provenance",
+  "rules",
+  "runtimeCompat",
+  "after",
+  "allowPinnedRemoteImports",
+  "loaders",
+  "transpileJsx",
+  "devImports",
+  "allowSlowTypes",
+];
+
+/// Publish settings from the configuration file that are only understood by
+/// the CLI (`deno_config` only knows about `include` and `exclude`).
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CliPublishConfig {
+  /// Settings keyed by registry URL, eg. `"https://jsr.io/"`.
+  pub registries: HashMap<String, RegistryConfig>,
+  /// URLs of the registries that the packages of a scope are published to
+  /// instead of the default registry, keyed by scope name, eg. `"@corp"`.
+  pub scopes: HashMap<String, String>,
+  /// Either the provenance settings or `false` to publish the package
+  /// without provenance.
+  #[serde(deserialize_with = "deserialize_provenance_config")]
+  pub provenance: ProvenanceConfig,
+  /// Severities of the publish diagnostics that differ from their default
+  /// one, keyed by diagnostic code, eg. `"invalid-external-import"`.
+  pub rules: HashMap<String, RuleSeverity>,
+  /// The runtimes the package works in, keyed by runtime, eg.
+  /// `"node": true`, like the runtime compatibility of a package on jsr.
+  pub runtime_compat: HashMap<String, bool>,
+  /// Workspace members that are published before this package, although
+  /// it doesn't import them, eg. `["@scope/runtime"]`.
+  pub after: Vec<String>,
+  /// Publish `http:` and `https:` imports that have an integrity entry in
+  /// the lockfile with a warning, instead of failing, for packages that are
+  /// migrating to `jsr:` and `npm:` dependencies.
+  pub allow_pinned_remote_imports: bool,
+  /// How the files with extensions other than those of JavaScript and
+  /// TypeScript modules are analyzed when the package imports them, keyed
+  /// by extension, eg. `".svelte": "script"`.
+  pub loaders: HashMap<String, AssetLoader>,
+  /// Publish the JSX and TSX modules of the package as JavaScript modules
+  /// with type declarations, transpiled with the compiler options of the
+  /// configuration file, for registries that don't support them.
+  pub transpile_jsx: bool,
+  /// The keys of the import map that are only used by tests and other
+  /// modules that aren't reachable from the exports, eg. `"@std/assert"`,
+  /// which also covers its subpaths, or `"@std/testing/"` for a prefix.
+  /// Their imports aren't checked or rewritten in those modules. `deno add
+  /// --dev` adds the keys of the packages it adds.
+  pub dev_imports: Vec<String>,
+  /// The modules and exported symbols that are allowed to have slow types,
+  /// eg. `"./legacy.ts"` or `"./mod.ts#createClient"`, while the rest of
+  /// the public API is still checked for them.
+  pub allow_slow_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleSeverity {
+  /// Fail the publish.
+  Error,
+  /// Print the diagnostic without failing the publish.
+  Warn,
+  /// Don't report the diagnostic.
+  Off,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct ProvenanceConfig {
+  /// Set with `"provenance": false`.
+  #[serde(skip)]
+  pub disabled: bool,
+  /// The SLSA provenance predicate that is generated.
+  pub predicate_version: SlsaPredicateVersion,
+  /// Additional attestations that are signed and submitted along with the
+  /// provenance of every published package.
+  pub attestations: Vec<AttestationConfig>,
+  /// Attach the CycloneDX SBOM of every published package as an attestation.
+  pub attest_sbom: bool,
+  /// Keys that the provenance of the packages of a scope is signed with,
+  /// instead of keyless signing, keyed by scope name, eg. `"@foo"`. Either a
+  /// path to a PKCS#8 PEM encoded ECDSA P-256 key relative to the
+  /// configuration file, `env://<VAR>` or `gcpkms://<key version name>`.
+  pub signing_keys: HashMap<String, String>,
+  /// What happens when the inclusion of the provenance in the transparency
+  /// log can't be proven after publishing.
+  pub inclusion_proof: InclusionProofCheck,
+}
+
+fn deserialize_provenance_config<'de, D>(
+  deserializer: D,
+) -> Result<ProvenanceConfig, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  match Value::deserialize(deserializer)? {
+    Value::Bool(enabled) => Ok(ProvenanceConfig {
+      disabled: !enabled,
+      ..Default::default()
+    }),
+    value => {
+      ProvenanceConfig::deserialize(value).map_err(serde::de::Error::custom)
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InclusionProofCheck {
+  /// Fail like any other provenance error, which only fails the publish
+  /// with `--require-provenance`.
+  #[default]
+  Error,
+  /// Print a warning.
+  Warn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AttestationConfig {
+  /// Path to a JSON document, relative to the configuration file.
+  pub path: String,
+  /// The predicate type of the document. When not set, the document must be
+  /// an in-toto statement.
+  pub predicate_type: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SlsaPredicateVersion {
+  /// SLSA v0.2 provenance, for verification policies that don't support
+  /// v1.0 yet. https://slsa.dev/spec/v0.2/provenance
+  #[serde(rename = "legacy")]
+  Legacy,
+  /// https://slsa.dev/spec/v1.0/provenance
+  #[default]
+  #[serde(rename = "v1.0")]
+  V1_0,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct RegistryConfig {
+  /// Name of the header the credential is sent in. Defaults to
+  /// `Authorization`.
+  pub auth_header_name: Option<String>,
+  /// Template for the header value when authenticating with a token or
+  /// interactively. `{token}` is replaced with the token. Defaults to
+  /// `Bearer {token}`.
+  pub auth_header_value: Option<String>,
+  /// Template for the header value when authenticating with an OIDC token.
+  /// Defaults to `githuboidc {token}`.
+  pub oidc_auth_header_value: Option<String>,
+  /// Path to a PEM encoded CA certificate that is trusted for requests to
+  /// the registry, relative to the configuration file. Overridden by the
+  /// `DENO_REGISTRY_CERT` environment variable.
+  pub ca_file: Option<String>,
+  /// The maximum number of permissions requested in a single OIDC token.
+  /// Defaults to 16.
+  pub oidc_max_permissions: Option<usize>,
+  /// The maximum length of the percent encoded audience of an OIDC token
+  /// request, which contains the requested permissions. Defaults to 4096.
+  pub oidc_max_audience_length: Option<usize>,
+  /// URL of the Fulcio instance that issues the certificates provenance is
+  /// signed with. Overridden by the `FULCIO_URL` environment variable.
+  pub fulcio_url: Option<String>,
+  /// URL of the Rekor transparency log provenance is recorded in.
+  /// Overridden by the `REKOR_URL` environment variable.
+  pub rekor_url: Option<String>,
+  /// Seconds a request to sigstore, or to the other services involved in
+  /// generating provenance, may take. Defaults to 30.
+  pub sigstore_timeout: Option<u64>,
+  /// How often a sigstore request is retried after a network error, a
+  /// timeout or a server error. Defaults to 2.
+  pub sigstore_retries: Option<u32>,
+  /// Base URLs of mirrors of the registry, in order of preference. Reads,
+  /// like getting package metadata, fail over to them when the registry
+  /// can't be reached. Publishing always goes to the registry itself.
+  pub mirrors: Vec<String>,
+  /// Name of the environment variable with the token that is used to
+  /// publish to the registry, instead of the one passed with `--token`.
+  pub token_env: Option<String>,
+}
+
+impl RegistryConfig {
+  pub fn mirror_urls(&self) -> Result<Vec<Url>, AnyError> {
+    self
+      .mirrors
+      .iter()
+      .map(|mirror| {
+        // ensure there is a trailing slash for the directory
+        let mirror = format!("{}/", mirror.trim_end_matches('/'));
+        Url::parse(&mirror)
+          .with_context(|| format!("Invalid registry mirror URL '{}'", mirror))
+      })
+      .collect()
+  }
+}
+
+impl CliPublishConfig {
+  pub fn from_config_file(
+    config_file: &ConfigFile,
+  ) -> Result<CliPublishConfig, AnyError> {
+    let Some(Value::Object(publish)) = &config_file.json.publish else {
+      return Ok(CliPublishConfig::default());
+    };
+    let cli_keys = publish
+      .iter()
+      .filter(|(key, _)| CLI_PUBLISH_KEYS.contains(&key.as_str()))
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect::<serde_json::Map<_, _>>();
+    let config: CliPublishConfig =
+      serde_json::from_value(Value::Object(cli_keys)).with_context(|| {
+        format!(
+          "Failed to parse \"publish\" configuration in {}",
+          config_file.specifier
+        )
+      })?;
+    for code in config.rules.keys() {
+      if !PUBLISH_DIAGNOSTIC_CODES.contains(&code.as_str()) {
+        bail!(
+          "Unknown rule '{}' in \"publish.rules\" of {}",
+          code,
+          config_file.specifier
+        );
+      }
+    }
+    Ok(config)
+  }
+
+  /// Gets the registry URLs of the scopes that are published to another
+  /// registry, keyed by scope name without the `@`.
+  pub fn scope_registry_urls(&self) -> Result<HashMap<String, Url>, AnyError> {
+    let mut urls = HashMap::with_capacity(self.scopes.len());
+    for (scope, url) in &self.scopes {
+      let Some(scope_name) = scope.strip_prefix('@') else {
+        bail!(
+          "Invalid scope '{}' in \"scopes\", use '@<scope_name>' format",
+          scope
+        );
+      };
+      // ensure there is a trailing slash for the directory
+      let url = format!("{}/", url.trim_end_matches('/'));
+      let url = Url::parse(&url).with_context(|| {
+        format!("Invalid registry URL '{}' of {}", url, scope)
+      })?;
+      urls.insert(scope_name.to_string(), url);
+    }
+    Ok(urls)
+  }
+
+  /// Gets the settings for the provided registry, if any.
+  pub fn registry(&self, registry_url: &Url) -> Option<&RegistryConfig> {
+    let registry_url = registry_url.as_str().trim_end_matches('/');
+    self
+      .registries
+      .iter()
+      .find(|(url, _)| url.trim_end_matches('/') == registry_url)
+      .map(|(_, config)| config)
+  }
+}
+
+/// Settings of `deno add` in the `"add"` object of the configuration file,
+/// eg. `"add": { "versionRange": "exact" }`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct CliAddConfig {
+  /// The range the versions of the added packages are recorded with when
+  /// neither `--exact` nor `--tilde` is passed, which is `"caret"`,
+  /// `"tilde"` or `"exact"`.
+  pub version_range: AddVersionRange,
+}
+
+impl CliAddConfig {
+  /// Reads the settings from the text of the configuration file, because
+  /// `deno_config` drops the keys it doesn't know about.
+  pub fn from_config_text(
+    text: &str,
+    specifier: &Url,
+  ) -> Result<CliAddConfig, AnyError> {
+    let value = jsonc_parser::parse_to_serde_value(text, &Default::default())
+      .with_context(|| format!("Failed to parse {}", specifier))?;
+    let Some(add) = value.as_ref().and_then(|value| value.get("add")) else {
+      return Ok(CliAddConfig::default());
+    };
+    serde_json::from_value(add.clone()).with_context(|| {
+      format!("Failed to parse \"add\" configuration in {}", specifier)
+    })
+  }
+}
+
+/// Whether the package of the configuration file opted out of publishing
+/// with `"publish": false`, eg. an example app in a workspace.
+pub fn is_publish_disabled(config_file: &ConfigFile) -> bool {
+  matches!(config_file.json.publish, Some(Value::Bool(false)))
+}
+
+/// Resolves the `publish` configuration with the keys handled by the CLI
+/// removed, because `deno_config` rejects unknown fields.
+pub fn to_publish_config(
+  config_file: &ConfigFile,
+) -> Result<Option<PublishConfig>, AnyError> {
+  match &config_file.json.publish {
+    Some(Value::Bool(false)) => Ok(None),
+    Some(Value::Object(publish))
+      if publish
+        .keys()
+        .any(|key| CLI_PUBLISH_KEYS.contains(&key.as_str())) =>
+    {
+      let mut config_file = config_file.clone();
+      let publish = publish
+        .iter()
+        .filter(|(key, _)| !CLI_PUBLISH_KEYS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+      config_file.json.publish = Some(Value::Object(publish));
+      config_file.to_publish_config()
+    }
+    _ => config_file.to_publish_config(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  fn config_file(json: Value) -> ConfigFile {
+    ConfigFile::new(
+      &json.to_string(),
+      Url::parse("file:///deno/deno.json").unwrap(),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn cli_publish_config_registries() {
+    let config_file = config_file(json!({
+      "publish": {
+        "exclude": ["tests/"],
+        "registries": {
+          "https://jsr.example.com": {
+            "authHeaderName": "X-Api-Key",
+            "authHeaderValue": "{token}",
+          },
+        },
+      },
+    }));
+    let config = CliPublishConfig::from_config_file(&config_file).unwrap();
+    assert_eq!(
+      config.registry(&Url::parse("https://jsr.example.com/").unwrap()),
+      Some(&RegistryConfig {
+        auth_header_name: Some("X-Api-Key".to_string()),
+        auth_header_value: Some("{token}".to_string()),
+        oidc_auth_header_value: None,
+        ca_file: None,
+        oidc_max_permissions: None,
+        oidc_max_audience_length: None,
+        fulcio_url: None,
+        rekor_url: None,
+        sigstore_timeout: None,
+        sigstore_retries: None,
+        mirrors: vec![],
+        token_env: None,
+      })
+    );
+    assert_eq!(
+      config.registry(&Url::parse("https://jsr.io/").unwrap()),
+      None
+    );
+
+    assert_eq!(
+      config.provenance.predicate_version,
+      SlsaPredicateVersion::V1_0
+    );
+
+    // the cli specific keys are not passed to deno_config
+    let publish_config = to_publish_config(&config_file).unwrap().unwrap();
+    assert_eq!(publish_config.files.exclude.inner().len(), 1);
+  }
+
+  #[test]
+  fn cli_publish_config_rules() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": {
+        "rules": {
+          "invalid-external-import": "warn",
+          "unanalyzable-dynamic-import": "off",
+          "unsupported-file-type": "error",
+        },
+      },
+    })))
+    .unwrap();
+    assert_eq!(
+      config.rules,
+      HashMap::from([
+        ("invalid-external-import".to_string(), RuleSeverity::Warn),
+        ("unanalyzable-dynamic-import".to_string(), RuleSeverity::Off),
+        ("unsupported-file-type".to_string(), RuleSeverity::Error),
+      ])
+    );
+
+    let err = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "rules": { "exluded-module": "off" } },
+    })))
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Unknown rule 'exluded-module' in \"publish.rules\" of file:///deno/deno.json"
+    );
+
+    let config_file = config_file(json!({
+      "publish": { "rules": { "invalid-external-import": "ignore" } },
+    }));
+    assert!(CliPublishConfig::from_config_file(&config_file).is_err());
+  }
+
+  #[test]
+  fn cli_publish_config_runtime_compat() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": {
+        "runtimeCompat": { "deno": true, "node": false },
+        "exclude": ["tests/"],
+      },
+    })))
+    .unwrap();
+    assert_eq!(
+      config.runtime_compat,
+      HashMap::from([("deno".to_string(), true), ("node".to_string(), false)])
+    );
+  }
+
+  #[test]
+  fn cli_publish_config_scopes() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": {
+        "scopes": { "@corp": "https://jsr.corp.example.com" },
+        "registries": {
+          "https://jsr.corp.example.com/": { "tokenEnv": "CORP_JSR_TOKEN" },
+        },
+      },
+    })))
+    .unwrap();
+    let urls = config.scope_registry_urls().unwrap();
+    let corp_url = Url::parse("https://jsr.corp.example.com/").unwrap();
+    assert_eq!(
+      urls,
+      HashMap::from([("corp".to_string(), corp_url.clone())])
+    );
+    assert_eq!(
+      config.registry(&corp_url).unwrap().token_env.as_deref(),
+      Some("CORP_JSR_TOKEN")
+    );
+
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "scopes": { "corp": "https://jsr.corp.example.com" } },
+    })))
+    .unwrap();
+    assert!(config.scope_registry_urls().is_err());
+  }
+
+  #[test]
+  fn cli_publish_config_provenance() {
+    let legacy_config_file = config_file(json!({
+      "publish": {
+        "provenance": { "predicateVersion": "legacy" },
+      },
+    }));
+    let config =
+      CliPublishConfig::from_config_file(&legacy_config_file).unwrap();
+    assert_eq!(
+      config.provenance.predicate_version,
+      SlsaPredicateVersion::Legacy
+    );
+    assert!(config.provenance.attestations.is_empty());
+    assert!(!config.provenance.attest_sbom);
+    assert!(config.provenance.signing_keys.is_empty());
+    assert!(!config.provenance.disabled);
+    assert_eq!(
+      config.provenance.inclusion_proof,
+      InclusionProofCheck::Error
+    );
+
+    let attestations_config_file = config_file(json!({
+      "publish": {
+        "provenance": {
+          "attestations": [
+            { "path": "./statement.json" },
+            {
+              "path": "./scan.json",
+              "predicateType": "https://cosign.sigstore.dev/attestation/vuln/v1",
+            },
+          ],
+          "attestSbom": true,
+          "signingKeys": { "@foo": "env://FOO_SIGNING_KEY" },
+          "inclusionProof": "warn",
+        },
+      },
+    }));
+    let config =
+      CliPublishConfig::from_config_file(&attestations_config_file).unwrap();
+    assert!(config.provenance.attest_sbom);
+    assert_eq!(config.provenance.inclusion_proof, InclusionProofCheck::Warn);
+    assert_eq!(
+      config.provenance.signing_keys,
+      HashMap::from([(
+        "@foo".to_string(),
+        "env://FOO_SIGNING_KEY".to_string()
+      )])
+    );
+    assert_eq!(
+      config.provenance.attestations,
+      vec![
+        AttestationConfig {
+          path: "./statement.json".to_string(),
+          predicate_type: None,
+        },
+        AttestationConfig {
+          path: "./scan.json".to_string(),
+          predicate_type: Some(
+            "https://cosign.sigstore.dev/attestation/vuln/v1".to_string()
+          ),
+        },
+      ]
+    );
+
+    let disabled_config_file = config_file(json!({
+      "publish": { "provenance": false },
+    }));
+    let config =
+      CliPublishConfig::from_config_file(&disabled_config_file).unwrap();
+    assert!(config.provenance.disabled);
+
+    let invalid_config_file = config_file(json!({
+      "publish": {
+        "provenance": { "predicateVersion": "v0.2" },
+      },
+    }));
+    assert!(CliPublishConfig::from_config_file(&invalid_config_file).is_err());
+  }
+
+  #[test]
+  fn cli_publish_config_after() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": {
+        "after": ["@scope/runtime"],
+        "exclude": ["tests/"],
+      },
+    })))
+    .unwrap();
+    assert_eq!(config.after, vec!["@scope/runtime".to_string()]);
+    let publish_config = to_publish_config(&config_file(json!({
+      "publish": { "after": ["@scope/runtime"] },
+    })))
+    .unwrap()
+    .unwrap();
+    assert!(publish_config.files.exclude.inner().is_empty());
+  }
+
+  #[test]
+  fn cli_publish_config_allow_pinned_remote_imports() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "allowPinnedRemoteImports": true },
+    })))
+    .unwrap();
+    assert!(config.allow_pinned_remote_imports);
+    let config =
+      CliPublishConfig::from_config_file(&config_file(json!({}))).unwrap();
+    assert!(!config.allow_pinned_remote_imports);
+  }
+
+  #[test]
+  fn cli_publish_config_loaders() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "loaders": { ".svelte": "script", ".css": "asset" } },
+    })))
+    .unwrap();
+    assert_eq!(config.loaders.get(".svelte"), Some(&AssetLoader::Script));
+    assert_eq!(config.loaders.get(".css"), Some(&AssetLoader::Asset));
+    assert!(CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "loaders": { ".svelte": "html" } },
+    })))
+    .is_err());
+  }
+
+  #[test]
+  fn cli_publish_config_transpile_jsx() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "transpileJsx": true },
+    })))
+    .unwrap();
+    assert!(config.transpile_jsx);
+    let config =
+      CliPublishConfig::from_config_file(&config_file(json!({}))).unwrap();
+    assert!(!config.transpile_jsx);
+  }
+
+  #[test]
+  fn cli_publish_config_dev_imports() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "devImports": ["@std/assert", "@std/testing/"] },
+    })))
+    .unwrap();
+    assert_eq!(config.dev_imports, vec!["@std/assert", "@std/testing/"]);
+  }
+
+  #[test]
+  fn cli_publish_config_allow_slow_types() {
+    let config = CliPublishConfig::from_config_file(&config_file(json!({
+      "publish": { "allowSlowTypes": ["./legacy.ts", "./mod.ts#createClient"] },
+    })))
+    .unwrap();
+    assert_eq!(
+      config.allow_slow_types,
+      vec!["./legacy.ts", "./mod.ts#createClient"]
+    );
+  }
+
+  #[test]
+  fn publish_disabled() {
+    let disabled = config_file(json!({ "publish": false }));
+    assert!(is_publish_disabled(&disabled));
+    assert!(to_publish_config(&disabled).unwrap().is_none());
+    assert!(CliPublishConfig::from_config_file(&disabled)
+      .unwrap()
+      .registries
+      .is_empty());
+    assert!(!is_publish_disabled(&config_file(json!({
+      "publish": { "exclude": ["examples/"] },
+    }))));
+    assert!(!is_publish_disabled(&config_file(json!({}))));
+  }
+
+  #[test]
+  fn cli_add_config() {
+    let specifier = Url::parse("file:///deno/deno.json").unwrap();
+    let add_config = CliAddConfig::from_config_text(
+      r#"{
+  // the versions are pinned
+  "add": { "versionRange": "exact" }
+}"#,
+      &specifier,
+    )
+    .unwrap();
+    assert_eq!(add_config.version_range, AddVersionRange::Exact);
+    let add_config = CliAddConfig::from_config_text("{}", &specifier).unwrap();
+    assert_eq!(add_config.version_range, AddVersionRange::Caret);
+    assert!(CliAddConfig::from_config_text(
+      r#"{ "add": { "versionRange": "minor" } }"#,
+      &specifier
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn cli_publish_config_invalid() {
+    let config_file = config_file(json!({
+      "publish": {
+        "registries": {
+          "https://jsr.example.com": { "authHeader": "X-Api-Key" },
+        },
+      },
+    }));
+    assert!(CliPublishConfig::from_config_file(&config_file).is_err());
+  }
+}
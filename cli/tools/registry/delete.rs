@@ -0,0 +1,159 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::io::IsTerminal;
+use std::io::Write;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Utc;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryDeleteFlags;
+
+use super::api;
+use super::auth::AuthHeaderTemplate;
+use super::commands::parse_package_version_arg;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+/// How long after publishing the registry allows deleting a version. Older
+/// versions can only be yanked.
+const DELETION_GRACE_PERIOD_HOURS: i64 = 72;
+
+#[derive(Debug, Serialize)]
+struct DeletedVersionJson {
+  package: String,
+}
+
+pub async fn delete(
+  flags: Flags,
+  delete_flags: RegistryDeleteFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let package = parse_package_version_arg(&delete_flags.package)?;
+  let Some(token) = delete_flags.token else {
+    bail!("No means to authenticate. Pass a token to `--token`.");
+  };
+  let authorization =
+    AuthHeaderTemplate::from_config(context.registry_config())?.token(&token);
+  let api_client = context.api_client()?;
+  let version = package.version.to_string();
+
+  let Some(version_info) = api_client
+    .get_package_version(&package.scope, &package.package, &version)
+    .await?
+  else {
+    bail!("{} was not found in the registry", package);
+  };
+  if let Some(created_at) = version_info
+    .created_at
+    .as_deref()
+    .and_then(|created_at| DateTime::parse_from_rfc3339(created_at).ok())
+  {
+    check_grace_period(&package.to_string(), created_at.into(), Utc::now())?;
+  }
+
+  if !delete_flags.yes && !confirm_deletion(&package.to_string())? {
+    bail!("Deletion of {} cancelled", package);
+  }
+
+  let result = api_client
+    .delete_package_version(
+      &authorization,
+      &package.scope,
+      &package.package,
+      &version,
+    )
+    .await;
+  match result {
+    Ok(()) => {}
+    Err(err)
+      if api::as_api_error(&err).is_some_and(|err| {
+        matches!(err.kind, api::ApiErrorKind::VersionDeletionPeriodPassed)
+      }) =>
+    {
+      bail!(
+        "{} can't be deleted anymore, versions can only be deleted within {} hours of publishing. Yank it instead.",
+        package,
+        DELETION_GRACE_PERIOD_HOURS
+      );
+    }
+    Err(err) => {
+      return Err(err.context(format!("Failed to delete {}", package)))
+    }
+  }
+
+  if delete_flags.json {
+    print_json(&DeletedVersionJson {
+      package: package.to_string(),
+    })?;
+  } else {
+    println!("{} {}", colors::green("Deleted"), package);
+  }
+  Ok(())
+}
+
+fn check_grace_period(
+  package: &str,
+  created_at: DateTime<Utc>,
+  now: DateTime<Utc>,
+) -> Result<(), AnyError> {
+  let age = now - created_at;
+  if age > Duration::hours(DELETION_GRACE_PERIOD_HOURS) {
+    bail!(
+      "{} was published {} hours ago, versions can only be deleted within {} hours of publishing. Yank it instead.",
+      package,
+      age.num_hours(),
+      DELETION_GRACE_PERIOD_HOURS
+    );
+  }
+  Ok(())
+}
+
+fn confirm_deletion(package: &str) -> Result<bool, AnyError> {
+  if !std::io::stdin().is_terminal() {
+    bail!(
+      "Can't confirm the deletion of {}, pass `--yes` to delete it without confirmation",
+      package
+    );
+  }
+  // prompts on stderr, to keep stdout parseable with `--json`
+  eprint!(
+    "Delete {}? This can't be undone. [y/N] ",
+    colors::bold(package)
+  );
+  std::io::stderr().flush()?;
+  let mut answer = String::new();
+  std::io::stdin().read_line(&mut answer)?;
+  Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn grace_period() {
+    let now: DateTime<Utc> =
+      DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+        .unwrap()
+        .into();
+    assert!(check_grace_period(
+      "@foo/bar@1.0.0",
+      now - Duration::hours(1),
+      now
+    )
+    .is_ok());
+    let err =
+      check_grace_period("@foo/bar@1.0.0", now - Duration::days(5), now)
+        .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "@foo/bar@1.0.0 was published 120 hours ago, versions can only be deleted within 72 hours of publishing. Yank it instead."
+    );
+  }
+}
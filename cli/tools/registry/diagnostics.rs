@@ -1,6 +1,10 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -15,38 +19,369 @@ use deno_ast::diagnostics::DiagnosticSourcePos;
 use deno_ast::diagnostics::DiagnosticSourceRange;
 use deno_ast::swc::common::util::take::Take;
 use deno_ast::SourcePos;
+use deno_ast::SourceRange;
 use deno_ast::SourceRanged;
 use deno_ast::SourceTextInfo;
 use deno_core::anyhow::anyhow;
 use deno_core::error::AnyError;
+use deno_doc::DocDiagnostic;
 use deno_graph::FastCheckDiagnostic;
+use deno_semver::jsr::JsrPackageReqReference;
 use lsp_types::Url;
+use serde::Serialize;
 
+use crate::args::PublishDiagnosticsFormat;
+
+use super::baseline::Baseline;
+use super::config::RuleSeverity;
+use super::exports::ExportsConflict;
+use super::report::write_report;
+use super::report::ReportDiagnostic;
 use super::unfurl::SpecifierUnfurlerDiagnostic;
 
-#[derive(Clone, Default)]
+/// The codes of all the publish diagnostics, which are the keys that
+/// `"publish.rules"` accepts. Includes the codes of the fast check
+/// diagnostics of `deno_graph` and of the specifier unfurler.
+pub const PUBLISH_DIAGNOSTIC_CODES: &[&str] = &[
+  "invalid-path",
+  "case-insensitive-duplicate-path",
+  "unicode-normalization-duplicate-path",
+  "unsupported-file-type",
+  "invalid-external-import",
+  "unsupported-jsx-tsx",
+  "unreachable-file",
+  "excluded-module",
+  "missing-jsdoc",
+  "conflicting-exports",
+  "unpinned-dependency",
+  "pinned-remote-import",
+  "deno-only-api",
+  // specifier unfurler
+  "unanalyzable-dynamic-import",
+  "cross-member-import",
+  "incompatible-member-version",
+  "dev-import",
+  // fast check
+  "not-found-reference",
+  "missing-explicit-type",
+  "missing-explicit-return-type",
+  "unsupported-ambient-module",
+  "unsupported-complex-reference",
+  "unsupported-default-export-expr",
+  "unsupported-destructuring",
+  "unsupported-global-module",
+  "unsupported-require",
+  "unsupported-private-member-reference",
+  "unsupported-super-class-expr",
+  "unsupported-ts-export-assignment",
+  "unsupported-ts-namespace-export",
+  "unsupported-using",
+  "unsupported-nested-javascript",
+  "unsupported-javascript-entrypoint",
+  "emit",
+  "external",
+  "cached",
+];
+
+/// How many diagnostics with the same code are shown for a file, the rest
+/// are only counted.
+const MAX_REPEATS_PER_FILE: usize = 3;
+/// Above this many diagnostics, the output ends with their counts by code.
+const SUMMARY_THRESHOLD: usize = 10;
+
+#[derive(Clone)]
 pub struct PublishDiagnosticsCollector {
   diagnostics: Arc<Mutex<Vec<PublishDiagnostic>>>,
+  /// The severities configured in `"publish.rules"`, keyed by code.
+  rules: Arc<HashMap<String, RuleSeverity>>,
+  /// The accepted diagnostics, which aren't reported.
+  baseline: Option<Arc<Mutex<Baseline>>>,
+  /// How many diagnostics are printed, the others are only counted.
+  max_diagnostics: Option<usize>,
+  /// Where all diagnostics are written as JSON.
+  report_file: Option<PathBuf>,
+  /// Whether warnings fail publishing like errors.
+  deny_warnings: bool,
+  format: PublishDiagnosticsFormat,
+  stream: Option<Arc<Mutex<DiagnosticsStream>>>,
+}
+
+/// The state of printing the diagnostics as they are pushed, see
+/// `PublishDiagnosticsCollector::with_streaming`.
+#[derive(Default)]
+struct DiagnosticsStream {
+  /// A copy of the baseline, since the collected diagnostics are checked
+  /// against the baseline again when summarizing them.
+  baseline: Option<Baseline>,
+  /// The file, code, position and message of the pushed diagnostics, to
+  /// skip duplicates.
+  seen: BTreeSet<(String, String, Option<SourcePos>, String)>,
+  /// The number of diagnostics by file and code.
+  repeats: BTreeMap<(String, String), usize>,
+  shown: usize,
+  /// The diagnostics over --max-diagnostics, counted by code and file.
+  omitted: BTreeMap<(String, String), usize>,
+}
+
+/// The number of printed diagnostics by level and by code, which is also
+/// part of the `--json` output of `deno publish` and the `--report-file`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiagnosticsSummary {
+  pub errors: usize,
+  pub warnings: usize,
+  /// Whether the diagnostics fail publishing, which warnings only do with
+  /// `--deny-warnings`.
+  pub failed: bool,
+  pub codes: BTreeMap<String, CodeCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodeCount {
+  pub count: usize,
+  pub level: &'static str,
+}
+
+impl DiagnosticsSummary {
+  pub fn to_result(&self) -> Result<(), AnyError> {
+    if self.errors > 0 {
+      Err(anyhow!(
+        "Found {} problem{}",
+        self.errors,
+        if self.errors == 1 { "" } else { "s" }
+      ))
+    } else if self.failed {
+      Err(anyhow!(
+        "Found {} warning{}, which fail{} publishing because of --deny-warnings",
+        self.warnings,
+        if self.warnings == 1 { "" } else { "s" },
+        if self.warnings == 1 { "s" } else { "" }
+      ))
+    } else {
+      Ok(())
+    }
+  }
 }
 
 impl PublishDiagnosticsCollector {
-  pub fn print_and_error(&self) -> Result<(), AnyError> {
-    let mut errors = 0;
-    let mut has_slow_types_errors = false;
+  pub fn new(rules: HashMap<String, RuleSeverity>) -> Self {
+    Self {
+      diagnostics: Default::default(),
+      rules: Arc::new(rules),
+      baseline: None,
+      max_diagnostics: None,
+      report_file: None,
+      deny_warnings: false,
+      format: PublishDiagnosticsFormat::Pretty,
+      stream: None,
+    }
+  }
+
+  pub fn with_baseline(mut self, baseline: Baseline) -> Self {
+    self.baseline = Some(Arc::new(Mutex::new(baseline)));
+    self
+  }
+
+  pub fn with_max_diagnostics(mut self, max_diagnostics: usize) -> Self {
+    self.max_diagnostics = Some(max_diagnostics);
+    self
+  }
+
+  pub fn with_report_file(mut self, report_file: PathBuf) -> Self {
+    self.report_file = Some(report_file);
+    self
+  }
+
+  pub fn with_deny_warnings(mut self) -> Self {
+    self.deny_warnings = true;
+    self
+  }
+
+  pub fn with_format(mut self, format: PublishDiagnosticsFormat) -> Self {
+    self.format = format;
+    self
+  }
+
+  /// Prints the diagnostics when they are pushed, so they show up while a
+  /// large graph is still being built. Only the summary is left for `print`.
+  /// Must be called after `with_baseline`.
+  pub fn with_streaming(mut self) -> Self {
+    let baseline = self
+      .baseline
+      .as_ref()
+      .map(|baseline| baseline.lock().unwrap().clone());
+    self.stream = Some(Arc::new(Mutex::new(DiagnosticsStream {
+      baseline,
+      ..Default::default()
+    })));
+    self
+  }
+
+  /// Records the diagnostics that aren't turned off in a baseline file,
+  /// returning how many were recorded.
+  pub fn write_baseline(&self, path: &Path) -> Result<usize, AnyError> {
+    let mut baseline = Baseline::new(path);
+    for diagnostic in self.take_diagnostics() {
+      let (file, code, _) = diagnostic.sorting_key();
+      baseline.add(&file, &code, &diagnostic.message());
+    }
+    baseline.write(path)?;
+    Ok(baseline.remaining())
+  }
+
+  /// The level of a diagnostic with the configured severity, `None` when
+  /// the diagnostic is turned off.
+  fn level_of(
+    &self,
+    diagnostic: &PublishDiagnostic,
+  ) -> Option<DiagnosticLevel> {
+    match self.rules.get(diagnostic.code().as_ref()) {
+      Some(RuleSeverity::Error) => Some(DiagnosticLevel::Error),
+      Some(RuleSeverity::Warn) => Some(DiagnosticLevel::Warning),
+      Some(RuleSeverity::Off) => None,
+      None if diagnostic.is_opt_in() => None,
+      None => Some(diagnostic.level()),
+    }
+  }
+
+  /// Whether a rule is turned off in `"publish.rules"`.
+  pub fn is_rule_off(&self, code: &str) -> bool {
+    matches!(self.rules.get(code), Some(RuleSeverity::Off))
+  }
+
+  /// Whether a rule is configured with a severity other than `"off"`, for
+  /// the opt-in diagnostics that are expensive to collect.
+  pub fn is_rule_enabled(&self, code: &str) -> bool {
+    matches!(
+      self.rules.get(code),
+      Some(RuleSeverity::Error | RuleSeverity::Warn)
+    )
+  }
+
+  /// Takes the collected diagnostics, sorted by file, code and position.
+  fn take_sorted(&self) -> Vec<PublishDiagnostic> {
     let mut diagnostics = self.diagnostics.lock().unwrap().take();
 
     diagnostics.sort_by_cached_key(|d| d.sorting_key());
+    // the same module can be reported more than once, eg. when it's part
+    // of several packages of a workspace
+    diagnostics.dedup_by(|a, b| {
+      a.sorting_key() == b.sorting_key() && a.message() == b.message()
+    });
+    diagnostics
+  }
+
+  /// Takes the collected diagnostics that aren't turned off, for reporting
+  /// them elsewhere than `deno publish`.
+  pub fn take_diagnostics(&self) -> Vec<PublishDiagnostic> {
+    let mut diagnostics = self.take_sorted();
+    diagnostics.retain(|diagnostic| self.level_of(diagnostic).is_some());
+    diagnostics
+  }
 
-    for diagnostic in diagnostics {
-      eprint!("{}", diagnostic.display());
-      if matches!(diagnostic.level(), DiagnosticLevel::Error) {
-        errors += 1;
+  /// Prints the diagnostics, returning their counts. Use
+  /// `DiagnosticsSummary::to_result` for whether they fail publishing.
+  pub fn print(&self) -> Result<DiagnosticsSummary, AnyError> {
+    let mut has_slow_types_errors = false;
+    let diagnostics = self.take_sorted();
+    let mut baseline = self
+      .baseline
+      .as_ref()
+      .map(|baseline| baseline.lock().unwrap());
+    let mut accepted = 0;
+
+    let diagnostics = diagnostics
+      .iter()
+      .filter_map(|diagnostic| {
+        let level = self.level_of(diagnostic)?;
+        let (file, code, _) = diagnostic.sorting_key();
+        if let Some(baseline) = &mut baseline {
+          if baseline.accept(&file, &code, &diagnostic.message()) {
+            accepted += 1;
+            return None;
+          }
+        }
+        Some((ConfiguredDiagnostic { diagnostic, level }, (file, code)))
+      })
+      .collect::<Vec<_>>();
+    let total = diagnostics.len();
+    let mut summary = DiagnosticsSummary::default();
+    for (diagnostic, (_, code)) in &diagnostics {
+      let is_error = matches!(diagnostic.level, DiagnosticLevel::Error);
+      summary
+        .codes
+        .entry(code.clone())
+        .or_insert(CodeCount {
+          count: 0,
+          level: if is_error { "error" } else { "warning" },
+        })
+        .count += 1;
+      if is_error {
+        summary.errors += 1;
+        if matches!(diagnostic.diagnostic, PublishDiagnostic::FastCheck(..)) {
+          has_slow_types_errors = true;
+        }
+      } else {
+        summary.warnings += 1;
       }
-      if matches!(diagnostic, PublishDiagnostic::FastCheck(..)) {
-        has_slow_types_errors = true;
+    }
+    summary.failed =
+      summary.errors > 0 || (self.deny_warnings && summary.warnings > 0);
+    if let Some(report_file) = &self.report_file {
+      let report = diagnostics
+        .iter()
+        .map(|(diagnostic, _)| ReportDiagnostic::new(diagnostic))
+        .collect::<Vec<_>>();
+      write_report(report_file, &summary, &report)?;
+    }
+    let (collapsed_any, omitted) = match &self.stream {
+      // the diagnostics were already printed when they were pushed
+      Some(stream) => self.finish_stream(&stream.lock().unwrap()),
+      None => self.print_collapsed(diagnostics),
+    };
+    if !omitted.is_empty() {
+      let count = omitted.values().sum::<usize>();
+      eprintln!(
+        "... and {} more diagnostic{} not shown because of --max-diagnostics:",
+        count,
+        if count == 1 { "" } else { "s" }
+      );
+      for ((code, file), count) in &omitted {
+        eprintln!("  {:>4} [{}] {}", count, code, file);
+      }
+      match &self.report_file {
+        Some(report_file) => eprintln!(
+          "All diagnostics were written to {}\n",
+          report_file.display()
+        ),
+        None => eprintln!("Write all of them to a file with --report-file\n"),
+      }
+    }
+    if collapsed_any || !omitted.is_empty() || total > SUMMARY_THRESHOLD {
+      eprintln!("Summary:");
+      for (code, CodeCount { count, level }) in &summary.codes {
+        eprintln!("  {:>4} {}[{}]", count, level, code);
       }
+      eprintln!();
     }
-    if errors > 0 {
+    if let Some(baseline) = &baseline {
+      if accepted > 0 {
+        eprintln!(
+          "{} diagnostic{} accepted by the baseline\n",
+          accepted,
+          if accepted == 1 { " was" } else { "s were" }
+        );
+      }
+      let fixed = baseline.remaining();
+      if fixed > 0 {
+        eprintln!(
+          "{} diagnostic{} of the baseline no longer occur{}, update it with --write-baseline\n",
+          fixed,
+          if fixed == 1 { "" } else { "s" },
+          if fixed == 1 { "s" } else { "" }
+        );
+      }
+    }
+    if summary.errors > 0 {
       if has_slow_types_errors {
         eprintln!(
           "This package contains errors for slow types. Fixing these errors will:\n"
@@ -61,20 +396,134 @@ impl PublishDiagnosticsCollector {
         );
         eprintln!("providing the --allow-slow-types flag.\n");
       }
+    }
 
-      Err(anyhow!(
-        "Found {} problem{}",
-        errors,
-        if errors == 1 { "" } else { "s" }
-      ))
-    } else {
-      Ok(())
+    Ok(summary)
+  }
+
+  /// Prints the diagnostics up to the limits of repeats and
+  /// --max-diagnostics. Returns whether repeats were collapsed and the
+  /// diagnostics over --max-diagnostics, counted by code and file.
+  fn print_collapsed(
+    &self,
+    diagnostics: Vec<(ConfiguredDiagnostic, (String, String))>,
+  ) -> (bool, BTreeMap<(String, String), usize>) {
+    let mut collapsed_any = false;
+    let mut shown = 0;
+    let mut omitted = BTreeMap::new();
+    let diagnostics = diagnostics
+      .into_iter()
+      .map(|(diagnostic, key)| ((diagnostic, key.clone()), key))
+      .collect::<Vec<_>>();
+    let items = match self.format {
+      PublishDiagnosticsFormat::Pretty => collapse_repeats(diagnostics),
+      // all lines are printed for tools that parse them
+      PublishDiagnosticsFormat::Short => diagnostics
+        .into_iter()
+        .map(|(diagnostic, _)| CollapsedDiagnostic::Shown(diagnostic))
+        .collect(),
+    };
+    for item in items {
+      let is_over_max = self.max_diagnostics.is_some_and(|max| shown >= max);
+      match item {
+        CollapsedDiagnostic::Shown((_, (file, code))) if is_over_max => {
+          *omitted.entry((code, file)).or_default() += 1;
+        }
+        CollapsedDiagnostic::Shown((diagnostic, _)) => {
+          shown += 1;
+          self.print_diagnostic(&diagnostic);
+        }
+        CollapsedDiagnostic::Hidden { file, code, count } if is_over_max => {
+          *omitted.entry((code, file)).or_default() += count;
+        }
+        CollapsedDiagnostic::Hidden { file, code, count } => {
+          collapsed_any = true;
+          eprintln!("... and {} more [{}] in {}\n", count, code, file);
+        }
+      }
     }
+    (collapsed_any, omitted)
+  }
+
+  /// Prints the counts of the streamed diagnostics that weren't shown, like
+  /// `print_collapsed`.
+  fn finish_stream(
+    &self,
+    stream: &DiagnosticsStream,
+  ) -> (bool, BTreeMap<(String, String), usize>) {
+    let mut collapsed_any = false;
+    if matches!(self.format, PublishDiagnosticsFormat::Pretty) {
+      for ((file, code), count) in &stream.repeats {
+        if *count > MAX_REPEATS_PER_FILE {
+          collapsed_any = true;
+          eprintln!(
+            "... and {} more [{}] in {}\n",
+            count - MAX_REPEATS_PER_FILE,
+            code,
+            file
+          );
+        }
+      }
+    }
+    (collapsed_any, stream.omitted.clone())
   }
 
   pub fn push(&self, diagnostic: PublishDiagnostic) {
+    if let Some(stream) = &self.stream {
+      self.stream_diagnostic(&mut stream.lock().unwrap(), &diagnostic);
+    }
     self.diagnostics.lock().unwrap().push(diagnostic);
   }
+
+  /// Prints a pushed diagnostic, unless it's a duplicate, accepted by the
+  /// baseline or over the limits of repeats and --max-diagnostics.
+  fn stream_diagnostic(
+    &self,
+    stream: &mut DiagnosticsStream,
+    diagnostic: &PublishDiagnostic,
+  ) {
+    let Some(level) = self.level_of(diagnostic) else {
+      return;
+    };
+    let message = diagnostic.message().into_owned();
+    let (file, code, pos) = diagnostic.sorting_key();
+    if !stream
+      .seen
+      .insert((file.clone(), code.clone(), pos, message.clone()))
+    {
+      return;
+    }
+    if let Some(baseline) = &mut stream.baseline {
+      if baseline.accept(&file, &code, &message) {
+        return;
+      }
+    }
+    let repeats = stream
+      .repeats
+      .entry((file.clone(), code.clone()))
+      .or_default();
+    *repeats += 1;
+    if matches!(self.format, PublishDiagnosticsFormat::Pretty)
+      && *repeats > MAX_REPEATS_PER_FILE
+    {
+      return;
+    }
+    if self.max_diagnostics.is_some_and(|max| stream.shown >= max) {
+      *stream.omitted.entry((code, file)).or_default() += 1;
+      return;
+    }
+    stream.shown += 1;
+    self.print_diagnostic(&ConfiguredDiagnostic { diagnostic, level });
+  }
+
+  fn print_diagnostic(&self, diagnostic: &ConfiguredDiagnostic) {
+    match self.format {
+      PublishDiagnosticsFormat::Pretty => eprint!("{}", diagnostic.display()),
+      PublishDiagnosticsFormat::Short => {
+        eprintln!("{}", ReportDiagnostic::new(diagnostic).short_line())
+      }
+    }
+  }
 }
 
 pub enum PublishDiagnostic {
@@ -87,6 +536,10 @@ pub enum PublishDiagnostic {
   DuplicatePath {
     path: PathBuf,
   },
+  NormalizationDuplicatePath {
+    path: PathBuf,
+    other_path: PathBuf,
+  },
   UnsupportedFileType {
     specifier: Url,
     kind: String,
@@ -100,22 +553,172 @@ pub enum PublishDiagnostic {
   UnsupportedJsxTsx {
     specifier: Url,
   },
+  UnreachableFile {
+    specifier: Url,
+  },
+  /// A module of the package that is imported from the exports, like a
+  /// JSON module imported with `with { type: "json" }`, but isn't part of
+  /// the tarball.
+  ExcludedModule {
+    specifier: Url,
+  },
+  MissingJsDoc(DocDiagnostic),
+  ConflictingExports {
+    specifier: Url,
+    conflict: ExportsConflict,
+  },
+  /// A `jsr:` dependency without a version constraint, or a remote module
+  /// without an integrity entry in the lockfile.
+  UnpinnedDependency {
+    imported: Url,
+    text_info: SourceTextInfo,
+    referrer: deno_graph::Range,
+  },
+  /// A remote module with an integrity entry in the lockfile, which is
+  /// allowed with `"publish.allowPinnedRemoteImports"`.
+  PinnedRemoteImport {
+    imported: Url,
+    text_info: SourceTextInfo,
+    referrer: deno_graph::Range,
+  },
+  DenoOnlyApi {
+    specifier: Url,
+    text_info: SourceTextInfo,
+    range: SourceRange,
+    /// The used API, eg. `Deno.readTextFile`.
+    api: String,
+  },
 }
 
 impl PublishDiagnostic {
+  /// Whether the diagnostic is only reported when a severity is configured
+  /// for it in `"publish.rules"`.
+  fn is_opt_in(&self) -> bool {
+    matches!(
+      self,
+      PublishDiagnostic::UnreachableFile { .. }
+        | PublishDiagnostic::MissingJsDoc(_)
+    )
+  }
+
+  /// A specifier to replace the one the diagnostic is about with, if one
+  /// can be determined, with the byte range of the replaced specifier.
+  fn suggested_replacement(&self) -> Option<(std::ops::Range<usize>, String)> {
+    let suggested = match self {
+      PublishDiagnostic::InvalidExternalImport { imported, .. } => {
+        suggest_specifier(imported)?
+      }
+      PublishDiagnostic::SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          jsr_specifier: Some(jsr_specifier),
+          ..
+        }
+        | SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          jsr_specifier,
+          ..
+        },
+      ) => jsr_specifier.clone(),
+      _ => return None,
+    };
+    let (text_info, range) = self.range()?;
+    let mut range = range.as_byte_range(text_info.range().start);
+    let text = text_info.text_str().get(range.clone())?;
+    // the range includes the quotes
+    if text.starts_with(['"', '\'']) && text.ends_with(['"', '\'']) {
+      range.start += 1;
+      range.end -= 1;
+    }
+    Some((range, suggested))
+  }
+
+  /// The source and the range the diagnostic is about, if it's about a
+  /// position in a module.
+  pub fn range(&self) -> Option<(&SourceTextInfo, SourceRange)> {
+    let to_source_range =
+      |text_info: &SourceTextInfo, range: &deno_graph::Range| {
+        SourceRange::new(
+          text_info.line_start(range.start.line) + range.start.character,
+          text_info.line_start(range.end.line) + range.end.character,
+        )
+      };
+    match self {
+      PublishDiagnostic::FastCheck(diagnostic) => diagnostic
+        .range()
+        .map(|range| (&range.text_info, range.range)),
+      PublishDiagnostic::SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::UnanalyzableDynamicImport {
+          text_info,
+          range,
+          ..
+        }
+        | SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          text_info, range, ..
+        }
+        | SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          text_info,
+          range,
+          ..
+        }
+        | SpecifierUnfurlerDiagnostic::DevImport {
+          text_info, range, ..
+        },
+      )
+      | PublishDiagnostic::DenoOnlyApi {
+        text_info, range, ..
+      } => Some((text_info, *range)),
+      PublishDiagnostic::InvalidExternalImport {
+        text_info,
+        referrer,
+        ..
+      }
+      | PublishDiagnostic::UnpinnedDependency {
+        text_info,
+        referrer,
+        ..
+      }
+      | PublishDiagnostic::PinnedRemoteImport {
+        text_info,
+        referrer,
+        ..
+      } => Some((text_info, to_source_range(text_info, referrer))),
+      PublishDiagnostic::InvalidPath { .. }
+      | PublishDiagnostic::DuplicatePath { .. }
+      | PublishDiagnostic::NormalizationDuplicatePath { .. }
+      | PublishDiagnostic::UnsupportedFileType { .. }
+      | PublishDiagnostic::UnsupportedJsxTsx { .. }
+      | PublishDiagnostic::UnreachableFile { .. }
+      | PublishDiagnostic::ExcludedModule { .. }
+      | PublishDiagnostic::MissingJsDoc(_)
+      | PublishDiagnostic::ConflictingExports { .. } => None,
+    }
+  }
+
+  /// The file, code and position of the diagnostic.
   fn sorting_key(&self) -> (String, String, Option<SourcePos>) {
     let loc = self.location();
+    let (specifier, source_pos) = file_and_source_pos(&loc);
+    let source_pos = source_pos.map(|(pos, _)| pos);
 
-    let (specifier, source_pos) = match loc {
-      DiagnosticLocation::Module { specifier } => (specifier.to_string(), None),
-      DiagnosticLocation::Path { path } => (path.display().to_string(), None),
-      DiagnosticLocation::ModulePosition {
-        specifier,
-        source_pos,
-        text_info,
-      } => (
-        specifier.to_string(),
-        Some(match source_pos {
+    (specifier, self.code().to_string(), source_pos)
+  }
+}
+
+/// The file of a diagnostic location and, when it has one, the position in
+/// the text of the module.
+pub fn file_and_source_pos<'a>(
+  location: &'a DiagnosticLocation,
+) -> (String, Option<(SourcePos, &'a SourceTextInfo)>) {
+  match location {
+    DiagnosticLocation::Module { specifier } => (specifier.to_string(), None),
+    DiagnosticLocation::Path { path } => (path.display().to_string(), None),
+    DiagnosticLocation::ModulePosition {
+      specifier,
+      source_pos,
+      text_info,
+    } => (
+      specifier.to_string(),
+      Some((
+        match *source_pos {
           DiagnosticSourcePos::SourcePos(s) => s,
           DiagnosticSourcePos::ByteIndex(index) => {
             text_info.range().start() + index
@@ -123,11 +726,10 @@ impl PublishDiagnostic {
           DiagnosticSourcePos::LineAndCol { line, column } => {
             text_info.line_start(line) + column
           }
-        }),
-      ),
-    };
-
-    (self.code().to_string(), specifier, source_pos)
+        },
+        text_info.as_ref(),
+      )),
+    ),
   }
 }
 
@@ -139,12 +741,29 @@ impl Diagnostic for PublishDiagnostic {
         ..
       }) => DiagnosticLevel::Warning,
       FastCheck(_) => DiagnosticLevel::Error,
+      // the import can only be rewritten when the module is exported
+      SpecifierUnfurl(SpecifierUnfurlerDiagnostic::CrossMemberImport {
+        jsr_specifier: None,
+        ..
+      }) => DiagnosticLevel::Error,
+      SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion { .. }
+        | SpecifierUnfurlerDiagnostic::DevImport { .. },
+      ) => DiagnosticLevel::Error,
       SpecifierUnfurl(_) => DiagnosticLevel::Warning,
       InvalidPath { .. } => DiagnosticLevel::Error,
       DuplicatePath { .. } => DiagnosticLevel::Error,
+      NormalizationDuplicatePath { .. } => DiagnosticLevel::Error,
       UnsupportedFileType { .. } => DiagnosticLevel::Warning,
       InvalidExternalImport { .. } => DiagnosticLevel::Error,
       UnsupportedJsxTsx { .. } => DiagnosticLevel::Warning,
+      UnreachableFile { .. } => DiagnosticLevel::Warning,
+      ExcludedModule { .. } => DiagnosticLevel::Error,
+      MissingJsDoc(_) => DiagnosticLevel::Warning,
+      ConflictingExports { .. } => DiagnosticLevel::Warning,
+      UnpinnedDependency { .. } => DiagnosticLevel::Warning,
+      PinnedRemoteImport { .. } => DiagnosticLevel::Warning,
+      DenoOnlyApi { .. } => DiagnosticLevel::Warning,
     }
   }
 
@@ -155,9 +774,19 @@ impl Diagnostic for PublishDiagnostic {
       SpecifierUnfurl(diagnostic) => Cow::Borrowed(diagnostic.code()),
       InvalidPath { .. } => Cow::Borrowed("invalid-path"),
       DuplicatePath { .. } => Cow::Borrowed("case-insensitive-duplicate-path"),
+      NormalizationDuplicatePath { .. } => {
+        Cow::Borrowed("unicode-normalization-duplicate-path")
+      }
       UnsupportedFileType { .. } => Cow::Borrowed("unsupported-file-type"),
       InvalidExternalImport { .. } => Cow::Borrowed("invalid-external-import"),
       UnsupportedJsxTsx { .. } => Cow::Borrowed("unsupported-jsx-tsx"),
+      UnreachableFile { .. } => Cow::Borrowed("unreachable-file"),
+      ExcludedModule { .. } => Cow::Borrowed("excluded-module"),
+      MissingJsDoc(_) => Cow::Borrowed("missing-jsdoc"),
+      ConflictingExports { .. } => Cow::Borrowed("conflicting-exports"),
+      UnpinnedDependency { .. } => Cow::Borrowed("unpinned-dependency"),
+      PinnedRemoteImport { .. } => Cow::Borrowed("pinned-remote-import"),
+      DenoOnlyApi { .. } => Cow::Borrowed("deno-only-api"),
     }
   }
 
@@ -170,11 +799,25 @@ impl Diagnostic for PublishDiagnostic {
       DuplicatePath { .. } => {
         Cow::Borrowed("package path is a case insensitive duplicate of another path in the package")
       }
+      NormalizationDuplicatePath { other_path, .. } => Cow::Owned(format!(
+        "package path only differs from '{}' by Unicode normalization",
+        other_path.display()
+      )),
       UnsupportedFileType { kind, .. } => {
         Cow::Owned(format!("unsupported file type '{kind}'"))
       }
       InvalidExternalImport { kind, .. } => Cow::Owned(format!("invalid import to a {kind} specifier")),
       UnsupportedJsxTsx { .. } => Cow::Borrowed("JSX and TSX files are currently not supported"),
+      UnreachableFile { .. } => Cow::Borrowed("module is published but not reachable from the exports"),
+      ExcludedModule { .. } => Cow::Borrowed("module is imported by the package but excluded from publishing"),
+      MissingJsDoc(diagnostic) => diagnostic.message(),
+      ConflictingExports { conflict, .. } => Cow::Owned(conflict.message()),
+      UnpinnedDependency { imported, .. } => match imported.scheme() {
+        "jsr" => Cow::Borrowed("jsr dependency without a version constraint"),
+        _ => Cow::Borrowed("remote dependency without an integrity entry in the lockfile"),
+      },
+      PinnedRemoteImport { imported, .. } => Cow::Owned(format!("remote '{}' import is pinned by the lockfile", imported.scheme())),
+      DenoOnlyApi { api, .. } => Cow::Owned(format!("'{api}' is not available in Node.js")),
     }
   }
 
@@ -187,6 +830,24 @@ impl Diagnostic for PublishDiagnostic {
           specifier,
           text_info,
           range,
+        }
+        | SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          specifier,
+          text_info,
+          range,
+          ..
+        }
+        | SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          specifier,
+          text_info,
+          range,
+          ..
+        }
+        | SpecifierUnfurlerDiagnostic::DevImport {
+          specifier,
+          text_info,
+          range,
+          ..
         } => DiagnosticLocation::ModulePosition {
           specifier: Cow::Borrowed(specifier),
           text_info: Cow::Borrowed(text_info),
@@ -199,6 +860,9 @@ impl Diagnostic for PublishDiagnostic {
       DuplicatePath { path, .. } => {
         DiagnosticLocation::Path { path: path.clone() }
       }
+      NormalizationDuplicatePath { path, .. } => {
+        DiagnosticLocation::Path { path: path.clone() }
+      }
       UnsupportedFileType { specifier, .. } => DiagnosticLocation::Module {
         specifier: Cow::Borrowed(specifier),
       },
@@ -217,6 +881,42 @@ impl Diagnostic for PublishDiagnostic {
       UnsupportedJsxTsx { specifier } => DiagnosticLocation::Module {
         specifier: Cow::Borrowed(specifier),
       },
+      UnreachableFile { specifier } | ExcludedModule { specifier } => {
+        DiagnosticLocation::Module {
+          specifier: Cow::Borrowed(specifier),
+        }
+      }
+      MissingJsDoc(diagnostic) => diagnostic.location(),
+      ConflictingExports { specifier, .. } => DiagnosticLocation::Module {
+        specifier: Cow::Borrowed(specifier),
+      },
+      UnpinnedDependency {
+        referrer,
+        text_info,
+        ..
+      }
+      | PinnedRemoteImport {
+        referrer,
+        text_info,
+        ..
+      } => DiagnosticLocation::ModulePosition {
+        specifier: Cow::Borrowed(&referrer.specifier),
+        text_info: Cow::Borrowed(text_info),
+        source_pos: DiagnosticSourcePos::LineAndCol {
+          line: referrer.start.line,
+          column: referrer.start.character,
+        },
+      },
+      DenoOnlyApi {
+        specifier,
+        text_info,
+        range,
+        ..
+      } => DiagnosticLocation::ModulePosition {
+        specifier: Cow::Borrowed(specifier),
+        text_info: Cow::Borrowed(text_info),
+        source_pos: DiagnosticSourcePos::SourcePos(range.start),
+      },
     }
   }
 
@@ -239,9 +939,57 @@ impl Diagnostic for PublishDiagnostic {
             description: Some("the unanalyzable dynamic import".into()),
           },
         }),
+        SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          text_info,
+          range,
+          jsr_specifier,
+          ..
+        } => Some(DiagnosticSnippet {
+          source: Cow::Borrowed(text_info),
+          highlight: DiagnosticSnippetHighlight {
+            style: match jsr_specifier {
+              Some(_) => DiagnosticSnippetHighlightStyle::Warning,
+              None => DiagnosticSnippetHighlightStyle::Error,
+            },
+            range: DiagnosticSourceRange {
+              start: DiagnosticSourcePos::SourcePos(range.start),
+              end: DiagnosticSourcePos::SourcePos(range.end),
+            },
+            description: Some("the path of another workspace member".into()),
+          },
+        }),
+        SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          text_info,
+          range,
+          ..
+        } => Some(DiagnosticSnippet {
+          source: Cow::Borrowed(text_info),
+          highlight: DiagnosticSnippetHighlight {
+            style: DiagnosticSnippetHighlightStyle::Error,
+            range: DiagnosticSourceRange {
+              start: DiagnosticSourcePos::SourcePos(range.start),
+              end: DiagnosticSourcePos::SourcePos(range.end),
+            },
+            description: Some("the version constraint".into()),
+          },
+        }),
+        SpecifierUnfurlerDiagnostic::DevImport {
+          text_info, range, ..
+        } => Some(DiagnosticSnippet {
+          source: Cow::Borrowed(text_info),
+          highlight: DiagnosticSnippetHighlight {
+            style: DiagnosticSnippetHighlightStyle::Error,
+            range: DiagnosticSourceRange {
+              start: DiagnosticSourcePos::SourcePos(range.start),
+              end: DiagnosticSourcePos::SourcePos(range.end),
+            },
+            description: Some("the dev-only dependency".into()),
+          },
+        }),
       },
       PublishDiagnostic::InvalidPath { .. } => None,
       PublishDiagnostic::DuplicatePath { .. } => None,
+      PublishDiagnostic::NormalizationDuplicatePath { .. } => None,
       PublishDiagnostic::UnsupportedFileType { .. } => None,
       PublishDiagnostic::InvalidExternalImport {
         referrer,
@@ -265,12 +1013,76 @@ impl Diagnostic for PublishDiagnostic {
         },
       }),
       PublishDiagnostic::UnsupportedJsxTsx { .. } => None,
+      PublishDiagnostic::UnreachableFile { .. } => None,
+      PublishDiagnostic::ExcludedModule { .. } => None,
+      PublishDiagnostic::MissingJsDoc(diagnostic) => diagnostic.snippet(),
+      PublishDiagnostic::ConflictingExports { .. } => None,
+      PublishDiagnostic::UnpinnedDependency {
+        referrer,
+        text_info,
+        ..
+      }
+      | PublishDiagnostic::PinnedRemoteImport {
+        referrer,
+        text_info,
+        ..
+      } => Some(DiagnosticSnippet {
+        source: Cow::Borrowed(text_info),
+        highlight: DiagnosticSnippetHighlight {
+          style: DiagnosticSnippetHighlightStyle::Warning,
+          range: DiagnosticSourceRange {
+            start: DiagnosticSourcePos::LineAndCol {
+              line: referrer.start.line,
+              column: referrer.start.character,
+            },
+            end: DiagnosticSourcePos::LineAndCol {
+              line: referrer.end.line,
+              column: referrer.end.character,
+            },
+          },
+          description: Some("the specifier".into()),
+        },
+      }),
+      PublishDiagnostic::DenoOnlyApi {
+        text_info, range, ..
+      } => Some(DiagnosticSnippet {
+        source: Cow::Borrowed(text_info),
+        highlight: DiagnosticSnippetHighlight {
+          style: DiagnosticSnippetHighlightStyle::Warning,
+          range: DiagnosticSourceRange {
+            start: DiagnosticSourcePos::SourcePos(range.start),
+            end: DiagnosticSourcePos::SourcePos(range.end),
+          },
+          description: Some("the Deno API".into()),
+        },
+      }),
     }
   }
 
   fn hint(&self) -> Option<Cow<'_, str>> {
     match &self {
       PublishDiagnostic::FastCheck(diagnostic) => diagnostic.hint(),
+      PublishDiagnostic::SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          imported,
+          member,
+          jsr_specifier,
+          ..
+        },
+      ) => match jsr_specifier {
+        Some(jsr_specifier) => Some(Cow::Owned(format!("replace '{}' with '{}', which `deno publish --fix` does", imported, jsr_specifier))),
+        None => Some(Cow::Owned(format!("export the module from '{}' and import it with a `jsr:` specifier", member))),
+      },
+      PublishDiagnostic::SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          imported,
+          jsr_specifier,
+          ..
+        },
+      ) => Some(Cow::Owned(format!("replace '{}' with '{}', which `deno publish --fix` does", imported, jsr_specifier))),
+      PublishDiagnostic::SpecifierUnfurl(
+        SpecifierUnfurlerDiagnostic::DevImport { imported, .. },
+      ) => Some(Cow::Owned(format!("remove '{}' from 'publish.devImports' in the config file, or only import it from tests", imported))),
       PublishDiagnostic::SpecifierUnfurl(_) => None,
       PublishDiagnostic::InvalidPath { .. } => Some(
         Cow::Borrowed("rename or remove the file, or add it to 'publish.exclude' in the config file"),
@@ -278,16 +1090,64 @@ impl Diagnostic for PublishDiagnostic {
       PublishDiagnostic::DuplicatePath { .. } => Some(
         Cow::Borrowed("rename or remove the file"),
       ),
+      PublishDiagnostic::NormalizationDuplicatePath { .. } => Some(
+        Cow::Borrowed("rename or remove the file, preferably using only ASCII characters"),
+      ),
       PublishDiagnostic::UnsupportedFileType { .. } => Some(
         Cow::Borrowed("remove the file, or add it to 'publish.exclude' in the config file"),
       ),
-      PublishDiagnostic::InvalidExternalImport { .. } => Some(Cow::Borrowed("replace this import with one from jsr or npm, or vendor the dependency into your package")),
+      PublishDiagnostic::InvalidExternalImport { text_info, .. } => match self.suggested_replacement() {
+        Some((range, suggested)) => Some(Cow::Owned(format!("replace '{}' with '{}'", &text_info.text_str()[range], suggested))),
+        None => Some(Cow::Borrowed("replace this import with one from jsr or npm, or vendor the dependency into your package")),
+      },
       PublishDiagnostic::UnsupportedJsxTsx { .. } => None,
+      PublishDiagnostic::UnreachableFile { .. } => Some(Cow::Borrowed(
+        "add an export for the module, or add it to 'publish.exclude' in the config file",
+      )),
+      PublishDiagnostic::ExcludedModule { .. } => Some(Cow::Borrowed(
+        "remove the file from 'publish.exclude' in the config file or from the .gitignore file",
+      )),
+      PublishDiagnostic::MissingJsDoc(diagnostic) => diagnostic.hint(),
+      PublishDiagnostic::ConflictingExports { conflict, .. } => {
+        Some(Cow::Owned(conflict.hint()))
+      }
+      PublishDiagnostic::UnpinnedDependency { imported, .. } => match JsrPackageReqReference::from_specifier(imported) {
+        Ok(req_ref) => Some(Cow::Owned(format!("add a version constraint to the specifier, eg. 'jsr:{}@^1.0.0'", req_ref.req().name))),
+        Err(_) => Some(Cow::Borrowed("run 'deno cache' with a lockfile to record the integrity of the module")),
+      },
+      PublishDiagnostic::PinnedRemoteImport { imported, .. } => match suggest_specifier(imported) {
+        Some(suggested) => Some(Cow::Owned(format!("replace the import with '{}' when migrating the package to jsr and npm dependencies", suggested))),
+        None => Some(Cow::Borrowed("import the dependency from jsr or npm when it is available there")),
+      },
+      PublishDiagnostic::DenoOnlyApi { .. } => Some(Cow::Borrowed(
+        "use a web standard or `node:` API instead, or set 'publish.runtimeCompat' in the config file to declare the runtimes the package supports",
+      )),
     }
   }
 
   fn snippet_fixed(&self) -> Option<DiagnosticSnippet<'_>> {
-    None
+    let (range, suggested) = self.suggested_replacement()?;
+    let (text_info, _) = self.range()?;
+    let start = range.start;
+    let end = start + suggested.len();
+    let fixed_text = deno_ast::apply_text_changes(
+      text_info.text_str(),
+      vec![deno_ast::TextChange {
+        range,
+        new_text: suggested,
+      }],
+    );
+    Some(DiagnosticSnippet {
+      source: Cow::Owned(SourceTextInfo::from_string(fixed_text)),
+      highlight: DiagnosticSnippetHighlight {
+        style: DiagnosticSnippetHighlightStyle::Hint,
+        range: DiagnosticSourceRange {
+          start: DiagnosticSourcePos::ByteIndex(start),
+          end: DiagnosticSourcePos::ByteIndex(end),
+        },
+        description: Some("the suggested specifier".into()),
+      },
+    })
   }
 
   fn info(&self) -> Cow<'_, [Cow<'_, str>]> {
@@ -301,6 +1161,24 @@ impl Diagnostic for PublishDiagnostic {
           Cow::Borrowed("dynamic imports that can not be analyzed at publish time will not be rewritten automatically"),
           Cow::Borrowed("make sure the dynamic import is resolvable at runtime without an import map / package.json")
         ]),
+        SpecifierUnfurlerDiagnostic::CrossMemberImport { jsr_specifier, .. } => match jsr_specifier {
+          Some(_) => Cow::Borrowed(&[
+            Cow::Borrowed("only the files of the package are published, so the paths of other workspace members don't resolve after publishing"),
+            Cow::Borrowed("the import was rewritten to a `jsr:` specifier of the member in the published package"),
+          ]),
+          None => Cow::Borrowed(&[
+            Cow::Borrowed("only the files of the package are published, so the paths of other workspace members don't resolve after publishing"),
+            Cow::Borrowed("the import can't be rewritten to a `jsr:` specifier, because the member doesn't export the module"),
+          ]),
+        },
+        SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion { member, version, .. } => Cow::Owned(vec![
+          Cow::Owned(format!("'{}' is published at version {}, which the version constraint doesn't match", member, version)),
+          Cow::Borrowed("the published package would resolve a different version of the member, or none at all"),
+        ]),
+        SpecifierUnfurlerDiagnostic::DevImport { .. } => Cow::Borrowed(&[
+          Cow::Borrowed("the dependencies in 'publish.devImports' are only for the modules that can't be reached from the exports of the package, like tests"),
+          Cow::Borrowed("this module is reachable from the exports, so users of the package would need the dependency"),
+        ]),
       },
       PublishDiagnostic::InvalidPath { .. } => Cow::Borrowed(&[
         Cow::Borrowed("to portably support all platforms, including windows, the allowed characters in package paths are limited"),
@@ -308,6 +1186,9 @@ impl Diagnostic for PublishDiagnostic {
       PublishDiagnostic::DuplicatePath { .. } => Cow::Borrowed(&[
         Cow::Borrowed("to support case insensitive file systems, no two package paths may differ only by case"),
       ]),
+      PublishDiagnostic::NormalizationDuplicatePath { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("macOS file systems normalize file names, so the files would overwrite each other when the package is extracted there"),
+      ]),
       PublishDiagnostic::UnsupportedFileType { .. } => Cow::Borrowed(&[
         Cow::Borrowed("only files and directories are supported"),
         Cow::Borrowed("the file was ignored and will not be published")
@@ -319,29 +1200,357 @@ impl Diagnostic for PublishDiagnostic {
       ]),
       PublishDiagnostic::UnsupportedJsxTsx { .. } => Cow::Owned(vec![
         Cow::Borrowed("follow https://github.com/jsr-io/jsr/issues/24 for updates"),
-      ])
+        Cow::Borrowed("set `\"publish\": { \"transpileJsx\": true }` to publish them as JavaScript with type declarations instead"),
+      ]),
+      PublishDiagnostic::UnreachableFile { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("no export of the package imports this module, so users of the package can't import it"),
+      ]),
+      PublishDiagnostic::ExcludedModule { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("the exports of the package import this module, so they fail to load when it isn't published"),
+      ]),
+      PublishDiagnostic::MissingJsDoc(diagnostic) => diagnostic.info(),
+      PublishDiagnostic::ConflictingExports { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("entries that overlap make it unclear which module an import of the package resolves to, and show up more than once in the documentation"),
+      ]),
+      PublishDiagnostic::UnpinnedDependency { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("the dependency can resolve to different code for each user of the package, so the package isn't reproducible"),
+      ]),
+      PublishDiagnostic::PinnedRemoteImport { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("remote imports are allowed because of 'publish.allowPinnedRemoteImports' in the config file"),
+        Cow::Borrowed("the users of the package download the module from the server, without the integrity check of the lockfile of the package"),
+      ]),
+      PublishDiagnostic::DenoOnlyApi { .. } => Cow::Borrowed(&[
+        Cow::Borrowed("the module is reachable from the exports of the package, so it breaks when the package is used in Node.js through the npm compatibility layer of jsr"),
+      ]),
     }
   }
 
   fn docs_url(&self) -> Option<Cow<'_, str>> {
     match &self {
       PublishDiagnostic::FastCheck(diagnostic) => diagnostic.docs_url(),
-      PublishDiagnostic::SpecifierUnfurl(diagnostic) => match diagnostic {
-        SpecifierUnfurlerDiagnostic::UnanalyzableDynamicImport { .. } => None,
-      },
-      PublishDiagnostic::InvalidPath { .. } => {
-        Some(Cow::Borrowed("https://jsr.io/go/invalid-path"))
+      PublishDiagnostic::MissingJsDoc(diagnostic) => Some(
+        diagnostic
+          .docs_url()
+          .unwrap_or(Cow::Borrowed("https://jsr.io/docs/writing-docs")),
+      ),
+      PublishDiagnostic::SpecifierUnfurl(_)
+      | PublishDiagnostic::InvalidPath { .. }
+      | PublishDiagnostic::DuplicatePath { .. }
+      | PublishDiagnostic::NormalizationDuplicatePath { .. }
+      | PublishDiagnostic::UnsupportedFileType { .. }
+      | PublishDiagnostic::InvalidExternalImport { .. }
+      | PublishDiagnostic::UnsupportedJsxTsx { .. }
+      | PublishDiagnostic::UnreachableFile { .. }
+      | PublishDiagnostic::ExcludedModule { .. }
+      | PublishDiagnostic::ConflictingExports { .. }
+      | PublishDiagnostic::UnpinnedDependency { .. }
+      | PublishDiagnostic::PinnedRemoteImport { .. }
+      | PublishDiagnostic::DenoOnlyApi { .. } => {
+        Some(Cow::Owned(format!("https://jsr.io/go/{}", self.code())))
       }
-      PublishDiagnostic::DuplicatePath { .. } => Some(Cow::Borrowed(
-        "https://jsr.io/go/case-insensitive-duplicate-path",
-      )),
-      PublishDiagnostic::UnsupportedFileType { .. } => {
-        Some(Cow::Borrowed("https://jsr.io/go/unsupported-file-type"))
+    }
+  }
+}
+
+/// Suggests a `jsr:` or `npm:` specifier for a remote module that is
+/// served by jsr or an npm CDN, eg. `npm:chalk@5` for
+/// `https://esm.sh/chalk@5`.
+fn suggest_specifier(imported: &Url) -> Option<String> {
+  let path = imported.path().trim_start_matches('/');
+  match imported.host_str()? {
+    "jsr.io" => {
+      // https://jsr.io/@<scope>/<package>/<version>/<path>
+      let mut segments = path.splitn(4, '/');
+      let scope = segments.next().filter(|s| s.starts_with('@'))?;
+      let package = segments.next()?;
+      let version = segments.next().filter(|v| !v.ends_with(".json"))?;
+      match segments.next() {
+        Some(sub_path) => Some(format!(
+          "jsr:{}/{}@{}/{}",
+          scope, package, version, sub_path
+        )),
+        None => Some(format!("jsr:{}/{}@{}", scope, package, version)),
       }
-      PublishDiagnostic::InvalidExternalImport { .. } => {
-        Some(Cow::Borrowed("https://jsr.io/go/invalid-external-import"))
+    }
+    "esm.sh" | "unpkg.com" | "cdn.skypack.dev" => {
+      // skip the build version of esm.sh, eg. `/v135/`
+      let path = match path.split_once('/') {
+        Some((first, rest))
+          if first == "stable"
+            || (first.starts_with('v')
+              && first[1..].chars().all(|c| c.is_ascii_digit())) =>
+        {
+          rest
+        }
+        _ => path,
+      };
+      npm_specifier(path)
+    }
+    "cdn.jsdelivr.net" => npm_specifier(path.strip_prefix("npm/")?),
+    _ => None,
+  }
+}
+
+fn npm_specifier(path: &str) -> Option<String> {
+  if path.is_empty() || path.starts_with('/') {
+    return None;
+  }
+  Some(format!("npm:{}", path.trim_end_matches('/')))
+}
+
+enum CollapsedDiagnostic<T> {
+  Shown(T),
+  /// The number of further diagnostics with a code in a file.
+  Hidden {
+    file: String,
+    code: String,
+    count: usize,
+  },
+}
+
+/// Only keeps the first diagnostics with the same code in a file, and
+/// counts the rest. The diagnostics are keyed by file and code, and must be
+/// sorted by them.
+fn collapse_repeats<T>(
+  diagnostics: Vec<(T, (String, String))>,
+) -> Vec<CollapsedDiagnostic<T>> {
+  let mut collapsed = Vec::with_capacity(diagnostics.len());
+  let mut current: Option<((String, String), usize)> = None;
+  let flush = |collapsed: &mut Vec<_>,
+               current: Option<((String, String), usize)>| {
+    if let Some(((file, code), count)) = current {
+      if count > MAX_REPEATS_PER_FILE {
+        collapsed.push(CollapsedDiagnostic::Hidden {
+          file,
+          code,
+          count: count - MAX_REPEATS_PER_FILE,
+        });
       }
-      PublishDiagnostic::UnsupportedJsxTsx { .. } => None,
     }
+  };
+  for (diagnostic, key) in diagnostics {
+    let count = match &mut current {
+      Some((current_key, count)) if *current_key == key => {
+        *count += 1;
+        *count
+      }
+      _ => {
+        flush(&mut collapsed, current.replace((key, 1)));
+        1
+      }
+    };
+    if count <= MAX_REPEATS_PER_FILE {
+      collapsed.push(CollapsedDiagnostic::Shown(diagnostic));
+    }
+  }
+  flush(&mut collapsed, current);
+  collapsed
+}
+
+/// A diagnostic with the level from the severity configured for it.
+struct ConfiguredDiagnostic<'a> {
+  diagnostic: &'a PublishDiagnostic,
+  level: DiagnosticLevel,
+}
+
+impl Diagnostic for ConfiguredDiagnostic<'_> {
+  fn level(&self) -> DiagnosticLevel {
+    self.level
+  }
+
+  fn code(&self) -> Cow<'_, str> {
+    self.diagnostic.code()
+  }
+
+  fn message(&self) -> Cow<'_, str> {
+    self.diagnostic.message()
+  }
+
+  fn location(&self) -> DiagnosticLocation {
+    self.diagnostic.location()
+  }
+
+  fn snippet(&self) -> Option<DiagnosticSnippet<'_>> {
+    self.diagnostic.snippet()
+  }
+
+  fn hint(&self) -> Option<Cow<'_, str>> {
+    self.diagnostic.hint()
+  }
+
+  fn snippet_fixed(&self) -> Option<DiagnosticSnippet<'_>> {
+    self.diagnostic.snippet_fixed()
+  }
+
+  fn info(&self) -> Cow<'_, [Cow<'_, str>]> {
+    self.diagnostic.info()
+  }
+
+  fn docs_url(&self) -> Option<Cow<'_, str>> {
+    self.diagnostic.docs_url()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn suggest_jsr_and_npm_specifiers() {
+    let suggest = |url: &str| suggest_specifier(&Url::parse(url).unwrap());
+    assert_eq!(
+      suggest("https://jsr.io/@std/path/0.220.0/mod.ts").as_deref(),
+      Some("jsr:@std/path@0.220.0/mod.ts")
+    );
+    assert_eq!(suggest("https://jsr.io/@std/path/meta.json"), None);
+    assert_eq!(
+      suggest("https://esm.sh/v135/chalk@5.3.0").as_deref(),
+      Some("npm:chalk@5.3.0")
+    );
+    assert_eq!(
+      suggest("https://esm.sh/@preact/signals@1/dist/index.js").as_deref(),
+      Some("npm:@preact/signals@1/dist/index.js")
+    );
+    assert_eq!(
+      suggest("https://cdn.jsdelivr.net/npm/lodash@4/").as_deref(),
+      Some("npm:lodash@4")
+    );
+    assert_eq!(suggest("https://deno.land/std/path/mod.ts"), None);
+    assert_eq!(suggest("http://localhost:4545/welcome.ts"), None);
+  }
+
+  #[test]
+  fn collapse_repeated_diagnostics() {
+    let key = |file: &str, code: &str| (file.to_string(), code.to_string());
+    let diagnostics = vec![
+      (1, key("a.ts", "x")),
+      (2, key("a.ts", "x")),
+      (3, key("a.ts", "x")),
+      (4, key("a.ts", "x")),
+      (5, key("a.ts", "x")),
+      (6, key("a.ts", "y")),
+      (7, key("b.ts", "x")),
+    ];
+    let collapsed = collapse_repeats(diagnostics)
+      .into_iter()
+      .map(|item| match item {
+        CollapsedDiagnostic::Shown(n) => n.to_string(),
+        CollapsedDiagnostic::Hidden { file, code, count } => {
+          format!("{} more {} in {}", count, code, file)
+        }
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(collapsed, vec!["1", "2", "3", "2 more x in a.ts", "6", "7"]);
+  }
+
+  #[test]
+  fn configured_rule_levels() {
+    let collector = PublishDiagnosticsCollector::new(HashMap::from([
+      ("unsupported-file-type".to_string(), RuleSeverity::Error),
+      ("invalid-path".to_string(), RuleSeverity::Off),
+    ]));
+    assert!(collector.is_rule_off("invalid-path"));
+    let unsupported_file_type = PublishDiagnostic::UnsupportedFileType {
+      specifier: Url::parse("file:///mod.ts").unwrap(),
+      kind: "symlink".to_string(),
+    };
+    assert!(matches!(
+      collector.level_of(&unsupported_file_type),
+      Some(DiagnosticLevel::Error)
+    ));
+    let invalid_path = PublishDiagnostic::InvalidPath {
+      path: PathBuf::from("/a:b.ts"),
+      message: "invalid character".to_string(),
+    };
+    assert!(collector.level_of(&invalid_path).is_none());
+    // opt-in diagnostics are off unless configured
+    let unreachable_file = PublishDiagnostic::UnreachableFile {
+      specifier: Url::parse("file:///unused.ts").unwrap(),
+    };
+    assert!(collector.level_of(&unreachable_file).is_none());
+    let collector = PublishDiagnosticsCollector::new(HashMap::from([(
+      "unreachable-file".to_string(),
+      RuleSeverity::Warn,
+    )]));
+    assert!(collector.is_rule_enabled("unreachable-file"));
+    assert!(!collector.is_rule_off("unreachable-file"));
+    assert!(!collector.is_rule_enabled("missing-jsdoc"));
+    assert!(matches!(
+      collector.level_of(&unreachable_file),
+      Some(DiagnosticLevel::Warning)
+    ));
+    let duplicate_path = PublishDiagnostic::DuplicatePath {
+      path: PathBuf::from("/Mod.ts"),
+    };
+    assert!(matches!(
+      collector.level_of(&duplicate_path),
+      Some(DiagnosticLevel::Error)
+    ));
+  }
+
+  #[test]
+  fn deny_warnings_fails_warning_only_runs() {
+    let unsupported_file_type = || PublishDiagnostic::UnsupportedFileType {
+      specifier: Url::parse("file:///mod.ts").unwrap(),
+      kind: "symlink".to_string(),
+    };
+    let collector = PublishDiagnosticsCollector::new(HashMap::new());
+    collector.push(unsupported_file_type());
+    let summary = collector.print().unwrap();
+    assert_eq!(
+      summary,
+      DiagnosticsSummary {
+        errors: 0,
+        warnings: 1,
+        failed: false,
+        codes: BTreeMap::from([(
+          "unsupported-file-type".to_string(),
+          CodeCount {
+            count: 1,
+            level: "warning",
+          }
+        )]),
+      }
+    );
+    summary.to_result().unwrap();
+
+    let collector =
+      PublishDiagnosticsCollector::new(HashMap::new()).with_deny_warnings();
+    collector.push(unsupported_file_type());
+    let summary = collector.print().unwrap();
+    assert!(summary.failed);
+    assert_eq!(
+      summary.to_result().unwrap_err().to_string(),
+      "Found 1 warning, which fails publishing because of --deny-warnings"
+    );
+  }
+
+  #[test]
+  fn streamed_diagnostics_skip_duplicates() {
+    let unsupported_file_type = || PublishDiagnostic::UnsupportedFileType {
+      specifier: Url::parse("file:///mod.ts").unwrap(),
+      kind: "symlink".to_string(),
+    };
+    let collector = PublishDiagnosticsCollector::new(HashMap::new())
+      .with_max_diagnostics(0)
+      .with_streaming();
+    collector.push(unsupported_file_type());
+    collector.clone().push(unsupported_file_type());
+    {
+      let stream = collector.stream.as_ref().unwrap().lock().unwrap();
+      assert_eq!(stream.shown, 0);
+      assert_eq!(
+        stream.omitted,
+        BTreeMap::from([(
+          (
+            "unsupported-file-type".to_string(),
+            "file:///mod.ts".to_string()
+          ),
+          1
+        )])
+      );
+    }
+    let summary = collector.print().unwrap();
+    assert_eq!(summary.warnings, 1);
   }
 }
@@ -0,0 +1,158 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_runtime::deno_fetch::reqwest;
+use serde::Deserialize;
+
+use super::trace;
+
+/// The path of the document that a registry describes its endpoints in,
+/// relative to the registry URL.
+const DISCOVERY_PATH: &str = ".well-known/deno-registry.json";
+
+/// The endpoints of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEndpoints {
+  /// The URL of the registry API, eg. `https://api.jsr.io/`.
+  pub api_url: Url,
+  /// The URL of the website that scopes, packages and tokens are managed
+  /// on, eg. `https://jsr.io/`.
+  pub manage_url: Url,
+  /// The optional features the registry advertises, eg. `chunkedUploads`.
+  pub capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscoveryDocument {
+  api_url: Option<String>,
+  manage_url: Option<String>,
+  #[serde(default)]
+  capabilities: Vec<String>,
+}
+
+impl RegistryEndpoints {
+  /// The endpoints of a registry that is laid out like jsr.io.
+  pub fn default_for(registry_url: &Url) -> Self {
+    let mut api_url = registry_url.clone();
+    api_url.set_path("api/");
+    Self {
+      api_url,
+      manage_url: registry_url.clone(),
+      capabilities: Vec::new(),
+    }
+  }
+
+  fn from_document(
+    registry_url: &Url,
+    document: DiscoveryDocument,
+  ) -> Result<Self, AnyError> {
+    let defaults = Self::default_for(registry_url);
+    // the URLs may be relative to the registry URL
+    let resolve = |url: Option<String>, default: Url| match url {
+      Some(url) => registry_url
+        .join(&format!("{}/", url.trim_end_matches('/')))
+        .with_context(|| format!("Invalid URL '{}'", url)),
+      None => Ok(default),
+    };
+    Ok(Self {
+      api_url: resolve(document.api_url, defaults.api_url)?,
+      manage_url: resolve(document.manage_url, defaults.manage_url)?,
+      capabilities: document.capabilities,
+    })
+  }
+
+  /// Whether the registry advertises support for an optional feature.
+  /// jsr.io and registries without a discovery document support none.
+  pub fn supports(&self, capability: &str) -> bool {
+    self.capabilities.iter().any(|c| c == capability)
+  }
+}
+
+/// Discovers the endpoints of a registry other than jsr.io from its
+/// `.well-known/deno-registry.json` document. Registries without the
+/// document are assumed to be laid out like jsr.io.
+pub async fn discover_registry(
+  client: &reqwest::Client,
+  registry_url: &Url,
+) -> Result<RegistryEndpoints, AnyError> {
+  if registry_url.as_str() == "https://jsr.io/" {
+    return Ok(RegistryEndpoints::default_for(registry_url));
+  }
+  let discovery_url = registry_url.join(DISCOVERY_PATH)?;
+  let response = trace::send(client, client.get(discovery_url.clone()))
+    .await
+    .with_context(|| {
+      format!("Failed to reach the registry {}", registry_url)
+    })?;
+  if !response.status().is_success() {
+    log::debug!(
+      "No registry discovery document at {}: status {}",
+      discovery_url,
+      response.status()
+    );
+    return Ok(RegistryEndpoints::default_for(registry_url));
+  }
+  let bytes = response.bytes().await?;
+  let document = serde_json::from_slice::<DiscoveryDocument>(&bytes)
+    .with_context(|| format!("Failed to parse {}", discovery_url))?;
+  let endpoints = RegistryEndpoints::from_document(registry_url, document)
+    .with_context(|| {
+      format!("Invalid registry discovery document at {}", discovery_url)
+    })?;
+  log::debug!("Discovered registry endpoints {:?}", endpoints);
+  Ok(endpoints)
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn endpoints_from_document() {
+    let registry_url = Url::parse("https://registry.example.com/").unwrap();
+    let document = serde_json::from_value::<DiscoveryDocument>(json!({
+      "apiUrl": "https://api.registry.example.com",
+      "manageUrl": "/manage",
+      "capabilities": ["chunkedUploads"],
+    }))
+    .unwrap();
+    let endpoints =
+      RegistryEndpoints::from_document(&registry_url, document).unwrap();
+    assert_eq!(
+      endpoints.api_url.as_str(),
+      "https://api.registry.example.com/"
+    );
+    assert_eq!(
+      endpoints.manage_url.as_str(),
+      "https://registry.example.com/manage/"
+    );
+    assert!(endpoints.supports("chunkedUploads"));
+    assert!(!endpoints.supports("provenance"));
+
+    let document =
+      serde_json::from_value::<DiscoveryDocument>(json!({})).unwrap();
+    let endpoints =
+      RegistryEndpoints::from_document(&registry_url, document).unwrap();
+    assert_eq!(endpoints.manage_url, registry_url);
+    assert_eq!(
+      endpoints.api_url.as_str(),
+      "https://registry.example.com/api/"
+    );
+    assert!(!endpoints.supports("chunkedUploads"));
+  }
+
+  #[test]
+  fn default_endpoints_support_no_capabilities() {
+    let endpoints =
+      RegistryEndpoints::default_for(&Url::parse("https://jsr.io/").unwrap());
+    assert_eq!(endpoints.api_url.as_str(), "https://jsr.io/api/");
+    assert!(endpoints.capabilities.is_empty());
+    assert!(!endpoints.supports("chunkedUploads"));
+  }
+}
@@ -0,0 +1,237 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_terminal::colors;
+
+/// The documentation of a publish diagnostic, printed by
+/// `deno publish --explain <code>`.
+struct DiagnosticExplanation {
+  code: &'static str,
+  summary: &'static str,
+  description: &'static str,
+  /// Code or a file layout that causes the diagnostic.
+  example: Option<&'static str>,
+  fix: &'static str,
+  /// The same URL as printed with the diagnostic.
+  docs_url: &'static str,
+}
+
+const EXPLANATIONS: &[DiagnosticExplanation] = &[
+  DiagnosticExplanation {
+    code: "invalid-path",
+    summary: "a file has a path that can't be published",
+    description: "To support all platforms, including Windows, package paths may only contain a limited set of characters, can't be longer than 155 characters and can't end with a dot or use reserved names like `CON` or `NUL`.",
+    example: Some("src/what?.ts\nsrc/aux.ts"),
+    fix: "Rename or remove the file, or exclude it from the package with \"publish.exclude\" in the configuration file.",
+    docs_url: "https://jsr.io/go/invalid-path",
+  },
+  DiagnosticExplanation {
+    code: "case-insensitive-duplicate-path",
+    summary: "two files have paths that only differ by case",
+    description: "Packages are extracted on case insensitive file systems too, where the two files would overwrite each other.",
+    example: Some("src/Util.ts\nsrc/util.ts"),
+    fix: "Rename or remove one of the files.",
+    docs_url: "https://jsr.io/go/case-insensitive-duplicate-path",
+  },
+  DiagnosticExplanation {
+    code: "unicode-normalization-duplicate-path",
+    summary: "two files have paths that only differ by Unicode normalization",
+    description: "Characters like `é` can be stored as one code point (NFC) or as a letter followed by a combining accent (NFD). Linux file systems keep both forms as different files, while macOS file systems treat them as the same file, so the package would extract differently on each.",
+    example: Some("src/caf\u{e9}.ts\nsrc/cafe\u{301}.ts"),
+    fix: "Rename or remove one of the files, preferably using only ASCII characters.",
+    docs_url: "https://jsr.io/go/unicode-normalization-duplicate-path",
+  },
+  DiagnosticExplanation {
+    code: "unsupported-file-type",
+    summary: "the package directory contains something that isn't a file or a directory",
+    description: "Only files and directories are published. Symlinks and other special files are ignored and won't be part of the package.",
+    example: Some("src/mod.ts -> ../mod.ts (symlink)"),
+    fix: "Replace the symlink with the file it points to, or exclude it from the package with \"publish.exclude\" in the configuration file.",
+    docs_url: "https://jsr.io/go/unsupported-file-type",
+  },
+  DiagnosticExplanation {
+    code: "invalid-external-import",
+    summary: "a module imports a specifier that isn't allowed in packages",
+    description: "Packages on the registry can only import `jsr:`, `npm:`, `node:` and `data:` specifiers and the modules of the package itself. Remote `http:` and `https:` imports can change or disappear after publishing, so they aren't allowed.",
+    example: Some("import { assert } from \"https://deno.land/std/assert/mod.ts\";"),
+    fix: "Import the dependency from jsr or npm instead, eg.\n\nimport { assert } from \"jsr:@std/assert@^0.220.0\";\n\nor copy the module into the package.",
+    docs_url: "https://jsr.io/go/invalid-external-import",
+  },
+  DiagnosticExplanation {
+    code: "unsupported-jsx-tsx",
+    summary: "the package contains JSX or TSX files",
+    description: "JSX and TSX files are published as they are, but the registry can't generate documentation or type declarations for them yet.",
+    example: Some("src/button.tsx"),
+    fix: "No action is needed. To publish them as JavaScript modules with type declarations instead, set `\"publish\": { \"transpileJsx\": true }` in the configuration file.",
+    docs_url: "https://jsr.io/go/unsupported-jsx-tsx",
+  },
+  DiagnosticExplanation {
+    code: "unanalyzable-dynamic-import",
+    summary: "a dynamic import can't be analyzed when publishing",
+    description: "Bare specifiers that are mapped by the import map or package.json of the package are rewritten to what they map to when publishing, since the import map isn't used by the users of the package. Dynamic imports of computed specifiers can't be rewritten, so they have to resolve without the import map.",
+    example: Some("const mod = await import(`lib/${name}.ts`);"),
+    fix: "Import a relative path or a full `jsr:` or `npm:` specifier, eg.\n\nconst mod = await import(`./lib/${name}.ts`);",
+    docs_url: "https://jsr.io/go/unanalyzable-dynamic-import",
+  },
+  DiagnosticExplanation {
+    code: "unreachable-file",
+    summary: "a published module isn't reachable from the exports of the package",
+    description: "Users of a package can only import its exports and the modules they import. Other modules are published but can't be used, which is often a leftover file or a missing export entry. This diagnostic is off by default and enabled with \"publish.rules\", eg.\n\n\"publish\": { \"rules\": { \"unreachable-file\": \"warn\" } }",
+    example: Some("deno.json: \"exports\": \"./mod.ts\"\nmod.ts\nold_mod.ts"),
+    fix: "Add an export for the module, or exclude it from the package with \"publish.exclude\" in the configuration file.",
+    docs_url: "https://jsr.io/go/unreachable-file",
+  },
+  DiagnosticExplanation {
+    code: "excluded-module",
+    summary: "a module that the package imports isn't published",
+    description: "The modules that are reachable from the exports of a package, including JSON modules imported with `with { type: \"json\" }`, have to be part of the package. A module that is excluded with \"publish.exclude\" or ignored by a .gitignore file would make the imports of the published package fail.",
+    example: Some("deno.json: \"publish\": { \"exclude\": [\"data/\"] }\nmod.ts: import config from \"./data/config.json\" with { type: \"json\" };"),
+    fix: "Remove the module from \"publish.exclude\" in the configuration file or from the .gitignore file, or stop importing it.",
+    docs_url: "https://jsr.io/go/excluded-module",
+  },
+  DiagnosticExplanation {
+    code: "missing-jsdoc",
+    summary: "an exported symbol has no JSDoc comment",
+    description: "The documentation of a package on the registry is generated from the JSDoc comments of its exports. This diagnostic is off by default and enabled with \"publish.rules\", eg.\n\n\"publish\": { \"rules\": { \"missing-jsdoc\": \"warn\" } }",
+    example: Some("export function add(a: number, b: number): number {\n  return a + b;\n}"),
+    fix: "Add a JSDoc comment to the symbol, eg.\n\n/** Adds two numbers. */\nexport function add(a: number, b: number): number {",
+    docs_url: "https://jsr.io/docs/writing-docs",
+  },
+  DiagnosticExplanation {
+    code: "conflicting-exports",
+    summary: "entries of \"exports\" overlap",
+    description: "Each module of a package should be exported once. Two entries that resolve to the same module, entries that only differ by a trailing slash, and entries that are also matched by a directory or pattern mapping make it unclear which entry an import uses and duplicate the documentation.",
+    example: Some("\"exports\": {\n  \".\": \"./mod.ts\",\n  \"./mod\": \"./mod.ts\"\n}"),
+    fix: "Remove the redundant entries from \"exports\".",
+    docs_url: "https://jsr.io/go/conflicting-exports",
+  },
+  DiagnosticExplanation {
+    code: "unpinned-dependency",
+    summary: "a dependency isn't pinned to a version or checksum",
+    description: "A `jsr:` dependency without a version constraint resolves to the latest version of the package, which changes over time. A remote module without an integrity entry in the lockfile can change on the server without being noticed. Both make the package behave differently for its users than when it was published.",
+    example: Some("import { assert } from \"jsr:@std/assert\";"),
+    fix: "Add a version constraint to the specifier, eg.\n\nimport { assert } from \"jsr:@std/assert@^0.220.0\";\n\nFor remote modules, enable the lockfile and run `deno cache` to record their checksums.",
+    docs_url: "https://jsr.io/go/unpinned-dependency",
+  },
+  DiagnosticExplanation {
+    code: "pinned-remote-import",
+    summary: "a module imports a remote module that is pinned by the lockfile",
+    description: "Remote `http:` and `https:` imports fail publishing, unless \"publish.allowPinnedRemoteImports\" is set in the configuration file, eg.\n\n\"publish\": { \"allowPinnedRemoteImports\": true }\n\nThen the remote modules that have an integrity entry in the lockfile are published with this warning, so packages can migrate to `jsr:` and `npm:` dependencies gradually. The users of the package still download the modules from the server, without checking them against the lockfile.",
+    example: Some("import { assert } from \"https://deno.land/std@0.220.0/assert/mod.ts\";"),
+    fix: "Import the dependency from jsr or npm instead, eg.\n\nimport { assert } from \"jsr:@std/assert@^0.220.0\";",
+    docs_url: "https://jsr.io/go/pinned-remote-import",
+  },
+  DiagnosticExplanation {
+    code: "deno-only-api",
+    summary: "a module of the package uses the `Deno` namespace",
+    description: "Packages on jsr can be installed in Node.js through its npm compatibility layer, where the `Deno` namespace doesn't exist. This diagnostic is reported for the modules reachable from the exports of the package, unless \"publish.runtimeCompat\" declares that the package doesn't support Node.js, eg.\n\n\"publish\": { \"runtimeCompat\": { \"deno\": true, \"node\": false } }",
+    example: Some("export function home(): string | undefined {\n  return Deno.env.get(\"HOME\");\n}"),
+    fix: "Use a web standard or `node:` API instead, eg.\n\nimport process from \"node:process\";\nexport function home(): string | undefined {\n  return process.env.HOME;\n}\n\nor declare the supported runtimes with \"publish.runtimeCompat\".",
+    docs_url: "https://jsr.io/go/deno-only-api",
+  },
+  DiagnosticExplanation {
+    code: "cross-member-import",
+    summary: "a workspace member imports a module of another member by its path",
+    description: "Only the files of a package are published, so a relative import of a module of another workspace member doesn't resolve for the users of the package. When the other member exports the module, publishing rewrites the import to a `jsr:` specifier of the version of the member in the workspace and reports a warning. Otherwise the import can't be rewritten and publishing fails.",
+    example: Some("packages/a/mod.ts:
+import { b } from \"../b/mod.ts\";"),
+    fix: "Import the member with a `jsr:` specifier of one of its exports, eg.\n\nimport { b } from \"jsr:@scope/b@^1.0.0\";\n\n`deno publish --fix` rewrites the imports of exported modules in the source files.",
+    docs_url: "https://jsr.io/go/cross-member-import",
+  },
+  DiagnosticExplanation {
+    code: "incompatible-member-version",
+    summary: "a `jsr:` import of a workspace member doesn't match the version being published",
+    description: "When the members of a workspace are published together, a member that imports another one with a version constraint the other member's version doesn't satisfy resolves to a different version from the registry, or to none at all. Publishing rewrites the import to a constraint of the version in the workspace and fails, since the published package would otherwise not use the version it was checked with.",
+    example: Some("packages/b/deno.json: \"version\": \"2.0.0\"\npackages/a/mod.ts: import { b } from \"jsr:@scope/b@^1.0.0\";"),
+    fix: "Update the version constraint to match the version of the member, eg.\n\nimport { b } from \"jsr:@scope/b@^2.0.0\";\n\n`deno publish --fix` rewrites the imports in the source files.",
+    docs_url: "https://jsr.io/go/incompatible-member-version",
+  },
+  DiagnosticExplanation {
+    code: "dev-import",
+    summary: "a module reachable from the exports imports a dev-only dependency",
+    description: "The dependencies listed in `\"publish.devImports\"` are only used by the modules of the package that can't be reached from its exports, like tests. Their imports are published as they are and not checked, so a module that users of the package import can't depend on them.",
+    example: Some("deno.json: \"publish\": { \"devImports\": [\"@std/assert\"] }\nmod.ts: import { assert } from \"@std/assert\";"),
+    fix: "Only import the dependency from tests, or remove it from `\"publish.devImports\"`, eg. by adding it again with `deno add` without `--dev`, to publish it as a dependency of the package.",
+    docs_url: "https://jsr.io/go/dev-import",
+  },
+];
+
+/// Prints the documentation of a publish diagnostic code.
+pub fn explain(code: &str) -> Result<(), AnyError> {
+  let Some(explanation) = find_explanation(code) else {
+    bail!(
+      "Unknown publish diagnostic code '{}'. The codes are: {}. Slow types diagnostics are explained at https://jsr.io/docs/about-slow-types",
+      code,
+      EXPLANATIONS
+        .iter()
+        .map(|explanation| explanation.code)
+        .collect::<Vec<_>>()
+        .join(", ")
+    );
+  };
+  println!(
+    "{}: {}",
+    colors::bold(explanation.code),
+    explanation.summary
+  );
+  println!();
+  println!("{}", explanation.description);
+  if let Some(example) = explanation.example {
+    println!();
+    println!("{}", colors::bold("Example:"));
+    for line in example.lines() {
+      println!("  {}", line);
+    }
+  }
+  println!();
+  println!("{}", colors::bold("Fix:"));
+  for line in explanation.fix.lines() {
+    println!("  {}", line);
+  }
+  println!();
+  println!("{} {}", colors::bold("Docs:"), explanation.docs_url);
+  Ok(())
+}
+
+fn find_explanation(code: &str) -> Option<&'static DiagnosticExplanation> {
+  // also accept the code as printed, eg. `error[invalid-path]`
+  let code = code
+    .split_once('[')
+    .map(|(_, code)| code.trim_end_matches(']'))
+    .unwrap_or(code);
+  EXPLANATIONS
+    .iter()
+    .find(|explanation| explanation.code == code)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use super::*;
+
+  #[test]
+  fn explanation_codes_unique() {
+    let mut codes = HashSet::new();
+    for explanation in EXPLANATIONS {
+      assert!(codes.insert(explanation.code), "{}", explanation.code);
+    }
+  }
+
+  #[test]
+  fn find_explanation_by_code() {
+    assert_eq!(
+      find_explanation("invalid-path").unwrap().code,
+      "invalid-path"
+    );
+    assert_eq!(
+      find_explanation("error[invalid-external-import]")
+        .unwrap()
+        .code,
+      "invalid-external-import"
+    );
+    assert!(find_explanation("missing-explicit-return-type").is_none());
+    assert!(explain("not-a-code").is_err());
+  }
+}
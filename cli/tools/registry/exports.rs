@@ -0,0 +1,180 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_config::ConfigFile;
+use deno_core::serde_json::Value;
+use lsp_types::Url;
+
+use super::diagnostics::PublishDiagnostic;
+use super::diagnostics::PublishDiagnosticsCollector;
+
+/// Entries of the `exports` of a package that confuse consumers and the
+/// documentation generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportsConflict {
+  /// Two keys resolve to the same module.
+  SameTarget { key: String, other_key: String },
+  /// Both `./foo` and `./foo/` are exported.
+  TrailingSlash { key: String, other_key: String },
+  /// A key is also matched by a more general mapping, eg. `./utils/` or
+  /// `./*`.
+  Shadowed { key: String, general_key: String },
+}
+
+impl ExportsConflict {
+  pub fn message(&self) -> String {
+    match self {
+      ExportsConflict::SameTarget { key, other_key } => format!(
+        "exports '{}' and '{}' resolve to the same module",
+        other_key, key
+      ),
+      ExportsConflict::TrailingSlash { key, other_key } => format!(
+        "exports '{}' and '{}' only differ by a trailing slash",
+        other_key, key
+      ),
+      ExportsConflict::Shadowed { key, general_key } => {
+        format!("export '{}' is shadowed by '{}'", key, general_key)
+      }
+    }
+  }
+
+  pub fn hint(&self) -> String {
+    match self {
+      ExportsConflict::SameTarget { key, .. }
+      | ExportsConflict::TrailingSlash { key, .. } => {
+        format!("remove the '{}' entry from \"exports\"", key)
+      }
+      ExportsConflict::Shadowed { key, general_key } => format!(
+        "remove the '{}' entry, or export the modules matched by '{}' individually",
+        key, general_key
+      ),
+    }
+  }
+}
+
+pub fn collect_exports_conflicts(
+  config_file: &ConfigFile,
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) {
+  let Some(Value::Object(exports)) = &config_file.json.exports else {
+    return;
+  };
+  let exports = exports
+    .iter()
+    .filter_map(|(key, value)| {
+      let target = config_file.specifier.join(value.as_str()?).ok()?;
+      Some((key.clone(), target))
+    })
+    .collect::<Vec<_>>();
+  for conflict in find_exports_conflicts(&exports) {
+    diagnostics_collector.push(PublishDiagnostic::ConflictingExports {
+      specifier: config_file.specifier.clone(),
+      conflict,
+    });
+  }
+}
+
+fn find_exports_conflicts(exports: &[(String, Url)]) -> Vec<ExportsConflict> {
+  let mut conflicts = Vec::new();
+  for (index, (key, target)) in exports.iter().enumerate() {
+    for (other_key, other_target) in &exports[..index] {
+      if target == other_target {
+        conflicts.push(ExportsConflict::SameTarget {
+          key: key.clone(),
+          other_key: other_key.clone(),
+        });
+      }
+      if key.trim_end_matches('/') == other_key.trim_end_matches('/') {
+        conflicts.push(ExportsConflict::TrailingSlash {
+          key: key.clone(),
+          other_key: other_key.clone(),
+        });
+      }
+    }
+    if let Some((general_key, _)) = exports
+      .iter()
+      .find(|(general_key, _)| is_shadowed_by(key, general_key))
+    {
+      conflicts.push(ExportsConflict::Shadowed {
+        key: key.clone(),
+        general_key: general_key.clone(),
+      });
+    }
+  }
+  conflicts
+}
+
+/// Whether a key is also matched by a directory (`./utils/`) or pattern
+/// (`./*`) mapping.
+fn is_shadowed_by(key: &str, general_key: &str) -> bool {
+  if key == general_key {
+    return false;
+  }
+  if let Some((prefix, suffix)) = general_key.split_once('*') {
+    key.len() >= prefix.len() + suffix.len()
+      && key.starts_with(prefix)
+      && key.ends_with(suffix)
+  } else if general_key.ends_with('/') {
+    key.starts_with(general_key)
+  } else {
+    false
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn exports(entries: &[(&str, &str)]) -> Vec<(String, Url)> {
+    entries
+      .iter()
+      .map(|(key, path)| {
+        let target = Url::parse("file:///pkg/").unwrap().join(path).unwrap();
+        (key.to_string(), target)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn exports_conflicts() {
+    assert!(find_exports_conflicts(&exports(&[
+      (".", "./mod.ts"),
+      ("./utils", "./utils.ts"),
+    ]))
+    .is_empty());
+    assert_eq!(
+      find_exports_conflicts(&exports(&[
+        (".", "./mod.ts"),
+        ("./mod", "./mod.ts"),
+        ("./foo", "./foo.ts"),
+        ("./foo/", "./foo/mod.ts"),
+      ])),
+      vec![
+        ExportsConflict::SameTarget {
+          key: "./mod".to_string(),
+          other_key: ".".to_string(),
+        },
+        ExportsConflict::TrailingSlash {
+          key: "./foo/".to_string(),
+          other_key: "./foo".to_string(),
+        },
+      ]
+    );
+    assert_eq!(
+      find_exports_conflicts(&exports(&[
+        ("./utils/", "./utils/"),
+        ("./utils/fs", "./utils/fs.ts"),
+        ("./*", "./src/*.ts"),
+      ])),
+      vec![
+        ExportsConflict::Shadowed {
+          key: "./utils/".to_string(),
+          general_key: "./*".to_string(),
+        },
+        ExportsConflict::Shadowed {
+          key: "./utils/fs".to_string(),
+          general_key: "./utils/".to_string(),
+        },
+      ]
+    );
+  }
+}
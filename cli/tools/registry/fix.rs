@@ -0,0 +1,112 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_terminal::colors;
+
+use super::unfurl::SpecifierRewrite;
+use super::PreparedPublishPackage;
+
+/// Writes the specifiers that publishing rewrites back to the source files
+/// of the packages, for `deno publish --fix`.
+pub fn fix_sources<'a>(
+  packages: impl IntoIterator<Item = &'a PreparedPublishPackage>,
+) -> Result<(), AnyError> {
+  let mut fixed_files = 0;
+  for package in packages {
+    for file in &package.tarball.files {
+      if file.rewrites.is_empty() {
+        continue;
+      }
+      let path = file.specifier.to_file_path().unwrap();
+      let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed reading '{}'", path.display()))?;
+      let Some(fixed_text) = apply_rewrites(&text, &file.rewrites) else {
+        log::warn!(
+          "{} Skipped fixing '{}', it changed while publishing",
+          colors::yellow("Warning"),
+          path.display()
+        );
+        continue;
+      };
+      std::fs::write(&path, fixed_text)
+        .with_context(|| format!("Failed writing '{}'", path.display()))?;
+      fixed_files += 1;
+      log::info!("{} {}", colors::green("Fixed"), path.display());
+      for rewrite in &file.rewrites {
+        log::info!(
+          "  {} {} -> {}",
+          colors::gray(format!("{}:", rewrite.line)),
+          rewrite.from,
+          rewrite.to
+        );
+      }
+    }
+  }
+  if fixed_files == 0 {
+    log::info!("Nothing to fix");
+  }
+  Ok(())
+}
+
+/// Applies rewrites to the text of the file they were found in, `None` when
+/// the text doesn't match them anymore.
+fn apply_rewrites(text: &str, rewrites: &[SpecifierRewrite]) -> Option<String> {
+  // the parsed source doesn't include the byte order mark
+  let (bom, text) = match text.strip_prefix('\u{FEFF}') {
+    Some(text) => ("\u{FEFF}", text),
+    None => ("", text),
+  };
+  let mut text_changes = Vec::with_capacity(rewrites.len());
+  for rewrite in rewrites {
+    if text.get(rewrite.range.clone())? != rewrite.from {
+      return None;
+    }
+    text_changes.push(deno_ast::TextChange {
+      range: rewrite.range.clone(),
+      new_text: rewrite.to.clone(),
+    });
+  }
+  Some(format!(
+    "{}{}",
+    bom,
+    deno_ast::apply_text_changes(text, text_changes)
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rewrite(
+    range: std::ops::Range<usize>,
+    from: &str,
+    to: &str,
+  ) -> SpecifierRewrite {
+    SpecifierRewrite {
+      range,
+      line: 1,
+      from: from.to_string(),
+      to: to.to_string(),
+    }
+  }
+
+  #[test]
+  fn apply_rewrites_to_source() {
+    let rewrites = vec![
+      rewrite(17..20, "url", "node:url"),
+      rewrite(38..43, "./mod", "./mod.ts"),
+    ];
+    let text = "import url from \"url\";\nexport * from \"./mod\";\n";
+    assert_eq!(
+      apply_rewrites(text, &rewrites).unwrap(),
+      "import url from \"node:url\";\nexport * from \"./mod.ts\";\n"
+    );
+    assert_eq!(
+      apply_rewrites(&format!("\u{FEFF}{}", text), &rewrites).unwrap(),
+      "\u{FEFF}import url from \"node:url\";\nexport * from \"./mod.ts\";\n"
+    );
+    // the file changed after it was read
+    assert!(apply_rewrites("import url from \"uri\";\n", &rewrites).is_none());
+  }
+}
@@ -1,20 +1,37 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 
+use deno_ast::MediaType;
 use deno_ast::SourceTextInfo;
+use deno_config::ConfigFile;
+use deno_core::error::AnyError;
+use deno_doc::DocDiagnosticKind;
+use deno_doc::DocParser;
+use deno_doc::DocParserOptions;
 use deno_graph::ModuleEntryRef;
 use deno_graph::ModuleGraph;
+use deno_graph::ModuleParser;
 use deno_graph::ResolutionResolved;
 use deno_graph::WalkOptions;
+use deno_lockfile::Lockfile;
+use deno_semver::jsr::JsrPackageReqReference;
 use lsp_types::Url;
 
 use super::diagnostics::PublishDiagnostic;
 use super::diagnostics::PublishDiagnosticsCollector;
+use super::tar::PublishableTarballFile;
 
-pub fn collect_invalid_external_imports(
+/// Reports the imports of modules that can't be published, and the
+/// dependencies that aren't pinned to a version or checksum. Remote imports
+/// with an integrity entry in the lockfile are only warned about when
+/// `allow_pinned_remote_imports` is set.
+pub fn collect_external_import_diagnostics(
   graph: &ModuleGraph,
+  lockfile: Option<&Lockfile>,
+  allow_pinned_remote_imports: bool,
   diagnostics_collector: &PublishDiagnosticsCollector,
 ) {
   let mut visited = HashSet::new();
@@ -27,11 +44,43 @@ pub fn collect_invalid_external_imports(
       if visited.insert(resolution.specifier.clone()) {
         match resolution.specifier.scheme() {
           "file" | "data" | "node" => {}
-          "jsr" | "npm" => {
+          "jsr" => {
+            skip_specifiers.insert(resolution.specifier.clone());
+            let is_unpinned =
+              JsrPackageReqReference::from_specifier(&resolution.specifier)
+                .map(|req_ref| req_ref.req().version_req.version_text() == "*")
+                .unwrap_or(false);
+            if is_unpinned {
+              diagnostics_collector.push(
+                PublishDiagnostic::UnpinnedDependency {
+                  imported: resolution.specifier.clone(),
+                  text_info: SourceTextInfo::new(text.clone()),
+                  referrer: resolution.range.clone(),
+                },
+              );
+            }
+          }
+          "npm" => {
             skip_specifiers.insert(resolution.specifier.clone());
           }
           "http" | "https" => {
             skip_specifiers.insert(resolution.specifier.clone());
+            let has_integrity = lockfile.is_some_and(|lockfile| {
+              lockfile
+                .content
+                .remote
+                .contains_key(resolution.specifier.as_str())
+            });
+            if allow_pinned_remote_imports && has_integrity {
+              diagnostics_collector.push(
+                PublishDiagnostic::PinnedRemoteImport {
+                  imported: resolution.specifier.clone(),
+                  text_info: SourceTextInfo::new(text.clone()),
+                  referrer: resolution.range.clone(),
+                },
+              );
+              return;
+            }
             diagnostics_collector.push(
               PublishDiagnostic::InvalidExternalImport {
                 kind: format!("non-JSR '{}'", resolution.specifier.scheme()),
@@ -40,6 +89,18 @@ pub fn collect_invalid_external_imports(
                 referrer: resolution.range.clone(),
               },
             );
+            // remote imports are errors anyway unless that rule is off
+            let is_allowed =
+              diagnostics_collector.is_rule_off("invalid-external-import");
+            if is_allowed && !has_integrity {
+              diagnostics_collector.push(
+                PublishDiagnostic::UnpinnedDependency {
+                  imported: resolution.specifier.clone(),
+                  text_info: SourceTextInfo::new(text.clone()),
+                  referrer: resolution.range.clone(),
+                },
+              );
+            }
           }
           _ => {
             skip_specifiers.insert(resolution.specifier.clone());
@@ -85,3 +146,123 @@ pub fn collect_invalid_external_imports(
     }
   }
 }
+
+/// Gets the modules that are reachable from the exports of a package.
+pub fn exported_modules(
+  graph: &ModuleGraph,
+  config_file: &ConfigFile,
+) -> Result<HashSet<Url>, AnyError> {
+  let roots = config_file.resolve_export_value_urls()?;
+  let options = WalkOptions {
+    check_js: true,
+    follow_dynamic: true,
+    follow_type_only: true,
+  };
+  Ok(
+    graph
+      .walk(&roots, options)
+      .map(|(specifier, _)| specifier.clone())
+      .collect(),
+  )
+}
+
+/// Reports the JavaScript and TypeScript modules that are published but
+/// can't be reached from the exports of the package.
+pub fn collect_unreachable_files(
+  exported_modules: &HashSet<Url>,
+  files: &[PublishableTarballFile],
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) {
+  for file in files {
+    let is_module = match MediaType::from_specifier(&file.specifier) {
+      MediaType::JavaScript
+      | MediaType::Jsx
+      | MediaType::Mjs
+      | MediaType::Cjs
+      | MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Dts
+      | MediaType::Dmts
+      | MediaType::Dcts
+      | MediaType::Tsx => true,
+      MediaType::Json
+      | MediaType::Wasm
+      | MediaType::TsBuildInfo
+      | MediaType::SourceMap
+      | MediaType::Unknown => false,
+    };
+    if is_module && !exported_modules.contains(&file.specifier) {
+      diagnostics_collector.push(PublishDiagnostic::UnreachableFile {
+        specifier: file.specifier.clone(),
+      });
+    }
+  }
+}
+
+/// Reports the modules of the package in `dir` that are reachable from its
+/// exports but aren't published, eg. JSON modules imported with attributes
+/// that are excluded from the package.
+pub fn collect_excluded_modules(
+  exported_modules: &HashSet<Url>,
+  dir: &Path,
+  files: &[PublishableTarballFile],
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) {
+  let published = files
+    .iter()
+    .map(|file| &file.specifier)
+    .collect::<HashSet<_>>();
+  let mut excluded = exported_modules
+    .iter()
+    .filter(|specifier| {
+      specifier.scheme() == "file"
+        && !published.contains(specifier)
+        && specifier
+          .to_file_path()
+          .is_ok_and(|path| path.starts_with(dir))
+    })
+    .collect::<Vec<_>>();
+  excluded.sort();
+  for specifier in excluded {
+    diagnostics_collector.push(PublishDiagnostic::ExcludedModule {
+      specifier: specifier.clone(),
+    });
+  }
+}
+
+/// Reports the exported symbols of a package that have no JSDoc, using the
+/// diagnostics of the documentation generation.
+pub fn collect_missing_jsdoc(
+  graph: &ModuleGraph,
+  parser: &dyn ModuleParser,
+  config_file: &ConfigFile,
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) -> Result<(), AnyError> {
+  let doc_parser = DocParser::new(
+    graph,
+    parser,
+    DocParserOptions {
+      diagnostics: true,
+      private: false,
+    },
+  )?;
+  for specifier in config_file.resolve_export_value_urls()? {
+    // exports of JSON modules don't have any JSDoc
+    if graph
+      .get(&specifier)
+      .and_then(|module| module.js())
+      .is_none()
+    {
+      continue;
+    }
+    doc_parser.parse_with_reexports(&specifier)?;
+  }
+  for diagnostic in doc_parser.take_diagnostics() {
+    // the other documentation lints overlap with the slow types checks
+    if matches!(diagnostic.kind, DocDiagnosticKind::MissingJsDoc) {
+      diagnostics_collector.push(PublishDiagnostic::MissingJsDoc(diagnostic));
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,226 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashSet;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_semver::Version;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryInfoFlags;
+
+use super::api::DependencyKind;
+use super::api::PackageVersion;
+use super::api::PackageVersionDependency;
+use super::commands::parse_package_arg;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageInfoJson {
+  name: String,
+  description: String,
+  latest_version: Option<String>,
+  /// The documentation score in percent.
+  score: Option<u32>,
+  /// The dependencies of the latest version.
+  dependencies: DependencyCountsJson,
+  /// Sorted from newest to oldest.
+  versions: Vec<VersionJson>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct DependencyCountsJson {
+  jsr: usize,
+  npm: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionJson {
+  version: String,
+  created_at: Option<String>,
+  yanked: bool,
+}
+
+pub async fn info(
+  flags: Flags,
+  info_flags: RegistryInfoFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let package = parse_package_arg(&info_flags.package)?;
+  let api_client = context.api_client()?;
+
+  let Some(package_info) = api_client
+    .get_package(&package.scope, &package.package)
+    .await?
+  else {
+    bail!("{} was not found in the registry", package);
+  };
+  let versions = api_client
+    .get_package_versions(&package.scope, &package.package)
+    .await?
+    .unwrap_or_default();
+  let dependencies = match &package_info.latest_version {
+    Some(latest_version) => api_client
+      .get_package_version_dependencies(
+        &package.scope,
+        &package.package,
+        latest_version,
+      )
+      .await?
+      .unwrap_or_default(),
+    None => Vec::new(),
+  };
+
+  let info = PackageInfoJson {
+    name: package.to_string(),
+    description: package_info.description,
+    latest_version: package_info.latest_version,
+    score: package_info.score,
+    dependencies: count_dependencies(&dependencies),
+    versions: sort_versions(versions)
+      .into_iter()
+      .map(|version| VersionJson {
+        version: version.version,
+        created_at: version.created_at,
+        yanked: version.yanked,
+      })
+      .collect(),
+  };
+
+  if info_flags.json {
+    print_json(&info)?;
+  } else {
+    print_info(&info, &context.endpoints.manage_url);
+  }
+  Ok(())
+}
+
+fn print_info(info: &PackageInfoJson, manage_url: &Url) {
+  println!("{}", colors::bold(&info.name));
+  if !info.description.is_empty() {
+    println!("{}", info.description);
+  }
+  println!(
+    "  {} {}",
+    colors::gray("Latest version:"),
+    info.latest_version.as_deref().unwrap_or("-")
+  );
+  if let Some(score) = info.score {
+    println!("  {} {}%", colors::gray("Score:"), score);
+  }
+  println!(
+    "  {} {} jsr, {} npm",
+    colors::gray("Dependencies:"),
+    info.dependencies.jsr,
+    info.dependencies.npm
+  );
+  println!(
+    "  {} {}{}",
+    colors::gray("Registry:"),
+    manage_url,
+    info.name
+  );
+  if info.versions.is_empty() {
+    return;
+  }
+  println!("  {}", colors::gray("Versions:"));
+  for version in &info.versions {
+    // only the date of the timestamp
+    let date = version
+      .created_at
+      .as_deref()
+      .and_then(|created_at| created_at.split('T').next())
+      .unwrap_or("");
+    if version.yanked {
+      println!(
+        "    {} {} {}",
+        version.version,
+        colors::gray(date),
+        colors::yellow("(yanked)")
+      );
+    } else {
+      println!("    {} {}", version.version, colors::gray(date));
+    }
+  }
+}
+
+/// Counts the distinct packages depended on, which are listed once for
+/// every module that imports them.
+fn count_dependencies(
+  dependencies: &[PackageVersionDependency],
+) -> DependencyCountsJson {
+  let mut counts = DependencyCountsJson::default();
+  let mut seen = HashSet::new();
+  for dependency in dependencies {
+    if !seen.insert((dependency.kind, dependency.name.as_str())) {
+      continue;
+    }
+    match dependency.kind {
+      DependencyKind::Jsr => counts.jsr += 1,
+      DependencyKind::Npm => counts.npm += 1,
+    }
+  }
+  counts
+}
+
+pub(super) fn sort_versions(
+  mut versions: Vec<PackageVersion>,
+) -> Vec<PackageVersion> {
+  versions.sort_by_cached_key(|version| {
+    std::cmp::Reverse(Version::parse_standard(&version.version).ok())
+  });
+  versions
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn dependency(kind: DependencyKind, name: &str) -> PackageVersionDependency {
+    PackageVersionDependency {
+      kind,
+      name: name.to_string(),
+      constraint: "*".to_string(),
+      path: String::new(),
+    }
+  }
+
+  #[test]
+  fn count_dependencies_distinct() {
+    let dependencies = vec![
+      dependency(DependencyKind::Jsr, "@std/path"),
+      dependency(DependencyKind::Jsr, "@std/path"),
+      dependency(DependencyKind::Jsr, "@std/fs"),
+      dependency(DependencyKind::Npm, "chalk"),
+    ];
+    assert_eq!(
+      count_dependencies(&dependencies),
+      DependencyCountsJson { jsr: 2, npm: 1 }
+    );
+  }
+
+  #[test]
+  fn sort_versions_newest_first() {
+    let versions = ["1.0.0", "1.10.0", "1.2.0"]
+      .into_iter()
+      .map(|version| PackageVersion {
+        scope: "foo".to_string(),
+        package: "bar".to_string(),
+        version: version.to_string(),
+        yanked: false,
+        created_at: None,
+      })
+      .collect();
+    let sorted = sort_versions(versions)
+      .into_iter()
+      .map(|version| version.version)
+      .collect::<Vec<_>>();
+    assert_eq!(sorted, vec!["1.10.0", "1.2.0", "1.0.0"]);
+  }
+}
@@ -0,0 +1,84 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The checks of `deno publish` that `deno lint` runs as the rules of the
+//! `jsr` tag, so they can run in editors and CI without publishing.
+
+use std::collections::HashMap;
+
+use deno_config::WorkspaceMemberConfig;
+use deno_core::error::AnyError;
+use deno_graph::ModuleGraph;
+use deno_lockfile::Lockfile;
+
+use crate::cache::LazyGraphSourceParser;
+use crate::cache::ParsedSourceCache;
+
+use super::config::CliPublishConfig;
+use super::config::RuleSeverity;
+use super::diagnostics::PublishDiagnostic;
+use super::diagnostics::PublishDiagnosticsCollector;
+use super::exports;
+use super::graph;
+use super::node_compat;
+
+/// The lint rule tag that enables all the publish checks.
+pub const JSR_LINT_TAG: &str = "jsr";
+
+/// The codes of the publish diagnostics that are available as lint rules.
+pub const JSR_LINT_RULES: &[&str] = &[
+  "invalid-external-import",
+  "unpinned-dependency",
+  "conflicting-exports",
+  "deno-only-api",
+];
+
+/// Runs the publish checks of the enabled rules on the packages of a
+/// workspace, with the module graph that is used for publishing.
+pub fn collect_jsr_lint_diagnostics(
+  graph: &ModuleGraph,
+  source_cache: &ParsedSourceCache,
+  lockfile: Option<&Lockfile>,
+  members: &[WorkspaceMemberConfig],
+  enabled_rules: &[&str],
+) -> Result<Vec<PublishDiagnostic>, AnyError> {
+  let rules = JSR_LINT_RULES
+    .iter()
+    .map(|code| {
+      let severity = if enabled_rules.contains(code) {
+        RuleSeverity::Error
+      } else {
+        RuleSeverity::Off
+      };
+      (code.to_string(), severity)
+    })
+    // allowed remote imports are only worth a warning when publishing
+    .chain([("pinned-remote-import".to_string(), RuleSeverity::Off)])
+    .collect::<HashMap<_, _>>();
+  let diagnostics_collector = PublishDiagnosticsCollector::new(rules);
+
+  let publish_configs = members
+    .iter()
+    .map(|member| CliPublishConfig::from_config_file(&member.config_file))
+    .collect::<Result<Vec<_>, _>>()?;
+  graph::collect_external_import_diagnostics(
+    graph,
+    lockfile,
+    publish_configs
+      .iter()
+      .any(|publish_config| publish_config.allow_pinned_remote_imports),
+    &diagnostics_collector,
+  );
+  for (member, publish_config) in members.iter().zip(&publish_configs) {
+    let config_file = &member.config_file;
+    exports::collect_exports_conflicts(config_file, &diagnostics_collector);
+    if node_compat::targets_node(&publish_config.runtime_compat) {
+      let exported_modules = graph::exported_modules(graph, config_file)?;
+      node_compat::collect_deno_only_apis(
+        LazyGraphSourceParser::new(source_cache, graph),
+        &exported_modules,
+        &diagnostics_collector,
+      )?;
+    }
+  }
+  Ok(diagnostics_collector.take_diagnostics())
+}
@@ -1,23 +1,33 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use bytes::Bytes;
 use deno_config::ConfigFile;
 use deno_config::WorkspaceMemberConfig;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures::FutureExt;
+use deno_core::futures::StreamExt;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::serde_json::Value;
 use deno_core::unsync::JoinSet;
+use deno_graph::packages::JsrPackageInfo;
 use deno_runtime::deno_fetch::reqwest;
+use deno_semver::Version;
 use deno_terminal::colors;
 use import_map::ImportMap;
 use lsp_types::Url;
@@ -25,14 +35,17 @@ use serde::Deserialize;
 use serde::Serialize;
 use sha2::Digest;
 
-use crate::args::jsr_api_url;
 use crate::args::jsr_url;
 use crate::args::CliOptions;
 use crate::args::Flags;
+use crate::args::PublishCheckLib;
 use crate::args::PublishFlags;
 use crate::args::TypeCheckMode;
+use crate::args::VersionBumpFlags;
+use crate::cache::FastInsecureHasher;
 use crate::cache::LazyGraphSourceParser;
 use crate::cache::ParsedSourceCache;
+use crate::cache::PublishCheckCache;
 use crate::factory::CliFactory;
 use crate::graph_util::ModuleGraphCreator;
 use crate::http_util::HttpClient;
@@ -42,34 +55,108 @@ use crate::tools::check::CheckOptions;
 use crate::tools::lint::no_slow_types;
 use crate::tools::registry::diagnostics::PublishDiagnostic;
 use crate::tools::registry::diagnostics::PublishDiagnosticsCollector;
-use crate::tools::registry::graph::collect_invalid_external_imports;
+use crate::tools::registry::graph::collect_external_import_diagnostics;
 use crate::util::display::human_size;
 
-mod api;
+pub mod api;
+mod assets;
+mod audit_log;
 mod auth;
+mod baseline;
+mod commands;
+mod config;
+mod delete;
 mod diagnostics;
+mod discovery;
+mod explain;
+mod exports;
+mod fix;
 mod graph;
+mod info;
+mod lint_rules;
+mod node_compat;
+mod open_package;
+mod outdated;
+mod output;
 mod paths;
+mod ping;
 mod pm;
 mod provenance;
+mod public_api;
 mod publish_order;
+mod report;
+mod sbom;
+mod scope_config;
+mod scopes;
+mod search;
+mod slow_types;
+mod stats;
 mod tar;
+mod trace;
+mod transpile;
 mod unfurl;
-
+mod verify;
+mod version;
+
+use api::ApiError;
+use api::ApiErrorKind;
+use api::ApiResponseCache;
+use api::EncodedTarball;
+use api::JsrApiClient;
+use api::UploadEncoding;
+pub use assets::AssetLoaders;
+pub use assets::AssetShimLoader;
 use auth::get_auth_method;
+use auth::AuthHeader;
+use auth::AuthHeaderTemplate;
 use auth::AuthMethod;
+use auth::UnauthorizedError;
+pub use commands::registry;
+use config::CliPublishConfig;
+use config::InclusionProofCheck;
+use config::RegistryConfig;
+use config::SlsaPredicateVersion;
+pub use diagnostics::PublishDiagnostic;
+pub use lint_rules::collect_jsr_lint_diagnostics;
+pub use lint_rules::JSR_LINT_RULES;
+pub use lint_rules::JSR_LINT_TAG;
+pub use outdated::outdated;
 pub use pm::add;
+use provenance::SigstoreConfig;
 use publish_order::PublishOrderGraph;
+pub use slow_types::SlowTypesAllowlist;
+pub use transpile::transpiles_jsx;
+use transpile::JsxTranspiler;
 pub use unfurl::deno_json_deps;
 use unfurl::SpecifierUnfurler;
+use unfurl::UnfurlWorkspaceMember;
+pub use version::version;
 
 use super::check::TypeChecker;
 
 use self::tar::PublishableTarball;
 
+/// Whether the result of publishing is printed as JSON, in which case the
+/// progress is printed to stderr to keep stdout parseable.
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+macro_rules! progress {
+  ($($arg:tt)*) => {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+      eprintln!($($arg)*);
+    } else {
+      println!($($arg)*);
+    }
+  };
+}
+
 fn ring_bell() {
   // ASCII code for the bell character.
-  print!("\x07");
+  if JSON_OUTPUT.load(Ordering::Relaxed) {
+    eprint!("\x07");
+  } else {
+    print!("\x07");
+  }
 }
 
 struct PreparedPublishPackage {
@@ -79,17 +166,34 @@ struct PreparedPublishPackage {
   tarball: PublishableTarball,
   config: String,
   exports: HashMap<String, String>,
+  /// The CycloneDX SBOM of the package, when requested.
+  sbom: Option<Value>,
+  /// Opted out of provenance with `"provenance": false` in its configuration
+  /// file.
+  provenance_disabled: bool,
 }
 
 impl PreparedPublishPackage {
   pub fn display_name(&self) -> String {
     format!("@{}/{}@{}", self.scope, self.package, self.version)
   }
+
+  fn auth_key(&self) -> (String, String, String) {
+    (
+      self.scope.clone(),
+      self.package.clone(),
+      self.version.clone(),
+    )
+  }
 }
 
 static SUGGESTED_ENTRYPOINTS: [&str; 4] =
   ["mod.ts", "mod.js", "index.ts", "index.js"];
 
+/// The libraries that the public API is type checked with for Node.js, which
+/// has the web APIs that the DOM library types, but not the `Deno` namespace.
+static NODE_CHECK_LIBS: [&str; 3] = ["esnext", "dom", "dom.iterable"];
+
 #[allow(clippy::too_many_arguments)]
 async fn prepare_publish(
   package_name: &str,
@@ -99,7 +203,9 @@ async fn prepare_publish(
   mapped_resolver: Arc<MappedSpecifierResolver>,
   sloppy_imports_resolver: Option<SloppyImportsResolver>,
   bare_node_builtins: bool,
+  workspace_members: Arc<[UnfurlWorkspaceMember]>,
   diagnostics_collector: &PublishDiagnosticsCollector,
+  sbom: Option<Value>,
 ) -> Result<Rc<PreparedPublishPackage>, AnyError> {
   let config_path = deno_json.specifier.to_file_path().unwrap();
   let dir_path = config_path.parent().unwrap().to_path_buf();
@@ -140,45 +246,135 @@ async fn prepare_publish(
   let Some((scope, name_no_scope)) = name_no_at.split_once('/') else {
     bail!("Invalid package name, use '@<scope_name>/<package_name> format");
   };
-  let file_patterns = deno_json.to_publish_config()?.map(|c| c.files);
-
-  let diagnostics_collector = diagnostics_collector.clone();
+  let file_patterns = config::to_publish_config(deno_json)?.map(|c| c.files);
+  let cli_publish_config = CliPublishConfig::from_config_file(deno_json)?;
+  let provenance_disabled = cli_publish_config.provenance.disabled;
+
+  let targets_node =
+    node_compat::targets_node(&cli_publish_config.runtime_compat);
+  // the JSX and TSX modules are transpiled with the compiler options of the
+  // package, rather than the ones of the workspace
+  let transpile_emit_options = if cli_publish_config.transpile_jsx {
+    let ts_config_for_emit = deno_config::get_ts_config_for_emit(
+      deno_config::TsConfigType::Emit,
+      Some(deno_json),
+    )?;
+    Some(crate::args::ts_config_to_emit_options(
+      ts_config_for_emit.ts_config,
+    ))
+  } else {
+    None
+  };
+  let dev_imports = cli_publish_config.dev_imports.clone();
+  let member_config = deno_json.clone();
+  let tarball_diagnostics_collector = diagnostics_collector.clone();
+  // analyzing the package and creating the tarball is CPU bound, so it's done
+  // on the blocking thread pool, where the members are prepared in parallel
   let tarball = deno_core::unsync::spawn_blocking(move || {
+    let diagnostics_collector = &tarball_diagnostics_collector;
+    exports::collect_exports_conflicts(&member_config, diagnostics_collector);
+    let exported_modules = graph::exported_modules(&graph, &member_config)?;
+    // documenting the package is slow, so only done when the rule is enabled
+    if diagnostics_collector.is_rule_enabled("missing-jsdoc") {
+      graph::collect_missing_jsdoc(
+        &graph,
+        &source_cache.as_capturing_parser(),
+        &member_config,
+        diagnostics_collector,
+      )?;
+    }
+    if targets_node {
+      node_compat::collect_deno_only_apis(
+        LazyGraphSourceParser::new(&source_cache, &graph),
+        &exported_modules,
+        diagnostics_collector,
+      )?;
+    }
+
+    let renamed_modules = match &transpile_emit_options {
+      Some(_) => {
+        let dir_url = Url::from_directory_path(&dir_path).unwrap();
+        transpile::renamed_modules(&graph, &dir_url)
+      }
+      None => HashMap::new(),
+    };
     let unfurler = SpecifierUnfurler::new(
       &mapped_resolver,
       sloppy_imports_resolver.as_ref(),
       bare_node_builtins,
-    );
-    tar::create_gzipped_tarball(
+    )
+    .with_workspace_members(&workspace_members)
+    .with_renamed_modules(&renamed_modules)
+    .with_dev_imports(&dev_imports, &exported_modules);
+    let jsx_transpiler = transpile_emit_options.map(|emit_options| {
+      JsxTranspiler::new(
+        emit_options,
+        &graph,
+        &unfurler,
+        member_config.specifier.clone(),
+      )
+    });
+    let tarball = tar::create_gzipped_tarball(
       &dir_path,
       LazyGraphSourceParser::new(&source_cache, &graph),
-      &diagnostics_collector,
+      diagnostics_collector,
       &unfurler,
+      jsx_transpiler.as_ref(),
       file_patterns,
     )
-    .context("Failed to create a tarball")
+    .context("Failed to create a tarball")?;
+
+    graph::collect_unreachable_files(
+      &exported_modules,
+      &tarball.files,
+      diagnostics_collector,
+    );
+    graph::collect_excluded_modules(
+      &exported_modules,
+      &dir_path,
+      &tarball.files,
+      diagnostics_collector,
+    );
+    // the sources of the package aren't needed after packaging it, and the
+    // other members parse them again in the rare case they need them
+    for file in &tarball.files {
+      source_cache.free(&file.specifier);
+    }
+    Ok::<_, AnyError>(tarball)
   })
   .await??;
 
-  log::debug!("Tarball size ({}): {}", package_name, tarball.bytes.len());
+  log::debug!(
+    "Tarball size ({}): {}",
+    package_name,
+    tarball.temp_file.size()
+  );
 
   Ok(Rc::new(PreparedPublishPackage {
     scope: scope.to_string(),
     package: name_no_scope.to_string(),
     version: version.to_string(),
     tarball,
+    sbom,
+    provenance_disabled,
     exports: match &deno_json.json.exports {
       Some(Value::Object(exports)) => exports
         .into_iter()
         .map(|(k, v)| (k.to_string(), v.as_str().unwrap().to_string()))
-        .collect(),
+        .collect::<HashMap<_, _>>(),
       Some(Value::String(exports)) => {
         let mut map = HashMap::new();
         map.insert(".".to_string(), exports.to_string());
         map
       }
       _ => HashMap::new(),
-    },
+    }
+    .into_iter()
+    .map(|(key, path)| match cli_publish_config.transpile_jsx {
+      true => (key, transpile::transpiled_path(&path).unwrap_or(path)),
+      false => (key, path),
+    })
+    .collect(),
     // the config file is always at the root of a publishing dir,
     // so getting the file name is always correct
     config: config_path
@@ -201,13 +397,10 @@ pub enum Permission<'s> {
   },
 }
 
-async fn get_auth_headers(
-  client: &reqwest::Client,
-  registry_url: String,
-  packages: Vec<Rc<PreparedPublishPackage>>,
-  auth_method: AuthMethod,
-) -> Result<HashMap<(String, String, String), Rc<str>>, AnyError> {
-  let permissions = packages
+fn publish_permissions(
+  packages: &[Rc<PreparedPublishPackage>],
+) -> Vec<Permission<'_>> {
+  packages
     .iter()
     .map(|package| Permission::VersionPublish {
       scope: &package.scope,
@@ -215,170 +408,252 @@ async fn get_auth_headers(
       version: &package.version,
       tarball_hash: &package.tarball.hash,
     })
-    .collect::<Vec<_>>();
+    .collect()
+}
+
+/// An error from requesting an authorization for a set of permissions.
+enum AuthorizationRequestError {
+  /// The request covered more permissions than the server accepts, so it
+  /// should be retried with fewer permissions.
+  TooLarge(AnyError),
+  Other(AnyError),
+}
 
+impl From<AnyError> for AuthorizationRequestError {
+  fn from(err: AnyError) -> Self {
+    AuthorizationRequestError::Other(err)
+  }
+}
+
+fn is_too_large_status(status: reqwest::StatusCode) -> bool {
+  matches!(
+    status,
+    reqwest::StatusCode::PAYLOAD_TOO_LARGE
+      | reqwest::StatusCode::URI_TOO_LONG
+      | reqwest::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+  )
+}
+
+/// Whether the OIDC provider rejected a token request because its audience
+/// is too long. Providers reject it as a bad request that says so, while
+/// the other bad requests, eg. for an invalid audience or request token,
+/// are reported right away instead of splitting up the packages.
+fn is_oidc_audience_too_large(status: reqwest::StatusCode, text: &str) -> bool {
+  if is_too_large_status(status) {
+    return true;
+  }
+  if status != reqwest::StatusCode::BAD_REQUEST {
+    return false;
+  }
+  let text = text.to_lowercase();
+  ["too long", "too large", "exceeds the maximum"]
+    .iter()
+    .any(|message| text.contains(message))
+}
+
+/// Gets the authorization for publishing each package.
+///
+/// A single authorization covering all the packages is requested whenever
+/// possible. Only when the registry or OIDC provider rejects a request for
+/// being too large are the packages split up and authorized in chunks.
+async fn get_auth_headers(
+  client: &reqwest::Client,
+  api_client: &JsrApiClient,
+  packages: Vec<Rc<PreparedPublishPackage>>,
+  auth_method: &AuthMethod,
+  auth_header_template: &AuthHeaderTemplate,
+) -> Result<HashMap<(String, String, String), AuthHeader>, AnyError> {
   let mut authorizations = HashMap::with_capacity(packages.len());
 
-  match auth_method {
-    AuthMethod::Interactive => {
-      let verifier = uuid::Uuid::new_v4().to_string();
-      let challenge = BASE64_STANDARD.encode(sha2::Sha256::digest(&verifier));
-
-      let response = client
-        .post(format!("{}authorizations", registry_url))
-        .json(&serde_json::json!({
-          "challenge": challenge,
-          "permissions": permissions,
-        }))
-        .send()
-        .await
-        .context("Failed to create interactive authorization")?;
-      let auth =
-        api::parse_response::<api::CreateAuthorizationResponse>(response)
-          .await
-          .context("Failed to create interactive authorization")?;
+  if let AuthMethod::Token(token) = auth_method {
+    let authorization = auth_header_template.token(token);
+    for pkg in &packages {
+      authorizations.insert(pkg.auth_key(), authorization.clone());
+    }
+    return Ok(authorizations);
+  }
 
-      let auth_url = format!("{}?code={}", auth.verification_url, auth.code);
-      print!(
-        "Visit {} to authorize publishing of",
-        colors::cyan(&auth_url)
-      );
-      if packages.len() > 1 {
-        println!(" {} packages", packages.len());
-      } else {
-        println!(" @{}/{}", packages[0].scope, packages[0].package);
-      }
+  let mut pending_chunks = match auth_method {
+    AuthMethod::Oidc(oidc_config) => auth::chunk_oidc_permissions(
+      &publish_permissions(&packages),
+      &oidc_config.limits,
+    )
+    .into_iter()
+    .map(|range| packages[range].to_vec())
+    .collect::<VecDeque<_>>(),
+    AuthMethod::Interactive | AuthMethod::Token(_) => {
+      VecDeque::from([packages.clone()])
+    }
+  };
 
-      ring_bell();
-      println!("{}", colors::gray("Waiting..."));
-      let _ = open::that_detached(&auth_url);
-
-      let interval = std::time::Duration::from_secs(auth.poll_interval);
-
-      loop {
-        tokio::time::sleep(interval).await;
-        let response = client
-          .post(format!("{}authorizations/exchange", registry_url))
-          .json(&serde_json::json!({
-            "exchangeToken": auth.exchange_token,
-            "verifier": verifier,
-          }))
-          .send()
+  while let Some(chunk) = pending_chunks.pop_front() {
+    let result = match auth_method {
+      AuthMethod::Interactive => {
+        request_interactive_authorization(api_client, &chunk)
           .await
-          .context("Failed to exchange authorization")?;
-        let res =
-          api::parse_response::<api::ExchangeAuthorizationResponse>(response)
-            .await;
-        match res {
-          Ok(res) => {
-            println!(
-              "{} {} {}",
-              colors::green("Authorization successful."),
-              colors::gray("Authenticated as"),
-              colors::cyan(res.user.name)
-            );
-            let authorization: Rc<str> = format!("Bearer {}", res.token).into();
-            for pkg in &packages {
-              authorizations.insert(
-                (pkg.scope.clone(), pkg.package.clone(), pkg.version.clone()),
-                authorization.clone(),
-              );
-            }
-            break;
-          }
-          Err(err) => {
-            if err.code == "authorizationPending" {
-              continue;
-            } else {
-              return Err(err).context("Failed to exchange authorization");
-            }
-          }
+          .map(|token| auth_header_template.token(&token))
+      }
+      AuthMethod::Oidc(oidc_config) => {
+        request_oidc_token(client, oidc_config, &chunk)
+          .await
+          .map(|token| auth_header_template.oidc(&token))
+      }
+      AuthMethod::Token(_) => unreachable!(),
+    };
+    match result {
+      Ok(authorization) => {
+        for pkg in &chunk {
+          authorizations.insert(pkg.auth_key(), authorization.clone());
         }
       }
-    }
-    AuthMethod::Token(token) => {
-      let authorization: Rc<str> = format!("Bearer {}", token).into();
-      for pkg in &packages {
-        authorizations.insert(
-          (pkg.scope.clone(), pkg.package.clone(), pkg.version.clone()),
-          authorization.clone(),
+      Err(AuthorizationRequestError::TooLarge(err)) if chunk.len() > 1 => {
+        log::debug!(
+          "Authorization for {} packages was too large, splitting it up: {:#}",
+          chunk.len(),
+          err
         );
+        let (first, second) = chunk.split_at(chunk.len() / 2);
+        pending_chunks.push_front(second.to_vec());
+        pending_chunks.push_front(first.to_vec());
       }
+      Err(
+        AuthorizationRequestError::TooLarge(err)
+        | AuthorizationRequestError::Other(err),
+      ) => return Err(err),
     }
-    AuthMethod::Oidc(oidc_config) => {
-      let mut chunked_packages = packages.chunks(16);
-      for permissions in permissions.chunks(16) {
-        let audience = json!({ "permissions": permissions }).to_string();
-        let url = format!(
-          "{}&audience={}",
-          oidc_config.url,
-          percent_encoding::percent_encode(
-            audience.as_bytes(),
-            percent_encoding::NON_ALPHANUMERIC
-          )
-        );
+  }
 
-        let response = client
-          .get(url)
-          .bearer_auth(&oidc_config.token)
-          .send()
-          .await
-          .context("Failed to get OIDC token")?;
-        let status = response.status();
-        let text = response.text().await.with_context(|| {
-          format!("Failed to get OIDC token: status {}", status)
-        })?;
-        if !status.is_success() {
-          bail!(
-            "Failed to get OIDC token: status {}, response: '{}'",
-            status,
-            text
-          );
-        }
-        let api::OidcTokenResponse { value } = serde_json::from_str(&text)
-          .with_context(|| {
-            format!(
-              "Failed to parse OIDC token: '{}' (status {})",
-              text, status
-            )
-          })?;
+  Ok(authorizations)
+}
 
-        let authorization: Rc<str> = format!("githuboidc {}", value).into();
-        for pkg in chunked_packages.next().unwrap() {
-          authorizations.insert(
-            (pkg.scope.clone(), pkg.package.clone(), pkg.version.clone()),
-            authorization.clone(),
-          );
+/// Creates an authorization that the user approves in the browser and
+/// returns the resulting token.
+async fn request_interactive_authorization(
+  api_client: &JsrApiClient,
+  packages: &[Rc<PreparedPublishPackage>],
+) -> Result<String, AuthorizationRequestError> {
+  let verifier = uuid::Uuid::new_v4().to_string();
+  let challenge = BASE64_STANDARD.encode(sha2::Sha256::digest(&verifier));
+
+  let result = api_client
+    .create_authorization(&challenge, &publish_permissions(packages))
+    .await;
+  let auth = match result {
+    Ok(auth) => auth,
+    Err(err)
+      if api::as_api_error(&err)
+        .and_then(|err| err.status)
+        .is_some_and(is_too_large_status) =>
+    {
+      return Err(AuthorizationRequestError::TooLarge(err));
+    }
+    Err(err) => {
+      return Err(
+        err
+          .context("Failed to create interactive authorization")
+          .into(),
+      );
+    }
+  };
+
+  let auth_url = format!("{}?code={}", auth.verification_url, auth.code);
+  let packages_text = if packages.len() > 1 {
+    format!("{} packages", packages.len())
+  } else {
+    format!("@{}/{}", packages[0].scope, packages[0].package)
+  };
+  progress!(
+    "Visit {} to authorize publishing of {}",
+    colors::cyan(&auth_url),
+    packages_text
+  );
+
+  ring_bell();
+  progress!("{}", colors::gray("Waiting..."));
+  let _ = open::that_detached(&auth_url);
+
+  let interval = std::time::Duration::from_secs(auth.poll_interval);
+
+  loop {
+    tokio::time::sleep(interval).await;
+    let res = api_client
+      .exchange_authorization(&auth.exchange_token, &verifier)
+      .await;
+    match res {
+      Ok(res) => {
+        progress!(
+          "{} {} {}",
+          colors::green("Authorization successful."),
+          colors::gray("Authenticated as"),
+          colors::cyan(res.user.name)
+        );
+        return Ok(res.token);
+      }
+      Err(err) => {
+        if api::as_api_error(&err).is_some_and(|err| {
+          matches!(err.kind, ApiErrorKind::AuthorizationPending)
+        }) {
+          continue;
+        } else {
+          return Err(err.context("Failed to exchange authorization").into());
         }
       }
     }
-  };
+  }
+}
 
-  Ok(authorizations)
+/// Requests an OIDC token from the CI provider with the permissions encoded
+/// in the audience.
+async fn request_oidc_token(
+  client: &reqwest::Client,
+  oidc_config: &auth::OidcConfig,
+  packages: &[Rc<PreparedPublishPackage>],
+) -> Result<String, AuthorizationRequestError> {
+  let url = format!(
+    "{}&audience={}",
+    oidc_config.url,
+    auth::encode_oidc_audience(&publish_permissions(packages))
+  );
+
+  let response = client
+    .get(url)
+    .bearer_auth(&oidc_config.token)
+    .send()
+    .await
+    .context("Failed to get OIDC token")?;
+  let status = response.status();
+  let text = response
+    .text()
+    .await
+    .with_context(|| format!("Failed to get OIDC token: status {}", status))?;
+  if !status.is_success() {
+    let err = deno_core::anyhow::anyhow!(
+      "Failed to get OIDC token: status {}, response: '{}'",
+      status,
+      text
+    );
+    if is_oidc_audience_too_large(status, &text) {
+      return Err(AuthorizationRequestError::TooLarge(err));
+    }
+    return Err(err.into());
+  }
+  let api::OidcTokenResponse { value } = serde_json::from_str(&text)
+    .with_context(|| {
+      format!("Failed to parse OIDC token: '{}' (status {})", text, status)
+    })?;
+  Ok(value)
 }
 
 /// Check if both `scope` and `package` already exist, if not return
 /// a URL to the management panel to create them.
 async fn check_if_scope_and_package_exist(
-  client: &reqwest::Client,
-  registry_api_url: &str,
+  api_client: &JsrApiClient,
   registry_manage_url: &str,
   scope: &str,
   package: &str,
 ) -> Result<Option<String>, AnyError> {
-  let mut needs_scope = false;
-  let mut needs_package = false;
-
-  let response = api::get_scope(client, registry_api_url, scope).await?;
-  if response.status() == 404 {
-    needs_scope = true;
-  }
-
-  let response =
-    api::get_package(client, registry_api_url, scope, package).await?;
-  if response.status() == 404 {
-    needs_package = true;
-  }
+  let needs_scope = api_client.get_scope(scope).await?.is_none();
+  let needs_package = api_client.get_package(scope, package).await?.is_none();
 
   if needs_scope || needs_package {
     let create_url = format!(
@@ -392,8 +667,7 @@ async fn check_if_scope_and_package_exist(
 }
 
 async fn ensure_scopes_and_packages_exist(
-  client: &reqwest::Client,
-  registry_api_url: String,
+  api_client: &JsrApiClient,
   registry_manage_url: String,
   packages: Vec<Rc<PreparedPublishPackage>>,
 ) -> Result<(), AnyError> {
@@ -401,8 +675,7 @@ async fn ensure_scopes_and_packages_exist(
     let mut missing_packages_lines = vec![];
     for package in packages {
       let maybe_create_package_url = check_if_scope_and_package_exist(
-        client,
-        &registry_api_url,
+        api_client,
         &registry_manage_url,
         &package.scope,
         &package.package,
@@ -425,8 +698,7 @@ async fn ensure_scopes_and_packages_exist(
 
   for package in packages {
     let maybe_create_package_url = check_if_scope_and_package_exist(
-      client,
-      &registry_api_url,
+      api_client,
       &registry_manage_url,
       &package.scope,
       &package.package,
@@ -438,27 +710,24 @@ async fn ensure_scopes_and_packages_exist(
     };
 
     ring_bell();
-    println!(
+    progress!(
       "'@{}/{}' doesn't exist yet. Visit {} to create the package",
       &package.scope,
       &package.package,
       colors::cyan_with_underline(&create_package_url)
     );
-    println!("{}", colors::gray("Waiting..."));
+    progress!("{}", colors::gray("Waiting..."));
     let _ = open::that_detached(&create_package_url);
 
-    let package_api_url = api::get_package_api_url(
-      &registry_api_url,
-      &package.scope,
-      &package.package,
-    );
-
     loop {
       tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-      let response = client.get(&package_api_url).send().await?;
-      if response.status() == 200 {
+      let created = api_client
+        .get_package(&package.scope, &package.package)
+        .await?
+        .is_some();
+      if created {
         let name = format!("@{}/{}", package.scope, package.package);
-        println!("Package {} created", colors::green(name));
+        progress!("Package {} created", colors::green(name));
         break;
       }
     }
@@ -467,36 +736,184 @@ async fn ensure_scopes_and_packages_exist(
   Ok(())
 }
 
+/// Creates the scopes and packages that don't exist yet, for `--create`.
+async fn create_scopes_and_packages(
+  api_client: &JsrApiClient,
+  authorization: &AuthHeader,
+  packages: &[Rc<PreparedPublishPackage>],
+) -> Result<(), AnyError> {
+  let mut checked_scopes = HashSet::new();
+  for package in packages {
+    if checked_scopes.insert(package.scope.clone())
+      && api_client.get_scope(&package.scope).await?.is_none()
+    {
+      api_client
+        .create_scope(authorization, &package.scope)
+        .await
+        .with_context(|| {
+          format!("Failed to create scope @{}", package.scope)
+        })?;
+      progress!(
+        "Scope {} created",
+        colors::green(format!("@{}", package.scope))
+      );
+    }
+
+    let exists = api_client
+      .get_package(&package.scope, &package.package)
+      .await?
+      .is_some();
+    if !exists {
+      let name = format!("@{}/{}", package.scope, package.package);
+      api_client
+        .create_package(authorization, &package.scope, &package.package)
+        .await
+        .with_context(|| {
+          format!(
+            "Failed to create package {}. The owner of the token must be an admin of @{}",
+            name, package.scope
+          )
+        })?;
+      progress!("Package {} created", colors::green(name));
+    }
+  }
+  Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn perform_publish(
   http_client: &Arc<HttpClient>,
+  registry_http_client: &Arc<HttpClient>,
+  api_client: JsrApiClient,
+  registry_url: &Url,
+  manage_url: &Url,
   mut publish_order_graph: PublishOrderGraph,
   mut prepared_package_by_name: HashMap<String, Rc<PreparedPublishPackage>>,
   auth_method: AuthMethod,
-  no_provenance: bool,
-) -> Result<(), AnyError> {
-  let client = http_client.client()?;
-  let registry_api_url = jsr_api_url().to_string();
-  let registry_url = jsr_url().to_string();
+  auth_header_template: AuthHeaderTemplate,
+  provenance_options: Rc<ProvenanceOptions>,
+  create: bool,
+  wait_for_available: bool,
+) -> Result<Vec<PublishedPackageJson>, AnyError> {
+  let client = registry_http_client.client()?;
+  let manage_url = manage_url.to_string();
 
   let packages = prepared_package_by_name
     .values()
     .cloned()
     .collect::<Vec<_>>();
 
-  ensure_scopes_and_packages_exist(
+  match &auth_method {
+    AuthMethod::Token(token) if create => {
+      create_scopes_and_packages(
+        &api_client,
+        &auth_header_template.token(token),
+        &packages,
+      )
+      .await?
+    }
+    _ => {
+      ensure_scopes_and_packages_exist(
+        &api_client,
+        manage_url.clone(),
+        packages.clone(),
+      )
+      .await?
+    }
+  }
+
+  let mut authorizations = get_auth_headers(
     client,
-    registry_api_url.clone(),
-    registry_url.clone(),
-    packages.clone(),
+    &api_client,
+    packages,
+    &auth_method,
+    &auth_header_template,
   )
   .await?;
 
-  let mut authorizations =
-    get_auth_headers(client, registry_api_url.clone(), packages, auth_method)
-      .await?;
-
   assert_eq!(prepared_package_by_name.len(), authorizations.len());
-  let mut futures: JoinSet<Result<String, AnyError>> = JoinSet::default();
+  let all_packages = prepared_package_by_name.clone();
+  let mut reauthenticated_packages = HashSet::new();
+  let mut futures: JoinSet<(
+    String,
+    AuthHeader,
+    Result<PublishStep, AnyError>,
+  )> = JoinSet::default();
+  let spawn_publish = |futures: &mut JoinSet<_>,
+                       package_name: String,
+                       package: Rc<PreparedPublishPackage>,
+                       authorization: AuthHeader| {
+    let api_client = api_client.clone();
+    let registry_url = registry_url.clone();
+    let manage_url = manage_url.clone();
+    let registry_http_client = registry_http_client.clone();
+    futures.spawn(async move {
+      let display_name = package.display_name();
+      let result = publish_package(
+        &api_client,
+        &registry_http_client,
+        package,
+        &registry_url,
+        &manage_url,
+        &authorization,
+        wait_for_available,
+      )
+      .await
+      .map(|meta_bytes| PublishStep::Uploaded { meta_bytes })
+      .with_context(|| format!("Failed to publish {}", display_name));
+      (package_name, authorization, result)
+    });
+  };
+  // the provenance is generated while the dependents of the package are
+  // published
+  let spawn_provenance = |futures: &mut JoinSet<_>,
+                          package_name: String,
+                          package: Rc<PreparedPublishPackage>,
+                          meta_bytes: Bytes,
+                          authorization: AuthHeader| {
+    let api_client = api_client.clone();
+    let manage_url = manage_url.clone();
+    let http_client = http_client.clone();
+    let provenance_options = provenance_options.clone();
+    futures.spawn(async move {
+      let display_name = package.display_name();
+      let result = publish_package_provenance(
+        &api_client,
+        &http_client,
+        package,
+        meta_bytes,
+        &manage_url,
+        &authorization,
+        &provenance_options,
+      )
+      .await;
+      let result = match result {
+        Ok(provenance) => Ok(PublishStep::ProvenanceDone {
+          provenance,
+          error: None,
+        }),
+        Err(err) if !provenance_options.required => {
+          log::warn!(
+            "{} Failed to publish the provenance of {}: {:#}",
+            colors::yellow("Warning"),
+            display_name,
+            err
+          );
+          Ok(PublishStep::ProvenanceDone {
+            provenance: None,
+            error: Some(format!("{:#}", err)),
+          })
+        }
+        Err(err) => Err(err.context(format!(
+          "Failed to publish the provenance of {}",
+          display_name
+        ))),
+      };
+      (package_name, authorization, result)
+    });
+  };
+
+  let mut published_packages = BTreeMap::new();
   loop {
     let next_batch = publish_order_graph.next();
 
@@ -515,30 +932,9 @@ async fn perform_publish(
         }
       }
 
-      let authorization = authorizations
-        .remove(&(
-          package.scope.clone(),
-          package.package.clone(),
-          package.version.clone(),
-        ))
-        .unwrap();
-      let registry_api_url = registry_api_url.clone();
-      let registry_url = registry_url.clone();
-      let http_client = http_client.clone();
-      futures.spawn(async move {
-        let display_name = package.display_name();
-        publish_package(
-          &http_client,
-          package,
-          &registry_api_url,
-          &registry_url,
-          &authorization,
-          no_provenance,
-        )
-        .await
-        .with_context(|| format!("Failed to publish {}", display_name))?;
-        Ok(package_name)
-      });
+      let authorization =
+        authorizations.get(&package.auth_key()).unwrap().clone();
+      spawn_publish(&mut futures, package_name, package, authorization);
     }
 
     let Some(result) = futures.join_next().await else {
@@ -547,23 +943,134 @@ async fn perform_publish(
       break;
     };
 
-    let package_name = result??;
-    publish_order_graph.finish_package(&package_name);
+    let (package_name, used_authorization, result) = result?;
+    let package = all_packages.get(&package_name).unwrap().clone();
+    match result {
+      Ok(PublishStep::Uploaded { meta_bytes }) => {
+        authorizations.remove(&package.auth_key());
+        publish_order_graph.finish_package(&package_name);
+        published_packages.insert(
+          package_name.clone(),
+          PublishedPackageJson {
+            name: package.display_name(),
+            version: package.version.clone(),
+            published: meta_bytes.is_some(),
+            provenance: None,
+            provenance_error: None,
+          },
+        );
+        if let Some(meta_bytes) = meta_bytes {
+          spawn_provenance(
+            &mut futures,
+            package_name,
+            package,
+            meta_bytes,
+            used_authorization,
+          );
+        }
+      }
+      Ok(PublishStep::ProvenanceDone { provenance, error }) => {
+        let published_package =
+          published_packages.get_mut(&package_name).unwrap();
+        published_package.provenance = provenance;
+        published_package.provenance_error = error;
+      }
+      Err(err)
+        if err.downcast_ref::<UnauthorizedError>().is_some()
+          && auth_method.can_reauthenticate()
+          && reauthenticated_packages.insert(package_name.clone()) =>
+      {
+        // Another package might have already refreshed the authorization
+        // after this request was sent.
+        if authorizations.get(&package.auth_key()) == Some(&used_authorization)
+        {
+          log::warn!(
+            "{} The authorization for {} was rejected, it may have expired. Re-authenticating...",
+            colors::yellow("Warning"),
+            package.display_name(),
+          );
+          // re-authenticate every package that hasn't been published yet
+          let pending_packages = all_packages
+            .values()
+            .filter(|p| authorizations.contains_key(&p.auth_key()))
+            .cloned()
+            .collect::<Vec<_>>();
+          authorizations.extend(
+            get_auth_headers(
+              client,
+              &api_client,
+              pending_packages,
+              &auth_method,
+              &auth_header_template,
+            )
+            .await
+            .context("Failed to re-authenticate")?,
+          );
+        }
+        let authorization =
+          authorizations.get(&package.auth_key()).unwrap().clone();
+        spawn_publish(&mut futures, package_name, package, authorization);
+      }
+      Err(err) => return Err(err),
+    }
   }
 
-  Ok(())
+  Ok(published_packages.into_values().collect())
+}
+
+/// The steps of publishing a package that finished.
+enum PublishStep {
+  /// The package was uploaded, or skipped when it was already published.
+  Uploaded {
+    /// The verified version manifest, `None` when already published.
+    meta_bytes: Option<Bytes>,
+  },
+  ProvenanceDone {
+    provenance: Option<ProvenanceJson>,
+    /// The error when provenance is not required.
+    error: Option<String>,
+  },
 }
 
+/// A package in the `--json` output of `deno publish`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedPackageJson {
+  name: String,
+  version: String,
+  /// Whether the version was published, rather than already published.
+  published: bool,
+  provenance: Option<ProvenanceJson>,
+  provenance_error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProvenanceJson {
+  subject: provenance::Subject,
+  /// Not set when the provenance is signed with a key.
+  certificate_identity: Option<String>,
+  log_index: u64,
+  transparency_log_url: String,
+  /// The file the bundle was written to with `--provenance-defer`, or the
+  /// URL it was submitted to.
+  bundle: String,
+}
+
+/// Uploads a package and waits for the registry to publish it. Returns the
+/// version manifest of the published package, or `None` when the version
+/// was already published.
 async fn publish_package(
-  http_client: &HttpClient,
+  api_client: &JsrApiClient,
+  registry_http_client: &HttpClient,
   package: Rc<PreparedPublishPackage>,
-  registry_api_url: &str,
-  registry_url: &str,
-  authorization: &str,
-  no_provenance: bool,
-) -> Result<(), AnyError> {
-  let client = http_client.client()?;
-  println!(
+  registry_url: &Url,
+  manage_url: &str,
+  authorization: &AuthHeader,
+  wait_for_available: bool,
+) -> Result<Option<Bytes>, AnyError> {
+  let client = registry_http_client.client()?;
+  progress!(
     "{} @{}/{}@{} ...",
     colors::intense_blue("Publishing"),
     package.scope,
@@ -571,51 +1078,55 @@ async fn publish_package(
     package.version
   );
 
-  let url = format!(
-    "{}scopes/{}/packages/{}/versions/{}?config=/{}",
-    registry_api_url,
-    package.scope,
-    package.package,
-    package.version,
-    package.config
-  );
-
-  let response = client
-    .post(url)
-    .header(reqwest::header::AUTHORIZATION, authorization)
-    .header(reqwest::header::CONTENT_ENCODING, "gzip")
-    .body(package.tarball.bytes.clone())
-    .send()
-    .await?;
-
-  let res = api::parse_response::<api::PublishingTask>(response).await;
+  let res = upload_package(api_client, &package, authorization)
+    .await
+    .map_err(|err| err.downcast::<ApiError>());
   let mut task = match res {
     Ok(task) => task,
-    Err(mut err) if err.code == "duplicateVersionPublish" => {
-      let task = serde_json::from_value::<api::PublishingTask>(
-        err.data.get_mut("task").unwrap().take(),
-      )
-      .unwrap();
+    Err(Err(err)) => return Err(err),
+    Err(Ok(err)) if err.status == Some(reqwest::StatusCode::UNAUTHORIZED) => {
+      return Err(UnauthorizedError(err).into());
+    }
+    Err(Ok(err)) if err.status == Some(reqwest::StatusCode::FORBIDDEN) => {
+      return Err(
+        match api::publish_permission_error(
+          err,
+          manage_url,
+          &package.scope,
+          &package.package,
+        ) {
+          Ok(err) => err.into(),
+          Err(err) => AnyError::from(err).context(format!(
+            "Failed to publish @{}/{} at {}",
+            package.scope, package.package, package.version
+          )),
+        },
+      );
+    }
+    Err(Ok(ApiError {
+      kind: ApiErrorKind::DuplicateVersionPublish(task),
+      ..
+    })) => {
       if task.status == "success" {
-        println!(
+        progress!(
           "{} @{}/{}@{}",
           colors::yellow("Warning: Skipping, already published"),
           package.scope,
           package.package,
           package.version
         );
-        return Ok(());
+        return Ok(None);
       }
-      println!(
+      progress!(
         "{} @{}/{}@{}",
         colors::yellow("Already uploaded, waiting for publishing"),
         package.scope,
         package.package,
         package.version
       );
-      task
+      *task
     }
-    Err(err) => {
+    Err(Ok(err)) => {
       return Err(err).with_context(|| {
         format!(
           "Failed to publish @{}/{} at {}",
@@ -626,26 +1137,37 @@ async fn publish_package(
   };
 
   let interval = std::time::Duration::from_secs(2);
+  if task.status != "success" && task.status != "failure" {
+    progress!(
+      "{}",
+      colors::gray(format!(
+        "Waiting for publishing task {}, check it later with `deno registry status {}`",
+        task.id, task.id
+      ))
+    );
+  }
   while task.status != "success" && task.status != "failure" {
     tokio::time::sleep(interval).await;
-    let resp = client
-      .get(format!("{}publish_status/{}", registry_api_url, task.id))
-      .send()
+    task = match api_client
+      .get_publishing_task(Some(authorization), &task.id)
       .await
-      .with_context(|| {
-        format!(
-          "Failed to get publishing status for @{}/{} at {}",
-          package.scope, package.package, package.version
-        )
-      })?;
-    task = api::parse_response::<api::PublishingTask>(resp)
-      .await
-      .with_context(|| {
-        format!(
+    {
+      Ok(task) => task,
+      Err(err) => {
+        let err = match err.downcast::<ApiError>() {
+          // the authorization can expire while the registry is publishing
+          Ok(err) if err.status == Some(reqwest::StatusCode::UNAUTHORIZED) => {
+            return Err(UnauthorizedError(err).into());
+          }
+          Ok(err) => AnyError::from(err),
+          Err(err) => err,
+        };
+        return Err(err.context(format!(
           "Failed to get publishing status for @{}/{} at {}",
           package.scope, package.package, package.version
-        )
-      })?;
+        )));
+      }
+    };
   }
 
   if let Some(error) = task.error {
@@ -659,7 +1181,7 @@ async fn publish_package(
     );
   }
 
-  println!(
+  progress!(
     "{} @{}/{}@{}",
     colors::green("Successfully published"),
     package.scope,
@@ -667,23 +1189,298 @@ async fn publish_package(
     package.version
   );
 
-  let enable_provenance = std::env::var("DISABLE_JSR_PROVENANCE").is_err()
-    || (auth::is_gha() && auth::gha_oidc_token().is_some() && !no_provenance);
+  let meta_bytes =
+    fetch_and_verify_version_manifest(api_client, registry_url, &package)
+      .await?;
+  if wait_for_available {
+    wait_for_version_available(client, registry_url, &package).await?;
+  }
+  Ok(Some(meta_bytes))
+}
 
-  // Enable provenance by default on Github actions with OIDC token
-  if enable_provenance {
-    // Get the version manifest from the registry
-    let meta_url = jsr_url().join(&format!(
-      "@{}/{}/{}_meta.json",
-      package.scope, package.package, package.version
-    ))?;
+/// Tarballs larger than this are uploaded in chunks when the registry
+/// supports it, so that a failed request only has to send one chunk again
+/// instead of the whole tarball.
+const CHUNKED_UPLOAD_THRESHOLD: usize = 16 * 1024 * 1024;
+/// The size of the chunks when the registry doesn't limit it.
+const DEFAULT_UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+/// How often uploading a chunk is retried.
+const UPLOAD_CHUNK_RETRIES: u32 = 3;
+
+/// Uploads the tarball of a package, in chunks when it's large and the
+/// registry supports it.
+async fn upload_package(
+  api_client: &JsrApiClient,
+  package: &PreparedPublishPackage,
+  authorization: &AuthHeader,
+) -> Result<api::PublishingTask, AnyError> {
+  let tarball = encode_tarball(package, api_client.upload_encoding())?;
+  if tarball.bytes.len() > CHUNKED_UPLOAD_THRESHOLD {
+    let maybe_session = api_client
+      .create_upload(
+        authorization,
+        &package.scope,
+        &package.package,
+        &package.version,
+        &package.config,
+        &tarball,
+      )
+      .await?;
+    if let Some(session) = maybe_session {
+      return upload_package_in_chunks(
+        api_client,
+        package,
+        &tarball,
+        authorization,
+        session,
+      )
+      .await;
+    }
+  }
+  api_client
+    .publish_version(
+      authorization,
+      &package.scope,
+      &package.package,
+      &package.version,
+      &package.config,
+      tarball,
+    )
+    .await
+}
 
-    let meta_bytes = client.get(meta_url).send().await?.bytes().await?;
+/// Compresses the tarball of a package with the encoding negotiated with the
+/// registry. The tarball is created gzipped, so it's only recompressed when
+/// that makes the upload smaller.
+fn encode_tarball(
+  package: &PreparedPublishPackage,
+  encoding: UploadEncoding,
+) -> Result<EncodedTarball, AnyError> {
+  let gz_bytes = package.tarball.temp_file.read()?;
+  let gz_size = gz_bytes.len();
+  let tarball = match encoding {
+    UploadEncoding::Zstd => {
+      let zstd_bytes = tar::recompress_with_zstd(&gz_bytes)?;
+      if zstd_bytes.len() < gz_size {
+        EncodedTarball {
+          bytes: zstd_bytes.into(),
+          encoding,
+        }
+      } else {
+        EncodedTarball {
+          bytes: gz_bytes,
+          encoding: UploadEncoding::Gzip,
+        }
+      }
+    }
+    UploadEncoding::Gzip => EncodedTarball {
+      bytes: gz_bytes,
+      encoding,
+    },
+  };
+  log::debug!(
+    "Uploading {} with {} content encoding ({}, gzip {})",
+    package.display_name(),
+    tarball.encoding.as_str(),
+    human_size(tarball.bytes.len() as f64),
+    human_size(gz_size as f64)
+  );
+  Ok(tarball)
+}
 
-    if std::env::var("DISABLE_JSR_MANIFEST_VERIFICATION_FOR_TESTING").is_err() {
-      verify_version_manifest(&meta_bytes, &package)?;
+async fn upload_package_in_chunks(
+  api_client: &JsrApiClient,
+  package: &PreparedPublishPackage,
+  tarball: &EncodedTarball,
+  authorization: &AuthHeader,
+  session: api::UploadSession,
+) -> Result<api::PublishingTask, AnyError> {
+  let tarball = &tarball.bytes;
+  let chunk_size = session
+    .chunk_size
+    .unwrap_or(DEFAULT_UPLOAD_CHUNK_SIZE)
+    .max(1) as usize;
+  let mut offset = session.offset as usize;
+  let mut failures = 0;
+  while offset < tarball.len() {
+    let end = (offset + chunk_size).min(tarball.len());
+    log::debug!(
+      "Uploading bytes {}-{} of {} of {}",
+      offset,
+      end,
+      tarball.len(),
+      package.display_name()
+    );
+    let result = api_client
+      .upload_chunk(
+        authorization,
+        &session.id,
+        offset as u64,
+        tarball.slice(offset..end),
+      )
+      .await;
+    match result {
+      Ok(updated) if updated.offset as usize > offset => {
+        offset = updated.offset as usize;
+        failures = 0;
+      }
+      Ok(updated) => bail!(
+        "The registry did not accept the upload of {} at offset {} (offset {})",
+        package.display_name(),
+        offset,
+        updated.offset
+      ),
+      Err(err)
+        if failures < UPLOAD_CHUNK_RETRIES
+          && is_retryable_upload_error(&err) =>
+      {
+        failures += 1;
+        log::warn!(
+          "{} Uploading {} failed, resuming at the last received chunk: {:#}",
+          colors::yellow("Warning"),
+          package.display_name(),
+          err
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(failures as u64))
+          .await;
+        // the registry may have received a part of the chunk
+        offset = api_client
+          .get_upload(authorization, &session.id)
+          .await?
+          .offset as usize;
+      }
+      Err(err) => return Err(err),
     }
+  }
+  api_client.complete_upload(authorization, &session.id).await
+}
+
+/// Network errors and server errors, but not errors the registry rejected
+/// the chunk with.
+fn is_retryable_upload_error(err: &AnyError) -> bool {
+  match api::as_api_error(err) {
+    Some(err) => err.status.is_some_and(|status| status.is_server_error()),
+    None => err.downcast_ref::<reqwest::Error>().is_some(),
+  }
+}
+
+/// How long `--wait-for-available` waits for a published version to show up
+/// in the package metadata served by the registry.
+const WAIT_FOR_AVAILABLE_TIMEOUT: std::time::Duration =
+  std::time::Duration::from_secs(300);
 
+/// Waits until the package metadata served by the registry, which is what
+/// `deno add` and the module resolution read, lists the published version.
+/// The metadata is cached by the CDN, so it can lag behind the publishing
+/// task.
+async fn wait_for_version_available(
+  client: &reqwest::Client,
+  registry_url: &Url,
+  package: &PreparedPublishPackage,
+) -> Result<(), AnyError> {
+  let meta_url = registry_url
+    .join(&format!("@{}/{}/meta.json", package.scope, package.package))?;
+  let version = Version::parse_standard(&package.version)?;
+  let started = std::time::Instant::now();
+  let interval = std::time::Duration::from_secs(2);
+  let mut waiting_logged = false;
+  loop {
+    let response = trace::send(client, client.get(meta_url.clone())).await?;
+    if response.status().is_success() {
+      let info =
+        serde_json::from_slice::<JsrPackageInfo>(&response.bytes().await?)?;
+      if info.versions.contains_key(&version) {
+        break;
+      }
+    }
+    if started.elapsed() >= WAIT_FOR_AVAILABLE_TIMEOUT {
+      bail!(
+        "{} was published, but isn't available from {} after {} seconds",
+        package.display_name(),
+        registry_url,
+        WAIT_FOR_AVAILABLE_TIMEOUT.as_secs()
+      );
+    }
+    if !waiting_logged {
+      progress!(
+        "{}",
+        colors::gray(format!(
+          "Waiting for {} to be available...",
+          package.display_name()
+        ))
+      );
+      waiting_logged = true;
+    }
+    tokio::time::sleep(interval).await;
+  }
+  progress!(
+    "{} {}",
+    package.display_name(),
+    colors::green("is available")
+  );
+  Ok(())
+}
+
+/// Downloads the version manifest of a published package and verifies that
+/// the registry serves the files that were uploaded.
+async fn fetch_and_verify_version_manifest(
+  api_client: &JsrApiClient,
+  registry_url: &Url,
+  package: &PreparedPublishPackage,
+) -> Result<Bytes, AnyError> {
+  let meta_path = format!(
+    "@{}/{}/{}_meta.json",
+    package.scope, package.package, package.version
+  );
+  let response = api_client
+    .get_registry_file(registry_url, &meta_path)
+    .await?;
+  if !response.status().is_success() {
+    bail!(
+      "Failed to get the version manifest of {}: status {}",
+      package.display_name(),
+      response.status()
+    );
+  }
+  let meta_bytes = response.bytes().await?;
+  verify_version_manifest(&meta_bytes, package).with_context(|| {
+    format!(
+      "{} was published, but the registry serves different files than were uploaded",
+      package.display_name()
+    )
+  })?;
+  Ok(meta_bytes)
+}
+
+async fn publish_package_provenance(
+  api_client: &JsrApiClient,
+  http_client: &HttpClient,
+  package: Rc<PreparedPublishPackage>,
+  meta_bytes: Bytes,
+  registry_url: &str,
+  authorization: &AuthHeader,
+  provenance_options: &ProvenanceOptions,
+) -> Result<Option<ProvenanceJson>, AnyError> {
+  let signing_key = provenance_options.signing_keys.get(&package.scope);
+  let enable_provenance = !package.provenance_disabled
+    && (std::env::var("DISABLE_JSR_PROVENANCE").is_err()
+      || (provenance::CiProvider::detect()
+        .is_some_and(|p| signing_key.is_some() || p.has_id_token())
+        && !provenance_options.disabled));
+  if !enable_provenance
+    && !package.provenance_disabled
+    && provenance_options.required
+  {
+    bail!(
+      "Provenance can only be generated on GitHub Actions, GitLab CI, Buildkite or CircleCI with an OIDC token or a signing key"
+    );
+  }
+
+  // Enable provenance by default on the supported CI providers with an OIDC
+  // token
+  let mut provenance_json = None;
+  if enable_provenance {
+    // the provenance attests to the manifest verified after publishing
     let subject = provenance::Subject {
       name: format!(
         "pkg:jsr/@{}/{}@{}",
@@ -693,53 +1490,243 @@ async fn publish_package(
         sha256: hex::encode(sha2::Sha256::digest(&meta_bytes)),
       },
     };
-    let bundle = provenance::generate_provenance(subject).await?;
+    let sbom_document = match &package.sbom {
+      Some(sbom) if provenance_options.attest_sbom => {
+        Some(provenance::AttestationDocument {
+          name: "SBOM".to_string(),
+          predicate_type: Some(sbom::CYCLONEDX_PREDICATE_TYPE.to_string()),
+          content: sbom.clone(),
+        })
+      }
+      _ => None,
+    };
+    let documents =
+      provenance_options.attestations.iter().chain(&sbom_document);
+    let attestations =
+      deno_core::futures::future::try_join_all(documents.map(|document| {
+        provenance::generate_attestation(
+          http_client,
+          &provenance_options.sigstore_config,
+          &provenance_options.keyless_session,
+          signing_key,
+          document,
+          &subject,
+        )
+      }));
+    let provenance = provenance::generate_provenance(
+      http_client,
+      &provenance_options.sigstore_config,
+      &provenance_options.keyless_session,
+      provenance_options.predicate_version,
+      signing_key,
+      subject.clone(),
+    );
+    let (attestations, bundle) =
+      deno_core::futures::try_join!(attestations, provenance)?;
 
     let tlog_entry = &bundle.verification_material.tlog_entries[0];
-    println!("{}",
+    progress!(
+      "{}",
       colors::green(format!(
-        "Provenance transparency log available at https://search.sigstore.dev/?logIndex={}",
-        tlog_entry.log_index
+        "Provenance transparency log available at {}",
+        provenance_options
+          .sigstore_config
+          .log_entry_url(tlog_entry.log_index)
       ))
-     );
-
-    // Submit bundle to JSR
-    let provenance_url = format!(
-      "{}scopes/{}/packages/{}/versions/{}/provenance",
-      registry_api_url, package.scope, package.package, package.version
     );
-    client
-      .post(provenance_url)
-      .header(reqwest::header::AUTHORIZATION, authorization)
-      .json(&json!({ "bundle": bundle }))
-      .send()
-      .await?;
+
+    let bundle_location = if let Some(defer_dir) = &provenance_options.defer_dir
+    {
+      let bundle_path = defer_dir.join(format!(
+        "{}_{}_{}.provenance.json",
+        package.scope, package.package, package.version
+      ));
+      std::fs::create_dir_all(defer_dir)?;
+      std::fs::write(&bundle_path, serde_json::to_string_pretty(&bundle)?)
+        .with_context(|| {
+          format!(
+            "Failed writing provenance bundle to '{}'",
+            bundle_path.display()
+          )
+        })?;
+      progress!(
+        "{} {}",
+        colors::green("Provenance bundle written to"),
+        bundle_path.display()
+      );
+      let attestations_arg = if attestations.is_empty() {
+        String::new()
+      } else {
+        let attestations_path = defer_dir.join(format!(
+          "{}_{}_{}.attestations.json",
+          package.scope, package.package, package.version
+        ));
+        std::fs::write(
+          &attestations_path,
+          serde_json::to_string_pretty(&attestations)?,
+        )
+        .with_context(|| {
+          format!(
+            "Failed writing attestations to '{}'",
+            attestations_path.display()
+          )
+        })?;
+        progress!(
+          "{} {}",
+          colors::green("Attestations written to"),
+          attestations_path.display()
+        );
+        format!(" --attestations {}", attestations_path.display())
+      };
+      progress!(
+        "{}",
+        colors::gray(format!(
+          "Attach it with `deno registry attach-provenance {} {}{}`",
+          bundle_path.display(),
+          package.display_name(),
+          attestations_arg
+        ))
+      );
+      bundle_path.display().to_string()
+    } else {
+      api_client
+        .submit_provenance(
+          authorization,
+          &package.scope,
+          &package.package,
+          &package.version,
+          &bundle,
+          &attestations,
+        )
+        .await
+        .with_context(|| {
+          format!("Failed to submit provenance for {}", package.display_name())
+        })?;
+      api_client.provenance_url(
+        &package.scope,
+        &package.package,
+        &package.version,
+      )
+    };
+
+    let inclusion_result = provenance::verify_bundle_inclusion(
+      http_client,
+      &provenance_options.sigstore_config,
+      &bundle,
+    )
+    .await;
+    if let Err(err) = inclusion_result {
+      let message = format!(
+        "Failed to prove that the provenance of {} is included in the transparency log",
+        package.display_name()
+      );
+      match provenance_options.inclusion_proof {
+        InclusionProofCheck::Error => return Err(err.context(message)),
+        InclusionProofCheck::Warn => {
+          log::warn!("{} {}: {:#}", colors::yellow("Warning"), message, err)
+        }
+      }
+    }
+
+    provenance_json = Some(ProvenanceJson {
+      subject,
+      certificate_identity: bundle.certificate_identity(),
+      log_index: tlog_entry.log_index,
+      transparency_log_url: provenance_options
+        .sigstore_config
+        .log_entry_url(tlog_entry.log_index),
+      bundle: bundle_location,
+    });
   }
 
-  println!(
+  progress!(
     "{}",
     colors::gray(format!(
       "Visit {}@{}/{}@{} for details",
       registry_url, package.scope, package.package, package.version
     ))
   );
-  Ok(())
+  Ok(provenance_json)
+}
+
+/// How provenance is generated for the published packages.
+struct ProvenanceOptions {
+  disabled: bool,
+  /// Fail when provenance can't be generated or submitted, instead of
+  /// printing a warning. The package is already published at that point.
+  required: bool,
+  /// Write the bundles to this directory instead of submitting them.
+  defer_dir: Option<PathBuf>,
+  sigstore_config: SigstoreConfig,
+  predicate_version: SlsaPredicateVersion,
+  attestations: Vec<provenance::AttestationDocument>,
+  /// Attach the SBOM of the packages as an attestation.
+  attest_sbom: bool,
+  /// Keys provenance is signed with instead of keyless signing, keyed by
+  /// scope name without the `@`.
+  signing_keys: HashMap<String, provenance::SigningKey>,
+  inclusion_proof: InclusionProofCheck,
+  keyless_session: provenance::KeylessSession,
 }
 
+/// The prepared workspace members. Their tarballs are kept in temporary
+/// files until uploading, and the parsed sources of their modules are
+/// already freed, so that this stays small for large workspaces.
 struct PreparePackagesData {
   publish_order_graph: PublishOrderGraph,
   package_by_name: HashMap<String, Rc<PreparedPublishPackage>>,
+  /// The members with `"publish": false` in their configuration file.
+  unpublished_names: Vec<String>,
+  /// The rendered public API of each package, with `--show-fast-check`.
+  public_api_by_name: HashMap<String, String>,
+}
+
+/// The workspace members selected with `--filter`.
+struct MemberFilter<'a> {
+  names: &'a [String],
+  include_dependencies: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn prepare_packages_for_publishing(
   cli_factory: &CliFactory,
   allow_slow_types: bool,
+  allow_pinned_remote_imports: bool,
+  check_libs: &[PublishCheckLib],
+  show_fast_check: bool,
+  create_sbom: bool,
   diagnostics_collector: &PublishDiagnosticsCollector,
-  deno_json: ConfigFile,
   mapped_resolver: Arc<MappedSpecifierResolver>,
+  member_filter: Option<MemberFilter<'_>>,
 ) -> Result<PreparePackagesData, AnyError> {
-  let members = deno_json.to_workspace_members()?;
+  let (all_members, unpublished_members) = partition_publishable_members(
+    cli_factory.cli_options().workspace_members()?,
+  );
+  if all_members.is_empty() && !unpublished_members.is_empty() {
+    bail!("No packages to publish, all of them have \"publish\": false in their configuration file");
+  }
+  publish_order::ensure_known_publish_after(
+    all_members.iter().chain(&unpublished_members),
+  )?;
+  // imports of other members by their path are rewritten to `jsr:`
+  // specifiers, including of the members that aren't published now
+  let unfurl_members = all_members
+    .iter()
+    .map(UnfurlWorkspaceMember::new)
+    .collect::<Arc<[_]>>();
   let module_graph_creator = cli_factory.module_graph_creator().await?.as_ref();
+  let members = match member_filter {
+    Some(member_filter) => {
+      filter_workspace_members(
+        module_graph_creator,
+        all_members,
+        &unpublished_members,
+        member_filter,
+      )
+      .await?
+    }
+    None => all_members,
+  };
   let source_cache = cli_factory.parsed_source_cache();
   let type_checker = cli_factory.type_checker().await?;
   let fs = cli_factory.fs();
@@ -747,7 +1734,7 @@ async fn prepare_packages_for_publishing(
   let bare_node_builtins = cli_options.unstable_bare_node_builtins();
 
   if members.len() > 1 {
-    println!("Publishing a workspace...");
+    progress!("Publishing a workspace...");
   }
 
   // create the module graph
@@ -755,19 +1742,53 @@ async fn prepare_packages_for_publishing(
     module_graph_creator,
     type_checker,
     cli_options,
+    &PublishCheckCache::new(cli_factory.caches()?.publish_check_cache_db()),
     allow_slow_types,
+    allow_pinned_remote_imports,
+    check_libs,
     diagnostics_collector,
     &members,
   )
   .await?;
 
   let mut package_by_name = HashMap::with_capacity(members.len());
-  let publish_order_graph =
-    publish_order::build_publish_order_graph(&graph, &members)?;
+  let publish_order_graph = publish_order::build_publish_order_graph(
+    &graph,
+    &members,
+    &unpublished_members,
+  )?;
+  publish_order_graph.ensure_no_unpublished_dependencies(
+    &unpublished_members
+      .iter()
+      .map(|member| member.package_name.clone())
+      .collect(),
+  )?;
+
+  let sboms = if create_sbom {
+    let npm_resolver = cli_factory.npm_resolver().await?;
+    let npm_snapshot = npm_resolver
+      .as_managed()
+      .map(|npm_resolver| npm_resolver.snapshot());
+    members
+      .iter()
+      .map(|member| {
+        sbom::create_cyclonedx_sbom(
+          &graph,
+          npm_snapshot.as_ref(),
+          &members,
+          member,
+        )
+        .map(Some)
+      })
+      .collect::<Result<Vec<_>, _>>()?
+  } else {
+    vec![None; members.len()]
+  };
 
   let results = members
     .into_iter()
-    .map(|member| {
+    .zip(sboms)
+    .map(|(member, sbom)| {
       let mapped_resolver = mapped_resolver.clone();
       let sloppy_imports_resolver = if cli_options.unstable_sloppy_imports() {
         Some(SloppyImportsResolver::new(fs.clone()))
@@ -775,42 +1796,206 @@ async fn prepare_packages_for_publishing(
         None
       };
       let graph = graph.clone();
+      let unfurl_members = unfurl_members.clone();
       async move {
         let package = prepare_publish(
           &member.package_name,
           &member.config_file,
           source_cache.clone(),
-          graph,
+          graph.clone(),
           mapped_resolver,
           sloppy_imports_resolver,
           bare_node_builtins,
+          unfurl_members,
           diagnostics_collector,
+          sbom,
         )
         .await
         .with_context(|| {
           format!("Failed preparing '{}'.", member.package_name)
         })?;
-        Ok::<_, AnyError>((member.package_name, package))
+        let public_api = if show_fast_check {
+          Some(public_api::render_public_api(
+            &graph,
+            &member.config_file,
+            &package.display_name(),
+          )?)
+        } else {
+          None
+        };
+        Ok::<_, AnyError>((member.package_name, package, public_api))
       }
       .boxed()
     })
     .collect::<Vec<_>>();
-  let results = deno_core::futures::future::join_all(results).await;
+  // the members are prepared on the blocking thread pool, with at most one
+  // per CPU at a time
+  let concurrency = std::thread::available_parallelism()
+    .map(|parallelism| parallelism.get())
+    .unwrap_or(1);
+  let results = deno_core::futures::stream::iter(results)
+    .buffer_unordered(concurrency)
+    .collect::<Vec<_>>()
+    .await;
+  let mut public_api_by_name = HashMap::new();
   for result in results {
-    let (package_name, package) = result?;
+    let (package_name, package, public_api) = result?;
+    if let Some(public_api) = public_api {
+      public_api_by_name.insert(package_name.clone(), public_api);
+    }
     package_by_name.insert(package_name, package);
   }
   Ok(PreparePackagesData {
     publish_order_graph,
     package_by_name,
+    unpublished_names: unpublished_members
+      .into_iter()
+      .map(|member| member.package_name)
+      .collect(),
+    public_api_by_name,
   })
 }
 
+/// The totals of a workspace `--dry-run`, to review a release at a glance.
+fn dry_run_summary(
+  package_by_name: &HashMap<String, Rc<PreparedPublishPackage>>,
+  skipped_members: &mut [(String, String)],
+) -> Vec<String> {
+  let total_size = package_by_name
+    .values()
+    .map(|package| package.tarball.temp_file.size())
+    .sum::<usize>();
+  let mut lines = vec![format!(
+    "{} package{}, {} in total",
+    package_by_name.len(),
+    if package_by_name.len() == 1 { "" } else { "s" },
+    human_size(total_size as f64)
+  )];
+  let largest = package_by_name.values().max_by(|a, b| {
+    a.tarball
+      .temp_file
+      .size()
+      .cmp(&b.tarball.temp_file.size())
+      // the first name on ties
+      .then_with(|| b.display_name().cmp(&a.display_name()))
+  });
+  if let Some(largest) = largest {
+    lines.push(format!(
+      "largest: {} ({})",
+      largest.display_name(),
+      human_size(largest.tarball.temp_file.size() as f64)
+    ));
+  }
+  if !skipped_members.is_empty() {
+    skipped_members.sort();
+    lines.push(format!(
+      "skipped: {}",
+      skipped_members
+        .iter()
+        .map(|(name, reason)| format!("{} ({})", name, reason))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ));
+  }
+  lines
+}
+
+/// Splits the workspace members into the ones that are published and the
+/// ones with `"publish": false` in their configuration file.
+fn partition_publishable_members(
+  members: Vec<WorkspaceMemberConfig>,
+) -> (Vec<WorkspaceMemberConfig>, Vec<WorkspaceMemberConfig>) {
+  members
+    .into_iter()
+    .partition(|member| !config::is_publish_disabled(&member.config_file))
+}
+
+/// Selects the workspace members of `--filter`, and the members they depend
+/// on with `--include-dependencies`.
+async fn filter_workspace_members(
+  module_graph_creator: &ModuleGraphCreator,
+  members: Vec<WorkspaceMemberConfig>,
+  unpublished_members: &[WorkspaceMemberConfig],
+  member_filter: MemberFilter<'_>,
+) -> Result<Vec<WorkspaceMemberConfig>, AnyError> {
+  for name in member_filter.names {
+    if let Some(member) = unpublished_members
+      .iter()
+      .find(|member| &member.package_name == name)
+    {
+      bail!(
+        "'{}' isn't published, because of \"publish\": false in {}",
+        name,
+        member.config_file.specifier
+      );
+    }
+    if !members.iter().any(|member| &member.package_name == name) {
+      bail!(
+        "'{}' isn't a member of the workspace. The members are: {}",
+        name,
+        members
+          .iter()
+          .map(|member| member.package_name.as_str())
+          .collect::<Vec<_>>()
+          .join(", ")
+      );
+    }
+  }
+
+  // the dependencies between the members are only complete in the graph
+  // of all of them
+  let graph = module_graph_creator.create_publish_graph(&members).await?;
+  let publish_order_graph = publish_order::build_publish_order_graph(
+    &graph,
+    &members,
+    unpublished_members,
+  )?;
+  let mut names = member_filter.names.iter().cloned().collect::<HashSet<_>>();
+  let dependencies = publish_order_graph.dependencies_outside(&names);
+  if !dependencies.is_empty() {
+    let display_names = dependencies
+      .iter()
+      .filter_map(|name| {
+        let member = members.iter().find(|m| &m.package_name == name)?;
+        Some(format!(
+          "{}@{}",
+          member.package_name, member.package_version
+        ))
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+    if member_filter.include_dependencies {
+      progress!(
+        "{} the dependencies {}",
+        colors::green("Including"),
+        display_names
+      );
+      names.extend(dependencies);
+    } else {
+      log::warn!(
+        "{} The selected packages depend on {}, which must already be published. Pass --include-dependencies to publish them too.",
+        colors::yellow("Warning"),
+        display_names
+      );
+    }
+  }
+  Ok(
+    members
+      .into_iter()
+      .filter(|member| names.contains(&member.package_name))
+      .collect(),
+  )
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn build_and_check_graph_for_publish(
   module_graph_creator: &ModuleGraphCreator,
   type_checker: &TypeChecker,
   cli_options: &CliOptions,
+  publish_check_cache: &PublishCheckCache,
   allow_slow_types: bool,
+  allow_pinned_remote_imports: bool,
+  check_libs: &[PublishCheckLib],
   diagnostics_collector: &PublishDiagnosticsCollector,
   packages: &[WorkspaceMemberConfig],
 ) -> Result<Arc<deno_graph::ModuleGraph>, deno_core::anyhow::Error> {
@@ -818,7 +2003,16 @@ async fn build_and_check_graph_for_publish(
   graph.valid()?;
 
   // todo(dsherret): move to lint rule
-  collect_invalid_external_imports(&graph, diagnostics_collector);
+  {
+    let lockfile = cli_options.maybe_lockfile();
+    let lockfile = lockfile.as_ref().map(|lockfile| lockfile.lock());
+    collect_external_import_diagnostics(
+      &graph,
+      lockfile.as_deref(),
+      allow_pinned_remote_imports,
+      diagnostics_collector,
+    );
+  }
 
   if allow_slow_types {
     log::info!(
@@ -834,11 +2028,36 @@ async fn build_and_check_graph_for_publish(
     Ok(Arc::new(graph))
   } else {
     log::info!("Checking for slow types in the public API...");
+    // the checks passed for the same modules before, eg. with --dry-run
+    let check_hash = get_publish_check_hash(&graph, packages, check_libs);
+    if !cli_options.reload_flag()
+      && publish_check_cache.has_check_hash(check_hash)
+    {
+      log::debug!(
+        "Skipping the slow types and type checks, which passed before"
+      );
+      return Ok(Arc::new(graph));
+    }
     let mut any_pkg_had_diagnostics = false;
+    let mut any_pkg_had_allowed_slow_types = false;
     for package in packages {
       let export_urls = package.config_file.resolve_export_value_urls()?;
       let diagnostics =
         no_slow_types::collect_no_slow_type_diagnostics(&export_urls, &graph);
+      let allowlist =
+        SlowTypesAllowlist::from_config_file(&package.config_file)?;
+      let (allowed, diagnostics): (Vec<_>, Vec<_>) = diagnostics
+        .into_iter()
+        .partition(|diagnostic| allowlist.allows(diagnostic));
+      if !allowed.is_empty() {
+        any_pkg_had_allowed_slow_types = true;
+        log::info!(
+          "{} {} has {} slow type(s) that \"publish.allowSlowTypes\" allows, so it will not be shipped with a .d.ts file for Node.js users.",
+          colors::yellow("Warning"),
+          package.package_name,
+          allowed.len(),
+        );
+      }
       if !diagnostics.is_empty() {
         any_pkg_had_diagnostics = true;
         for diagnostic in diagnostics {
@@ -847,9 +2066,47 @@ async fn build_and_check_graph_for_publish(
       }
     }
 
-    if any_pkg_had_diagnostics {
+    // the fast check output of packages with slow types isn't used, so it's
+    // only type checked when none of them has any
+    if any_pkg_had_diagnostics || any_pkg_had_allowed_slow_types {
       Ok(Arc::new(graph))
     } else {
+      // the public API is also used in other environments than Deno's main
+      // thread, where it can't reference the types of its globals
+      for check_lib in check_libs {
+        let (lib, lib_override, name) = match check_lib {
+          PublishCheckLib::Worker => {
+            (cli_options.ts_type_lib_worker(), None, "Deno worker")
+          }
+          PublishCheckLib::Node => (
+            cli_options.ts_type_lib_window(),
+            Some(NODE_CHECK_LIBS.iter().map(|lib| lib.to_string()).collect()),
+            "Node.js",
+          ),
+        };
+        log::info!("Checking the public API with the {} libraries...", name);
+        let (_, check_diagnostics) = type_checker
+          .check_diagnostics(
+            graph.clone(),
+            CheckOptions {
+              build_fast_check_graph: false, // already built
+              lib,
+              lib_override,
+              log_ignored_options: false,
+              reload: cli_options.reload_flag(),
+              type_check_mode: TypeCheckMode::Local,
+            },
+          )
+          .await?;
+        if !check_diagnostics.is_empty() {
+          bail!(
+            "The public API doesn't type check with the {} libraries.\n\n{:#}",
+            name,
+            check_diagnostics
+          );
+        }
+      }
+
       // fast check passed, type check the output as a temporary measure
       // until we know that it's reliable and stable
       let (graph, check_diagnostics) = type_checker
@@ -858,6 +2115,7 @@ async fn build_and_check_graph_for_publish(
           CheckOptions {
             build_fast_check_graph: false, // already built
             lib: cli_options.ts_type_lib_window(),
+            lib_override: None,
             log_ignored_options: false,
             reload: cli_options.reload_flag(),
             // force type checking this
@@ -876,18 +2134,102 @@ async fn build_and_check_graph_for_publish(
           check_diagnostics
         );
       }
+      publish_check_cache.add_check_hash(check_hash);
       Ok(graph)
     }
   }
 }
 
+/// Gets a hash of the inputs of the slow types and type checks of the
+/// packages, which are the modules of the graph and their exports.
+fn get_publish_check_hash(
+  graph: &deno_graph::ModuleGraph,
+  packages: &[WorkspaceMemberConfig],
+  check_libs: &[PublishCheckLib],
+) -> u64 {
+  let mut hasher = FastInsecureHasher::new();
+  hasher.write_hashable(check_libs);
+  // this iterator of modules is already deterministic, so no need to sort it
+  for module in graph.modules() {
+    hasher.write_str(module.specifier().as_str());
+    match module {
+      deno_graph::Module::Js(module) => {
+        hasher.write_str(&module.source);
+      }
+      deno_graph::Module::Json(module) => {
+        hasher.write_str(&module.source);
+      }
+      deno_graph::Module::Npm(_)
+      | deno_graph::Module::Node(_)
+      | deno_graph::Module::External(_) => {}
+    }
+  }
+  for package in packages {
+    hasher.write_str(package.config_file.specifier.as_str());
+    hasher.write_hashable(
+      package
+        .config_file
+        .json
+        .exports
+        .as_ref()
+        .map(|exports| exports.to_string()),
+    );
+  }
+  hasher.finish()
+}
+
 pub async fn publish(
   flags: Flags,
   publish_flags: PublishFlags,
 ) -> Result<(), AnyError> {
-  let cli_factory = CliFactory::from_flags(flags).await?;
+  if let Some(code) = &publish_flags.explain {
+    return explain::explain(code);
+  }
+
+  let bumped_files = match &publish_flags.bump {
+    // the configuration files are loaded again below, with the new versions,
+    // except with --dry-run, which only prints them
+    Some(increment) => Some(
+      version::bump(
+        flags.clone(),
+        VersionBumpFlags {
+          increment: increment.clone(),
+          packages: Vec::new(),
+          dry_run: publish_flags.dry_run,
+        },
+      )
+      .await?,
+    ),
+    None => None,
+  };
+
+  let result = publish_packages(flags, publish_flags).await;
+  if let (Err(_), Some(bumped_files)) = (&result, bumped_files) {
+    // don't leave the workspace with versions that weren't published
+    if let Err(err) = bumped_files.restore() {
+      log::warn!(
+        "{} Failed restoring the versions from before --bump: {:#}",
+        colors::yellow("Warning"),
+        err
+      );
+    }
+  }
+  result
+}
 
-  let auth_method = get_auth_method(publish_flags.token)?;
+async fn publish_packages(
+  flags: Flags,
+  publish_flags: PublishFlags,
+) -> Result<(), AnyError> {
+  let cli_factory = CliFactory::from_flags(flags).await?;
+  JSON_OUTPUT.store(publish_flags.json, Ordering::Relaxed);
+  if publish_flags.trace_registry {
+    let trace_file = publish_flags
+      .trace_registry_file
+      .as_ref()
+      .map(|path| cli_factory.cli_options().initial_cwd().join(path));
+    trace::enable(trace_file.as_deref())?;
+  }
 
   let import_map = cli_factory
     .maybe_import_map()
@@ -911,25 +2253,336 @@ pub async fn publish(
     );
   };
 
-  let diagnostics_collector = PublishDiagnosticsCollector::default();
+  let publish_config = CliPublishConfig::from_config_file(config_file)?;
+  let auth_method = get_registry_auth_method(
+    publish_config.registry(jsr_url()),
+    publish_flags.token.clone(),
+  )?;
+
+  if publish_flags.auth_info {
+    let auth_header_template =
+      AuthHeaderTemplate::from_config(publish_config.registry(jsr_url()))?;
+    let (members, _) =
+      partition_publishable_members(cli_options.workspace_members()?);
+    let package_names = members
+      .into_iter()
+      .map(|member| {
+        format!("{}@{}", member.package_name, member.package_version)
+      })
+      .collect::<Vec<_>>();
+    auth::print_auth_info(
+      &auth_method,
+      &auth_header_template,
+      jsr_url(),
+      &package_names,
+    );
+    return Ok(());
+  }
+
+  // invalid names are reported when preparing the packages
+  let (members, _) =
+    partition_publishable_members(cli_options.workspace_members()?);
+  let workspace_packages = members
+    .into_iter()
+    .filter_map(|member| {
+      let (scope, package) =
+        member.package_name.strip_prefix('@')?.split_once('/')?;
+      Some((scope.to_string(), package.to_string()))
+    })
+    .collect::<Vec<_>>();
+  let scope_registry_urls = publish_config.scope_registry_urls()?;
+  let registry_url_of_scope =
+    |scope: &str| scope_registry_urls.get(scope).unwrap_or(jsr_url());
+
+  // the default registry comes first, so that the packages published to
+  // the other registries can depend on its packages
+  let mut registries = vec![
+    PublishRegistry::new(
+      &cli_factory,
+      config_file,
+      publish_config.registry(jsr_url()),
+      jsr_url().clone(),
+      auth_method,
+      publish_flags.dry_run,
+    )
+    .await?,
+  ];
+  for (scope, _) in &workspace_packages {
+    let registry_url = registry_url_of_scope(scope);
+    if registries
+      .iter()
+      .any(|registry| &registry.url == registry_url)
+    {
+      continue;
+    }
+    let registry_config = publish_config.registry(registry_url);
+    let auth_method =
+      get_registry_auth_method(registry_config, publish_flags.token.clone())?;
+    registries.push(
+      PublishRegistry::new(
+        &cli_factory,
+        config_file,
+        registry_config,
+        registry_url.clone(),
+        auth_method,
+        publish_flags.dry_run,
+      )
+      .await?,
+    );
+  }
+
+  let config_path = config_file.specifier.to_file_path().unwrap();
+  let config_dir = config_path.parent().unwrap();
+  let provenance_disabled =
+    publish_flags.no_provenance || publish_config.provenance.disabled;
+  let (attestations, signing_keys) = if provenance_disabled {
+    (Vec::new(), HashMap::new())
+  } else {
+    let attestations = publish_config
+      .provenance
+      .attestations
+      .iter()
+      .map(|config| provenance::AttestationDocument::load(config_dir, config))
+      .collect::<Result<Vec<_>, _>>()?;
+    let mut signing_keys = HashMap::new();
+    for (scope, reference) in &publish_config.provenance.signing_keys {
+      let Some(scope_name) = scope.strip_prefix('@') else {
+        bail!(
+          "Invalid scope '{}' in \"signingKeys\", use '@<scope_name>' format",
+          scope
+        );
+      };
+      let signing_key = provenance::SigningKey::load(config_dir, reference)
+        .with_context(|| format!("Failed loading signing key for {}", scope))?;
+      signing_keys.insert(scope_name.to_string(), signing_key);
+    }
+    (attestations, signing_keys)
+  };
+  let provenance_options = ProvenanceOptions {
+    disabled: provenance_disabled,
+    required: publish_flags.require_provenance,
+    defer_dir: publish_flags
+      .provenance_defer
+      .map(|dir| cli_options.initial_cwd().join(dir)),
+    sigstore_config: SigstoreConfig::resolve(
+      publish_config.registry(jsr_url()),
+    )?,
+    predicate_version: publish_config.provenance.predicate_version,
+    attestations,
+    attest_sbom: publish_config.provenance.attest_sbom,
+    signing_keys,
+    inclusion_proof: publish_config.provenance.inclusion_proof,
+    keyless_session: Default::default(),
+  };
+
+  if !publish_flags.dry_run
+    && !publish_flags.fix
+    && !publish_flags.write_baseline
+  {
+    // the dependencies included with --include-dependencies aren't known
+    // yet, so all members are checked then
+    let is_selected = |scope: &str, package: &str| {
+      publish_flags.filter.is_empty()
+        || publish_flags.include_dependencies
+        || publish_flags
+          .filter
+          .contains(&format!("@{}/{}", scope, package))
+    };
+    for registry in &registries {
+      let packages = workspace_packages
+        .iter()
+        .filter(|(scope, _)| registry_url_of_scope(scope) == &registry.url)
+        .filter(|(scope, package)| is_selected(scope, package))
+        .cloned()
+        .collect::<Vec<_>>();
+      if packages.is_empty() {
+        continue;
+      }
+      auth::check_publish_permissions(
+        &registry.api_client,
+        &registry.auth_method,
+        &registry.auth_header_template,
+        &packages,
+      )
+      .await?;
+    }
+  }
+
+  let mut diagnostics_collector =
+    PublishDiagnosticsCollector::new(publish_config.rules.clone());
+  let baseline_path = publish_flags
+    .baseline
+    .as_ref()
+    .map(|path| cli_options.initial_cwd().join(path));
+  if publish_flags.deny_warnings {
+    diagnostics_collector = diagnostics_collector.with_deny_warnings();
+  }
+  diagnostics_collector =
+    diagnostics_collector.with_format(publish_flags.diagnostics_format.clone());
+  if let Some(max_diagnostics) = publish_flags.max_diagnostics {
+    diagnostics_collector =
+      diagnostics_collector.with_max_diagnostics(max_diagnostics);
+  }
+  if let Some(report_file) = &publish_flags.report_file {
+    diagnostics_collector = diagnostics_collector
+      .with_report_file(cli_options.initial_cwd().join(report_file));
+  }
+  if let Some(baseline_path) = &baseline_path {
+    if !publish_flags.write_baseline {
+      diagnostics_collector = diagnostics_collector
+        .with_baseline(baseline::Baseline::read(baseline_path)?);
+    }
+  }
+  // show the diagnostics while the packages are still being prepared,
+  // piped output stays sorted
+  if std::io::stderr().is_terminal() && !publish_flags.write_baseline {
+    diagnostics_collector = diagnostics_collector.with_streaming();
+  }
 
   let prepared_data = prepare_packages_for_publishing(
     &cli_factory,
     publish_flags.allow_slow_types,
+    publish_config.allow_pinned_remote_imports,
+    &publish_flags.check_libs,
+    publish_flags.show_fast_check,
+    publish_flags.sbom.is_some() || publish_config.provenance.attest_sbom,
     &diagnostics_collector,
-    config_file.clone(),
     mapped_resolver,
+    (!publish_flags.filter.is_empty()).then_some(MemberFilter {
+      names: &publish_flags.filter,
+      include_dependencies: publish_flags.include_dependencies,
+    }),
   )
   .await?;
 
-  diagnostics_collector.print_and_error()?;
+  if publish_flags.fix {
+    fix::fix_sources(
+      prepared_data
+        .package_by_name
+        .values()
+        .map(|package| package.as_ref()),
+    )?;
+  }
+
+  if publish_flags.write_baseline {
+    // --write-baseline requires --baseline
+    let baseline_path = baseline_path.unwrap();
+    let count = diagnostics_collector.write_baseline(&baseline_path)?;
+    progress!(
+      "{} {} diagnostic{} to {}",
+      colors::green("Wrote"),
+      count,
+      if count == 1 { "" } else { "s" },
+      baseline_path.display()
+    );
+    return Ok(());
+  }
+
+  let diagnostics_summary = diagnostics_collector.print()?;
+  if diagnostics_summary.failed && publish_flags.json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&json!({
+        "packages": [],
+        "diagnostics": diagnostics_summary,
+      }))?
+    );
+  }
+  diagnostics_summary.to_result()?;
+
+  if publish_flags.fix {
+    return Ok(());
+  }
+
+  if publish_flags.show_fast_check {
+    let mut public_apis =
+      prepared_data.public_api_by_name.iter().collect::<Vec<_>>();
+    public_apis.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (_, public_api) in public_apis {
+      progress!("{}", public_api);
+    }
+  }
 
   if prepared_data.package_by_name.is_empty() {
     bail!("No packages to publish");
   }
 
+  let mut package_by_name = prepared_data.package_by_name;
+  // the members that aren't published, with the reason
+  let mut skipped_members = prepared_data
+    .unpublished_names
+    .iter()
+    .map(|name| (name.clone(), "\"publish\": false".to_string()))
+    .collect::<Vec<_>>();
+  if publish_flags.changed_only {
+    let mut unchanged_names = Vec::new();
+    for registry in &registries {
+      for (name, package) in &package_by_name {
+        if registry_url_of_scope(&package.scope) != &registry.url {
+          continue;
+        }
+        if let Some(version) =
+          find_unchanged_version(&registry.api_client, &registry.url, package)
+            .await?
+        {
+          progress!(
+            "{} {}, unchanged since {}",
+            colors::gray("Skipping"),
+            name,
+            version
+          );
+          unchanged_names.push(name.clone());
+          skipped_members
+            .push((name.clone(), format!("unchanged since {}", version)));
+        }
+      }
+    }
+    for name in unchanged_names {
+      package_by_name.remove(&name);
+    }
+    if package_by_name.is_empty() {
+      progress!(
+        "{} No packages changed since their latest versions",
+        colors::green("Done")
+      );
+      if publish_flags.json {
+        println!(
+          "{}",
+          serde_json::to_string_pretty(&json!({
+            "packages": [],
+            "diagnostics": diagnostics_summary,
+          }))?
+        );
+      }
+      return Ok(());
+    }
+  }
+
+  if let Some(sbom_dir) = &publish_flags.sbom {
+    let sbom_dir = cli_options.initial_cwd().join(sbom_dir);
+    std::fs::create_dir_all(&sbom_dir)?;
+    for package in package_by_name.values() {
+      let Some(sbom) = &package.sbom else {
+        continue;
+      };
+      let sbom_path = sbom_dir.join(format!(
+        "{}_{}_{}.cdx.json",
+        package.scope, package.package, package.version
+      ));
+      std::fs::write(&sbom_path, serde_json::to_string_pretty(sbom)?)
+        .with_context(|| {
+          format!("Failed writing SBOM to '{}'", sbom_path.display())
+        })?;
+      progress!(
+        "{} {}",
+        colors::green("SBOM written to"),
+        sbom_path.display()
+      );
+    }
+  }
+
   if publish_flags.dry_run {
-    for (_, package) in prepared_data.package_by_name {
+    for package in package_by_name.values() {
       log::info!(
         "{} of {} with files:",
         colors::green_bold("Simulating publish"),
@@ -939,22 +2592,193 @@ pub async fn publish(
         log::info!("   {} ({})", file.specifier, human_size(file.size as f64),);
       }
     }
+    if package_by_name.len() > 1 || !skipped_members.is_empty() {
+      log::info!("{}", colors::green_bold("Summary:"));
+      for line in dry_run_summary(&package_by_name, &mut skipped_members) {
+        log::info!("   {}", line);
+      }
+    }
     log::warn!("{} Aborting due to --dry-run", colors::yellow("Warning"));
     return Ok(());
   }
 
-  perform_publish(
-    cli_factory.http_client(),
-    prepared_data.publish_order_graph,
-    prepared_data.package_by_name,
-    auth_method,
-    publish_flags.no_provenance,
-  )
-  .await?;
+  let provenance_options = Rc::new(provenance_options);
+  let mut published_packages = Vec::new();
+  for registry in registries {
+    let names = package_by_name
+      .iter()
+      .filter(|(_, package)| {
+        registry_url_of_scope(&package.scope) == &registry.url
+      })
+      .map(|(name, _)| name.clone())
+      .collect::<HashSet<_>>();
+    if names.is_empty() {
+      continue;
+    }
+    let registry_package_by_name = names
+      .iter()
+      .filter_map(|name| Some((name.clone(), package_by_name.remove(name)?)))
+      .collect::<HashMap<_, _>>();
+    if &registry.url != jsr_url() {
+      progress!("{} {}", colors::green("Publishing to"), registry.url);
+    }
+    published_packages.extend(
+      perform_publish(
+        cli_factory.http_client(),
+        &registry.http_client,
+        registry.api_client,
+        &registry.url,
+        &registry.endpoints.manage_url,
+        prepared_data.publish_order_graph.subgraph(&names),
+        registry_package_by_name,
+        registry.auth_method,
+        registry.auth_header_template,
+        provenance_options.clone(),
+        publish_flags.create,
+        publish_flags.wait_for_available,
+      )
+      .await?,
+    );
+  }
+
+  if publish_flags.json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&json!({
+        "packages": published_packages,
+        "diagnostics": diagnostics_summary,
+      }))?
+    );
+  }
 
   Ok(())
 }
 
+/// A registry that packages are published to, eg. the one configured for
+/// some scopes in `"publish.scopes"`.
+struct PublishRegistry {
+  url: Url,
+  http_client: Arc<HttpClient>,
+  api_client: JsrApiClient,
+  endpoints: discovery::RegistryEndpoints,
+  auth_method: AuthMethod,
+  auth_header_template: AuthHeaderTemplate,
+}
+
+impl PublishRegistry {
+  async fn new(
+    cli_factory: &CliFactory,
+    config_file: &ConfigFile,
+    registry_config: Option<&RegistryConfig>,
+    url: Url,
+    auth_method: AuthMethod,
+    dry_run: bool,
+  ) -> Result<Self, AnyError> {
+    let http_client = create_registry_http_client(
+      cli_factory,
+      Some(config_file),
+      registry_config,
+    )?;
+    let mirror_urls = match registry_config {
+      Some(registry_config) => registry_config.mirror_urls()?,
+      None => Vec::new(),
+    };
+    // a dry run doesn't send requests to the registry, so it works offline
+    let endpoints = if dry_run {
+      discovery::RegistryEndpoints::default_for(&url)
+    } else {
+      discovery::discover_registry(http_client.client()?, &url).await?
+    };
+    let api_client =
+      JsrApiClient::new(http_client.client()?.clone(), &endpoints.api_url)
+        .with_cache(ApiResponseCache::new(
+          cli_factory.deno_dir()?.registry_api_folder_path(),
+        ))
+        .with_mirrors(mirror_urls)
+        .with_chunked_uploads(endpoints.supports("chunkedUploads"));
+    Ok(Self {
+      url,
+      http_client,
+      api_client,
+      endpoints,
+      auth_method,
+      auth_header_template: AuthHeaderTemplate::from_config(registry_config)?,
+    })
+  }
+}
+
+/// Gets the means to authenticate to a registry, which is the token from
+/// the environment variable in `"tokenEnv"` when the registry has one.
+fn get_registry_auth_method(
+  registry_config: Option<&RegistryConfig>,
+  token: Option<String>,
+) -> Result<AuthMethod, AnyError> {
+  let token = match registry_config.and_then(|c| c.token_env.as_ref()) {
+    Some(token_env) => match std::env::var(token_env) {
+      Ok(token) => Some(token),
+      Err(_) => bail!(
+        "The environment variable {} with the registry token is not set",
+        token_env
+      ),
+    },
+    None => token,
+  };
+  let mut auth_method = get_auth_method(token)?;
+  if let AuthMethod::Oidc(oidc_config) = &mut auth_method {
+    oidc_config.limits = auth::OidcLimits::from_config(registry_config)?;
+  }
+  Ok(auth_method)
+}
+
+/// How long idle connections to the registry are kept. Publishing uploads
+/// the packages of a workspace in batches, which reuse the connection of the
+/// checks and authentication before them.
+const REGISTRY_POOL_IDLE_TIMEOUT: std::time::Duration =
+  std::time::Duration::from_secs(300);
+/// How often idle HTTP/2 connections to the registry are pinged, so that
+/// they survive authenticating in the browser.
+const REGISTRY_HTTP2_KEEP_ALIVE_INTERVAL: std::time::Duration =
+  std::time::Duration::from_secs(20);
+
+/// Creates the client used for requests to the registry, which keeps its
+/// connections open to multiplex concurrent requests over HTTP/2 and
+/// trusts the registry's CA certificate if one is configured.
+fn create_registry_http_client(
+  cli_factory: &CliFactory,
+  config_file: Option<&ConfigFile>,
+  registry_config: Option<&RegistryConfig>,
+) -> Result<Arc<HttpClient>, AnyError> {
+  let http_client = HttpClient::new(
+    Some(cli_factory.root_cert_store_provider().clone()),
+    cli_factory
+      .cli_options()
+      .unsafely_ignore_certificate_errors()
+      .clone(),
+  )
+  .with_persistent_connections(
+    REGISTRY_POOL_IDLE_TIMEOUT,
+    REGISTRY_HTTP2_KEEP_ALIVE_INTERVAL,
+  );
+  let ca_file = match std::env::var("DENO_REGISTRY_CERT") {
+    Ok(ca_file) => PathBuf::from(ca_file),
+    Err(_) => match registry_config.and_then(|c| c.ca_file.as_ref()) {
+      Some(ca_file) => match config_file {
+        Some(config_file) => {
+          let config_path = config_file.specifier.to_file_path().unwrap();
+          config_path.parent().unwrap().join(ca_file)
+        }
+        None => PathBuf::from(ca_file),
+      },
+      None => return Ok(Arc::new(http_client)),
+    },
+  };
+  let ca_cert = std::fs::read(&ca_file).with_context(|| {
+    format!("Failed reading registry CA file '{}'", ca_file.display())
+  })?;
+  log::debug!("Using registry CA file {}", ca_file.display());
+  Ok(Arc::new(http_client.with_ca_certs(vec![ca_cert])))
+}
+
 #[derive(Deserialize)]
 struct ManifestEntry {
   checksum: String,
@@ -966,6 +2790,69 @@ struct VersionManifest {
   exports: HashMap<String, String>,
 }
 
+/// Gets the latest published version of a package when its files didn't
+/// change since, or `None`.
+async fn find_unchanged_version(
+  api_client: &JsrApiClient,
+  registry_url: &Url,
+  package: &PreparedPublishPackage,
+) -> Result<Option<String>, AnyError> {
+  let Some(latest_version) = api_client
+    .get_package(&package.scope, &package.package)
+    .await?
+    .and_then(|published| published.latest_version)
+  else {
+    return Ok(None);
+  };
+  let meta_path = format!(
+    "@{}/{}/{}_meta.json",
+    package.scope, package.package, latest_version
+  );
+  let response = api_client
+    .get_registry_file(registry_url, &meta_path)
+    .await?;
+  if !response.status().is_success() {
+    bail!(
+      "Failed to get the version manifest of @{}/{}@{}: status {}",
+      package.scope,
+      package.package,
+      latest_version,
+      response.status()
+    );
+  }
+  let meta_bytes = response.bytes().await?;
+  if is_same_as_published(&meta_bytes, package)? {
+    Ok(Some(latest_version))
+  } else {
+    Ok(None)
+  }
+}
+
+/// Whether a package has the same files and exports as a published version
+/// of it. The configuration file is ignored, since its version differs
+/// after bumping it.
+fn is_same_as_published(
+  meta_bytes: &[u8],
+  package: &PreparedPublishPackage,
+) -> Result<bool, AnyError> {
+  let manifest = serde_json::from_slice::<VersionManifest>(meta_bytes)?;
+  let config_path = format!("/{}", package.config);
+  let published_files = manifest
+    .manifest
+    .iter()
+    .filter(|(path, _)| **path != config_path)
+    .map(|(path, entry)| (path.as_str(), entry.checksum.as_str()))
+    .collect::<HashMap<_, _>>();
+  let files = package
+    .tarball
+    .files
+    .iter()
+    .filter(|file| file.path_str != config_path)
+    .map(|file| (file.path_str.as_str(), file.hash.as_str()))
+    .collect::<HashMap<_, _>>();
+  Ok(published_files == files && manifest.exports == package.exports)
+}
+
 fn verify_version_manifest(
   meta_bytes: &[u8],
   package: &PreparedPublishPackage,
@@ -1024,10 +2911,22 @@ fn verify_version_manifest(
 
 #[cfg(test)]
 mod tests {
+  use super::dry_run_summary;
+  use super::human_size;
+  use super::is_oidc_audience_too_large;
+  use super::is_retryable_upload_error;
+  use super::is_same_as_published;
+  use super::reqwest;
   use super::tar::PublishableTarball;
   use super::tar::PublishableTarballFile;
+  use super::tar::TarballTempFile;
   use super::verify_version_manifest;
+  use super::AnyError;
+  use super::ApiError;
+  use super::ApiErrorKind;
+  use deno_core::serde_json::json;
   use std::collections::HashMap;
+  use std::rc::Rc;
 
   #[test]
   fn test_verify_version_manifest() {
@@ -1046,17 +2945,20 @@ mod tests {
       package: "test".to_string(),
       version: "1.0.0".to_string(),
       tarball: PublishableTarball {
-        bytes: vec![].into(),
+        temp_file: TarballTempFile::new(&[]).unwrap(),
         hash: "abc123".to_string(),
         files: vec![PublishableTarballFile {
           specifier: "file://mod.ts".try_into().unwrap(),
           path_str: "mod.ts".to_string(),
           hash: "abc123".to_string(),
           size: 0,
+          rewrites: Vec::new(),
         }],
       },
       config: "deno.json".to_string(),
       exports: HashMap::new(),
+      sbom: None,
+      provenance_disabled: false,
     };
 
     assert!(verify_version_manifest(meta_bytes, &package).is_ok());
@@ -1077,17 +2979,20 @@ mod tests {
       package: "test".to_string(),
       version: "1.0.0".to_string(),
       tarball: PublishableTarball {
-        bytes: vec![].into(),
+        temp_file: TarballTempFile::new(&[]).unwrap(),
         hash: "abc123".to_string(),
         files: vec![PublishableTarballFile {
           specifier: "file://mod.ts".try_into().unwrap(),
           path_str: "mod.ts".to_string(),
           hash: "abc123".to_string(),
           size: 0,
+          rewrites: Vec::new(),
         }],
       },
       config: "deno.json".to_string(),
       exports: HashMap::new(),
+      sbom: None,
+      provenance_disabled: false,
     };
 
     assert!(verify_version_manifest(meta_bytes, &package).is_err());
@@ -1110,19 +3015,154 @@ mod tests {
       package: "test".to_string(),
       version: "1.0.0".to_string(),
       tarball: PublishableTarball {
-        bytes: vec![].into(),
+        temp_file: TarballTempFile::new(&[]).unwrap(),
         hash: "abc123".to_string(),
         files: vec![PublishableTarballFile {
           specifier: "file://mod.ts".try_into().unwrap(),
           path_str: "mod.ts".to_string(),
           hash: "abc123".to_string(),
           size: 0,
+          rewrites: Vec::new(),
         }],
       },
       config: "deno.json".to_string(),
       exports: HashMap::new(),
+      sbom: None,
+      provenance_disabled: false,
     };
 
     assert!(verify_version_manifest(meta_bytes, &package).is_err());
   }
+  #[test]
+  fn test_is_same_as_published() {
+    let meta = r#"{
+      "manifest": {
+        "/deno.json": { "checksum": "sha256-1" },
+        "/mod.ts": { "checksum": "sha256-2" }
+      },
+      "exports": { ".": "./mod.ts" }
+    }"#;
+    let file = |path: &str, hash: &str| PublishableTarballFile {
+      specifier: format!("file://{}", path).try_into().unwrap(),
+      path_str: path.to_string(),
+      hash: hash.to_string(),
+      size: 0,
+      rewrites: Vec::new(),
+    };
+    let package =
+      |files: Vec<PublishableTarballFile>| super::PreparedPublishPackage {
+        scope: "test".to_string(),
+        package: "test".to_string(),
+        version: "1.1.0".to_string(),
+        tarball: PublishableTarball {
+          temp_file: TarballTempFile::new(&[]).unwrap(),
+          hash: "abc123".to_string(),
+          files,
+        },
+        config: "deno.json".to_string(),
+        exports: HashMap::from([(".".to_string(), "./mod.ts".to_string())]),
+        sbom: None,
+        provenance_disabled: false,
+      };
+
+    // the version in the configuration file changed
+    let unchanged = package(vec![
+      file("/deno.json", "sha256-3"),
+      file("/mod.ts", "sha256-2"),
+    ]);
+    assert!(is_same_as_published(meta.as_bytes(), &unchanged).unwrap());
+    let changed = package(vec![
+      file("/deno.json", "sha256-1"),
+      file("/mod.ts", "sha256-4"),
+    ]);
+    assert!(!is_same_as_published(meta.as_bytes(), &changed).unwrap());
+    let added = package(vec![
+      file("/deno.json", "sha256-1"),
+      file("/mod.ts", "sha256-2"),
+      file("/util.ts", "sha256-5"),
+    ]);
+    assert!(!is_same_as_published(meta.as_bytes(), &added).unwrap());
+  }
+
+  #[test]
+  fn test_dry_run_summary() {
+    let package = |name: &str, size: usize| {
+      Rc::new(super::PreparedPublishPackage {
+        scope: "scope".to_string(),
+        package: name.to_string(),
+        version: "1.0.0".to_string(),
+        tarball: PublishableTarball {
+          temp_file: TarballTempFile::new(&vec![0; size]).unwrap(),
+          hash: "abc123".to_string(),
+          files: Vec::new(),
+        },
+        config: "deno.json".to_string(),
+        exports: HashMap::new(),
+        sbom: None,
+        provenance_disabled: false,
+      })
+    };
+    let package_by_name = HashMap::from([
+      ("@scope/a".to_string(), package("a", 1000)),
+      ("@scope/b".to_string(), package("b", 3000)),
+    ]);
+    let mut skipped_members = vec![
+      ("@scope/d".to_string(), "unchanged since 0.9.0".to_string()),
+      ("@scope/c".to_string(), "\"publish\": false".to_string()),
+    ];
+    assert_eq!(
+      dry_run_summary(&package_by_name, &mut skipped_members),
+      vec![
+        format!("2 packages, {} in total", human_size(4000.0)),
+        format!("largest: @scope/b@1.0.0 ({})", human_size(3000.0)),
+        "skipped: @scope/c (\"publish\": false), @scope/d (unchanged since 0.9.0)"
+          .to_string(),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_is_retryable_upload_error() {
+    let api_error = |status| -> AnyError {
+      ApiError {
+        code: "error".to_string(),
+        message: "error".to_string(),
+        data: json!({}),
+        x_deno_ray: None,
+        status: Some(status),
+        kind: ApiErrorKind::Other,
+      }
+      .into()
+    };
+    assert!(is_retryable_upload_error(&api_error(
+      reqwest::StatusCode::BAD_GATEWAY
+    )));
+    assert!(!is_retryable_upload_error(&api_error(
+      reqwest::StatusCode::CONFLICT
+    )));
+    assert!(!is_retryable_upload_error(&deno_core::anyhow::anyhow!(
+      "other error"
+    )));
+  }
+
+  #[test]
+  fn test_is_oidc_audience_too_large() {
+    assert!(is_oidc_audience_too_large(
+      reqwest::StatusCode::URI_TOO_LONG,
+      ""
+    ));
+    assert!(is_oidc_audience_too_large(
+      reqwest::StatusCode::BAD_REQUEST,
+      r#"{"message":"Audience is too long"}"#
+    ));
+    // the other bad requests aren't retried with fewer packages
+    assert!(!is_oidc_audience_too_large(
+      reqwest::StatusCode::BAD_REQUEST,
+      r#"{"message":"Invalid audience"}"#
+    ));
+    assert!(!is_oidc_audience_too_large(
+      reqwest::StatusCode::UNAUTHORIZED,
+      "too long"
+    ));
+  }
 }
@@ -0,0 +1,160 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use deno_ast::swc::ast::Expr;
+use deno_ast::swc::ast::MemberExpr;
+use deno_ast::swc::ast::MemberProp;
+use deno_ast::swc::ast::Program;
+use deno_ast::swc::ast::TsEntityName;
+use deno_ast::swc::ast::TsQualifiedName;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::ParsedSource;
+use deno_ast::SourceRange;
+use deno_ast::SourceRangedForSpanned;
+use deno_core::error::AnyError;
+use lsp_types::Url;
+
+use crate::cache::LazyGraphSourceParser;
+
+use super::diagnostics::PublishDiagnostic;
+use super::diagnostics::PublishDiagnosticsCollector;
+
+/// Whether the package claims to work in Node.js according to the
+/// `"publish.runtimeCompat"` setting, which is assumed when it's not set.
+pub fn targets_node(runtime_compat: &HashMap<String, bool>) -> bool {
+  runtime_compat.is_empty() || runtime_compat.get("node") == Some(&true)
+}
+
+/// Reports the uses of the `Deno` namespace in the modules reachable from
+/// the exports of a package, which break when the package is used in
+/// Node.js through the npm compatibility layer of the registry.
+pub fn collect_deno_only_apis(
+  source_parser: LazyGraphSourceParser,
+  exported_modules: &HashSet<Url>,
+  diagnostics_collector: &PublishDiagnosticsCollector,
+) -> Result<(), AnyError> {
+  for specifier in exported_modules {
+    if specifier.scheme() != "file" {
+      continue;
+    }
+    let Some(parsed_source) = source_parser.get_or_parse_source(specifier)?
+    else {
+      continue;
+    };
+    for (api, range) in find_deno_apis(&parsed_source) {
+      diagnostics_collector.push(PublishDiagnostic::DenoOnlyApi {
+        specifier: specifier.clone(),
+        text_info: parsed_source.text_info().clone(),
+        range,
+        api,
+      });
+    }
+  }
+  Ok(())
+}
+
+/// Gets the first use of every `Deno.*` API in a module.
+fn find_deno_apis(parsed_source: &ParsedSource) -> Vec<(String, SourceRange)> {
+  let mut finder = DenoApiFinder {
+    seen: HashSet::new(),
+    apis: Vec::new(),
+  };
+  let program = parsed_source.program();
+  let program: &Program = &program;
+  program.visit_with(&mut finder);
+  finder.apis
+}
+
+struct DenoApiFinder {
+  seen: HashSet<String>,
+  apis: Vec<(String, SourceRange)>,
+}
+
+impl DenoApiFinder {
+  fn add(&mut self, name: &str, range: SourceRange) {
+    let api = format!("Deno.{}", name);
+    if self.seen.insert(api.clone()) {
+      self.apis.push((api, range));
+    }
+  }
+}
+
+impl Visit for DenoApiFinder {
+  fn visit_member_expr(&mut self, member_expr: &MemberExpr) {
+    // `typeof Deno` checks and `globalThis.Deno?.` are fine, since they
+    // don't throw in other runtimes
+    if let (Expr::Ident(obj), MemberProp::Ident(prop)) =
+      (&*member_expr.obj, &member_expr.prop)
+    {
+      if &*obj.sym == "Deno" {
+        self.add(&prop.sym, member_expr.range());
+      }
+    }
+    member_expr.visit_children_with(self);
+  }
+
+  fn visit_ts_qualified_name(&mut self, name: &TsQualifiedName) {
+    if let TsEntityName::Ident(left) = &name.left {
+      if &*left.sym == "Deno" {
+        self.add(&name.right.sym, name.range());
+      }
+    }
+    name.visit_children_with(self);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::MediaType;
+  use deno_ast::ModuleSpecifier;
+  use deno_ast::ParseParams;
+  use deno_ast::SourceTextInfo;
+
+  use super::*;
+
+  fn parse_module(text: &str) -> ParsedSource {
+    deno_ast::parse_module(ParseParams {
+      specifier: ModuleSpecifier::parse("file:///mod.ts").unwrap(),
+      capture_tokens: false,
+      maybe_syntax: None,
+      media_type: MediaType::TypeScript,
+      scope_analysis: false,
+      text_info: SourceTextInfo::from_string(text.to_string()),
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn find_deno_apis_in_module() {
+    let parsed_source = parse_module(
+      r#"export function read(path: string): string {
+  if (typeof Deno === "undefined") throw new Error("unsupported");
+  Deno.env.get("HOME");
+  return Deno.readTextFileSync(path) + Deno.env.get("USER");
+}
+export function close(file: Deno.FsFile) {
+  globalThis.Deno?.exit(0);
+}
+"#,
+    );
+    let apis = find_deno_apis(&parsed_source)
+      .into_iter()
+      .map(|(api, _)| api)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      apis,
+      vec!["Deno.env", "Deno.readTextFileSync", "Deno.FsFile"]
+    );
+  }
+
+  #[test]
+  fn targets_node_runtime_compat() {
+    assert!(targets_node(&HashMap::new()));
+    assert!(targets_node(&HashMap::from([("node".to_string(), true)])));
+    assert!(!targets_node(&HashMap::from([("node".to_string(), false)])));
+    assert!(!targets_node(&HashMap::from([("deno".to_string(), true)])));
+  }
+}
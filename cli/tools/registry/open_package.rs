@@ -0,0 +1,115 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_terminal::colors;
+
+use crate::args::Flags;
+use crate::args::RegistryOpenFlags;
+
+use super::commands::parse_package_arg;
+use super::commands::parse_package_version_arg;
+use super::commands::RegistryContext;
+
+pub async fn open_package(
+  flags: Flags,
+  open_flags: RegistryOpenFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let (name, version) = match &open_flags.package {
+    Some(package) => parse_open_arg(package)?,
+    None => {
+      let Some(config_file) =
+        context.cli_factory.cli_options().maybe_config_file()
+      else {
+        bail!("No package specified and no configuration file found. Pass a package, eg. `deno registry open @foo/bar`.");
+      };
+      let Some(name) = &config_file.json.name else {
+        bail!(
+          "No package specified and {} is missing 'name' field",
+          config_file.specifier
+        );
+      };
+      // validates the name
+      let name = parse_package_arg(name)?.to_string();
+      (name, config_file.json.version.clone())
+    }
+  };
+
+  let url = package_page_url(&context.endpoints.manage_url, &name, version)?;
+  println!("{} {}", colors::green("Opening"), url);
+  if let Err(err) = open::that_detached(url.as_str()) {
+    log::warn!(
+      "{} Failed to open the browser: {}",
+      colors::yellow("Warning"),
+      err
+    );
+  }
+  Ok(())
+}
+
+/// Parses a package with an optional version, eg. `@foo/bar@1.0.0`.
+fn parse_open_arg(text: &str) -> Result<(String, Option<String>), AnyError> {
+  let text = text.strip_prefix("jsr:").unwrap_or(text);
+  let has_version = text
+    .split_once('/')
+    .is_some_and(|(_, package)| package.contains('@'));
+  if has_version {
+    let package = parse_package_version_arg(text)?;
+    Ok((
+      format!("@{}/{}", package.scope, package.package),
+      Some(package.version.to_string()),
+    ))
+  } else {
+    Ok((parse_package_arg(text)?.to_string(), None))
+  }
+}
+
+fn package_page_url(
+  manage_url: &Url,
+  name: &str,
+  version: Option<String>,
+) -> Result<Url, AnyError> {
+  let path = match version {
+    Some(version) => format!("{}@{}", name, version),
+    None => name.to_string(),
+  };
+  Ok(manage_url.join(&path)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn open_arg_with_optional_version() {
+    assert_eq!(
+      parse_open_arg("@foo/bar").unwrap(),
+      ("@foo/bar".to_string(), None)
+    );
+    assert_eq!(
+      parse_open_arg("jsr:@foo/bar@1.2.3").unwrap(),
+      ("@foo/bar".to_string(), Some("1.2.3".to_string()))
+    );
+    assert!(parse_open_arg("@foo/bar@latest").is_err());
+    assert!(parse_open_arg("foo").is_err());
+  }
+
+  #[test]
+  fn package_page_urls() {
+    let manage_url = Url::parse("https://jsr.io/").unwrap();
+    assert_eq!(
+      package_page_url(&manage_url, "@foo/bar", None)
+        .unwrap()
+        .as_str(),
+      "https://jsr.io/@foo/bar"
+    );
+    assert_eq!(
+      package_page_url(&manage_url, "@foo/bar", Some("1.0.0".to_string()))
+        .unwrap()
+        .as_str(),
+      "https://jsr.io/@foo/bar@1.0.0"
+    );
+  }
+}
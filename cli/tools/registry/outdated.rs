@@ -0,0 +1,606 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! `deno outdated` and `deno update`, which compare the `jsr:` and `npm:`
+//! dependencies in the configuration file, its import map and package.json
+//! with the versions in the registries.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use deno_ast::TextChange;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::StreamExt;
+use deno_core::serde_json;
+use deno_semver::package::PackageReq;
+use deno_semver::Version;
+use deno_terminal::colors;
+use jsonc_parser::ast::ObjectProp;
+use jsonc_parser::ast::Value;
+
+use crate::args::Flags;
+use crate::args::OutdatedFlags;
+use crate::factory::CliFactory;
+use crate::lsp::jsr::CliJsrSearchApi;
+use crate::lsp::npm::CliNpmSearchApi;
+use crate::lsp::search::PackageSearchApi;
+
+use super::pm::create_deps_file_fetcher;
+use super::version::bump_constraint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DependencyKind {
+  Jsr,
+  Npm,
+}
+
+impl DependencyKind {
+  fn scheme(&self) -> &'static str {
+    match self {
+      DependencyKind::Jsr => "jsr",
+      DependencyKind::Npm => "npm",
+    }
+  }
+}
+
+/// A configuration file, import map or package.json.
+struct DependencyFile {
+  path: PathBuf,
+  text: String,
+  /// Whether the values are the version constraints of npm packages, like
+  /// in package.json, instead of specifiers.
+  bare: bool,
+}
+
+impl DependencyFile {
+  fn read(path: PathBuf, bare: bool) -> Result<Self, AnyError> {
+    let text = std::fs::read_to_string(&path)
+      .with_context(|| format!("Failed reading '{}'", path.display()))?;
+    Ok(Self { path, text, bare })
+  }
+
+  /// The `jsr:` and `npm:` dependencies of the file.
+  fn dependencies(&self) -> Result<Vec<Dependency>, AnyError> {
+    let object_names: &[&str] = if self.bare {
+      &["dependencies", "devDependencies"]
+    } else {
+      &["imports"]
+    };
+    let values = string_values(&self.text, object_names)
+      .with_context(|| format!("Failed parsing '{}'", self.path.display()))?;
+    Ok(
+      values
+        .into_iter()
+        .filter_map(|(key, value, range)| {
+          parse_dependency(&key, &value, self.bare, range)
+        })
+        .collect(),
+    )
+  }
+
+  /// Replaces the values of the dependencies, keeping the formatting and
+  /// comments of the file.
+  fn write_values(
+    &self,
+    new_values: Vec<(Range<usize>, String)>,
+  ) -> Result<(), AnyError> {
+    let text_changes = new_values
+      .into_iter()
+      .map(|(range, new_value)| {
+        Ok(TextChange {
+          range,
+          new_text: serde_json::to_string(&new_value)?,
+        })
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
+    let new_text = deno_ast::apply_text_changes(&self.text, text_changes);
+    std::fs::write(&self.path, new_text)
+      .with_context(|| format!("Failed writing '{}'", self.path.display()))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dependency {
+  /// The import map key or the name in package.json.
+  key: String,
+  kind: DependencyKind,
+  req: PackageReq,
+  constraint: String,
+  /// The text of the value before and after the version constraint, eg.
+  /// `jsr:@std/path@` and `/posix`.
+  prefix: String,
+  suffix: String,
+  /// The range of the string literal of the value in the file.
+  range: Range<usize>,
+}
+
+impl Dependency {
+  fn display_name(&self) -> String {
+    format!("{}:{}", self.kind.scheme(), self.req.name)
+  }
+
+  /// The version in the `"specifiers"` of the lockfile, eg.
+  /// `"jsr:@std/path@^0.220.0": "jsr:@std/path@0.220.1"`.
+  fn locked_version(
+    &self,
+    specifiers: &BTreeMap<String, String>,
+  ) -> Option<Version> {
+    let name = self.display_name();
+    let resolved = specifiers.get(&format!("{}@{}", name, self.constraint))?;
+    let version = resolved.strip_prefix(&format!("{}@", name))?;
+    // the peer dependencies of npm packages are after an underscore
+    let version = version.split('_').next()?;
+    Version::parse_standard(version).ok()
+  }
+}
+
+/// Parses a `jsr:` or `npm:` specifier with a version constraint, or the
+/// version constraint of an npm package in package.json. Other values, eg.
+/// local paths, URLs and unversioned specifiers, aren't dependencies that
+/// can be outdated.
+fn parse_dependency(
+  key: &str,
+  value: &str,
+  bare: bool,
+  range: Range<usize>,
+) -> Option<Dependency> {
+  let (kind, rest) = if let Some(rest) = value.strip_prefix("jsr:") {
+    (DependencyKind::Jsr, rest)
+  } else if let Some(rest) = value.strip_prefix("npm:") {
+    (DependencyKind::Npm, rest)
+  } else if bare {
+    let req = PackageReq::from_str(&format!("{}@{}", key, value)).ok()?;
+    return Some(Dependency {
+      key: key.to_string(),
+      kind: DependencyKind::Npm,
+      req,
+      constraint: value.to_string(),
+      prefix: String::new(),
+      suffix: String::new(),
+      range,
+    });
+  } else {
+    return None;
+  };
+  // skip the `@` of the scope
+  let constraint_start = rest.get(1..)?.find('@')? + 2;
+  let name = &rest[..constraint_start - 1];
+  let after = &rest[constraint_start..];
+  let constraint_end = after.find('/').unwrap_or(after.len());
+  let constraint = &after[..constraint_end];
+  let req = PackageReq::from_str(&format!("{}@{}", name, constraint)).ok()?;
+  Some(Dependency {
+    key: key.to_string(),
+    kind,
+    req,
+    constraint: constraint.to_string(),
+    prefix: value[..value.len() - after.len()].to_string(),
+    suffix: after[constraint_end..].to_string(),
+    range,
+  })
+}
+
+/// The string values in the objects with the names, with their keys and the
+/// ranges of their literals.
+fn string_values(
+  text: &str,
+  object_names: &[&str],
+) -> Result<Vec<(String, String, Range<usize>)>, AnyError> {
+  let ast =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())?;
+  let Some(Value::Object(obj)) = ast.value else {
+    bail!("Expected an object");
+  };
+  let mut values = Vec::new();
+  for object_name in object_names {
+    let Some(ObjectProp {
+      value: Value::Object(object),
+      ..
+    }) = obj.get(object_name)
+    else {
+      continue;
+    };
+    for prop in &object.properties {
+      if let Value::StringLit(lit) = &prop.value {
+        values.push((
+          prop.name.as_str().to_string(),
+          lit.value.to_string(),
+          lit.range.start..lit.range.end,
+        ));
+      }
+    }
+  }
+  Ok(values)
+}
+
+/// The latest version that the version constraint allows, and the latest
+/// stable version, from the versions sorted from the newest.
+pub fn select_versions(
+  req: &PackageReq,
+  versions: &[Version],
+) -> (Option<Version>, Option<Version>) {
+  let wanted = versions
+    .iter()
+    .find(|version| req.version_req.matches(version))
+    .cloned();
+  let latest = versions
+    .iter()
+    .find(|version| version.pre.is_empty())
+    .or(versions.first())
+    .cloned();
+  (wanted, latest)
+}
+
+struct DependencyVersions {
+  file_index: usize,
+  dependency: Dependency,
+  current: Option<Version>,
+  wanted: Option<Version>,
+  latest: Option<Version>,
+}
+
+impl DependencyVersions {
+  fn is_outdated(&self) -> bool {
+    match (&self.latest, self.current.as_ref().or(self.wanted.as_ref())) {
+      (Some(latest), Some(current)) => latest > current,
+      (Some(_), None) => true,
+      (None, _) => false,
+    }
+  }
+
+  /// The value with the new version constraint, or `None` when it stays
+  /// the same.
+  fn updated_value(&self, latest: bool) -> Option<String> {
+    let dependency = &self.dependency;
+    let new_constraint = if latest {
+      bump_constraint(&dependency.constraint, self.latest.as_ref()?)?
+    } else {
+      // a range like `>=1.2.0 <3.0.0` already allows the wanted version,
+      // and replacing it would narrow it
+      let version = dependency.constraint.trim_start_matches(['^', '~', '=']);
+      Version::parse_standard(version).ok()?;
+      bump_constraint(&dependency.constraint, self.wanted.as_ref()?)?
+    };
+    if new_constraint == dependency.constraint {
+      return None;
+    }
+    Some(format!(
+      "{}{}{}",
+      dependency.prefix, new_constraint, dependency.suffix
+    ))
+  }
+}
+
+pub async fn outdated(
+  flags: Flags,
+  outdated_flags: OutdatedFlags,
+) -> Result<(), AnyError> {
+  let cli_factory = CliFactory::from_flags(flags).await?;
+  let cli_options = cli_factory.cli_options();
+
+  let mut files = Vec::new();
+  if let Some(config_file) = cli_options.maybe_config_file() {
+    if config_file.specifier.scheme() == "file" {
+      files.push(DependencyFile::read(
+        config_file.specifier.to_file_path().unwrap(),
+        false,
+      )?);
+    }
+    if let Some(import_map) = &config_file.json.import_map {
+      let import_map = config_file.specifier.join(import_map)?;
+      if import_map.scheme() == "file" {
+        files.push(DependencyFile::read(
+          import_map.to_file_path().unwrap(),
+          false,
+        )?);
+      }
+    }
+  }
+  if let Some(package_json) = cli_options.maybe_package_json() {
+    files.push(DependencyFile::read(package_json.path.clone(), true)?);
+  }
+  let mut dependencies = Vec::new();
+  for (file_index, file) in files.iter().enumerate() {
+    for dependency in file.dependencies()? {
+      dependencies.push((file_index, dependency));
+    }
+  }
+  if dependencies.is_empty() {
+    log::info!("No jsr: or npm: dependencies found.");
+    return Ok(());
+  }
+
+  let deps_file_fetcher = create_deps_file_fetcher(&cli_factory)?;
+  let jsr_search_api = CliJsrSearchApi::new(deps_file_fetcher.clone());
+  let npm_search_api = CliNpmSearchApi::new(deps_file_fetcher);
+  let versions = deno_core::futures::stream::iter(dependencies.iter().map(
+    |(_, dependency)| {
+      let jsr_search_api = &jsr_search_api;
+      let npm_search_api = &npm_search_api;
+      async move {
+        let versions = match dependency.kind {
+          DependencyKind::Jsr => {
+            jsr_search_api.versions(&dependency.req.name).await
+          }
+          DependencyKind::Npm => {
+            npm_search_api.versions(&dependency.req.name).await
+          }
+        };
+        versions.with_context(|| {
+          format!(
+            "Failed fetching the versions of {}",
+            dependency.display_name()
+          )
+        })
+      }
+    },
+  ))
+  .buffered(10)
+  .collect::<Vec<_>>()
+  .await;
+
+  let locked_specifiers = cli_options
+    .maybe_lockfile()
+    .map(|lockfile| lockfile.lock().content.packages.specifiers.clone())
+    .unwrap_or_default();
+  let mut rows = Vec::with_capacity(dependencies.len());
+  for ((file_index, dependency), versions) in
+    dependencies.into_iter().zip(versions)
+  {
+    let (wanted, latest) = select_versions(&dependency.req, &versions?);
+    rows.push(DependencyVersions {
+      file_index,
+      current: dependency.locked_version(&locked_specifiers),
+      dependency,
+      wanted,
+      latest,
+    });
+  }
+  rows.sort_by(|a, b| a.dependency.key.cmp(&b.dependency.key));
+
+  if outdated_flags.update {
+    update(&files, &rows, outdated_flags.latest)
+  } else {
+    print_outdated(&rows);
+    Ok(())
+  }
+}
+
+fn print_outdated(rows: &[DependencyVersions]) {
+  let version_text = |version: &Option<Version>| {
+    version
+      .as_ref()
+      .map(|version| version.to_string())
+      .unwrap_or_else(|| "-".to_string())
+  };
+  let mut table = vec![[
+    "Package".to_string(),
+    "Current".to_string(),
+    "Wanted".to_string(),
+    "Latest".to_string(),
+  ]];
+  for row in rows.iter().filter(|row| row.is_outdated()) {
+    table.push([
+      row.dependency.display_name(),
+      version_text(&row.current),
+      version_text(&row.wanted),
+      version_text(&row.latest),
+    ]);
+  }
+  if table.len() == 1 {
+    log::info!("All dependencies are up to date.");
+    return;
+  }
+  let mut widths = [0; 4];
+  for cells in &table {
+    for (width, cell) in widths.iter_mut().zip(cells) {
+      *width = (*width).max(cell.len());
+    }
+  }
+  for (index, cells) in table.iter().enumerate() {
+    let line = cells
+      .iter()
+      .zip(widths)
+      .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+      .collect::<Vec<_>>()
+      .join("  ");
+    let line = line.trim_end();
+    if index == 0 {
+      println!("{}", colors::bold(line));
+    } else {
+      println!("{}", line);
+    }
+  }
+  log::info!(
+    "\nRun {} to update them within their version constraints, or {} to update them to the latest versions.",
+    colors::bold("deno update"),
+    colors::bold("deno update --latest")
+  );
+}
+
+fn update(
+  files: &[DependencyFile],
+  rows: &[DependencyVersions],
+  latest: bool,
+) -> Result<(), AnyError> {
+  let mut new_values_by_file =
+    files.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+  for row in rows {
+    let Some(new_value) = row.updated_value(latest) else {
+      continue;
+    };
+    log::info!(
+      "{} {} {} -> {}",
+      colors::green("Update"),
+      row.dependency.key,
+      format!(
+        "{}{}{}",
+        row.dependency.prefix, row.dependency.constraint, row.dependency.suffix
+      ),
+      new_value
+    );
+    new_values_by_file[row.file_index]
+      .push((row.dependency.range.clone(), new_value));
+  }
+  if new_values_by_file
+    .iter()
+    .all(|new_values| new_values.is_empty())
+  {
+    log::info!("All dependencies are up to date.");
+    return Ok(());
+  }
+  for (file, new_values) in files.iter().zip(new_values_by_file) {
+    if !new_values.is_empty() {
+      file.write_values(new_values)?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn version(text: &str) -> Version {
+    Version::parse_standard(text).unwrap()
+  }
+
+  #[test]
+  fn parses_dependencies() {
+    let dependency =
+      parse_dependency("@std/path/", "jsr:@std/path@^0.220.0/", false, 0..1)
+        .unwrap();
+    assert_eq!(dependency.kind, DependencyKind::Jsr);
+    assert_eq!(dependency.req.name, "@std/path");
+    assert_eq!(dependency.constraint, "^0.220.0");
+    assert_eq!(dependency.prefix, "jsr:@std/path@");
+    assert_eq!(dependency.suffix, "/");
+
+    let dependency =
+      parse_dependency("chalk", "npm:chalk@5", false, 0..1).unwrap();
+    assert_eq!(dependency.kind, DependencyKind::Npm);
+    assert_eq!(dependency.req.name, "chalk");
+    assert_eq!(dependency.constraint, "5");
+    assert_eq!(dependency.suffix, "");
+
+    // package.json
+    let dependency = parse_dependency("chalk", "~5.3.0", true, 0..1).unwrap();
+    assert_eq!(dependency.req.name, "chalk");
+    assert_eq!(dependency.constraint, "~5.3.0");
+    assert_eq!(dependency.prefix, "");
+    let dependency =
+      parse_dependency("react", "npm:preact@^10.0.0", true, 0..1).unwrap();
+    assert_eq!(dependency.req.name, "preact");
+
+    assert!(
+      parse_dependency("@std/path", "jsr:@std/path", false, 0..1).is_none()
+    );
+    assert!(
+      parse_dependency("a", "https://deno.land/x/a/mod.ts", false, 0..1)
+        .is_none()
+    );
+    assert!(parse_dependency("a", "file:../a", true, 0..1).is_none());
+  }
+
+  #[test]
+  fn locked_versions() {
+    let specifiers = BTreeMap::from([
+      (
+        "jsr:@std/path@^0.220.0".to_string(),
+        "jsr:@std/path@0.220.1".to_string(),
+      ),
+      (
+        "npm:react-dom@^18.0.0".to_string(),
+        "npm:react-dom@18.2.0_react@18.2.0".to_string(),
+      ),
+    ]);
+    let locked_version = |value: &str| {
+      parse_dependency("a", value, false, 0..1)
+        .unwrap()
+        .locked_version(&specifiers)
+    };
+    assert_eq!(
+      locked_version("jsr:@std/path@^0.220.0"),
+      Some(version("0.220.1"))
+    );
+    assert_eq!(
+      locked_version("npm:react-dom@^18.0.0"),
+      Some(version("18.2.0"))
+    );
+    assert_eq!(locked_version("jsr:@std/path@^0.221.0"), None);
+  }
+
+  #[test]
+  fn selects_versions() {
+    let versions = vec![
+      version("2.0.0-rc.1"),
+      version("1.1.0"),
+      version("1.0.1"),
+      version("1.0.0"),
+    ];
+    let select = |req: &str| {
+      select_versions(&PackageReq::from_str(req).unwrap(), &versions)
+    };
+    assert_eq!(
+      select("a@~1.0.0"),
+      (Some(version("1.0.1")), Some(version("1.1.0")))
+    );
+    assert_eq!(select("a@^3.0.0"), (None, Some(version("1.1.0"))));
+  }
+
+  #[test]
+  fn updated_values() {
+    let row = |value: &str, wanted: &str, latest: &str| DependencyVersions {
+      file_index: 0,
+      dependency: parse_dependency("a", value, false, 0..1).unwrap(),
+      current: None,
+      wanted: Some(version(wanted)),
+      latest: Some(version(latest)),
+    };
+    let outdated = row("jsr:@std/path@^0.220.0/posix", "0.220.1", "1.0.0");
+    assert!(outdated.is_outdated());
+    assert_eq!(
+      outdated.updated_value(false).unwrap(),
+      "jsr:@std/path@^0.220.1/posix"
+    );
+    assert_eq!(
+      outdated.updated_value(true).unwrap(),
+      "jsr:@std/path@^1.0.0/posix"
+    );
+    let up_to_date = row("npm:chalk@5.3.0", "5.3.0", "5.3.0");
+    assert!(!up_to_date.is_outdated());
+    assert!(up_to_date.updated_value(false).is_none());
+    assert!(up_to_date.updated_value(true).is_none());
+    let range = row("npm:chalk@>=4.0.0 <6.0.0", "5.3.0", "6.0.0");
+    assert!(range.updated_value(false).is_none());
+    assert_eq!(range.updated_value(true).unwrap(), "npm:chalk@^6.0.0");
+  }
+
+  #[test]
+  fn reads_string_values() {
+    let text = r#"{
+  // comment
+  "dependencies": { "chalk": "^5.0.0", "local": 1 },
+  "devDependencies": { "@types/node": "*" },
+  "imports": { "@std/path": "jsr:@std/path@^0.220.0" }
+}"#;
+    let values =
+      string_values(text, &["dependencies", "devDependencies"]).unwrap();
+    assert_eq!(
+      values
+        .iter()
+        .map(|(key, value, range)| (
+          key.as_str(),
+          value.as_str(),
+          &text[range.clone()]
+        ))
+        .collect::<Vec<_>>(),
+      vec![
+        ("chalk", "^5.0.0", "\"^5.0.0\""),
+        ("@types/node", "*", "\"*\""),
+      ]
+    );
+  }
+}
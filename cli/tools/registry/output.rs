@@ -0,0 +1,15 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use serde::Serialize;
+
+/// Prints the output of a `deno registry` subcommand for `--json`.
+///
+/// The printed types are the schema scripts rely on, so they are defined
+/// by the subcommands rather than passed through from the registry API,
+/// and fields are only ever added to them.
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), AnyError> {
+  println!("{}", serde_json::to_string_pretty(value)?);
+  Ok(())
+}
@@ -147,6 +147,9 @@ fn valid_char(c: char) -> Option<PackagePathValidationError> {
       Some(PackagePathValidationError::InvalidWhitespace(c))
     }
     '%' | '#' => Some(PackagePathValidationError::InvalidSpecialUrlChar(c)),
+    c if !c.is_ascii() => {
+      Some(PackagePathValidationError::InvalidNonAsciiChar(c))
+    }
     // other invalid characters
     c => Some(PackagePathValidationError::InvalidOtherChar(c)),
   }
@@ -193,6 +196,12 @@ pub enum PackagePathValidationError {
   #[error("package path must not contain special URL characters (found '{}')", .0.escape_debug())]
   InvalidSpecialUrlChar(char),
 
+  #[error("package path must only contain ASCII characters, since file systems store other characters in different Unicode normalization forms (found '{}')", .0.escape_debug())]
+  InvalidNonAsciiChar(char),
+
   #[error("package path must not contain invalid characters (found '{}')", .0.escape_debug())]
   InvalidOtherChar(char),
+
+  #[error("package path must be valid UTF-8")]
+  InvalidUtf8,
 }
@@ -0,0 +1,250 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::header::HeaderMap;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::jsr_url;
+use crate::args::Flags;
+use crate::args::RegistryPingFlags;
+
+use super::api;
+use super::auth::AuthHeaderTemplate;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PingJson {
+  api: EndpointJson,
+  registry: EndpointJson,
+  /// Only checked when a token is passed.
+  auth: Option<AuthJson>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointJson {
+  url: String,
+  ok: bool,
+  status: Option<u16>,
+  latency_ms: Option<u64>,
+  error: Option<String>,
+  /// The `Server` header of the response.
+  server: Option<String>,
+  rate_limit: Option<RateLimitJson>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct RateLimitJson {
+  limit: Option<u64>,
+  remaining: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthJson {
+  ok: bool,
+  user: Option<String>,
+  error: Option<String>,
+}
+
+pub async fn ping(
+  flags: Flags,
+  ping_flags: RegistryPingFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let client = context.http_client.client()?;
+
+  let api = ping_endpoint(client, &context.endpoints.api_url).await;
+  let registry = ping_endpoint(client, jsr_url()).await;
+  let auth = match &ping_flags.token {
+    Some(token) if api.ok => {
+      let authorization =
+        AuthHeaderTemplate::from_config(context.registry_config())?
+          .token(token);
+      let auth = match context.api_client()?.get_user(&authorization).await {
+        Ok(user) => AuthJson {
+          ok: true,
+          user: Some(user.name),
+          error: None,
+        },
+        Err(err)
+          if api::as_api_error(&err).is_some_and(|err| {
+            err.status == Some(StatusCode::UNAUTHORIZED)
+          }) =>
+        {
+          AuthJson {
+            ok: false,
+            user: None,
+            error: Some(
+              "The token was rejected, it may be invalid or expired"
+                .to_string(),
+            ),
+          }
+        }
+        Err(err) => AuthJson {
+          ok: false,
+          user: None,
+          error: Some(format!("{:#}", err)),
+        },
+      };
+      Some(auth)
+    }
+    _ => None,
+  };
+  let ping = PingJson {
+    api,
+    registry,
+    auth,
+  };
+
+  if ping_flags.json {
+    print_json(&ping)?;
+  } else {
+    print_endpoint("API", &ping.api);
+    print_endpoint("Module registry", &ping.registry);
+    match &ping.auth {
+      Some(AuthJson {
+        user: Some(user), ..
+      }) => println!("{} Token of {}", colors::green("ok"), user),
+      Some(AuthJson {
+        error: Some(error), ..
+      }) => println!("{} Token: {}", colors::red("failed"), error),
+      _ => {}
+    }
+  }
+
+  if !ping.api.ok || !ping.registry.ok {
+    bail!("The registry at {} is not available", jsr_url());
+  }
+  if ping.auth.is_some_and(|auth| !auth.ok) {
+    bail!("The registry rejected the token");
+  }
+  Ok(())
+}
+
+/// Sends a request to an endpoint. Any response other than a server error
+/// counts as reachable, since the root of the API doesn't have to exist.
+async fn ping_endpoint(
+  client: &reqwest::Client,
+  url: &deno_core::url::Url,
+) -> EndpointJson {
+  let started = Instant::now();
+  let result = client.get(url.clone()).timeout(PING_TIMEOUT).send().await;
+  let latency_ms = started.elapsed().as_millis() as u64;
+  match result {
+    Ok(response) => {
+      let status = response.status();
+      EndpointJson {
+        url: url.to_string(),
+        ok: !status.is_server_error(),
+        status: Some(status.as_u16()),
+        latency_ms: Some(latency_ms),
+        error: None,
+        server: header_value(response.headers(), "server"),
+        rate_limit: rate_limit(response.headers()),
+      }
+    }
+    Err(err) => EndpointJson {
+      url: url.to_string(),
+      ok: false,
+      error: Some(if err.is_timeout() {
+        format!("no response within {} seconds", PING_TIMEOUT.as_secs())
+      } else {
+        err.to_string()
+      }),
+      ..Default::default()
+    },
+  }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+  headers
+    .get(name)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.to_string())
+}
+
+fn rate_limit(headers: &HeaderMap) -> Option<RateLimitJson> {
+  let number =
+    |name| -> Option<u64> { header_value(headers, name)?.trim().parse().ok() };
+  let limit = number("x-ratelimit-limit");
+  let remaining = number("x-ratelimit-remaining");
+  if limit.is_none() && remaining.is_none() {
+    return None;
+  }
+  Some(RateLimitJson { limit, remaining })
+}
+
+fn print_endpoint(name: &str, endpoint: &EndpointJson) {
+  match (endpoint.ok, endpoint.status) {
+    (true, _) => println!(
+      "{} {} {} {}",
+      colors::green("ok"),
+      name,
+      endpoint.url,
+      colors::gray(format!("{}ms", endpoint.latency_ms.unwrap_or_default()))
+    ),
+    (false, Some(status)) => println!(
+      "{} {} {} {}",
+      colors::red("failed"),
+      name,
+      endpoint.url,
+      colors::gray(format!("status {}", status))
+    ),
+    (false, None) => println!(
+      "{} {} {}: {}",
+      colors::red("failed"),
+      name,
+      endpoint.url,
+      endpoint.error.as_deref().unwrap_or_default()
+    ),
+  }
+  if let Some(server) = &endpoint.server {
+    println!("  {} {}", colors::gray("Server:"), server);
+  }
+  if let Some(rate_limit) = &endpoint.rate_limit {
+    let number = |n: Option<u64>| n.map(|n| n.to_string());
+    println!(
+      "  {} {} remaining of {}",
+      colors::gray("Rate limit:"),
+      number(rate_limit.remaining).as_deref().unwrap_or("?"),
+      number(rate_limit.limit).as_deref().unwrap_or("?")
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_runtime::deno_fetch::reqwest::header::HeaderValue;
+
+  use super::*;
+
+  #[test]
+  fn rate_limit_headers() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(rate_limit(&headers), None);
+    headers.insert("x-ratelimit-limit", HeaderValue::from_static("1000"));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from_static("990"));
+    assert_eq!(
+      rate_limit(&headers),
+      Some(RateLimitJson {
+        limit: Some(1000),
+        remaining: Some(990),
+      })
+    );
+    headers.remove("x-ratelimit-limit");
+    headers.insert("x-ratelimit-remaining", HeaderValue::from_static("n/a"));
+    assert_eq!(rate_limit(&headers), None);
+  }
+}
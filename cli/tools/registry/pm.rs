@@ -1,9 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 
 use deno_ast::TextChange;
+use deno_config::ConfigFile;
 use deno_config::FmtOptionsConfig;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
@@ -11,41 +16,82 @@ use deno_core::error::AnyError;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
 use deno_semver::jsr::JsrPackageReqReference;
 use deno_semver::npm::NpmPackageReqReference;
 use deno_semver::package::PackageReq;
+use deno_semver::Version;
 use jsonc_parser::ast::ObjectProp;
 use jsonc_parser::ast::Value;
 
 use crate::args::AddFlags;
+use crate::args::AddTarget;
+use crate::args::AddVersionRange;
 use crate::args::CacheSetting;
 use crate::args::Flags;
+use crate::cache::CACHE_PERM;
 use crate::factory::CliFactory;
 use crate::file_fetcher::FileFetcher;
 use crate::lsp::jsr::CliJsrSearchApi;
+use crate::lsp::npm::CliNpmSearchApi;
 use crate::lsp::search::PackageSearchApi;
+use crate::util::fs::atomic_write_file;
+
+use super::api::JsrApiClient;
+use super::api::PackageVersion;
+use super::commands::parse_package_arg;
+use super::commands::RegistryContext;
+use super::config::CliAddConfig;
+use super::config::CliPublishConfig;
+use super::info::sort_versions;
+use super::outdated::select_versions;
 
 pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
-  let cli_factory = CliFactory::from_flags(flags.clone()).await?;
+  // the versions are only selected when the user can be prompted for them
+  let registry_context = if add_flags.interactive
+    && std::io::stdin().is_terminal()
+    && std::io::stderr().is_terminal()
+  {
+    Some(RegistryContext::new(flags.clone()).await?)
+  } else {
+    None
+  };
+  let cli_factory = CliFactory::from_flags(flags).await?;
   let cli_options = cli_factory.cli_options();
 
-  let Some(config_file) = cli_options.maybe_config_file() else {
-    tokio::fs::write(cli_options.initial_cwd().join("deno.json"), "{}\n")
-      .await
-      .context("Failed to create deno.json file")?;
-    log::info!("Created deno.json configuration file.");
-    return add(flags, add_flags).boxed_local().await;
-  };
+  // the configuration file is only created once all the packages are
+  // resolved, so nothing is left behind when one of them fails
+  let (config_file, config_file_path, original_contents) =
+    match cli_options.maybe_config_file() {
+      Some(config_file) => {
+        if config_file.specifier.scheme() != "file" {
+          bail!("Can't add dependencies to a remote configuration file");
+        }
+        let config_file_path = config_file.specifier.to_file_path().unwrap();
+        let contents = tokio::fs::read_to_string(&config_file_path)
+          .await
+          .with_context(|| {
+            format!("Failed reading '{}'", config_file_path.display())
+          })?;
+        (config_file.clone(), config_file_path, Some(contents))
+      }
+      None => {
+        let config_file_path = cli_options.initial_cwd().join("deno.json");
+        let config_file = ConfigFile::new(
+          "{}",
+          ModuleSpecifier::from_file_path(&config_file_path).unwrap(),
+        )?;
+        (config_file, config_file_path, None)
+      }
+    };
 
-  if config_file.specifier.scheme() != "file" {
-    bail!("Can't add dependencies to a remote configuration file");
+  if add_flags.alias.is_some() && add_flags.packages.len() != 1 {
+    bail!("--alias can only be used when adding a single package");
   }
-  let config_file_path = config_file.specifier.to_file_path().unwrap();
-
-  let http_client = cli_factory.http_client();
 
   let mut selected_packages = Vec::with_capacity(add_flags.packages.len());
   let mut package_reqs = Vec::with_capacity(add_flags.packages.len());
+  let mut unversioned_jsr_names = HashSet::new();
 
   for package_name in add_flags.packages.iter() {
     let req = if package_name.starts_with("npm:") {
@@ -62,30 +108,46 @@ pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
       .with_context(|| {
         format!("Failed to parse package required: {}", package_name)
       })?;
+      if add_flags.target == Some(AddTarget::PackageJson) {
+        bail!(
+          "Only npm: packages can be added to package.json. Package: {}",
+          package_name
+        );
+      }
       AddPackageReq::Jsr(pkg_req)
     };
 
+    if let AddPackageReq::Jsr(pkg_ref) = &req {
+      if pkg_ref.req().version_req.version_text() == "*" {
+        unversioned_jsr_names.insert(format!("jsr:{}", pkg_ref.req().name));
+      }
+    }
     package_reqs.push(req);
   }
 
-  let deps_http_cache = cli_factory.global_http_cache()?;
-  let mut deps_file_fetcher = FileFetcher::new(
-    deps_http_cache.clone(),
-    CacheSetting::ReloadAll,
-    true,
-    http_client.clone(),
-    Default::default(),
-    None,
-  );
-  deps_file_fetcher.set_download_log_level(log::Level::Trace);
-  let jsr_search_api = CliJsrSearchApi::new(deps_file_fetcher);
+  let version_range = match add_flags.version_range {
+    Some(version_range) => version_range,
+    None => match &original_contents {
+      Some(contents) => {
+        CliAddConfig::from_config_text(contents, &config_file.specifier)?
+          .version_range
+      }
+      None => AddVersionRange::default(),
+    },
+  };
+
+  let deps_file_fetcher = create_deps_file_fetcher(&cli_factory)?;
+  let jsr_search_api = CliJsrSearchApi::new(deps_file_fetcher.clone());
+  let npm_search_api = CliNpmSearchApi::new(deps_file_fetcher);
 
   let package_futures = package_reqs
     .into_iter()
     .map(|package_req| {
       find_package_and_select_version_for_req(
         jsr_search_api.clone(),
+        npm_search_api.clone(),
         package_req,
+        version_range,
       )
       .boxed_local()
     })
@@ -94,23 +156,124 @@ pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
   let stream_of_futures = deno_core::futures::stream::iter(package_futures);
   let mut buffered = stream_of_futures.buffer_unordered(10);
 
+  let mut not_found_names = Vec::new();
   while let Some(package_and_version_result) = buffered.next().await {
     let package_and_version = package_and_version_result?;
 
     match package_and_version {
       PackageAndVersion::NotFound(package_name) => {
-        bail!("{} was not found.", crate::colors::red(package_name));
+        not_found_names.push(package_name);
       }
-      PackageAndVersion::Selected(selected) => {
+      PackageAndVersion::Selected(mut selected) => {
+        if let Some(alias) = &add_flags.alias {
+          selected.import_name = alias.clone();
+        }
         selected_packages.push(selected);
       }
     }
   }
+  if !not_found_names.is_empty() {
+    not_found_names.sort();
+    bail!(
+      "{} {} not found, so no packages were added.",
+      crate::colors::red(not_found_names.join(", ")),
+      if not_found_names.len() == 1 {
+        "was"
+      } else {
+        "were"
+      }
+    );
+  }
 
-  let config_file_contents =
-    tokio::fs::read_to_string(&config_file_path).await.unwrap();
+  if let Some(registry_context) = &registry_context {
+    let api_client = registry_context.api_client()?;
+    for selected_package in selected_packages.iter_mut() {
+      if !unversioned_jsr_names.contains(&selected_package.package_name) {
+        continue;
+      }
+      if let Some(version) =
+        prompt_for_version(&api_client, &selected_package.package_name).await?
+      {
+        selected_package.version_req =
+          version_req_in_range(version_range, &version);
+      }
+    }
+  }
+
+  // the npm packages are dependencies of the package.json file, when there
+  // is one, unless they are added to the configuration file explicitly
+  let npm_target = add_flags.target.unwrap_or_else(|| {
+    if cli_options.maybe_package_json().is_some() {
+      AddTarget::PackageJson
+    } else {
+      AddTarget::Deno
+    }
+  });
+  let (package_json_packages, selected_packages): (Vec<_>, Vec<_>) =
+    selected_packages.into_iter().partition(|selected_package| {
+      npm_target == AddTarget::PackageJson
+        && selected_package.package_name.starts_with("npm:")
+    });
+
+  let mut file_changes = FileChanges::default();
+  if !selected_packages.is_empty() {
+    let new_text = update_config_file(
+      &config_file,
+      original_contents.as_deref(),
+      selected_packages,
+      add_flags.dev,
+    )?;
+    file_changes.add(config_file_path, original_contents, new_text);
+  }
+  if !package_json_packages.is_empty() {
+    let package_json_path = match cli_options.maybe_package_json() {
+      Some(package_json) => package_json.path.clone(),
+      None => cli_options.initial_cwd().join("package.json"),
+    };
+    let original_contents = match std::fs::read_to_string(&package_json_path) {
+      Ok(contents) => Some(contents),
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+      Err(err) => {
+        return Err(err).with_context(|| {
+          format!("Failed reading '{}'", package_json_path.display())
+        })
+      }
+    };
+    let new_text = update_package_json(
+      &config_file,
+      original_contents.as_deref(),
+      package_json_packages,
+      add_flags.dev,
+    )?;
+    file_changes.add(package_json_path, original_contents, new_text);
+  }
+  file_changes
+    .write()
+    .context("Failed to update configuration file")?;
+  for path in file_changes.created_paths() {
+    if path.ends_with("package.json") {
+      log::info!("Created package.json file.");
+    } else {
+      log::info!("Created deno.json configuration file.");
+    }
+  }
+
+  // TODO(bartlomieju): we should now cache the imports from the config file.
+
+  Ok(())
+}
+
+/// Adds the packages to the `"imports"` of the configuration file, and to
+/// its `"publish.devImports"` when they are dev-only dependencies.
+fn update_config_file(
+  config_file: &ConfigFile,
+  original_contents: Option<&str>,
+  selected_packages: Vec<SelectedPackage>,
+  dev: bool,
+) -> Result<String, AnyError> {
+  let config_file_contents = original_contents.unwrap_or("{}\n");
   let ast = jsonc_parser::parse_to_ast(
-    &config_file_contents,
+    config_file_contents,
     &Default::default(),
     &Default::default(),
   )?;
@@ -120,6 +283,18 @@ pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
     _ => bail!("Failed updating config file due to no object."),
   };
 
+  // the packages added with `--dev` are dev imports, and the ones that are
+  // added again without it aren't anymore
+  let existing_dev_imports =
+    CliPublishConfig::from_config_file(config_file)?.dev_imports;
+  let mut dev_imports = existing_dev_imports.clone();
+  for selected_package in &selected_packages {
+    dev_imports.retain(|key| *key != selected_package.import_name);
+    if dev {
+      dev_imports.push(selected_package.import_name.clone());
+    }
+  }
+
   let mut existing_imports =
     if let Some(imports) = config_file.json.imports.clone() {
       match serde_json::from_value::<HashMap<String, String>>(imports) {
@@ -132,10 +307,11 @@ pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
 
   for selected_package in selected_packages {
     log::info!(
-      "Add {} - {}@{}",
+      "Add {} - {}@{}{}",
       crate::colors::green(&selected_package.import_name),
       selected_package.package_name,
-      selected_package.version_req
+      selected_package.version_req,
+      if dev { " (dev)" } else { "" }
     );
     existing_imports.insert(
       selected_package.import_name,
@@ -151,27 +327,314 @@ pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
   import_list.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
   let generated_imports = generate_imports(import_list);
 
-  let fmt_config_options = config_file
+  let fmt_config_options = config_file_fmt_options(config_file);
+
+  let mut new_text = update_config_file_content(
+    obj,
+    config_file_contents,
+    "imports",
+    generated_imports,
+    fmt_config_options.clone(),
+  );
+  if dev_imports != existing_dev_imports {
+    new_text =
+      update_dev_imports_content(&new_text, &dev_imports, fmt_config_options)?;
+  }
+  Ok(new_text)
+}
+
+/// Adds the npm packages to the `"dependencies"` of the `package.json`
+/// file, or to its `"devDependencies"` when they are dev-only dependencies,
+/// and removes them from the other one like npm does.
+fn update_package_json(
+  config_file: &ConfigFile,
+  original_contents: Option<&str>,
+  selected_packages: Vec<SelectedPackage>,
+  dev: bool,
+) -> Result<String, AnyError> {
+  let package_json_contents = original_contents.unwrap_or("{}\n");
+  let (key, other_key) = if dev {
+    ("devDependencies", "dependencies")
+  } else {
+    ("dependencies", "devDependencies")
+  };
+
+  let value = serde_json::from_str::<serde_json::Value>(package_json_contents)
+    .context("Failed parsing package.json file")?;
+  let dependencies_of =
+    |key: &str| -> Result<HashMap<String, String>, AnyError> {
+      match value.get(key) {
+        Some(dependencies) => {
+          match serde_json::from_value::<HashMap<String, String>>(
+            dependencies.clone(),
+          ) {
+            Ok(dependencies) => Ok(dependencies),
+            Err(_) => bail!("Malformed \"{}\" in package.json", key),
+          }
+        }
+        None => Ok(HashMap::default()),
+      }
+    };
+  let mut dependencies = dependencies_of(key)?;
+  let mut other_dependencies = dependencies_of(other_key)?;
+  let other_dependencies_len = other_dependencies.len();
+
+  for selected_package in selected_packages {
+    log::info!(
+      "Add {} - {}@{}{}",
+      crate::colors::green(&selected_package.import_name),
+      selected_package.package_name,
+      selected_package.version_req,
+      if dev { " (dev)" } else { "" }
+    );
+    // the aliased packages are written like `"react": "npm:preact@^10.0.0"`
+    let is_alias = selected_package.package_name.strip_prefix("npm:")
+      != Some(selected_package.import_name.as_str());
+    let value = if is_alias {
+      format!(
+        "{}@{}",
+        selected_package.package_name, selected_package.version_req
+      )
+    } else {
+      selected_package.version_req
+    };
+    other_dependencies.remove(&selected_package.import_name);
+    dependencies.insert(selected_package.import_name, value);
+  }
+
+  let fmt_options = config_file_fmt_options(config_file);
+  let mut new_text = update_dependencies_content(
+    package_json_contents,
+    key,
+    dependencies,
+    fmt_options.clone(),
+  )?;
+  if other_dependencies.len() != other_dependencies_len {
+    new_text = update_dependencies_content(
+      &new_text,
+      other_key,
+      other_dependencies,
+      fmt_options,
+    )?;
+  }
+  Ok(new_text)
+}
+
+fn update_dependencies_content(
+  package_json_contents: &str,
+  key: &str,
+  dependencies: HashMap<String, String>,
+  fmt_options: FmtOptionsConfig,
+) -> Result<String, AnyError> {
+  let ast = jsonc_parser::parse_to_ast(
+    package_json_contents,
+    &Default::default(),
+    &Default::default(),
+  )?;
+  let Some(Value::Object(obj)) = ast.value else {
+    bail!("Failed updating package.json file due to no object.");
+  };
+  let mut dependency_list: Vec<(String, String)> =
+    dependencies.into_iter().collect();
+  dependency_list.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+  Ok(update_config_file_content(
+    obj,
+    package_json_contents,
+    key,
+    generate_imports(dependency_list),
+    fmt_options,
+  ))
+}
+
+fn config_file_fmt_options(config_file: &ConfigFile) -> FmtOptionsConfig {
+  config_file
     .to_fmt_config()
     .ok()
     .flatten()
     .map(|config| config.options)
-    .unwrap_or_default();
+    .unwrap_or_default()
+}
 
-  let new_text = update_config_file_content(
-    obj,
-    &config_file_contents,
-    generated_imports,
-    fmt_config_options,
+/// A file fetcher for the metadata of the packages in the registries, which
+/// is always fetched again to find their latest versions.
+pub fn create_deps_file_fetcher(
+  cli_factory: &CliFactory,
+) -> Result<FileFetcher, AnyError> {
+  let mut deps_file_fetcher = FileFetcher::new(
+    cli_factory.global_http_cache()?.clone(),
+    CacheSetting::ReloadAll,
+    true,
+    cli_factory.http_client().clone(),
+    Default::default(),
+    None,
   );
+  deps_file_fetcher.set_download_log_level(log::Level::Trace);
+  Ok(deps_file_fetcher)
+}
 
-  tokio::fs::write(&config_file_path, new_text)
-    .await
-    .context("Failed to update configuration file")?;
+/// The new contents of the files that adding the packages changes. They are
+/// written together, and the ones that were already written are restored
+/// when writing one of them fails.
+#[derive(Default)]
+struct FileChanges {
+  /// The path, original contents, or `None` for a new file, and new
+  /// contents of each file.
+  changes: Vec<(PathBuf, Option<String>, String)>,
+}
 
-  // TODO(bartlomieju): we should now cache the imports from the config file.
+impl FileChanges {
+  fn add(
+    &mut self,
+    path: PathBuf,
+    original_contents: Option<String>,
+    new_contents: String,
+  ) {
+    if original_contents.as_ref() != Some(&new_contents) {
+      self.changes.push((path, original_contents, new_contents));
+    }
+  }
 
-  Ok(())
+  /// The paths of the files that don't exist yet.
+  fn created_paths(&self) -> Vec<&Path> {
+    self
+      .changes
+      .iter()
+      .filter(|(_, original_contents, _)| original_contents.is_none())
+      .map(|(path, _, _)| path.as_path())
+      .collect()
+  }
+
+  fn write(&self) -> Result<(), AnyError> {
+    for (index, (path, _, new_contents)) in self.changes.iter().enumerate() {
+      if let Err(err) = atomic_write_file(path, new_contents, CACHE_PERM) {
+        self.restore(index);
+        return Err(err)
+          .with_context(|| format!("Failed writing '{}'", path.display()));
+      }
+    }
+    Ok(())
+  }
+
+  /// Restores the files that were written before the one that failed.
+  fn restore(&self, written_count: usize) {
+    for (path, original_contents, _) in &self.changes[..written_count] {
+      let result = match original_contents {
+        Some(original_contents) => {
+          atomic_write_file(path, original_contents, CACHE_PERM)
+        }
+        None => std::fs::remove_file(path),
+      };
+      if let Err(err) = result {
+        log::warn!(
+          "{} Failed restoring '{}': {:#}",
+          crate::colors::yellow("Warning"),
+          path.display(),
+          err
+        );
+      }
+    }
+  }
+}
+
+/// The number of recent versions that can be selected from.
+const MAX_LISTED_VERSIONS: usize = 10;
+
+/// Lets the user select one of the recent versions of a jsr package, eg.
+/// `jsr:@std/path`. Returns `None` when the API has no versions of it.
+async fn prompt_for_version(
+  api_client: &JsrApiClient,
+  package_name: &str,
+) -> Result<Option<Version>, AnyError> {
+  let package = parse_package_arg(package_name)?;
+  let versions = api_client
+    .get_package_versions(&package.scope, &package.package)
+    .await?
+    .unwrap_or_default();
+  let versions = sort_versions(versions)
+    .into_iter()
+    .filter(|version| Version::parse_standard(&version.version).is_ok())
+    .take(MAX_LISTED_VERSIONS)
+    .collect::<Vec<_>>();
+  if versions.is_empty() {
+    return Ok(None);
+  }
+  let default_index = default_version_index(&versions);
+  // prompts on stderr, like the other prompts of the registry commands
+  eprint!("{}", render_version_choices(package_name, &versions));
+  loop {
+    eprint!("Select a version [{}]: ", default_index + 1);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+      bail!("No version of {} was selected", package_name);
+    }
+    match parse_version_choice(answer.trim(), &versions, default_index) {
+      Some(version) => return Ok(Some(version)),
+      None => eprintln!(
+        "Enter a number from 1 to {} or one of the versions.",
+        versions.len()
+      ),
+    }
+  }
+}
+
+/// The latest stable version that isn't yanked, from the versions sorted
+/// from the newest.
+fn default_version_index(versions: &[PackageVersion]) -> usize {
+  let is_stable = |version: &PackageVersion| {
+    Version::parse_standard(&version.version)
+      .map(|version| version.pre.is_empty())
+      .unwrap_or(false)
+  };
+  versions
+    .iter()
+    .position(|version| !version.yanked && is_stable(version))
+    .or_else(|| versions.iter().position(|version| !version.yanked))
+    .unwrap_or(0)
+}
+
+fn render_version_choices(
+  package_name: &str,
+  versions: &[PackageVersion],
+) -> String {
+  let mut text = format!("Recent versions of {}:\n", package_name);
+  for (index, version) in versions.iter().enumerate() {
+    // only the date of the timestamp
+    let date = version
+      .created_at
+      .as_deref()
+      .and_then(|created_at| created_at.split('T').next())
+      .unwrap_or("");
+    text.push_str(&format!(
+      "  {}) {} {}{}\n",
+      index + 1,
+      version.version,
+      crate::colors::gray(date),
+      if version.yanked {
+        format!(" {}", crate::colors::yellow("(yanked)"))
+      } else {
+        String::new()
+      }
+    ));
+  }
+  text
+}
+
+/// Gets the version of an answer, which is empty for the default version,
+/// the number of a version or the version itself.
+fn parse_version_choice(
+  answer: &str,
+  versions: &[PackageVersion],
+  default_index: usize,
+) -> Option<Version> {
+  let version = if answer.is_empty() {
+    versions.get(default_index)?
+  } else if let Ok(number) = answer.parse::<usize>() {
+    versions.get(number.checked_sub(1)?)?
+  } else {
+    versions.iter().find(|version| version.version == answer)?
+  };
+  Version::parse_standard(&version.version).ok()
 }
 
 struct SelectedPackage {
@@ -185,48 +648,127 @@ enum PackageAndVersion {
   Selected(SelectedPackage),
 }
 
+/// The version requirement of a version in the range, eg. `^1.2.3`.
+fn version_req_in_range(
+  version_range: AddVersionRange,
+  version: &Version,
+) -> String {
+  match version_range {
+    AddVersionRange::Caret => format!("^{}", version),
+    AddVersionRange::Tilde => format!("~{}", version),
+    AddVersionRange::Exact => version.to_string(),
+  }
+}
+
+/// The version requirement that was passed, where a partial version is
+/// written as an x-range to make it explicit, eg. `10.x` for `10`.
+fn explicit_version_req(version_text: &str) -> String {
+  let parts = version_text.split('.').collect::<Vec<_>>();
+  let is_partial_version = parts.len() < 3
+    && parts
+      .iter()
+      .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+  if is_partial_version {
+    format!("{}.x", version_text)
+  } else {
+    version_text.to_string()
+  }
+}
+
+/// Selects the latest version of a package, or the latest one that the
+/// version constraint allows, eg. `npm:preact@10`.
+fn select_package_version(
+  prefixed_name: String,
+  req: &PackageReq,
+  versions: &[Version],
+  version_range: AddVersionRange,
+) -> PackageAndVersion {
+  let version_text = req.version_req.version_text();
+  let (wanted, latest) = select_versions(req, versions);
+  if version_text == "*" {
+    let Some(latest_version) = latest else {
+      return PackageAndVersion::NotFound(prefixed_name);
+    };
+    PackageAndVersion::Selected(SelectedPackage {
+      import_name: req.name.to_string(),
+      package_name: prefixed_name,
+      version_req: version_req_in_range(version_range, &latest_version),
+    })
+  } else {
+    if wanted.is_none() {
+      return PackageAndVersion::NotFound(format!(
+        "{}@{}",
+        prefixed_name, version_text
+      ));
+    }
+    PackageAndVersion::Selected(SelectedPackage {
+      import_name: req.name.to_string(),
+      package_name: prefixed_name,
+      version_req: explicit_version_req(version_text),
+    })
+  }
+}
+
 async fn jsr_find_package_and_select_version(
   jsr_search_api: CliJsrSearchApi,
   req: &PackageReq,
+  version_range: AddVersionRange,
 ) -> Result<PackageAndVersion, AnyError> {
   let jsr_prefixed_name = format!("jsr:{}", req.name);
 
-  // TODO(bartlomieju): Need to do semver as well - @luca/flag@^0.14 should use to
-  // highest possible `0.14.x` version.
-  let version_req = req.version_req.version_text();
-  if version_req != "*" {
-    bail!("Specifying version constraints is currently not supported. Package: {}@{}", jsr_prefixed_name, version_req);
-  }
-
   let Ok(versions) = jsr_search_api.versions(&req.name).await else {
     return Ok(PackageAndVersion::NotFound(jsr_prefixed_name));
   };
 
-  let Some(latest_version) = versions.first() else {
-    return Ok(PackageAndVersion::NotFound(jsr_prefixed_name));
+  Ok(select_package_version(
+    jsr_prefixed_name,
+    req,
+    &versions,
+    version_range,
+  ))
+}
+
+async fn npm_find_package_and_select_version(
+  npm_search_api: CliNpmSearchApi,
+  req: &PackageReq,
+  version_range: AddVersionRange,
+) -> Result<PackageAndVersion, AnyError> {
+  let npm_prefixed_name = format!("npm:{}", req.name);
+
+  let Ok(versions) = npm_search_api.versions(&req.name).await else {
+    return Ok(PackageAndVersion::NotFound(npm_prefixed_name));
   };
 
-  Ok(PackageAndVersion::Selected(SelectedPackage {
-    import_name: req.name.to_string(),
-    package_name: jsr_prefixed_name,
-    // TODO(bartlomieju): fix it, it should not always be caret
-    version_req: format!("^{}", latest_version),
-  }))
+  Ok(select_package_version(
+    npm_prefixed_name,
+    req,
+    &versions,
+    version_range,
+  ))
 }
 
 async fn find_package_and_select_version_for_req(
   jsr_search_api: CliJsrSearchApi,
+  npm_search_api: CliNpmSearchApi,
   add_package_req: AddPackageReq,
+  version_range: AddVersionRange,
 ) -> Result<PackageAndVersion, AnyError> {
   match add_package_req {
     AddPackageReq::Jsr(pkg_ref) => {
-      jsr_find_package_and_select_version(jsr_search_api, pkg_ref.req()).await
+      jsr_find_package_and_select_version(
+        jsr_search_api,
+        pkg_ref.req(),
+        version_range,
+      )
+      .await
     }
-    AddPackageReq::Npm(pkg_req) => {
-      bail!(
-        "Adding npm: packages is currently not supported. Package: npm:{}",
-        pkg_req.req().name
-      );
+    AddPackageReq::Npm(pkg_ref) => {
+      npm_find_package_and_select_version(
+        npm_search_api,
+        pkg_ref.req(),
+        version_range,
+      )
+      .await
     }
   }
 }
@@ -249,15 +791,19 @@ fn generate_imports(packages_to_version: Vec<(String, String)>) -> String {
   contents.join("\n")
 }
 
+/// Replaces the entries of an object property of the file, eg. the
+/// `"imports"` of the configuration file or the `"dependencies"` of the
+/// `package.json` file, which is inserted when it doesn't exist.
 fn update_config_file_content(
   obj: jsonc_parser::ast::Object,
   config_file_contents: &str,
+  key: &str,
   generated_imports: String,
   fmt_options: FmtOptionsConfig,
 ) -> String {
   let mut text_changes = vec![];
 
-  match obj.get("imports") {
+  match obj.get(key) {
     Some(ObjectProp {
       value: Value::Object(lit),
       ..
@@ -269,22 +815,246 @@ fn update_config_file_content(
       let insert_position = obj.range.end - 1;
       text_changes.push(TextChange {
         range: insert_position..insert_position,
-        new_text: format!("\"imports\": {{ {} }}", generated_imports),
+        new_text: format!("\"{}\": {{ {} }}", key, generated_imports),
       })
     }
-    // we verified the shape of `imports` above
+    // we verified the shape of the property above
     Some(_) => unreachable!(),
   }
 
   let new_text =
     deno_ast::apply_text_changes(config_file_contents, text_changes);
 
+  format_config_file_content(new_text, &fmt_options)
+}
+
+/// Sets the `"publish.devImports"` of the configuration file, which keeps
+/// the dev-only dependencies out of the published packages.
+fn update_dev_imports_content(
+  config_file_contents: &str,
+  dev_imports: &[String],
+  fmt_options: FmtOptionsConfig,
+) -> Result<String, AnyError> {
+  let ast = jsonc_parser::parse_to_ast(
+    config_file_contents,
+    &Default::default(),
+    &Default::default(),
+  )?;
+  let Some(Value::Object(obj)) = ast.value else {
+    bail!("Failed updating config file due to no object.");
+  };
+  let dev_imports_text = serde_json::to_string(dev_imports)?;
+  let text_change = match obj.get("publish") {
+    Some(ObjectProp {
+      value: Value::Object(publish),
+      ..
+    }) => match (publish.get("devImports"), publish.properties.last()) {
+      (
+        Some(ObjectProp {
+          value: Value::Array(array),
+          ..
+        }),
+        _,
+      ) => TextChange {
+        range: array.range.start..array.range.end,
+        new_text: dev_imports_text,
+      },
+      (Some(_), _) => bail!("Malformed \"publish.devImports\" configuration"),
+      (None, Some(last)) => TextChange {
+        range: last.range.end..last.range.end,
+        new_text: format!(", \"devImports\": {}", dev_imports_text),
+      },
+      (None, None) => TextChange {
+        range: (publish.range.start + 1)..(publish.range.start + 1),
+        new_text: format!("\"devImports\": {}", dev_imports_text),
+      },
+    },
+    Some(_) => bail!("Malformed \"publish\" configuration"),
+    None => {
+      // there is at least the "imports" property after adding a package
+      let end = obj.properties.last().map(|last| last.range.end).unwrap();
+      TextChange {
+        range: end..end,
+        new_text: format!(
+          ", \"publish\": {{ \"devImports\": {} }}",
+          dev_imports_text
+        ),
+      }
+    }
+  };
+  let new_text =
+    deno_ast::apply_text_changes(config_file_contents, vec![text_change]);
+  Ok(format_config_file_content(new_text, &fmt_options))
+}
+
+fn format_config_file_content(
+  new_text: String,
+  fmt_options: &FmtOptionsConfig,
+) -> String {
   crate::tools::fmt::format_json(
     &PathBuf::from("deno.json"),
     &new_text,
-    &fmt_options,
+    fmt_options,
   )
   .ok()
   .map(|formatted_text| formatted_text.unwrap_or_else(|| new_text.clone()))
   .unwrap_or(new_text)
 }
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json::json;
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn package_json_dependencies() {
+    let config_file = ConfigFile::new(
+      "{}",
+      ModuleSpecifier::parse("file:///deno.json").unwrap(),
+    )
+    .unwrap();
+    let selected_package = |name: &str, version_req: &str| SelectedPackage {
+      import_name: name.to_string(),
+      package_name: format!("npm:{}", name),
+      version_req: version_req.to_string(),
+    };
+    let text = r#"{
+  "name": "foo",
+  "dependencies": {
+    "chalk": "^5.0.0"
+  }
+}
+"#;
+
+    let text = update_package_json(
+      &config_file,
+      Some(text),
+      vec![selected_package("express", "^4.18.2")],
+      false,
+    )
+    .unwrap();
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+      json!({
+        "name": "foo",
+        "dependencies": {
+          "chalk": "^5.0.0",
+          "express": "^4.18.2"
+        }
+      })
+    );
+
+    // the dev dependencies aren't dependencies anymore, and the aliases
+    // keep the name of the package
+    let text = update_package_json(
+      &config_file,
+      Some(&text),
+      vec![
+        selected_package("chalk", "^5.3.0"),
+        SelectedPackage {
+          import_name: "react".to_string(),
+          package_name: "npm:preact".to_string(),
+          version_req: "10.x".to_string(),
+        },
+      ],
+      true,
+    )
+    .unwrap();
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+      json!({
+        "name": "foo",
+        "dependencies": {
+          "express": "^4.18.2"
+        },
+        "devDependencies": {
+          "chalk": "^5.3.0",
+          "react": "npm:preact@10.x"
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn explicit_version_reqs() {
+    assert_eq!(explicit_version_req("10"), "10.x");
+    assert_eq!(explicit_version_req("10.2"), "10.2.x");
+    assert_eq!(explicit_version_req("10.2.1"), "10.2.1");
+    assert_eq!(explicit_version_req("^10.2"), "^10.2");
+    assert_eq!(explicit_version_req("1.x"), "1.x");
+  }
+
+  #[test]
+  fn version_choices() {
+    let package_version =
+      |version: &str, yanked: bool, created_at: &str| PackageVersion {
+        scope: "foo".to_string(),
+        package: "bar".to_string(),
+        version: version.to_string(),
+        yanked,
+        created_at: Some(created_at.to_string()),
+      };
+    let versions = vec![
+      package_version("2.0.0-rc.1", false, "2024-03-03T10:00:00Z"),
+      package_version("1.1.0", true, "2024-03-02T10:00:00Z"),
+      package_version("1.0.0", false, "2024-03-01T10:00:00Z"),
+    ];
+
+    let default_index = default_version_index(&versions);
+    assert_eq!(default_index, 2);
+    assert_eq!(
+      test_util::strip_ansi_codes(&render_version_choices(
+        "jsr:@foo/bar",
+        &versions
+      )),
+      "Recent versions of jsr:@foo/bar:
+  1) 2.0.0-rc.1 2024-03-03
+  2) 1.1.0 2024-03-02 (yanked)
+  3) 1.0.0 2024-03-01
+"
+    );
+    let choice = |answer: &str| {
+      parse_version_choice(answer, &versions, default_index)
+        .map(|version| version.to_string())
+    };
+    assert_eq!(choice("").unwrap(), "1.0.0");
+    assert_eq!(choice("1").unwrap(), "2.0.0-rc.1");
+    assert_eq!(choice("1.1.0").unwrap(), "1.1.0");
+    assert!(choice("0").is_none());
+    assert!(choice("4").is_none());
+    assert!(choice("3.0.0").is_none());
+  }
+
+  #[test]
+  fn file_changes_are_restored_when_writing_fails() {
+    let temp_dir = TempDir::new();
+    let config_path = temp_dir.path().join("deno.json");
+    config_path.write("{}\n");
+    let new_file_path = temp_dir.path().join("new.json");
+    // a directory can't be replaced with a file
+    let directory_path = temp_dir.path().join("package.json");
+    directory_path.create_dir_all();
+    directory_path.join("file.txt").write("");
+
+    let mut file_changes = FileChanges::default();
+    file_changes.add(
+      config_path.to_path_buf(),
+      Some("{}\n".to_string()),
+      "{ \"imports\": {} }\n".to_string(),
+    );
+    file_changes.add(new_file_path.to_path_buf(), None, "{}\n".to_string());
+    file_changes.add(
+      directory_path.to_path_buf(),
+      Some("{}\n".to_string()),
+      "{ \"dependencies\": {} }\n".to_string(),
+    );
+    assert_eq!(file_changes.created_paths(), vec![new_file_path.as_path()]);
+    file_changes.write().unwrap_err();
+
+    assert_eq!(config_path.read_to_string(), "{}\n");
+    assert!(!new_file_path.exists());
+    assert!(directory_path.is_dir());
+  }
+}
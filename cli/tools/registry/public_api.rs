@@ -0,0 +1,151 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The public API of a package as its users and the generated documentation
+//! see it, which are the fast check modules of the modules reachable from
+//! its exports, for `deno publish --show-fast-check`.
+
+use std::fmt::Write;
+
+use deno_config::ConfigFile;
+use deno_core::error::AnyError;
+use deno_graph::ModuleGraph;
+use deno_graph::WalkOptions;
+use deno_terminal::colors;
+
+/// Renders the fast check modules of a package, starting with its exports.
+pub fn render_public_api(
+  graph: &ModuleGraph,
+  config_file: &ConfigFile,
+  display_name: &str,
+) -> Result<String, AnyError> {
+  let dir_url = config_file.specifier.join("./")?;
+  let roots = config_file.resolve_export_value_urls()?;
+  let mut text = String::new();
+  writeln!(text, "{} {}", colors::green("Public API of"), display_name)?;
+  let relative = |specifier: &deno_ast::ModuleSpecifier| {
+    dir_url
+      .make_relative(specifier)
+      .map(|path| format!("./{}", path))
+      .unwrap_or_else(|| specifier.to_string())
+  };
+  let options = WalkOptions {
+    check_js: true,
+    follow_dynamic: false,
+    follow_type_only: true,
+  };
+  for (specifier, _) in graph.walk(&roots, options) {
+    // the dependencies are part of the public API of their own packages
+    if !specifier.as_str().starts_with(dir_url.as_str()) {
+      continue;
+    }
+    let Some(module) = graph.get(specifier).and_then(|module| module.js())
+    else {
+      continue;
+    };
+    let is_export = roots.contains(specifier);
+    writeln!(
+      text,
+      "\n{}{}",
+      colors::gray(format!("// {}", relative(specifier))),
+      if is_export {
+        colors::gray(" (export)").to_string()
+      } else {
+        String::new()
+      }
+    )?;
+    match module.fast_check_module() {
+      Some(fast_check_module) => {
+        writeln!(text, "{}", fast_check_module.source.trim_end())?
+      }
+      None if module.fast_check_diagnostics().is_some() => writeln!(
+        text,
+        "{}",
+        colors::yellow(
+          "// this module has slow types, so its whole source is used"
+        )
+      )?,
+      None => writeln!(
+        text,
+        "{}",
+        colors::yellow("// this module has no fast check output")
+      )?,
+    }
+  }
+  Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::ModuleSpecifier;
+  use deno_graph::source::MemoryLoader;
+  use deno_graph::source::Source;
+  use deno_graph::BuildFastCheckTypeGraphOptions;
+  use deno_graph::GraphKind;
+  use deno_graph::WorkspaceFastCheckOption;
+  use deno_graph::WorkspaceMember;
+  use deno_semver::package::PackageNv;
+
+  use super::*;
+
+  #[test]
+  fn renders_exported_modules() {
+    let sources = vec![
+      (
+        "file:///pkg/mod.ts",
+        Source::Module {
+          specifier: "file:///pkg/mod.ts",
+          maybe_headers: None,
+          content: "export * from \"./add.ts\";\nexport const version: string = \"1.0.0\";\n",
+        },
+      ),
+      (
+        "file:///pkg/add.ts",
+        Source::Module {
+          specifier: "file:///pkg/add.ts",
+          maybe_headers: None,
+          content: "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+        },
+      ),
+    ];
+    let mut loader = MemoryLoader::new(sources, Vec::new());
+    let root = ModuleSpecifier::parse("file:///pkg/mod.ts").unwrap();
+    let mut graph = ModuleGraph::new(GraphKind::All);
+    deno_core::futures::executor::block_on(graph.build(
+      vec![root.clone()],
+      &mut loader,
+      Default::default(),
+    ));
+    let members = vec![WorkspaceMember {
+      base: ModuleSpecifier::parse("file:///pkg/").unwrap(),
+      nv: PackageNv::from_str("@foo/bar@1.0.0").unwrap(),
+      exports: [(".".to_string(), "./mod.ts".to_string())]
+        .into_iter()
+        .collect(),
+    }];
+    graph.build_fast_check_type_graph(BuildFastCheckTypeGraphOptions {
+      fast_check_cache: None,
+      fast_check_dts: false,
+      jsr_url_provider: None,
+      module_parser: None,
+      resolver: None,
+      npm_resolver: None,
+      workspace_fast_check: WorkspaceFastCheckOption::Enabled(&members),
+    });
+    let config_file = ConfigFile::new(
+      r#"{ "name": "@foo/bar", "version": "1.0.0", "exports": "./mod.ts" }"#,
+      ModuleSpecifier::parse("file:///pkg/deno.json").unwrap(),
+    )
+    .unwrap();
+
+    let text =
+      render_public_api(&graph, &config_file, "@foo/bar@1.0.0").unwrap();
+    let text = test_util::strip_ansi_codes(&text);
+    assert!(text.starts_with("Public API of @foo/bar@1.0.0\n"));
+    assert!(text.contains("// ./mod.ts (export)\nexport * from \"./add.ts\";"));
+    assert!(text.contains(
+      "// ./add.ts\nexport function add(a: number, b: number): number {"
+    ));
+    // the function bodies aren't part of the public API
+    assert!(!text.contains("return a + b;"));
+  }
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -9,11 +10,24 @@ use deno_config::WorkspaceMemberConfig;
 use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_graph::ModuleGraph;
+use deno_graph::Resolution;
+
+use super::config::CliPublishConfig;
 
 pub struct PublishOrderGraph {
   packages: HashMap<String, HashSet<String>>,
   in_degree: HashMap<String, usize>,
   reverse_map: HashMap<String, Vec<String>>,
+  /// The first import found of each dependency, keyed by the names of the
+  /// importing package and the dependency.
+  imports: HashMap<(String, String), PackageImport>,
+}
+
+/// An import of a module of another workspace member.
+#[derive(Debug, Clone)]
+struct PackageImport {
+  specifier: String,
+  range: deno_graph::Range,
 }
 
 impl PublishOrderGraph {
@@ -49,6 +63,53 @@ impl PublishOrderGraph {
     }
   }
 
+  /// Gets the graph of a subset of the packages, without the dependencies
+  /// on the other packages.
+  pub fn subgraph(&self, names: &HashSet<String>) -> PublishOrderGraph {
+    let packages = self
+      .packages
+      .iter()
+      .filter(|(name, _)| names.contains(*name))
+      .map(|(name, deps)| {
+        let deps = deps
+          .iter()
+          .filter(|dep| names.contains(*dep))
+          .cloned()
+          .collect();
+        (name.clone(), deps)
+      })
+      .collect();
+    let mut graph = build_publish_order_graph_from_pkgs_deps(packages);
+    graph.imports = self
+      .imports
+      .iter()
+      .filter(|((name, dep), _)| names.contains(name) && names.contains(dep))
+      .map(|(key, import)| (key.clone(), import.clone()))
+      .collect();
+    graph
+  }
+
+  /// Gets the packages that a set of packages depends on, directly or
+  /// transitively, that aren't part of the set.
+  pub fn dependencies_outside(
+    &self,
+    names: &HashSet<String>,
+  ) -> BTreeSet<String> {
+    let mut dependencies = BTreeSet::new();
+    let mut pending = names.iter().collect::<Vec<_>>();
+    while let Some(name) = pending.pop() {
+      let Some(deps) = self.packages.get(name) else {
+        continue;
+      };
+      for dep in deps {
+        if !names.contains(dep) && dependencies.insert(dep.clone()) {
+          pending.push(dep);
+        }
+      }
+    }
+    dependencies
+  }
+
   /// There could be pending packages if there's a circular dependency.
   pub fn ensure_no_pending(&self) -> Result<(), AnyError> {
     // this is inefficient, but that's ok because it's simple and will
@@ -74,6 +135,14 @@ impl PublishOrderGraph {
       }
     }
 
+    // the packages that depend on the cycle are pending too, so drop the
+    // path that leads to it
+    fn trim_to_cycle(path: Vec<String>) -> Vec<String> {
+      let last = path.last().unwrap();
+      let start = path.iter().position(|name| name == last).unwrap();
+      path[start..].to_vec()
+    }
+
     if self.in_degree.is_empty() {
       Ok(())
     } else {
@@ -82,13 +151,71 @@ impl PublishOrderGraph {
       let mut cycle =
         identify_cycle(pkg_names[0], HashSet::new(), &self.packages).unwrap();
       cycle.reverse();
-      bail!(
+      let cycle = trim_to_cycle(cycle);
+      let mut message = format!(
         "Circular package dependency detected: {}",
         cycle.join(" -> ")
       );
+      let imports = cycle
+        .windows(2)
+        .filter_map(|names| self.import_display(&names[0], &names[1]))
+        .collect::<String>();
+      if !imports.is_empty() {
+        message.push_str(&imports);
+        message.push_str("\n\nRemove one of these imports to break the cycle.");
+      }
+      bail!("{}", message);
     }
   }
 
+  /// Fails when a package depends on a workspace member that isn't
+  /// published because of `"publish": false` in its configuration file.
+  pub fn ensure_no_unpublished_dependencies(
+    &self,
+    unpublished: &HashSet<String>,
+  ) -> Result<(), AnyError> {
+    let mut dependencies = self
+      .packages
+      .iter()
+      .flat_map(|(name, deps)| {
+        deps
+          .iter()
+          .filter(|dep| unpublished.contains(*dep))
+          .map(move |dep| (name, dep))
+      })
+      .collect::<Vec<_>>();
+    if dependencies.is_empty() {
+      return Ok(());
+    }
+    dependencies.sort(); // determinism
+    let mut message = String::from(
+      "Packages depend on workspace members that aren't published because of \"publish\": false",
+    );
+    for (name, dep) in dependencies {
+      match self.import_display(name, dep) {
+        Some(import) => message.push_str(&import),
+        None => message.push_str(&format!("\n  {} -> {}", name, dep)),
+      }
+    }
+    message.push_str("\n\nOnly import them from modules that aren't reachable from the exports of the packages, eg. tests, or publish them too.");
+    bail!("{}", message);
+  }
+
+  /// The first import of a dependency of a package, as a line of an error
+  /// message.
+  fn import_display(&self, name: &str, dep: &str) -> Option<String> {
+    let import = self.imports.get(&(name.to_string(), dep.to_string()))?;
+    Some(format!(
+      "\n  {} -> {}: {}:{}:{} imports '{}'",
+      name,
+      dep,
+      import.range.specifier,
+      import.range.start.line + 1,
+      import.range.start.character + 1,
+      import.specifier
+    ))
+  }
+
   fn compute_depth(
     &self,
     package_name: &String,
@@ -112,25 +239,46 @@ impl PublishOrderGraph {
   }
 }
 
+/// Builds the publish order of the `roots` from their imports of each other
+/// and their "publish.after" constraints. The `unpublished` workspace
+/// members are only found as dependencies of them, so that
+/// `ensure_no_unpublished_dependencies` can report them.
 pub fn build_publish_order_graph(
   graph: &ModuleGraph,
   roots: &[WorkspaceMemberConfig],
+  unpublished: &[WorkspaceMemberConfig],
 ) -> Result<PublishOrderGraph, AnyError> {
-  let packages = build_pkg_deps(graph, roots)?;
-  Ok(build_publish_order_graph_from_pkgs_deps(packages))
+  let (packages, imports) = build_pkg_deps(graph, roots, unpublished)?;
+  let mut graph = build_publish_order_graph_from_pkgs_deps(packages);
+  graph.imports = imports;
+  Ok(graph)
 }
 
+#[allow(clippy::type_complexity)]
 fn build_pkg_deps(
   graph: &deno_graph::ModuleGraph,
   roots: &[WorkspaceMemberConfig],
-) -> Result<HashMap<String, HashSet<String>>, AnyError> {
+  unpublished: &[WorkspaceMemberConfig],
+) -> Result<
+  (
+    HashMap<String, HashSet<String>>,
+    HashMap<(String, String), PackageImport>,
+  ),
+  AnyError,
+> {
   let mut members = HashMap::with_capacity(roots.len());
+  let mut imports = HashMap::new();
   let mut seen_modules = HashSet::with_capacity(graph.modules().count());
   let roots = roots
     .iter()
     .map(|r| (ModuleSpecifier::from_file_path(&r.dir_path).unwrap(), r))
     .collect::<Vec<_>>();
+  let unpublished = unpublished
+    .iter()
+    .map(|r| (ModuleSpecifier::from_file_path(&r.dir_path).unwrap(), r))
+    .collect::<Vec<_>>();
   for (root_dir_url, root) in &roots {
+    let root_name = &root.package_name;
     let mut deps = HashSet::new();
     let mut pending = VecDeque::new();
     pending.extend(root.config_file.resolve_export_value_urls()?);
@@ -141,21 +289,21 @@ fn build_pkg_deps(
       let mut dep_specifiers =
         Vec::with_capacity(module.dependencies.len() + 1);
       if let Some(types_dep) = &module.maybe_types_dependency {
-        if let Some(specifier) = types_dep.dependency.maybe_specifier() {
-          dep_specifiers.push(specifier);
+        if let Resolution::Ok(resolved) = &types_dep.dependency {
+          dep_specifiers.push((types_dep.specifier.as_str(), resolved));
         }
       }
-      for (_, dep) in &module.dependencies {
-        if let Some(specifier) = dep.maybe_code.maybe_specifier() {
-          dep_specifiers.push(specifier);
+      for (text, dep) in &module.dependencies {
+        if let Resolution::Ok(resolved) = &dep.maybe_code {
+          dep_specifiers.push((text.as_str(), resolved));
         }
-        if let Some(specifier) = dep.maybe_type.maybe_specifier() {
-          dep_specifiers.push(specifier);
+        if let Resolution::Ok(resolved) = &dep.maybe_type {
+          dep_specifiers.push((text.as_str(), resolved));
         }
       }
 
-      for specifier in dep_specifiers {
-        let specifier = graph.resolve(specifier);
+      for (text, resolved) in dep_specifiers {
+        let specifier = graph.resolve(&resolved.specifier);
         if specifier.scheme() != "file" {
           continue;
         }
@@ -164,18 +312,54 @@ fn build_pkg_deps(
             pending.push_back(specifier.clone());
           }
         } else {
-          let found_root = roots.iter().find(|(dir_url, _)| {
-            specifier.as_str().starts_with(dir_url.as_str())
-          });
+          let found_root =
+            roots.iter().chain(&unpublished).find(|(dir_url, _)| {
+              specifier.as_str().starts_with(dir_url.as_str())
+            });
           if let Some(root) = found_root {
             deps.insert(root.1.package_name.clone());
+            imports
+              .entry((root_name.clone(), root.1.package_name.clone()))
+              .or_insert_with(|| PackageImport {
+                specifier: text.to_string(),
+                range: resolved.range.clone(),
+              });
           }
         }
       }
     }
+    // the ordering constraints of "publish.after"
+    for name in CliPublishConfig::from_config_file(&root.config_file)?.after {
+      let is_member = roots
+        .iter()
+        .chain(&unpublished)
+        .any(|(_, member)| member.package_name == name);
+      if is_member && &name != root_name {
+        deps.insert(name);
+      }
+    }
     members.insert(root.package_name.clone(), deps);
   }
-  Ok(members)
+  Ok((members, imports))
+}
+
+/// Fails when "publish.after" of a workspace member names a package that
+/// isn't a member of the workspace.
+pub fn ensure_known_publish_after<'a>(
+  members: impl Iterator<Item = &'a WorkspaceMemberConfig> + Clone,
+) -> Result<(), AnyError> {
+  for member in members.clone() {
+    for name in CliPublishConfig::from_config_file(&member.config_file)?.after {
+      if !members.clone().any(|other| other.package_name == name) {
+        bail!(
+          "'{}' in \"publish.after\" of {} isn't a member of the workspace",
+          name,
+          member.config_file.specifier
+        );
+      }
+    }
+  }
+  Ok(())
 }
 
 fn build_publish_order_graph_from_pkgs_deps(
@@ -199,6 +383,7 @@ fn build_publish_order_graph_from_pkgs_deps(
     packages: packages.clone(),
     in_degree,
     reverse_map,
+    imports: HashMap::new(),
   }
 }
 
@@ -275,6 +460,46 @@ mod test {
     graph.ensure_no_pending().unwrap();
   }
 
+  #[test]
+  fn test_subgraph() {
+    let graph = build_publish_order_graph_from_pkgs_deps(HashMap::from([
+      ("a".to_string(), HashSet::from(["b".to_string()])),
+      ("b".to_string(), HashSet::from(["c".to_string()])),
+      ("c".to_string(), HashSet::new()),
+    ]));
+    let mut subgraph =
+      graph.subgraph(&HashSet::from(["a".to_string(), "b".to_string()]));
+    assert_eq!(subgraph.next(), vec!["b".to_string()]);
+    subgraph.finish_package("b");
+    assert_eq!(subgraph.next(), vec!["a".to_string()]);
+    subgraph.finish_package("a");
+    subgraph.ensure_no_pending().unwrap();
+  }
+
+  #[test]
+  fn test_dependencies_outside() {
+    let graph = build_publish_order_graph_from_pkgs_deps(HashMap::from([
+      ("a".to_string(), HashSet::from(["b".to_string()])),
+      ("b".to_string(), HashSet::from(["c".to_string()])),
+      ("c".to_string(), HashSet::new()),
+      ("d".to_string(), HashSet::from(["a".to_string()])),
+    ]));
+    assert_eq!(
+      graph.dependencies_outside(&HashSet::from(["a".to_string()])),
+      BTreeSet::from(["b".to_string(), "c".to_string()])
+    );
+    assert_eq!(
+      graph.dependencies_outside(&HashSet::from([
+        "a".to_string(),
+        "b".to_string()
+      ])),
+      BTreeSet::from(["c".to_string()])
+    );
+    assert!(graph
+      .dependencies_outside(&HashSet::from(["c".to_string()]))
+      .is_empty());
+  }
+
   #[test]
   fn test_graph_circular_dep() {
     let mut graph = build_publish_order_graph_from_pkgs_deps(HashMap::from([
@@ -288,4 +513,95 @@ mod test {
       "Circular package dependency detected: a -> b -> c -> a"
     );
   }
+
+  #[test]
+  fn test_graph_circular_dep_imports() {
+    let mut graph = build_publish_order_graph_from_pkgs_deps(HashMap::from([
+      ("@a/d".to_string(), HashSet::from(["@a/x".to_string()])),
+      ("@a/x".to_string(), HashSet::from(["@a/y".to_string()])),
+      ("@a/y".to_string(), HashSet::from(["@a/x".to_string()])),
+    ]));
+    let import = |file: &str, specifier: &str| PackageImport {
+      specifier: specifier.to_string(),
+      range: deno_graph::Range {
+        specifier: ModuleSpecifier::parse(file).unwrap(),
+        start: deno_graph::Position {
+          line: 2,
+          character: 18,
+        },
+        end: deno_graph::Position {
+          line: 2,
+          character: 31,
+        },
+      },
+    };
+    graph.imports = HashMap::from([
+      (
+        ("@a/x".to_string(), "@a/y".to_string()),
+        import("file:///x/mod.ts", "../y/mod.ts"),
+      ),
+      (
+        ("@a/y".to_string(), "@a/x".to_string()),
+        import("file:///y/mod.ts", "../x/mod.ts"),
+      ),
+    ]);
+    assert!(graph.next().is_empty());
+    assert_eq!(
+      graph.ensure_no_pending().unwrap_err().to_string(),
+      concat!(
+        "Circular package dependency detected: @a/x -> @a/y -> @a/x\n",
+        "  @a/x -> @a/y: file:///x/mod.ts:3:19 imports '../y/mod.ts'\n",
+        "  @a/y -> @a/x: file:///y/mod.ts:3:19 imports '../x/mod.ts'\n",
+        "\n",
+        "Remove one of these imports to break the cycle.",
+      )
+    );
+  }
+
+  #[test]
+  fn test_graph_unpublished_dependencies() {
+    let mut graph = build_publish_order_graph_from_pkgs_deps(HashMap::from([
+      (
+        "@a/app".to_string(),
+        HashSet::from(["@a/internal".to_string()]),
+      ),
+      ("@a/lib".to_string(), HashSet::new()),
+    ]));
+    graph.imports = HashMap::from([(
+      ("@a/app".to_string(), "@a/internal".to_string()),
+      PackageImport {
+        specifier: "../internal/mod.ts".to_string(),
+        range: deno_graph::Range {
+          specifier: ModuleSpecifier::parse("file:///app/mod.ts").unwrap(),
+          start: deno_graph::Position {
+            line: 0,
+            character: 18,
+          },
+          end: deno_graph::Position {
+            line: 0,
+            character: 38,
+          },
+        },
+      },
+    )]);
+    graph
+      .ensure_no_unpublished_dependencies(&HashSet::from([
+        "@a/tools".to_string()
+      ]))
+      .unwrap();
+    assert_eq!(
+      graph
+        .ensure_no_unpublished_dependencies(&HashSet::from([
+          "@a/internal".to_string()
+        ]))
+        .unwrap_err()
+        .to_string(),
+      concat!(
+        "Packages depend on workspace members that aren't published because of \"publish\": false\n",
+        "  @a/app -> @a/internal: file:///app/mod.ts:1:19 imports '../internal/mod.ts'\n",
+        "\n",
+        "Only import them from modules that aren't reachable from the exports of the packages, eg. tests, or publish them too.",
+      )
+    );
+  }
 }
@@ -0,0 +1,160 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+
+use deno_ast::diagnostics::Diagnostic;
+use deno_ast::diagnostics::DiagnosticLevel;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use serde::Serialize;
+
+use super::diagnostics::file_and_source_pos;
+use super::diagnostics::DiagnosticsSummary;
+
+/// A diagnostic in the file written by `deno publish --report-file`.
+#[derive(Debug, Serialize)]
+pub struct ReportDiagnostic {
+  pub level: &'static str,
+  pub code: String,
+  pub message: String,
+  /// The URL of the module or the path of the file.
+  pub file: String,
+  /// The 1-based line and column, when the diagnostic has a position.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub line: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub column: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub hint: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub docs_url: Option<String>,
+}
+
+impl ReportDiagnostic {
+  pub fn new(diagnostic: &impl Diagnostic) -> Self {
+    let location = diagnostic.location();
+    let (file, pos) = file_and_source_pos(&location);
+    let position =
+      pos.map(|(pos, text_info)| text_info.line_and_column_display(pos));
+    Self {
+      level: match diagnostic.level() {
+        DiagnosticLevel::Error => "error",
+        DiagnosticLevel::Warning => "warning",
+      },
+      code: diagnostic.code().into_owned(),
+      message: diagnostic.message().into_owned(),
+      file,
+      line: position.as_ref().map(|position| position.line_number),
+      column: position.as_ref().map(|position| position.column_number),
+      hint: diagnostic.hint().map(|hint| hint.into_owned()),
+      docs_url: diagnostic.docs_url().map(|docs_url| docs_url.into_owned()),
+    }
+  }
+}
+
+impl ReportDiagnostic {
+  /// The diagnostic as one `file:line:col level[code] message` line, for
+  /// `--diagnostics-format=short`.
+  pub fn short_line(&self) -> String {
+    let file = Url::parse(&self.file)
+      .ok()
+      .filter(|url| url.scheme() == "file")
+      .and_then(|url| url.to_file_path().ok())
+      .map(|path| path.display().to_string())
+      .unwrap_or_else(|| self.file.clone());
+    let position = match (self.line, self.column) {
+      (Some(line), Some(column)) => format!(":{}:{}", line, column),
+      _ => String::new(),
+    };
+    format!(
+      "{}{} {}[{}] {}",
+      file, position, self.level, self.code, self.message
+    )
+  }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+  summary: &'a DiagnosticsSummary,
+  diagnostics: &'a [ReportDiagnostic],
+}
+
+pub fn write_report(
+  path: &Path,
+  summary: &DiagnosticsSummary,
+  diagnostics: &[ReportDiagnostic],
+) -> Result<(), AnyError> {
+  let text = serde_json::to_string_pretty(&Report {
+    summary,
+    diagnostics,
+  })?;
+  std::fs::write(path, format!("{}\n", text)).with_context(|| {
+    format!("Failed writing diagnostics report '{}'", path.display())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::ModuleSpecifier;
+  use deno_ast::SourceTextInfo;
+
+  use super::super::diagnostics::PublishDiagnostic;
+  use super::*;
+
+  #[test]
+  fn report_diagnostic_position() {
+    let specifier = ModuleSpecifier::parse("file:///pkg/mod.ts").unwrap();
+    let diagnostic = PublishDiagnostic::InvalidExternalImport {
+      kind: "https".to_string(),
+      imported: ModuleSpecifier::parse("https://example.com/mod.ts").unwrap(),
+      text_info: SourceTextInfo::from_string(
+        "// a\nimport \"https://example.com/mod.ts\";\n".to_string(),
+      ),
+      referrer: deno_graph::Range {
+        specifier,
+        start: deno_graph::Position {
+          line: 1,
+          character: 7,
+        },
+        end: deno_graph::Position {
+          line: 1,
+          character: 35,
+        },
+      },
+    };
+    let report = ReportDiagnostic::new(&diagnostic);
+    assert_eq!(report.level, "error");
+    assert_eq!(report.code, "invalid-external-import");
+    assert_eq!(report.file, "file:///pkg/mod.ts");
+    assert_eq!((report.line, report.column), (Some(2), Some(8)));
+    assert_eq!(
+      report.docs_url.as_deref(),
+      Some("https://jsr.io/go/invalid-external-import")
+    );
+    if cfg!(not(windows)) {
+      assert_eq!(
+        report.short_line(),
+        format!(
+          "/pkg/mod.ts:2:8 error[invalid-external-import] {}",
+          report.message
+        )
+      );
+    }
+
+    let diagnostic = PublishDiagnostic::DuplicatePath {
+      path: "mod.ts".into(),
+    };
+    let report = ReportDiagnostic::new(&diagnostic);
+    assert_eq!(report.file, "mod.ts");
+    assert_eq!((report.line, report.column), (None, None));
+    assert_eq!(
+      report.short_line(),
+      format!(
+        "mod.ts error[case-insensitive-duplicate-path] {}",
+        report.message
+      )
+    );
+  }
+}
@@ -0,0 +1,298 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use deno_ast::ModuleSpecifier;
+use deno_config::WorkspaceMemberConfig;
+use deno_core::error::AnyError;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_graph::Module;
+use deno_graph::ModuleEntryRef;
+use deno_graph::ModuleGraph;
+use deno_graph::WalkOptions;
+use deno_npm::resolution::NpmResolutionSnapshot;
+use deno_semver::package::PackageNv;
+
+use crate::args::jsr_url;
+
+/// The in-toto predicate type of a CycloneDX SBOM.
+pub const CYCLONEDX_PREDICATE_TYPE: &str = "https://cyclonedx.org/bom";
+
+/// A jsr or npm package the published package depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Component {
+  purl: String,
+  name: String,
+  version: String,
+}
+
+impl Component {
+  fn jsr(name: &str, version: &str) -> Component {
+    Component {
+      purl: format!("pkg:jsr/{}@{}", name, version),
+      name: name.to_string(),
+      version: version.to_string(),
+    }
+  }
+
+  fn npm(nv: &PackageNv) -> Component {
+    // the purl spec requires the `@` of a scope to be percent encoded
+    let purl_name = match nv.name.strip_prefix('@') {
+      Some(name) => format!("%40{}", name),
+      None => nv.name.clone(),
+    };
+    Component {
+      purl: format!("pkg:npm/{}@{}", purl_name, nv.version),
+      name: nv.name.clone(),
+      version: nv.version.to_string(),
+    }
+  }
+}
+
+#[derive(Default)]
+struct DependencyGraph {
+  components: BTreeMap<String, Component>,
+  /// The package URLs each component depends on, keyed by package URL.
+  dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl DependencyGraph {
+  fn add(&mut self, from: &Component, to: Component) {
+    if from.purl != to.purl {
+      self
+        .dependencies
+        .entry(from.purl.clone())
+        .or_default()
+        .insert(to.purl.clone());
+      self.components.entry(to.purl.clone()).or_insert(to);
+    }
+  }
+}
+
+/// Creates a CycloneDX SBOM of the jsr and npm packages, direct and
+/// transitive, a workspace member depends on.
+pub fn create_cyclonedx_sbom(
+  graph: &ModuleGraph,
+  npm_snapshot: Option<&NpmResolutionSnapshot>,
+  members: &[WorkspaceMemberConfig],
+  member: &WorkspaceMemberConfig,
+) -> Result<Value, AnyError> {
+  let member_dirs = members
+    .iter()
+    .map(|m| {
+      (
+        ModuleSpecifier::from_directory_path(&m.dir_path).unwrap(),
+        m,
+      )
+    })
+    .collect::<Vec<_>>();
+  let component_of = |specifier: &ModuleSpecifier| -> Option<Component> {
+    if let Some(Module::Npm(module)) = graph.get(specifier) {
+      return Some(Component::npm(module.nv_reference.nv()));
+    }
+    if specifier.scheme() == "file" {
+      let (_, member) = member_dirs
+        .iter()
+        .find(|(dir, _)| specifier.as_str().starts_with(dir.as_str()))?;
+      return Some(Component::jsr(
+        &member.package_name,
+        &member.package_version,
+      ));
+    }
+    // jsr modules are at `<registry>/@<scope>/<name>/<version>/<path>`
+    let path = specifier.as_str().strip_prefix(jsr_url().as_str())?;
+    let mut parts = path.splitn(4, '/');
+    let scope = parts.next().filter(|scope| scope.starts_with('@'))?;
+    let name = parts.next()?;
+    let version = parts.next()?;
+    Some(Component::jsr(&format!("{}/{}", scope, name), version))
+  };
+
+  let root = Component::jsr(&member.package_name, &member.package_version);
+  let mut deps = DependencyGraph::default();
+
+  let roots = member.config_file.resolve_export_value_urls()?;
+  let options = WalkOptions {
+    check_js: true,
+    follow_dynamic: true,
+    follow_type_only: true,
+  };
+  for (specifier, entry) in graph.walk(&roots, options) {
+    let ModuleEntryRef::Module(module) = entry else {
+      continue;
+    };
+    let Some(module) = module.js() else {
+      continue;
+    };
+    let Some(from) = component_of(specifier) else {
+      continue;
+    };
+    let mut dep_specifiers = Vec::new();
+    if let Some(types_dep) = &module.maybe_types_dependency {
+      dep_specifiers.extend(types_dep.dependency.maybe_specifier());
+    }
+    for dep in module.dependencies.values() {
+      dep_specifiers.extend(dep.maybe_code.maybe_specifier());
+      dep_specifiers.extend(dep.maybe_type.maybe_specifier());
+    }
+    for dep_specifier in dep_specifiers {
+      if let Some(to) = component_of(graph.resolve(dep_specifier)) {
+        deps.add(&from, to);
+      }
+    }
+  }
+
+  // the graph only has the npm packages that are imported directly
+  let mut hashes = HashMap::new();
+  if let Some(npm_snapshot) = npm_snapshot {
+    let mut pending = graph
+      .modules()
+      .filter_map(|module| match module {
+        Module::Npm(module) => Some(module.nv_reference.nv()),
+        _ => None,
+      })
+      .filter(|nv| deps.components.contains_key(&Component::npm(nv).purl))
+      .filter_map(|nv| npm_snapshot.resolve_package_from_deno_module(nv).ok())
+      .collect::<VecDeque<_>>();
+    while let Some(package) = pending.pop_front() {
+      let from = Component::npm(&package.id.nv);
+      if hashes.contains_key(&from.purl) {
+        continue;
+      }
+      hashes.insert(
+        from.purl.clone(),
+        integrity_to_hash(&package.dist.integrity().for_lockfile()),
+      );
+      for id in package.dependencies.values() {
+        if let Some(dep) = npm_snapshot.package_from_id(id) {
+          deps.add(&from, Component::npm(&dep.id.nv));
+          pending.push_back(dep);
+        }
+      }
+    }
+  }
+  for (nv, checksum, _) in graph.packages.packages_with_checksum_and_deps() {
+    let component = Component::jsr(&nv.name, &nv.version.to_string());
+    hashes.insert(
+      component.purl,
+      Some(json!({ "alg": "SHA-256", "content": checksum })),
+    );
+  }
+
+  let DependencyGraph {
+    mut components,
+    dependencies,
+  } = deps;
+  components.remove(&root.purl);
+  let components_json = components
+    .values()
+    .map(|component| {
+      let hash = hashes.get(&component.purl).cloned().flatten();
+      component_json(component, hash)
+    })
+    .collect::<Vec<_>>();
+  let dependencies_json = std::iter::once(&root.purl)
+    .chain(components.keys())
+    .map(|purl| {
+      let depends_on = dependencies.get(purl).cloned().unwrap_or_default();
+      json!({ "ref": purl, "dependsOn": depends_on })
+    })
+    .collect::<Vec<_>>();
+  let timestamp = crate::util::time::utc_now()
+    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+  Ok(json!({
+    "bomFormat": "CycloneDX",
+    "specVersion": "1.5",
+    "serialNumber": format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+    "version": 1,
+    "metadata": {
+      "timestamp": timestamp,
+      "tools": {
+        "components": [{
+          "type": "application",
+          "name": "deno",
+          "version": crate::version::deno(),
+        }],
+      },
+      "component": component_json(&root, None),
+    },
+    "components": components_json,
+    "dependencies": dependencies_json,
+  }))
+}
+
+fn component_json(component: &Component, hash: Option<Value>) -> Value {
+  let mut value = json!({
+    "type": "library",
+    "bom-ref": component.purl,
+    "name": component.name,
+    "version": component.version,
+    "purl": component.purl,
+  });
+  if let Some(hash) = hash {
+    value["hashes"] = json!([hash]);
+  }
+  value
+}
+
+/// Converts the integrity of an npm package, a subresource integrity string
+/// or the hex encoded sha1 checksum of old packages, to a CycloneDX hash.
+fn integrity_to_hash(integrity: &str) -> Option<Value> {
+  let (alg, content) = match integrity.split_once('-') {
+    Some((alg, digest)) => {
+      let alg = match alg {
+        "sha1" => "SHA-1",
+        "sha256" => "SHA-256",
+        "sha384" => "SHA-384",
+        "sha512" => "SHA-512",
+        _ => return None,
+      };
+      (alg, hex::encode(BASE64_STANDARD.decode(digest).ok()?))
+    }
+    None
+      if integrity.len() == 40
+        && integrity.chars().all(|c| c.is_ascii_hexdigit()) =>
+    {
+      ("SHA-1", integrity.to_lowercase())
+    }
+    None => return None,
+  };
+  Some(json!({ "alg": alg, "content": content }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn npm_component_purl() {
+    let nv = PackageNv::from_str("@types/node@20.0.0").unwrap();
+    assert_eq!(Component::npm(&nv).purl, "pkg:npm/%40types/node@20.0.0");
+    let nv = PackageNv::from_str("chalk@5.0.0").unwrap();
+    assert_eq!(Component::npm(&nv).purl, "pkg:npm/chalk@5.0.0");
+  }
+
+  #[test]
+  fn integrity_to_hash_formats() {
+    assert_eq!(
+      integrity_to_hash("sha512-AAEC"),
+      Some(json!({ "alg": "SHA-512", "content": "000102" }))
+    );
+    assert_eq!(
+      integrity_to_hash("DA39A3EE5E6B4B0D3255BFEF95601890AFD80709"),
+      Some(json!({
+        "alg": "SHA-1",
+        "content": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+      }))
+    );
+    assert_eq!(integrity_to_hash("md5-AAEC"), None);
+    assert_eq!(integrity_to_hash("invalid"), None);
+  }
+}
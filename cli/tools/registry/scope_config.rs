@@ -0,0 +1,185 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::serde_json::json;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryScopeConfigFlags;
+
+use super::api::GithubRepository;
+use super::auth::AuthHeaderTemplate;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeConfigJson {
+  scope: String,
+  require_publishing_from_ci: Option<bool>,
+  verify_github_actions_actor: Option<bool>,
+}
+
+pub async fn scope_config(
+  flags: Flags,
+  scope_config_flags: RegistryScopeConfigFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let scope = scope_config_flags.scope.trim_start_matches('@').to_string();
+  if scope.is_empty() || scope.contains('/') {
+    bail!(
+      "Invalid scope '{}', use '@<scope_name>' format",
+      scope_config_flags.scope
+    );
+  }
+  let Some(token) = scope_config_flags.token else {
+    bail!("No means to authenticate. Pass a token to `--token`.");
+  };
+  let authorization =
+    AuthHeaderTemplate::from_config(context.registry_config())?.token(&token);
+  let api_client = context.api_client()?;
+
+  // the registry updates one setting per request
+  let mut updates = Vec::new();
+  if let Some(value) = scope_config_flags.require_publishing_from_ci {
+    updates.push(json!({ "requirePublishingFromCI": value }));
+  }
+  if let Some(value) = scope_config_flags.verify_github_actions_actor {
+    updates.push(json!({ "ghActionsVerifyActor": value }));
+  }
+  for update in &updates {
+    api_client
+      .update_scope_settings(&authorization, &scope, update)
+      .await
+      .map_err(|err| {
+        err.context(format!("Failed to update the settings of @{}", scope))
+      })?;
+  }
+
+  if let Some(github_repository) = &scope_config_flags.github_repository {
+    // clap requires the package to be passed with the repository
+    let package = scope_config_flags.package.as_deref().unwrap();
+    let package = match package.split_once('/') {
+      Some((_, name)) => name,
+      None => package,
+    };
+    let repository = parse_github_repository(github_repository)?;
+    api_client
+      .update_package_github_repository(
+        &authorization,
+        &scope,
+        package,
+        repository.as_ref(),
+      )
+      .await
+      .map_err(|err| {
+        err.context(format!(
+          "Failed to link @{}/{} to a GitHub repository",
+          scope, package
+        ))
+      })?;
+    if !scope_config_flags.json {
+      match &repository {
+        Some(repository) => println!(
+          "{} @{}/{} to github.com/{}/{}",
+          colors::green("Linked"),
+          scope,
+          package,
+          repository.owner,
+          repository.name
+        ),
+        None => println!(
+          "{} @{}/{} from its GitHub repository",
+          colors::green("Unlinked"),
+          scope,
+          package
+        ),
+      }
+    }
+  }
+
+  let settings = api_client
+    .get_scope_settings(&authorization, &scope)
+    .await
+    .map_err(|err| {
+      err.context(format!("Failed to get the settings of @{}", scope))
+    })?;
+  let config = ScopeConfigJson {
+    scope: settings.scope,
+    require_publishing_from_ci: settings.require_publishing_from_ci,
+    verify_github_actions_actor: settings.gh_actions_verify_actor,
+  };
+
+  if scope_config_flags.json {
+    print_json(&config)?;
+  } else {
+    let setting = |value: Option<bool>| match value {
+      Some(true) => colors::green("yes").to_string(),
+      Some(false) => "no".to_string(),
+      None => colors::gray("unknown").to_string(),
+    };
+    println!("{}", colors::bold(format!("@{}", config.scope)));
+    println!(
+      "  {} {}",
+      colors::gray("Require publishing from CI:"),
+      setting(config.require_publishing_from_ci)
+    );
+    println!(
+      "  {} {}",
+      colors::gray("Verify GitHub Actions actor:"),
+      setting(config.verify_github_actions_actor)
+    );
+  }
+  Ok(())
+}
+
+/// Parses a GitHub repository, eg. `denoland/deno`, or `none` to unlink
+/// the package.
+fn parse_github_repository(
+  text: &str,
+) -> Result<Option<GithubRepository>, AnyError> {
+  if text == "none" {
+    return Ok(None);
+  }
+  let repository = text
+    .trim_start_matches("https://")
+    .trim_start_matches("github.com/")
+    .trim_end_matches(".git");
+  match repository.split_once('/') {
+    Some((owner, name))
+      if !owner.is_empty() && !name.is_empty() && !name.contains('/') =>
+    {
+      Ok(Some(GithubRepository {
+        owner: owner.to_string(),
+        name: name.to_string(),
+      }))
+    }
+    _ => bail!(
+      "Invalid GitHub repository '{}', use '<owner>/<name>' format or 'none'",
+      text
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn github_repository_arg() {
+    let expected = Some(GithubRepository {
+      owner: "denoland".to_string(),
+      name: "deno".to_string(),
+    });
+    assert_eq!(parse_github_repository("denoland/deno").unwrap(), expected);
+    assert_eq!(
+      parse_github_repository("https://github.com/denoland/deno.git").unwrap(),
+      expected
+    );
+    assert_eq!(parse_github_repository("none").unwrap(), None);
+    assert!(parse_github_repository("deno").is_err());
+    assert!(parse_github_repository("denoland/deno/cli").is_err());
+  }
+}
@@ -0,0 +1,82 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryScopesFlags;
+
+use super::api;
+use super::auth::AuthHeaderTemplate;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeJson {
+  scope: String,
+  is_admin: bool,
+}
+
+pub async fn scopes(
+  flags: Flags,
+  scopes_flags: RegistryScopesFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let Some(token) = scopes_flags.token else {
+    bail!("No means to authenticate. Pass a token to `--token`.");
+  };
+  let authorization =
+    AuthHeaderTemplate::from_config(context.registry_config())?.token(&token);
+  let api_client = context.api_client()?;
+
+  let user_scopes = match api_client.get_user_scopes(&authorization).await {
+    Ok(user_scopes) => user_scopes,
+    Err(err)
+      if api::as_api_error(&err)
+        .is_some_and(|err| err.status == Some(StatusCode::UNAUTHORIZED)) =>
+    {
+      bail!(
+        "The provided token was rejected by the registry. It may be invalid or expired."
+      )
+    }
+    Err(err) => {
+      return Err(err.context("Failed to get the scopes of the user"))
+    }
+  };
+  let mut scopes = Vec::with_capacity(user_scopes.len());
+  for scope in user_scopes {
+    let member = api_client
+      .get_user_scope_member(&authorization, &scope.scope)
+      .await?;
+    scopes.push(ScopeJson {
+      scope: scope.scope,
+      is_admin: member.is_admin,
+    });
+  }
+  scopes.sort_by(|a, b| a.scope.cmp(&b.scope));
+
+  if scopes_flags.json {
+    print_json(&scopes)?;
+    return Ok(());
+  }
+  if scopes.is_empty() {
+    println!("The owner of the token is not a member of any scope.");
+    println!(
+      "{}",
+      colors::gray(format!(
+        "Create a scope at {}new",
+        context.endpoints.manage_url
+      ))
+    );
+    return Ok(());
+  }
+  for scope in &scopes {
+    let role = if scope.is_admin { "admin" } else { "member" };
+    println!("@{} {}", scope.scope, colors::gray(role));
+  }
+  Ok(())
+}
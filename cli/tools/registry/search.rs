@@ -0,0 +1,75 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistrySearchFlags;
+
+use super::api::Package;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResultJson {
+  name: String,
+  description: String,
+  latest_version: Option<String>,
+  /// The documentation score in percent.
+  score: Option<u32>,
+}
+
+impl From<Package> for SearchResultJson {
+  fn from(package: Package) -> Self {
+    SearchResultJson {
+      name: format!("@{}/{}", package.scope, package.name),
+      description: package.description,
+      latest_version: package.latest_version,
+      score: package.score,
+    }
+  }
+}
+
+pub async fn search(
+  flags: Flags,
+  search_flags: RegistrySearchFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let packages = context
+    .api_client()?
+    .search_packages(&search_flags.query, search_flags.limit)
+    .await?;
+  let results = packages
+    .into_iter()
+    // packages that were created, but never published
+    .filter(|package| package.version_count > 0)
+    .take(search_flags.limit.unwrap_or(usize::MAX))
+    .map(SearchResultJson::from)
+    .collect::<Vec<_>>();
+
+  if search_flags.json {
+    print_json(&results)?;
+    return Ok(());
+  }
+  if results.is_empty() {
+    println!("No packages found for '{}'", search_flags.query);
+    return Ok(());
+  }
+  for result in &results {
+    let mut line = colors::bold(&result.name).to_string();
+    if let Some(latest_version) = &result.latest_version {
+      line.push_str(&format!(" {}", latest_version));
+    }
+    if let Some(score) = result.score {
+      line
+        .push_str(&format!(" {}", colors::gray(format!("(score {}%)", score))));
+    }
+    println!("{}", line);
+    if !result.description.is_empty() {
+      println!("  {}", result.description);
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,240 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The slow types that a package allows in its public API with
+//! `"publish.allowSlowTypes"`, eg. in a module or symbol that can't have
+//! explicit types yet, while the rest of its API is still checked.
+
+use deno_ast::swc::ast::Decl;
+use deno_ast::swc::ast::DefaultDecl;
+use deno_ast::swc::ast::ModuleDecl;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::Pat;
+use deno_ast::swc::ast::Program;
+use deno_ast::swc::ast::Stmt;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_ast::SourceRangedForSpanned;
+use deno_config::ConfigFile;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_graph::FastCheckDiagnostic;
+
+use super::config::CliPublishConfig;
+
+#[derive(Debug)]
+pub struct SlowTypesAllowlist {
+  /// The allowed modules, with the allowed symbol of the module or `None`
+  /// for all of them.
+  entries: Vec<(ModuleSpecifier, Option<String>)>,
+}
+
+impl SlowTypesAllowlist {
+  /// Parses the entries of the allowlist, which are paths relative to the
+  /// configuration file, eg. `"./legacy.ts"`, optionally followed by the
+  /// name of an exported symbol, eg. `"./mod.ts#createClient"`.
+  pub fn new(
+    config_file: &ConfigFile,
+    entries: &[String],
+  ) -> Result<Self, AnyError> {
+    let entries = entries
+      .iter()
+      .map(|entry| {
+        let (path, symbol) = match entry.split_once('#') {
+          Some((path, symbol)) => (path, Some(symbol.to_string())),
+          None => (entry.as_str(), None),
+        };
+        let specifier =
+          config_file.specifier.join(path).with_context(|| {
+            format!(
+              "Invalid entry '{}' in \"publish.allowSlowTypes\" of '{}'",
+              entry, config_file.specifier
+            )
+          })?;
+        Ok((specifier, symbol))
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
+    Ok(Self { entries })
+  }
+
+  pub fn from_config_file(config_file: &ConfigFile) -> Result<Self, AnyError> {
+    let publish_config = CliPublishConfig::from_config_file(config_file)?;
+    Self::new(config_file, &publish_config.allow_slow_types)
+  }
+
+  /// Whether the slow type of the diagnostic is allowed.
+  pub fn allows(&self, diagnostic: &FastCheckDiagnostic) -> bool {
+    let specifier = diagnostic.specifier();
+    let symbols = self
+      .entries
+      .iter()
+      .filter(|(allowed, _)| allowed == specifier)
+      .map(|(_, symbol)| symbol.as_deref())
+      .collect::<Vec<_>>();
+    if symbols.is_empty() {
+      return false;
+    }
+    if symbols.contains(&None) {
+      return true;
+    }
+    // the diagnostics without a position are about the whole module
+    let Some(range) = diagnostic.range() else {
+      return false;
+    };
+    let Ok(parsed_source) = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: specifier.clone(),
+      text_info: range.text_info.clone(),
+      media_type: MediaType::from_specifier(specifier),
+      capture_tokens: false,
+      maybe_syntax: None,
+      scope_analysis: false,
+    }) else {
+      return false;
+    };
+    declared_names_at(&parsed_source, range.range.start)
+      .iter()
+      .any(|name| symbols.contains(&Some(name.as_str())))
+  }
+}
+
+/// Gets the names that the top level declaration at a position declares.
+fn declared_names_at(
+  parsed_source: &ParsedSource,
+  pos: SourcePos,
+) -> Vec<String> {
+  let program = parsed_source.program();
+  let Program::Module(module) = &*program else {
+    return Vec::new();
+  };
+  let Some(item) = module.body.iter().find(|item| {
+    let range = item.range();
+    range.start <= pos && pos < range.end
+  }) else {
+    return Vec::new();
+  };
+  match item {
+    ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+      decl_names(&export_decl.decl)
+    }
+    ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export_default)) => {
+      let mut names = vec!["default".to_string()];
+      let ident = match &export_default.decl {
+        DefaultDecl::Class(class_expr) => class_expr.ident.as_ref(),
+        DefaultDecl::Fn(fn_expr) => fn_expr.ident.as_ref(),
+        DefaultDecl::TsInterfaceDecl(_) => None,
+      };
+      names.extend(ident.map(|ident| ident.sym.to_string()));
+      names
+    }
+    ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(_)) => {
+      vec!["default".to_string()]
+    }
+    // declarations that are exported with `export { name }`
+    ModuleItem::Stmt(Stmt::Decl(decl)) => decl_names(decl),
+    _ => Vec::new(),
+  }
+}
+
+fn decl_names(decl: &Decl) -> Vec<String> {
+  match decl {
+    Decl::Class(class_decl) => vec![class_decl.ident.sym.to_string()],
+    Decl::Fn(fn_decl) => vec![fn_decl.ident.sym.to_string()],
+    Decl::Var(var_decl) => var_decl
+      .decls
+      .iter()
+      .filter_map(|decl| match &decl.name {
+        Pat::Ident(ident) => Some(ident.id.sym.to_string()),
+        _ => None,
+      })
+      .collect(),
+    Decl::TsInterface(interface_decl) => {
+      vec![interface_decl.id.sym.to_string()]
+    }
+    Decl::TsTypeAlias(type_alias_decl) => {
+      vec![type_alias_decl.id.sym.to_string()]
+    }
+    Decl::TsEnum(enum_decl) => vec![enum_decl.id.sym.to_string()],
+    _ => Vec::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::SourceTextInfo;
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn declared_names() {
+    let text = r#"export function createClient() {
+  return {};
+}
+export class Client {
+  method() {
+    return 1;
+  }
+}
+const a = 1, b = 2;
+export { a, b };
+export default function () {}
+"#;
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: ModuleSpecifier::parse("file:///mod.ts").unwrap(),
+      text_info: SourceTextInfo::from_string(text.to_string()),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      maybe_syntax: None,
+      scope_analysis: false,
+    })
+    .unwrap();
+    let start = parsed_source.text_info().range().start;
+    let names_at = |needle: &str| {
+      declared_names_at(&parsed_source, start + text.find(needle).unwrap())
+    };
+    assert_eq!(names_at("createClient"), vec!["createClient"]);
+    assert_eq!(names_at("method()"), vec!["Client"]);
+    assert_eq!(names_at("a = 1"), vec!["a", "b"]);
+    assert_eq!(names_at("export { a"), Vec::<String>::new());
+    assert_eq!(names_at("export default"), vec!["default"]);
+  }
+
+  #[test]
+  fn allowlist_entries() {
+    let config_file = ConfigFile::new(
+      &json!({ "name": "@foo/bar" }).to_string(),
+      ModuleSpecifier::parse("file:///pkg/deno.json").unwrap(),
+    )
+    .unwrap();
+    let allowlist = SlowTypesAllowlist::new(
+      &config_file,
+      &[
+        "./legacy.ts".to_string(),
+        "./mod.ts#createClient".to_string(),
+      ],
+    )
+    .unwrap();
+    assert_eq!(
+      allowlist.entries,
+      vec![
+        (
+          ModuleSpecifier::parse("file:///pkg/legacy.ts").unwrap(),
+          None
+        ),
+        (
+          ModuleSpecifier::parse("file:///pkg/mod.ts").unwrap(),
+          Some("createClient".to_string())
+        ),
+      ]
+    );
+    let diagnostic =
+      |specifier: &str| FastCheckDiagnostic::UnsupportedJavaScriptEntrypoint {
+        specifier: ModuleSpecifier::parse(specifier).unwrap(),
+      };
+    assert!(allowlist.allows(&diagnostic("file:///pkg/legacy.ts")));
+    // only a symbol of the module is allowed
+    assert!(!allowlist.allows(&diagnostic("file:///pkg/mod.ts")));
+    assert!(!allowlist.allows(&diagnostic("file:///pkg/other.ts")));
+  }
+}
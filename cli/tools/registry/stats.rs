@@ -0,0 +1,194 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Utc;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_semver::Version;
+use deno_terminal::colors;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::RegistryStatsFlags;
+
+use super::api::DownloadDataPoint;
+use super::api::PackageDownloads;
+use super::commands::parse_package_arg;
+use super::commands::RegistryContext;
+use super::output::print_json;
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadCountsJson {
+  last_week: u64,
+  total: u64,
+}
+
+impl DownloadCountsJson {
+  fn from_data_points(
+    data_points: &[DownloadDataPoint],
+    now: DateTime<Utc>,
+  ) -> Self {
+    let week_start = now - Duration::days(7);
+    DownloadCountsJson {
+      last_week: data_points
+        .iter()
+        .filter(|point| point.time_bucket >= week_start)
+        .map(|point| point.count)
+        .sum(),
+      total: data_points.iter().map(|point| point.count).sum(),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionDownloadCountsJson {
+  version: String,
+  #[serde(flatten)]
+  downloads: DownloadCountsJson,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageStatsJson {
+  name: String,
+  #[serde(flatten)]
+  downloads: DownloadCountsJson,
+  /// The recently downloaded versions, sorted from newest to oldest.
+  versions: Vec<VersionDownloadCountsJson>,
+}
+
+pub async fn stats(
+  flags: Flags,
+  stats_flags: RegistryStatsFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let package = parse_package_arg(&stats_flags.package)?;
+  let Some(downloads) = context
+    .api_client()?
+    .get_package_downloads(&package.scope, &package.package)
+    .await?
+  else {
+    bail!("{} was not found in the registry", package);
+  };
+  let stats =
+    package_stats(package.to_string(), downloads, crate::util::time::utc_now());
+
+  if stats_flags.json {
+    print_json(&stats)?;
+    return Ok(());
+  }
+  println!("{}", colors::bold(&stats.name));
+  println!(
+    "  {} {}",
+    colors::gray("Downloads in the last 7 days:"),
+    stats.downloads.last_week
+  );
+  println!(
+    "  {} {}",
+    colors::gray("Total downloads:"),
+    stats.downloads.total
+  );
+  if !stats.versions.is_empty() {
+    println!("  {}", colors::gray("Recently downloaded versions:"));
+    for version in &stats.versions {
+      println!(
+        "    {} {} {}",
+        version.version,
+        version.downloads.total,
+        colors::gray(format!(
+          "({} in the last 7 days)",
+          version.downloads.last_week
+        ))
+      );
+    }
+  }
+  Ok(())
+}
+
+fn package_stats(
+  name: String,
+  downloads: PackageDownloads,
+  now: DateTime<Utc>,
+) -> PackageStatsJson {
+  let mut versions = downloads
+    .recent_versions
+    .iter()
+    .map(|version| VersionDownloadCountsJson {
+      version: version.version.clone(),
+      downloads: DownloadCountsJson::from_data_points(&version.downloads, now),
+    })
+    .collect::<Vec<_>>();
+  versions.sort_by_cached_key(|version| {
+    std::cmp::Reverse(Version::parse_standard(&version.version).ok())
+  });
+  PackageStatsJson {
+    name,
+    downloads: DownloadCountsJson::from_data_points(&downloads.total, now),
+    versions,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json;
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn package_stats_counts() {
+    let downloads = serde_json::from_value::<PackageDownloads>(json!({
+      "total": [
+        { "timeBucket": "2024-01-01T00:00:00Z", "count": 10 },
+        { "timeBucket": "2024-01-25T00:00:00Z", "count": 3 },
+        { "timeBucket": "2024-01-30T00:00:00Z", "count": 2 },
+      ],
+      "recentVersions": [
+        {
+          "version": "0.9.0",
+          "downloads": [
+            { "timeBucket": "2024-01-01T00:00:00Z", "count": 10 },
+          ],
+        },
+        {
+          "version": "1.0.0",
+          "downloads": [
+            { "timeBucket": "2024-01-25T00:00:00Z", "count": 3 },
+            { "timeBucket": "2024-01-30T00:00:00Z", "count": 2 },
+          ],
+        },
+      ],
+    }))
+    .unwrap();
+    let now = "2024-01-31T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    assert_eq!(
+      package_stats("@foo/bar".to_string(), downloads, now),
+      PackageStatsJson {
+        name: "@foo/bar".to_string(),
+        downloads: DownloadCountsJson {
+          last_week: 5,
+          total: 15,
+        },
+        versions: vec![
+          VersionDownloadCountsJson {
+            version: "1.0.0".to_string(),
+            downloads: DownloadCountsJson {
+              last_week: 5,
+              total: 5,
+            },
+          },
+          VersionDownloadCountsJson {
+            version: "0.9.0".to_string(),
+            downloads: DownloadCountsJson {
+              last_week: 0,
+              total: 10,
+            },
+          },
+        ],
+      }
+    );
+  }
+}
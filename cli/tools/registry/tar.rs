@@ -10,17 +10,24 @@ use deno_core::url::Url;
 use ignore::overrides::OverrideBuilder;
 use ignore::WalkBuilder;
 use sha2::Digest;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Write as FmtWrite;
 use std::io::Write;
 use std::path::Path;
 use tar::Header;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::cache::LazyGraphSourceParser;
 use crate::tools::registry::paths::PackagePath;
+use crate::tools::registry::paths::PackagePathValidationError;
 
 use super::diagnostics::PublishDiagnostic;
 use super::diagnostics::PublishDiagnosticsCollector;
+use super::transpile::transpiled_dts_path;
+use super::transpile::transpiled_path;
+use super::transpile::JsxTranspiler;
+use super::unfurl::SpecifierRewrite;
 use super::unfurl::SpecifierUnfurler;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -29,13 +36,54 @@ pub struct PublishableTarballFile {
   pub specifier: Url,
   pub hash: String,
   pub size: usize,
+  /// The specifiers that were rewritten in the published content.
+  pub rewrites: Vec<SpecifierRewrite>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug)]
 pub struct PublishableTarball {
   pub files: Vec<PublishableTarballFile>,
   pub hash: String,
-  pub bytes: Bytes,
+  pub temp_file: TarballTempFile,
+}
+
+/// The gzipped bytes of a tarball, spilled to a temporary file so that the
+/// tarballs of all workspace members aren't held in memory until they're
+/// uploaded. The file is removed when this is dropped.
+#[derive(Debug)]
+pub struct TarballTempFile {
+  path: tempfile::TempPath,
+  size: usize,
+}
+
+impl TarballTempFile {
+  pub fn new(bytes: &[u8]) -> Result<Self, AnyError> {
+    let mut file = tempfile::Builder::new()
+      .prefix("deno_publish_")
+      .suffix(".tgz")
+      .tempfile()
+      .context("Unable to create a temporary file for the tarball")?;
+    file
+      .write_all(bytes)
+      .and_then(|_| file.flush())
+      .context("Unable to write the tarball to a temporary file")?;
+    Ok(Self {
+      path: file.into_temp_path(),
+      size: bytes.len(),
+    })
+  }
+
+  pub fn size(&self) -> usize {
+    self.size
+  }
+
+  /// Reads the tarball back into memory, eg. to upload it.
+  pub fn read(&self) -> Result<Bytes, AnyError> {
+    let bytes = std::fs::read(&self.path).with_context(|| {
+      format!("Unable to read the tarball '{}'", self.path.display())
+    })?;
+    Ok(Bytes::from(bytes))
+  }
 }
 
 pub fn create_gzipped_tarball(
@@ -43,12 +91,14 @@ pub fn create_gzipped_tarball(
   source_parser: LazyGraphSourceParser,
   diagnostics_collector: &PublishDiagnosticsCollector,
   unfurler: &SpecifierUnfurler,
+  jsx_transpiler: Option<&JsxTranspiler>,
   file_patterns: Option<FilePatterns>,
 ) -> Result<PublishableTarball, AnyError> {
   let mut tar = TarGzArchive::new();
   let mut files = vec![];
 
   let mut paths = HashSet::new();
+  let mut normalized_paths = HashMap::new();
 
   let mut ob = OverrideBuilder::new(dir);
   ob.add("!.git")?.add("!node_modules")?.add("!.DS_Store")?;
@@ -127,7 +177,27 @@ pub fn create_gzipped_tarball(
         },
       );
 
-      match PackagePath::new(path_str.clone()) {
+      // paths that only differ by Unicode normalization are the same file
+      // on macOS, but different files on Linux
+      if !path_str.is_ascii() {
+        let normalized = path_str.nfc().collect::<String>().to_lowercase();
+        if let Some(other_path) =
+          normalized_paths.insert(normalized, path.to_path_buf())
+        {
+          diagnostics_collector.to_owned().push(
+            PublishDiagnostic::NormalizationDuplicatePath {
+              path: path.to_path_buf(),
+              other_path,
+            },
+          );
+        }
+      }
+
+      let package_path = match relative_path.to_str() {
+        Some(_) => PackagePath::new(path_str.clone()),
+        None => Err(PackagePathValidationError::InvalidUtf8),
+      };
+      match package_path {
         Ok(package_path) => {
           if !paths.insert(package_path) {
             diagnostics_collector.to_owned().push(
@@ -147,7 +217,7 @@ pub fn create_gzipped_tarball(
         }
       }
 
-      let content = resolve_content_maybe_unfurling(
+      let (content, rewrites) = resolve_content_maybe_unfurling(
         path,
         &specifier,
         unfurler,
@@ -156,24 +226,83 @@ pub fn create_gzipped_tarball(
       )?;
 
       let media_type = MediaType::from_specifier(&specifier);
-      if matches!(media_type, MediaType::Jsx | MediaType::Tsx) {
-        diagnostics_collector.push(PublishDiagnostic::UnsupportedJsxTsx {
-          specifier: specifier.clone(),
-        });
+      let mut outputs = Vec::with_capacity(1);
+      match jsx_transpiler {
+        Some(jsx_transpiler)
+          if matches!(media_type, MediaType::Jsx | MediaType::Tsx) =>
+        {
+          let transpiled = jsx_transpiler.transpile(
+            &specifier,
+            media_type,
+            String::from_utf8(content)?,
+          )?;
+          let js_path_str = transpiled_path(&path_str).unwrap();
+          if let (Some(dts), Some(dts_path_str)) =
+            (transpiled.dts, transpiled_dts_path(&path_str))
+          {
+            // the declarations are generated from the module, so they're
+            // published as part of it
+            outputs.push((
+              dts_path_str,
+              specifier.clone(),
+              dts.into_bytes(),
+              vec![],
+            ));
+          }
+          outputs.push((
+            js_path_str,
+            specifier.clone(),
+            transpiled.js.into_bytes(),
+            rewrites,
+          ));
+          // the transpiled modules are published in place of the original one
+          for (output_path_str, _, _, _) in &outputs {
+            if let Ok(package_path) = PackagePath::new(output_path_str.clone())
+            {
+              if !paths.insert(package_path) {
+                diagnostics_collector.to_owned().push(
+                  PublishDiagnostic::DuplicatePath {
+                    path: dir.join(output_path_str.trim_start_matches('/')),
+                  },
+                );
+              }
+            }
+          }
+        }
+        _ => {
+          if matches!(media_type, MediaType::Jsx | MediaType::Tsx) {
+            diagnostics_collector.push(PublishDiagnostic::UnsupportedJsxTsx {
+              specifier: specifier.clone(),
+            });
+          }
+          let content = match jsx_transpiler {
+            Some(jsx_transpiler) => {
+              jsx_transpiler.transpile_config(&specifier, content)?
+            }
+            None => content,
+          };
+          outputs.push((path_str, specifier, content, rewrites));
+        }
       }
 
-      files.push(PublishableTarballFile {
-        path_str: path_str.clone(),
-        specifier: specifier.clone(),
-        // This hash string matches the checksum computed by registry
-        hash: format!("sha256-{:x}", sha2::Sha256::digest(&content)),
-        size: content.len(),
-      });
-      tar
-        .add_file(format!(".{}", path_str), &content)
-        .with_context(|| {
-          format!("Unable to add file to tarball '{}'", entry.path().display())
-        })?;
+      for (path_str, specifier, content, rewrites) in outputs {
+        files.push(PublishableTarballFile {
+          path_str: path_str.clone(),
+          specifier,
+          // This hash string matches the checksum computed by registry
+          hash: format!("sha256-{:x}", sha2::Sha256::digest(&content)),
+          size: content.len(),
+          rewrites,
+        });
+        tar
+          .add_file(format!(".{}", path_str), &content)
+          .with_context(|| {
+            format!(
+              "Unable to add file to tarball '{}'",
+              entry.path().display()
+            )
+          })?;
+      }
     } else if !file_type.is_dir() {
       diagnostics_collector.push(PublishDiagnostic::UnsupportedFileType {
         specifier,
@@ -198,7 +327,7 @@ pub fn create_gzipped_tarball(
   Ok(PublishableTarball {
     files,
     hash,
-    bytes: Bytes::from(v),
+    temp_file: TarballTempFile::new(&v)?,
   })
 }
 
@@ -208,7 +337,7 @@ fn resolve_content_maybe_unfurling(
   unfurler: &SpecifierUnfurler,
   source_parser: LazyGraphSourceParser,
   diagnostics_collector: &PublishDiagnosticsCollector,
-) -> Result<Vec<u8>, AnyError> {
+) -> Result<(Vec<u8>, Vec<SpecifierRewrite>), AnyError> {
   let parsed_source = match source_parser.get_or_parse_source(specifier)? {
     Some(parsed_source) => parsed_source,
     None => {
@@ -236,7 +365,7 @@ fn resolve_content_maybe_unfurling(
         | MediaType::Wasm
         | MediaType::TsBuildInfo => {
           // not unfurlable data
-          return Ok(data);
+          return Ok((data, Vec::new()));
         }
       }
 
@@ -256,8 +385,28 @@ fn resolve_content_maybe_unfurling(
   let mut reporter = |diagnostic| {
     diagnostics_collector.push(PublishDiagnostic::SpecifierUnfurl(diagnostic));
   };
-  let content = unfurler.unfurl(specifier, &parsed_source, &mut reporter);
-  Ok(content.into_bytes())
+  let unfurled = unfurler.unfurl(specifier, &parsed_source, &mut reporter);
+  if MediaType::from_specifier(specifier) == MediaType::Unknown
+    && parsed_source.media_type() != MediaType::Unknown
+  {
+    // the graph analyzed a loader shim of the file, which has the same
+    // positions, so the imports are rewritten in the file itself
+    let text = std::fs::read_to_string(path)
+      .with_context(|| format!("Unable to read file '{}'", path.display()))?;
+    let text = deno_ast::apply_text_changes(
+      &text,
+      unfurled
+        .rewrites
+        .iter()
+        .map(|rewrite| deno_ast::TextChange {
+          range: rewrite.range.clone(),
+          new_text: rewrite.to.clone(),
+        })
+        .collect(),
+    );
+    return Ok((text.into_bytes(), unfurled.rewrites));
+  }
+  Ok((unfurled.text.into_bytes(), unfurled.rewrites))
 }
 
 struct TarGzArchive {
@@ -295,3 +444,12 @@ impl TarGzArchive {
     Ok(gz_bytes)
   }
 }
+
+/// Recompresses a gzipped tarball with zstd, which compresses better.
+pub fn recompress_with_zstd(gz_bytes: &[u8]) -> Result<Vec<u8>, AnyError> {
+  let decoder = flate2::read::GzDecoder::new(gz_bytes);
+  Ok(zstd::stream::encode_all(decoder, ZSTD_COMPRESSION_LEVEL)?)
+}
+
+/// Higher levels barely make tarballs smaller, but take much longer.
+const ZSTD_COMPRESSION_LEVEL: i32 = 12;
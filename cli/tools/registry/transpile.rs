@@ -0,0 +1,266 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Transpiling the JSX and TSX modules of a package to JavaScript and type
+//! declarations when publishing, with `"publish.transpileJsx"`.
+
+use std::collections::HashMap;
+
+use deno_ast::EmitOptions;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::TextChange;
+use deno_config::WorkspaceMemberConfig;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_graph::ModuleGraph;
+use jsonc_parser::ast::ObjectProp;
+use jsonc_parser::ast::Value;
+
+use super::config::CliPublishConfig;
+use super::unfurl::SpecifierUnfurler;
+
+/// Whether any of the packages transpiles its JSX and TSX modules, which
+/// needs the type declarations of the fast check modules.
+pub fn transpiles_jsx(
+  members: &[WorkspaceMemberConfig],
+) -> Result<bool, AnyError> {
+  for member in members {
+    if CliPublishConfig::from_config_file(&member.config_file)?.transpile_jsx {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
+
+/// The path a JSX or TSX module is published under, eg. `./button.js` for
+/// `./button.tsx`.
+pub fn transpiled_path(path: &str) -> Option<String> {
+  let stem = path
+    .strip_suffix(".jsx")
+    .or_else(|| path.strip_suffix(".tsx"))?;
+  Some(format!("{}.js", stem))
+}
+
+/// The path of the type declarations of a transpiled TSX module, eg.
+/// `./button.d.ts` for `./button.tsx`.
+pub fn transpiled_dts_path(path: &str) -> Option<String> {
+  let stem = path.strip_suffix(".tsx")?;
+  Some(format!("{}.d.ts", stem))
+}
+
+/// The JSX and TSX modules of the graph in a package directory, by the
+/// specifier of the JavaScript module they are published as.
+pub fn renamed_modules(
+  graph: &ModuleGraph,
+  dir_url: &ModuleSpecifier,
+) -> HashMap<ModuleSpecifier, ModuleSpecifier> {
+  graph
+    .specifiers()
+    .filter(|(specifier, _)| specifier.as_str().starts_with(dir_url.as_str()))
+    .filter_map(|(specifier, _)| {
+      let mut renamed = specifier.clone();
+      renamed.set_path(&transpiled_path(specifier.path())?);
+      Some((specifier.clone(), renamed))
+    })
+    .collect()
+}
+
+pub struct TranspiledModule {
+  pub js: String,
+  /// The type declarations, which are generated from the fast check module
+  /// of a TSX module, so they aren't available with `--allow-slow-types`.
+  pub dts: Option<String>,
+}
+
+pub struct JsxTranspiler<'a> {
+  emit_options: EmitOptions,
+  graph: &'a ModuleGraph,
+  unfurler: &'a SpecifierUnfurler<'a>,
+  config_specifier: ModuleSpecifier,
+}
+
+impl<'a> JsxTranspiler<'a> {
+  pub fn new(
+    emit_options: EmitOptions,
+    graph: &'a ModuleGraph,
+    unfurler: &'a SpecifierUnfurler<'a>,
+    config_specifier: ModuleSpecifier,
+  ) -> Self {
+    Self {
+      emit_options: EmitOptions {
+        // the original source isn't published
+        inline_source_map: false,
+        inline_sources: false,
+        source_map: false,
+        ..emit_options
+      },
+      graph,
+      unfurler,
+      config_specifier,
+    }
+  }
+
+  /// Points the `"exports"` of the package's configuration file to the
+  /// transpiled modules, and returns any other file as it is.
+  pub fn transpile_config(
+    &self,
+    specifier: &ModuleSpecifier,
+    content: Vec<u8>,
+  ) -> Result<Vec<u8>, AnyError> {
+    if *specifier != self.config_specifier {
+      return Ok(content);
+    }
+    let text = String::from_utf8(content)?;
+    let transpiled = transpile_config_exports(&text)
+      .with_context(|| format!("Failed transpiling '{}'", specifier))?;
+    Ok(transpiled.unwrap_or(text).into_bytes())
+  }
+
+  /// Transpiles the unfurled text of a JSX or TSX module.
+  pub fn transpile(
+    &self,
+    specifier: &ModuleSpecifier,
+    media_type: MediaType,
+    text: String,
+  ) -> Result<TranspiledModule, AnyError> {
+    let parsed_source = parse(specifier, media_type, text)?;
+    let transpiled = parsed_source
+      .transpile(&self.emit_options)
+      .with_context(|| format!("Failed transpiling '{}'", specifier))?;
+    // the JSX runtime imports are added by transpiling, so they are
+    // unfurled afterwards, eg. when "jsxImportSource" is mapped
+    let js = self.unfurl(specifier, MediaType::JavaScript, transpiled.text)?;
+    let dts = self
+      .graph
+      .get(specifier)
+      .and_then(|module| module.js())
+      .and_then(|module| module.fast_check_module())
+      .and_then(|module| module.dts.as_ref())
+      .map(|dts| self.unfurl(specifier, MediaType::Dts, dts.text.clone()))
+      .transpose()?;
+    let js = match &dts {
+      Some(_) => {
+        let file_name = specifier.path().rsplit('/').next().unwrap_or("");
+        format!(
+          "/// <reference types=\"./{}\" />\n{}",
+          transpiled_dts_path(file_name).unwrap_or_default(),
+          js
+        )
+      }
+      None => js,
+    };
+    Ok(TranspiledModule { js, dts })
+  }
+
+  fn unfurl(
+    &self,
+    specifier: &ModuleSpecifier,
+    media_type: MediaType,
+    text: String,
+  ) -> Result<String, AnyError> {
+    let parsed_source = parse(specifier, media_type, text)?;
+    // the diagnostics were reported for the source of the module already
+    let unfurled = self.unfurler.unfurl(specifier, &parsed_source, &mut |_| {});
+    Ok(unfurled.text)
+  }
+}
+
+fn parse(
+  specifier: &ModuleSpecifier,
+  media_type: MediaType,
+  text: String,
+) -> Result<deno_ast::ParsedSource, AnyError> {
+  deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.clone(),
+    text_info: deno_ast::SourceTextInfo::from_string(text),
+    media_type,
+    capture_tokens: false,
+    maybe_syntax: None,
+    scope_analysis: false,
+  })
+  .with_context(|| format!("Failed parsing '{}'", specifier))
+}
+
+/// Points the `"exports"` of the published configuration file to the
+/// transpiled modules. Returns `None` when nothing changed.
+pub fn transpile_config_exports(
+  text: &str,
+) -> Result<Option<String>, AnyError> {
+  let ast =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())?;
+  let Some(Value::Object(obj)) = ast.value else {
+    bail!("Expected an object");
+  };
+  let mut text_changes = Vec::new();
+  let mut transpile_lit = |lit: &jsonc_parser::ast::StringLit| {
+    if let Some(path) = transpiled_path(&lit.value) {
+      text_changes.push(TextChange {
+        range: lit.range.start..lit.range.end,
+        new_text: serde_json::to_string(&path).unwrap(),
+      });
+    }
+  };
+  match obj.get("exports") {
+    Some(ObjectProp {
+      value: Value::StringLit(lit),
+      ..
+    }) => transpile_lit(lit),
+    Some(ObjectProp {
+      value: Value::Object(exports),
+      ..
+    }) => {
+      for prop in &exports.properties {
+        if let Value::StringLit(lit) = &prop.value {
+          transpile_lit(lit);
+        }
+      }
+    }
+    _ => {}
+  }
+  if text_changes.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(deno_ast::apply_text_changes(text, text_changes)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn transpiled_paths() {
+    assert_eq!(transpiled_path("./button.tsx").unwrap(), "./button.js");
+    assert_eq!(transpiled_path("/src/app.jsx").unwrap(), "/src/app.js");
+    assert!(transpiled_path("./mod.ts").is_none());
+    assert_eq!(
+      transpiled_dts_path("./button.tsx").unwrap(),
+      "./button.d.ts"
+    );
+  }
+
+  #[test]
+  fn transpiles_config_exports() {
+    let text = r#"{
+  // the components
+  "exports": {
+    ".": "./mod.ts",
+    "./button": "./button.tsx"
+  }
+}"#;
+    assert_eq!(
+      transpile_config_exports(text).unwrap().unwrap(),
+      r#"{
+  // the components
+  "exports": {
+    ".": "./mod.ts",
+    "./button": "./button.js"
+  }
+}"#
+    );
+    assert!(transpile_config_exports(r#"{ "exports": "./mod.ts" }"#)
+      .unwrap()
+      .is_none());
+  }
+}
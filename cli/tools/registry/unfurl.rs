@@ -1,10 +1,12 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 
 use deno_ast::ParsedSource;
 use deno_ast::SourceRange;
 use deno_ast::SourceTextInfo;
+use deno_config::WorkspaceMemberConfig;
 use deno_core::serde_json;
 use deno_core::ModuleSpecifier;
 use deno_graph::DefaultModuleAnalyzer;
@@ -15,6 +17,7 @@ use deno_runtime::deno_node::is_builtin_node_module;
 use deno_semver::jsr::JsrDepPackageReq;
 use deno_semver::jsr::JsrPackageReqReference;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
 
 use crate::resolver::MappedSpecifierResolver;
 use crate::resolver::SloppyImportsResolver;
@@ -69,12 +72,53 @@ pub enum SpecifierUnfurlerDiagnostic {
     text_info: SourceTextInfo,
     range: SourceRange,
   },
+  /// An import of a module of another workspace member by its path, eg.
+  /// `../b/mod.ts`, which doesn't resolve after publishing.
+  CrossMemberImport {
+    specifier: ModuleSpecifier,
+    text_info: SourceTextInfo,
+    range: SourceRange,
+    /// The imported specifier, as written.
+    imported: String,
+    /// The name of the imported workspace member.
+    member: String,
+    /// The `jsr:` specifier the import is rewritten to, or `None` when the
+    /// module isn't exported by the member.
+    jsr_specifier: Option<String>,
+  },
+  /// A `jsr:` specifier of a workspace member with a version constraint
+  /// that the version of the member being published doesn't match.
+  IncompatibleMemberVersion {
+    specifier: ModuleSpecifier,
+    text_info: SourceTextInfo,
+    range: SourceRange,
+    /// The imported specifier, as written.
+    imported: String,
+    /// The name of the imported workspace member.
+    member: String,
+    version: String,
+    /// The `jsr:` specifier the import is rewritten to, constrained to the
+    /// version of the member.
+    jsr_specifier: String,
+  },
+  /// An import of a dependency that's declared in `"publish.devImports"`
+  /// by a module that's reachable from the exports of the package.
+  DevImport {
+    specifier: ModuleSpecifier,
+    text_info: SourceTextInfo,
+    range: SourceRange,
+    /// The imported specifier, as written.
+    imported: String,
+  },
 }
 
 impl SpecifierUnfurlerDiagnostic {
   pub fn code(&self) -> &'static str {
     match self {
       Self::UnanalyzableDynamicImport { .. } => "unanalyzable-dynamic-import",
+      Self::CrossMemberImport { .. } => "cross-member-import",
+      Self::IncompatibleMemberVersion { .. } => "incompatible-member-version",
+      Self::DevImport { .. } => "dev-import",
     }
   }
 
@@ -83,14 +127,91 @@ impl SpecifierUnfurlerDiagnostic {
       Self::UnanalyzableDynamicImport { .. } => {
         "unable to analyze dynamic import"
       }
+      Self::CrossMemberImport { .. } => {
+        "import of another workspace member by its path"
+      }
+      Self::IncompatibleMemberVersion { .. } => {
+        "version constraint doesn't match the workspace member"
+      }
+      Self::DevImport { .. } => "import of a dev-only dependency",
+    }
+  }
+}
+
+/// A workspace member that the other members have to import with a `jsr:`
+/// specifier, because the paths of its modules don't resolve after
+/// publishing.
+#[derive(Debug, Clone)]
+pub struct UnfurlWorkspaceMember {
+  /// The directory of the member, ending with a slash.
+  dir_url: ModuleSpecifier,
+  name: String,
+  version: String,
+  /// The export names by the modules they resolve to, eg. `./utils` for
+  /// `file:///b/utils.ts`.
+  exports: HashMap<ModuleSpecifier, String>,
+}
+
+impl UnfurlWorkspaceMember {
+  pub fn new(member: &WorkspaceMemberConfig) -> Self {
+    // invalid exports are reported when publishing the member
+    let exports = member
+      .config_file
+      .to_exports_config()
+      .map(|exports| exports.into_map())
+      .unwrap_or_default();
+    let mut resolved_exports = HashMap::with_capacity(exports.len());
+    for (name, value) in exports {
+      if let Ok(resolved) = member.config_file.specifier.join(&value) {
+        resolved_exports.entry(resolved).or_insert(name);
+      }
     }
+    Self {
+      dir_url: ModuleSpecifier::from_directory_path(&member.dir_path).unwrap(),
+      name: member.package_name.clone(),
+      version: member.package_version.clone(),
+      exports: resolved_exports,
+    }
+  }
+
+  /// The `jsr:` specifier of a module of the member, if it's exported.
+  fn jsr_specifier(&self, module: &ModuleSpecifier) -> Option<String> {
+    let export_name = self.exports.get(module)?;
+    // `.` is the main export and `./utils` is imported as `/utils`
+    let sub_path = export_name.strip_prefix('.').unwrap_or(export_name);
+    Some(format!("jsr:{}@^{}{}", self.name, self.version, sub_path))
   }
 }
 
+/// A specifier that was rewritten by unfurling, which `deno publish --fix`
+/// writes back to the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecifierRewrite {
+  /// The byte range of the specifier in the source text.
+  pub range: std::ops::Range<usize>,
+  /// The line of the specifier, starting at 1.
+  pub line: usize,
+  pub from: String,
+  pub to: String,
+}
+
+pub struct UnfurledSource {
+  pub text: String,
+  pub rewrites: Vec<SpecifierRewrite>,
+}
+
 pub struct SpecifierUnfurler<'a> {
   mapped_resolver: &'a MappedSpecifierResolver,
   sloppy_imports_resolver: Option<&'a SloppyImportsResolver>,
   bare_node_builtins: bool,
+  workspace_members: &'a [UnfurlWorkspaceMember],
+  /// The modules that are published under another path, by their path.
+  renamed_modules: Option<&'a HashMap<ModuleSpecifier, ModuleSpecifier>>,
+  /// The import map keys of the dependencies that are only used by tests.
+  dev_imports: &'a [String],
+  /// The modules that are reachable from the exports of the package, which
+  /// can't use the dev-only dependencies.
+  exported_modules: Option<&'a HashSet<ModuleSpecifier>>,
 }
 
 impl<'a> SpecifierUnfurler<'a> {
@@ -103,14 +224,112 @@ impl<'a> SpecifierUnfurler<'a> {
       mapped_resolver,
       sloppy_imports_resolver,
       bare_node_builtins,
+      workspace_members: &[],
+      renamed_modules: None,
+      dev_imports: &[],
+      exported_modules: None,
     }
   }
 
+  /// Rewrites the imports of modules of other workspace members by their
+  /// path to `jsr:` specifiers, and reports them.
+  pub fn with_workspace_members(
+    mut self,
+    workspace_members: &'a [UnfurlWorkspaceMember],
+  ) -> Self {
+    self.workspace_members = workspace_members;
+    self
+  }
+
+  /// Rewrites the imports of modules that are published under another
+  /// path, eg. JSX modules that are transpiled to JavaScript. These aren't
+  /// reported as rewrites, since the source files import the modules by
+  /// their actual path.
+  pub fn with_renamed_modules(
+    mut self,
+    renamed_modules: &'a HashMap<ModuleSpecifier, ModuleSpecifier>,
+  ) -> Self {
+    self.renamed_modules = Some(renamed_modules);
+    self
+  }
+
+  /// Leaves the imports of dev-only dependencies as they are in the modules
+  /// that aren't reachable from the exports of the package, like tests, and
+  /// reports them in the ones that are.
+  pub fn with_dev_imports(
+    mut self,
+    dev_imports: &'a [String],
+    exported_modules: &'a HashSet<ModuleSpecifier>,
+  ) -> Self {
+    self.dev_imports = dev_imports;
+    self.exported_modules = Some(exported_modules);
+    self
+  }
+
+  /// Whether the import uses a dev import key, or one of its subpaths, like
+  /// `@std/assert/equals` for `@std/assert`, which `deno add --dev` adds.
+  fn is_dev_import(&self, specifier: &str) -> bool {
+    self.dev_imports.iter().any(|key| {
+      specifier.strip_prefix(key.as_str()).is_some_and(|rest| {
+        rest.is_empty() || key.ends_with('/') || rest.starts_with('/')
+      })
+    })
+  }
+
+  fn is_dev_module(&self, url: &ModuleSpecifier) -> bool {
+    self
+      .exported_modules
+      .is_some_and(|exported_modules| !exported_modules.contains(url))
+  }
+
+  fn is_renamed_module(
+    &self,
+    referrer: &ModuleSpecifier,
+    unfurled: &str,
+  ) -> bool {
+    let Some(renamed_modules) = self.renamed_modules else {
+      return false;
+    };
+    referrer.join(unfurled).is_ok_and(|unfurled| {
+      renamed_modules.values().any(|renamed| *renamed == unfurled)
+    })
+  }
+
   fn unfurl_specifier(
     &self,
     referrer: &ModuleSpecifier,
     specifier: &str,
   ) -> Option<String> {
+    let resolved = self.resolve(referrer, specifier)?;
+    if let Some(member) = self.other_workspace_member(referrer, &resolved) {
+      // an import of a module that isn't exported can't be rewritten
+      return member.jsr_specifier(&resolved);
+    }
+    if let Some((_, jsr_specifier)) =
+      self.incompatible_member_version(&resolved)
+    {
+      return Some(jsr_specifier);
+    }
+    let resolved = match self
+      .renamed_modules
+      .and_then(|renamed_modules| renamed_modules.get(&resolved))
+    {
+      Some(renamed) => renamed.clone(),
+      None => resolved,
+    };
+    let relative_resolved = relative_url(&resolved, referrer);
+    if relative_resolved == specifier {
+      None // nothing to unfurl
+    } else {
+      Some(relative_resolved)
+    }
+  }
+
+  fn resolve(
+    &self,
+    referrer: &ModuleSpecifier,
+    specifier: &str,
+  ) -> Option<ModuleSpecifier> {
     let resolved =
       if let Ok(resolved) = self.mapped_resolver.resolve(specifier, referrer) {
         resolved.into_specifier()
@@ -148,21 +367,106 @@ impl<'a> SpecifierUnfurler<'a> {
     // } else {
     //   resolved
     // };
-    let resolved =
-      if let Some(sloppy_imports_resolver) = self.sloppy_imports_resolver {
+    if let Some(sloppy_imports_resolver) = self.sloppy_imports_resolver {
+      Some(
         sloppy_imports_resolver
           .resolve(&resolved, deno_graph::source::ResolutionMode::Execution)
           .as_specifier()
-          .clone()
-      } else {
-        resolved
-      };
-    let relative_resolved = relative_url(&resolved, referrer);
-    if relative_resolved == specifier {
-      None // nothing to unfurl
+          .clone(),
+      )
     } else {
-      Some(relative_resolved)
+      Some(resolved)
+    }
+  }
+
+  /// Gets the workspace member of a module that belongs to another member
+  /// than the referrer.
+  fn other_workspace_member(
+    &self,
+    referrer: &ModuleSpecifier,
+    resolved: &ModuleSpecifier,
+  ) -> Option<&'a UnfurlWorkspaceMember> {
+    if resolved.scheme() != "file" {
+      return None;
+    }
+    // the innermost member, since members can be nested in the root
+    let member_of = |specifier: &ModuleSpecifier| {
+      self
+        .workspace_members
+        .iter()
+        .filter(|member| {
+          specifier.as_str().starts_with(member.dir_url.as_str())
+        })
+        .max_by_key(|member| member.dir_url.as_str().len())
+    };
+    let member = member_of(resolved)?;
+    match member_of(referrer) {
+      Some(referrer_member) if referrer_member.name == member.name => None,
+      _ => Some(member),
+    }
+  }
+
+  /// Gets the workspace member of a `jsr:` specifier with a version
+  /// constraint that the version of the member doesn't match, and the
+  /// specifier constrained to that version instead.
+  fn incompatible_member_version(
+    &self,
+    resolved: &ModuleSpecifier,
+  ) -> Option<(&'a UnfurlWorkspaceMember, String)> {
+    let req_ref = JsrPackageReqReference::from_specifier(resolved).ok()?;
+    let req = req_ref.req();
+    let member = self
+      .workspace_members
+      .iter()
+      .find(|member| member.name == req.name)?;
+    // invalid versions are reported when publishing the member
+    let version = Version::parse_standard(&member.version).ok()?;
+    if req.version_req.matches(&version) {
+      return None;
+    }
+    let sub_path = req_ref
+      .sub_path()
+      .map(|sub_path| format!("/{}", sub_path))
+      .unwrap_or_default();
+    Some((
+      member,
+      format!("jsr:{}@^{}{}", member.name, member.version, sub_path),
+    ))
+  }
+
+  fn workspace_member_diagnostic(
+    &self,
+    referrer: &ModuleSpecifier,
+    parsed_source: &ParsedSource,
+    specifier: &str,
+    range: &deno_graph::PositionRange,
+  ) -> Option<SpecifierUnfurlerDiagnostic> {
+    if self.workspace_members.is_empty() {
+      return None;
+    }
+    let resolved = self.resolve(referrer, specifier)?;
+    let text_info = parsed_source.text_info();
+    if let Some(member) = self.other_workspace_member(referrer, &resolved) {
+      return Some(SpecifierUnfurlerDiagnostic::CrossMemberImport {
+        specifier: referrer.clone(),
+        text_info: text_info.clone(),
+        range: range.as_source_range(text_info),
+        imported: specifier.to_string(),
+        member: member.name.clone(),
+        jsr_specifier: member.jsr_specifier(&resolved),
+      });
     }
+    let (member, jsr_specifier) =
+      self.incompatible_member_version(&resolved)?;
+    Some(SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+      specifier: referrer.clone(),
+      text_info: text_info.clone(),
+      range: range.as_source_range(text_info),
+      imported: specifier.to_string(),
+      member: member.name.clone(),
+      version: member.version.clone(),
+      jsr_specifier,
+    })
   }
 
   /// Attempts to unfurl the dynamic dependency returning `true` on success
@@ -173,9 +477,31 @@ impl<'a> SpecifierUnfurler<'a> {
     parsed_source: &ParsedSource,
     dep: &deno_graph::DynamicDependencyDescriptor,
     text_changes: &mut Vec<deno_ast::TextChange>,
+    diagnostic_reporter: &mut dyn FnMut(SpecifierUnfurlerDiagnostic),
   ) -> bool {
     match &dep.argument {
       deno_graph::DynamicArgument::String(specifier) => {
+        if self.is_dev_import(specifier) {
+          if self.is_dev_module(module_url) {
+            return true;
+          }
+          diagnostic_reporter(SpecifierUnfurlerDiagnostic::DevImport {
+            specifier: module_url.clone(),
+            text_info: parsed_source.text_info().clone(),
+            range: dep
+              .argument_range
+              .as_source_range(parsed_source.text_info()),
+            imported: specifier.to_string(),
+          });
+        }
+        if let Some(diagnostic) = self.workspace_member_diagnostic(
+          module_url,
+          parsed_source,
+          specifier,
+          &dep.argument_range,
+        ) {
+          diagnostic_reporter(diagnostic);
+        }
         let range = to_range(parsed_source, &dep.argument_range);
         let maybe_relative_index = parsed_source.text_info().text_str()
           [range.start..range.end]
@@ -239,13 +565,30 @@ impl<'a> SpecifierUnfurler<'a> {
     url: &ModuleSpecifier,
     parsed_source: &ParsedSource,
     diagnostic_reporter: &mut dyn FnMut(SpecifierUnfurlerDiagnostic),
-  ) -> String {
+  ) -> UnfurledSource {
     let mut text_changes = Vec::new();
     let module_info = DefaultModuleAnalyzer::module_info(parsed_source);
     let analyze_specifier =
       |specifier: &str,
        range: &deno_graph::PositionRange,
-       text_changes: &mut Vec<deno_ast::TextChange>| {
+       text_changes: &mut Vec<deno_ast::TextChange>,
+       diagnostic_reporter: &mut dyn FnMut(SpecifierUnfurlerDiagnostic)| {
+        if self.is_dev_import(specifier) {
+          if self.is_dev_module(url) {
+            return;
+          }
+          diagnostic_reporter(SpecifierUnfurlerDiagnostic::DevImport {
+            specifier: url.clone(),
+            text_info: parsed_source.text_info().clone(),
+            range: range.as_source_range(parsed_source.text_info()),
+            imported: specifier.to_string(),
+          });
+        }
+        if let Some(diagnostic) =
+          self.workspace_member_diagnostic(url, parsed_source, specifier, range)
+        {
+          diagnostic_reporter(diagnostic);
+        }
         if let Some(unfurled) = self.unfurl_specifier(url, specifier) {
           text_changes.push(deno_ast::TextChange {
             range: to_range(parsed_source, range),
@@ -260,6 +603,7 @@ impl<'a> SpecifierUnfurler<'a> {
             &dep.specifier,
             &dep.specifier_range,
             &mut text_changes,
+            &mut *diagnostic_reporter,
           );
         }
         DependencyDescriptor::Dynamic(dep) => {
@@ -268,6 +612,7 @@ impl<'a> SpecifierUnfurler<'a> {
             parsed_source,
             dep,
             &mut text_changes,
+            &mut *diagnostic_reporter,
           );
 
           if !success {
@@ -299,6 +644,7 @@ impl<'a> SpecifierUnfurler<'a> {
         &specifier_with_range.text,
         &specifier_with_range.range,
         &mut text_changes,
+        &mut *diagnostic_reporter,
       );
     }
     for specifier_with_range in &module_info.jsdoc_imports {
@@ -306,6 +652,7 @@ impl<'a> SpecifierUnfurler<'a> {
         &specifier_with_range.text,
         &specifier_with_range.range,
         &mut text_changes,
+        &mut *diagnostic_reporter,
       );
     }
     if let Some(specifier_with_range) = &module_info.jsx_import_source {
@@ -313,14 +660,29 @@ impl<'a> SpecifierUnfurler<'a> {
         &specifier_with_range.text,
         &specifier_with_range.range,
         &mut text_changes,
+        &mut *diagnostic_reporter,
       );
     }
 
-    let rewritten_text = deno_ast::apply_text_changes(
-      parsed_source.text_info().text_str(),
-      text_changes,
-    );
-    rewritten_text
+    let text_info = parsed_source.text_info();
+    let rewrites = text_changes
+      .iter()
+      .filter(|change| !self.is_renamed_module(url, &change.new_text))
+      .map(|change| SpecifierRewrite {
+        range: change.range.clone(),
+        line: text_info
+          .line_index(text_info.range().start + change.range.start)
+          + 1,
+        from: text_info.text_str()[change.range.clone()].to_string(),
+        to: change.new_text.clone(),
+      })
+      .collect();
+    let rewritten_text =
+      deno_ast::apply_text_changes(text_info.text_str(), text_changes);
+    UnfurledSource {
+      text: rewritten_text,
+      rewrites,
+    }
   }
 }
 
@@ -362,6 +724,7 @@ mod tests {
   use super::*;
   use deno_ast::MediaType;
   use deno_ast::ModuleSpecifier;
+  use deno_config::WorkspaceMemberConfig;
   use deno_core::serde_json::json;
   use deno_core::url::Url;
   use deno_runtime::deno_fs::RealFs;
@@ -494,7 +857,178 @@ const test6 = await import(`./lib/something.ts`);
 const warn1 = await import(`lib${expr}`);
 const warn2 = await import(`${expr}`);
 "#;
-      assert_eq!(unfurled_source, expected_source);
+      assert_eq!(unfurled_source.text, expected_source);
+      assert_eq!(unfurled_source.rewrites.len(), 12);
+      assert_eq!(
+        unfurled_source.rewrites[0],
+        SpecifierRewrite {
+          range: 21..28,
+          line: 1,
+          from: "express".to_string(),
+          to: "npm:express@5".to_string(),
+        }
+      );
     }
   }
+
+  #[test]
+  fn test_unfurling_cross_member_imports() {
+    let cwd = testdata_path().join("unfurl").to_path_buf();
+    let member = |name: &str, exports: &str| {
+      let dir_path = cwd.join(&name[5..]);
+      let config_file = deno_config::ConfigFile::new(
+        &format!(
+          r#"{{ "name": "{}", "version": "1.2.0", "exports": {} }}"#,
+          name, exports
+        ),
+        ModuleSpecifier::from_file_path(dir_path.join("deno.json")).unwrap(),
+      )
+      .unwrap();
+      UnfurlWorkspaceMember::new(&WorkspaceMemberConfig {
+        member_name: name[5..].to_string(),
+        dir_path,
+        package_name: name.to_string(),
+        package_version: "1.2.0".to_string(),
+        config_file,
+      })
+    };
+    let members = vec![
+      member("@foo/a", r#""./mod.ts""#),
+      member("@foo/b", r#"{ ".": "./mod.ts", "./utils": "./utils.ts" }"#),
+      member("@foo/c", r#"{ ".": "./mod.ts", "./utils": "./utils.ts" }"#),
+    ];
+    let mapped_resolver = MappedSpecifierResolver::new(
+      None,
+      Arc::new(PackageJsonDepsProvider::new(None)),
+    );
+    let unfurler = SpecifierUnfurler::new(&mapped_resolver, None, false)
+      .with_workspace_members(&members);
+
+    let source_code = r#"import b from "../b/mod.ts";
+import { util } from "../b/utils.ts";
+import { internal } from "../b/internal.ts";
+import { a } from "./a.ts";
+import { c } from "jsr:@foo/c@^0.9.0/utils";
+import { c2 } from "jsr:@foo/c@^1.0.0";
+const lazy = await import("../b/mod.ts");
+"#;
+    let specifier =
+      ModuleSpecifier::from_file_path(cwd.join("a/mod.ts")).unwrap();
+    let source = parse_ast(&specifier, source_code);
+    let mut d = Vec::new();
+    let mut reporter = |diagnostic| d.push(diagnostic);
+    let unfurled_source = unfurler.unfurl(&specifier, &source, &mut reporter);
+    let jsr_specifiers = d
+      .iter()
+      .map(|diagnostic| match diagnostic {
+        SpecifierUnfurlerDiagnostic::CrossMemberImport {
+          member,
+          jsr_specifier,
+          ..
+        } => {
+          assert_eq!(member, "@foo/b");
+          jsr_specifier.clone()
+        }
+        SpecifierUnfurlerDiagnostic::IncompatibleMemberVersion {
+          member,
+          jsr_specifier,
+          ..
+        } => {
+          assert_eq!(member, "@foo/c");
+          Some(jsr_specifier.clone())
+        }
+        _ => unreachable!("{:?}", diagnostic),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      jsr_specifiers,
+      vec![
+        Some("jsr:@foo/b@^1.2.0".to_string()),
+        Some("jsr:@foo/b@^1.2.0/utils".to_string()),
+        None,
+        Some("jsr:@foo/c@^1.2.0/utils".to_string()),
+        Some("jsr:@foo/b@^1.2.0".to_string()),
+      ]
+    );
+    assert_eq!(
+      unfurled_source.text,
+      r#"import b from "jsr:@foo/b@^1.2.0";
+import { util } from "jsr:@foo/b@^1.2.0/utils";
+import { internal } from "../b/internal.ts";
+import { a } from "./a.ts";
+import { c } from "jsr:@foo/c@^1.2.0/utils";
+import { c2 } from "jsr:@foo/c@^1.0.0";
+const lazy = await import("jsr:@foo/b@^1.2.0");
+"#
+    );
+    assert_eq!(unfurled_source.rewrites.len(), 4);
+  }
+
+  #[test]
+  fn test_unfurling_dev_imports() {
+    let cwd = testdata_path().join("unfurl").to_path_buf();
+    let deno_json_url =
+      ModuleSpecifier::from_file_path(cwd.join("deno.json")).unwrap();
+    let value = json!({
+      "imports": {
+        "@std/assert": "jsr:@std/assert@0.219",
+        "@std/testing/": "jsr:/@std/testing@0.219/",
+        "@std/path": "jsr:@std/path@0.219",
+      }
+    });
+    let ImportMapWithDiagnostics { import_map, .. } =
+      import_map::parse_from_value(deno_json_url, value).unwrap();
+    let mapped_resolver = MappedSpecifierResolver::new(
+      Some(Arc::new(import_map)),
+      Arc::new(PackageJsonDepsProvider::new(None)),
+    );
+    let dev_imports =
+      vec!["@std/assert".to_string(), "@std/testing/".to_string()];
+    let mod_specifier =
+      ModuleSpecifier::from_file_path(cwd.join("mod.ts")).unwrap();
+    let exported_modules = HashSet::from([mod_specifier.clone()]);
+    let unfurler = SpecifierUnfurler::new(&mapped_resolver, None, false)
+      .with_dev_imports(&dev_imports, &exported_modules);
+
+    let source_code = r#"import { assert } from "@std/assert";
+import { spy } from "@std/testing/mock.ts";
+import { join } from "@std/path";
+"#;
+    // the imports are left as they are in tests
+    let test_specifier =
+      ModuleSpecifier::from_file_path(cwd.join("mod_test.ts")).unwrap();
+    let source = parse_ast(&test_specifier, source_code);
+    let mut d = Vec::new();
+    let mut reporter = |diagnostic| d.push(diagnostic);
+    let unfurled_source =
+      unfurler.unfurl(&test_specifier, &source, &mut reporter);
+    assert!(d.is_empty());
+    assert_eq!(
+      unfurled_source.text,
+      r#"import { assert } from "@std/assert";
+import { spy } from "@std/testing/mock.ts";
+import { join } from "jsr:@std/path@0.219";
+"#
+    );
+
+    // and reported in the modules that are reachable from the exports
+    let source = parse_ast(&mod_specifier, source_code);
+    let mut d = Vec::new();
+    let mut reporter = |diagnostic| d.push(diagnostic);
+    unfurler.unfurl(&mod_specifier, &source, &mut reporter);
+    let imported = d
+      .iter()
+      .map(|diagnostic| match diagnostic {
+        SpecifierUnfurlerDiagnostic::DevImport { imported, .. } => {
+          imported.as_str()
+        }
+        _ => unreachable!("{:?}", diagnostic),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(imported, vec!["@std/assert", "@std/testing/mock.ts"]);
+
+    assert!(unfurler.is_dev_import("@std/assert/equals"));
+    assert!(!unfurler.is_dev_import("@std/assertions"));
+    assert!(!unfurler.is_dev_import("@std/testing"));
+  }
 }
@@ -0,0 +1,271 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+use deno_terminal::colors;
+use serde::Serialize;
+use sha2::Digest;
+
+use crate::args::jsr_url;
+use crate::args::Flags;
+use crate::args::RegistryVerifyFlags;
+
+use super::commands::parse_package_version_arg;
+use super::commands::RegistryContext;
+use super::output::print_json;
+use super::provenance;
+use super::provenance::SigstoreConfig;
+use super::VersionManifest;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationJson {
+  package: String,
+  signer: Option<String>,
+  log_index: u64,
+  log_entry_url: String,
+  /// Only set when the files of a local tarball are verified.
+  files: Option<VerifiedFilesJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifiedFilesJson {
+  path: String,
+  count: usize,
+}
+
+pub async fn verify(
+  flags: Flags,
+  verify_flags: RegistryVerifyFlags,
+) -> Result<(), AnyError> {
+  let context = RegistryContext::new(flags).await?;
+  let cli_options = context.cli_factory.cli_options();
+  let package = parse_package_version_arg(&verify_flags.package)?;
+  let api_client = context.api_client()?;
+
+  let version_info = api_client
+    .get_package_version(
+      &package.scope,
+      &package.package,
+      &package.version.to_string(),
+    )
+    .await?;
+  match version_info {
+    Some(version_info) if version_info.yanked => {
+      log::warn!("{} {} is yanked", colors::yellow("Warning"), package)
+    }
+    Some(_) => {}
+    None => bail!("{} was not found in the registry", package),
+  }
+
+  let meta_path = format!(
+    "@{}/{}/{}_meta.json",
+    package.scope, package.package, package.version
+  );
+  let response = api_client.get_registry_file(jsr_url(), &meta_path).await?;
+  if !response.status().is_success() {
+    bail!(
+      "Failed to get the version manifest of {}: status {}",
+      package,
+      response.status()
+    );
+  }
+  let meta_bytes = response.bytes().await?;
+  let manifest = serde_json::from_slice::<VersionManifest>(&meta_bytes)
+    .context("Failed to parse the version manifest")?;
+
+  let bundle = match &verify_flags.bundle {
+    Some(bundle_path) => {
+      let bundle_path = cli_options.initial_cwd().join(bundle_path);
+      let text = std::fs::read_to_string(&bundle_path).with_context(|| {
+        format!(
+          "Failed reading provenance bundle '{}'",
+          bundle_path.display()
+        )
+      })?;
+      serde_json::from_str::<Value>(&text)?
+    }
+    None => {
+      let bundle = api_client
+        .get_provenance(
+          &package.scope,
+          &package.package,
+          &package.version.to_string(),
+        )
+        .await
+        .with_context(|| {
+          format!("Failed to get the provenance of {}", package)
+        })?;
+      match bundle {
+        Some(bundle) => bundle,
+        None => bail!("{} was published without provenance", package),
+      }
+    }
+  };
+
+  let sigstore_config = SigstoreConfig::resolve(context.registry_config())?;
+  let verified = provenance::verify_provenance(
+    context.cli_factory.http_client(),
+    &sigstore_config,
+    &bundle,
+    &format!("pkg:jsr/{}", package),
+    &hex::encode(sha2::Sha256::digest(&meta_bytes)),
+  )
+  .await
+  .with_context(|| format!("Failed to verify the provenance of {}", package))?;
+  if !verify_flags.json {
+    println!("{} {}", colors::green("Verified provenance of"), package);
+    if let Some(signer) = &verified.signer {
+      println!("  {} {}", colors::gray("Signed by:"), signer);
+    }
+    println!(
+      "  {} {}",
+      colors::gray("Transparency log:"),
+      sigstore_config.log_entry_url(verified.log_index)
+    );
+  }
+
+  let mut verified_files = None;
+  if let Some(path) = &verify_flags.path {
+    let path = cli_options.initial_cwd().join(path);
+    // the published files are not the sources, imports are rewritten when
+    // publishing, so only a tarball of the published files can be compared
+    if path.is_dir() {
+      bail!(
+        "'{}' is a directory, only a .tgz tarball of the published files can be verified",
+        path.display()
+      );
+    }
+    let checksums = checksums_from_tarball(&path)?;
+    let problems = compare_checksums(&manifest, &checksums);
+    if !problems.is_empty() {
+      bail!(
+        "The files in '{}' don't match {}:\n{}",
+        path.display(),
+        package,
+        problems
+          .iter()
+          .map(|problem| format!(" - {}", problem))
+          .collect::<Vec<_>>()
+          .join("\n")
+      );
+    }
+    if !verify_flags.json {
+      println!(
+        "{} {} files in {}",
+        colors::green("Verified"),
+        manifest.manifest.len(),
+        path.display()
+      );
+    }
+    verified_files = Some(VerifiedFilesJson {
+      path: path.display().to_string(),
+      count: manifest.manifest.len(),
+    });
+  }
+
+  if verify_flags.json {
+    print_json(&VerificationJson {
+      package: package.to_string(),
+      signer: verified.signer,
+      log_index: verified.log_index,
+      log_entry_url: sigstore_config.log_entry_url(verified.log_index),
+      files: verified_files,
+    })?;
+  }
+  Ok(())
+}
+
+fn checksum(data: &[u8]) -> String {
+  format!("sha256-{:x}", sha2::Sha256::digest(data))
+}
+
+/// Gets the checksums of all the files in a gzipped tarball.
+fn checksums_from_tarball(
+  tarball_path: &Path,
+) -> Result<BTreeMap<String, String>, AnyError> {
+  let file = std::fs::File::open(tarball_path)
+    .with_context(|| format!("Failed opening '{}'", tarball_path.display()))?;
+  let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+  let mut checksums = BTreeMap::new();
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    if !entry.header().entry_type().is_file() {
+      continue;
+    }
+    let path = entry.path()?.to_string_lossy().to_string();
+    let path = format!("/{}", path.trim_start_matches("./"));
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data)?;
+    checksums.insert(path, checksum(&data));
+  }
+  Ok(checksums)
+}
+
+fn compare_checksums(
+  manifest: &VersionManifest,
+  checksums: &BTreeMap<String, String>,
+) -> Vec<String> {
+  let mut problems = Vec::new();
+  let expected = manifest.manifest.iter().collect::<BTreeMap<_, _>>();
+  for (path, entry) in expected {
+    match checksums.get(path) {
+      Some(actual) if *actual == entry.checksum => {}
+      Some(actual) => problems.push(format!(
+        "{}: expected {}, got {}",
+        path, entry.checksum, actual
+      )),
+      None => problems.push(format!("{}: missing", path)),
+    }
+  }
+  for path in checksums.keys() {
+    if !manifest.manifest.contains_key(path) {
+      problems.push(format!("{}: not in the published version", path));
+    }
+  }
+  problems
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn compare_checksums_reports_problems() {
+    let manifest = serde_json::from_value::<VersionManifest>(json!({
+      "manifest": {
+        "/mod.ts": { "size": 1, "checksum": checksum(b"a") },
+        "/util.ts": { "size": 1, "checksum": checksum(b"b") },
+        "/deno.json": { "size": 1, "checksum": checksum(b"c") },
+      },
+      "exports": { ".": "./mod.ts" },
+    }))
+    .unwrap();
+    let checksums = BTreeMap::from([
+      ("/mod.ts".to_string(), checksum(b"a")),
+      ("/util.ts".to_string(), checksum(b"changed")),
+      ("/extra.ts".to_string(), checksum(b"d")),
+    ]);
+    assert_eq!(
+      compare_checksums(&manifest, &checksums),
+      vec![
+        "/deno.json: missing".to_string(),
+        format!(
+          "/util.ts: expected {}, got {}",
+          checksum(b"b"),
+          checksum(b"changed")
+        ),
+        "/extra.ts: not in the published version".to_string(),
+      ]
+    );
+  }
+}
@@ -0,0 +1,536 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_ast::TextChange;
+use deno_config::WorkspaceMemberConfig;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_semver::Version;
+use deno_terminal::colors;
+use jsonc_parser::ast::ObjectProp;
+use jsonc_parser::ast::Value;
+
+use crate::args::Flags;
+use crate::args::VersionBumpFlags;
+use crate::args::VersionIncrement;
+use crate::args::VersionSubcommand;
+use crate::factory::CliFactory;
+
+pub async fn version(
+  flags: Flags,
+  subcommand: VersionSubcommand,
+) -> Result<(), AnyError> {
+  match subcommand {
+    VersionSubcommand::Bump(bump_flags) => {
+      bump(flags, bump_flags).await?;
+      Ok(())
+    }
+  }
+}
+
+/// Bumps the versions of the workspace members, which `deno publish --bump`
+/// does before loading the configuration files. The updated files can be
+/// restored when publishing fails.
+pub async fn bump(
+  flags: Flags,
+  bump_flags: VersionBumpFlags,
+) -> Result<BumpedFiles, AnyError> {
+  let cli_factory = CliFactory::from_flags(flags).await?;
+  let cli_options = cli_factory.cli_options();
+  let Some(config_file) = cli_options.maybe_config_file() else {
+    bail!(
+      "Couldn't find a deno.json, deno.jsonc, jsr.json or jsr.jsonc configuration file in {}.",
+      cli_options.initial_cwd().display()
+    );
+  };
+  let members = cli_options.workspace_members()?;
+  let new_versions = bump_versions(&members, &bump_flags)?;
+  for member in &members {
+    if let Some(new_version) = new_versions.get(&member.package_name) {
+      log::info!(
+        "{} {} {} -> {}",
+        colors::green("Bump"),
+        member.package_name,
+        member.package_version,
+        new_version
+      );
+    }
+  }
+
+  // the configuration files with the version of their package, and the
+  // import maps, which only contain imports of the packages
+  let mut files = BTreeMap::<Url, Option<&Version>>::new();
+  for member in &members {
+    files.insert(
+      member.config_file.specifier.clone(),
+      new_versions.get(&member.package_name),
+    );
+  }
+  files.entry(config_file.specifier.clone()).or_insert(None);
+  for config_file in members
+    .iter()
+    .map(|member| &member.config_file)
+    .chain(std::iter::once(config_file))
+  {
+    if let Some(import_map) = &config_file.json.import_map {
+      let import_map = config_file.specifier.join(import_map)?;
+      files.entry(import_map).or_insert(None);
+    }
+  }
+
+  let mut bumped_files = BumpedFiles(Vec::new());
+  for (url, new_version) in files {
+    if url.scheme() != "file" {
+      continue;
+    }
+    let path = url.to_file_path().unwrap();
+    let text = std::fs::read_to_string(&path)
+      .with_context(|| format!("Failed reading '{}'", path.display()))?;
+    let Some(new_text) = update_json_text(&text, new_version, &new_versions)
+      .with_context(|| format!("Failed updating '{}'", path.display()))?
+    else {
+      continue;
+    };
+    if !bump_flags.dry_run {
+      if let Err(err) = std::fs::write(&path, new_text) {
+        bumped_files.restore()?;
+        return Err(err)
+          .with_context(|| format!("Failed writing '{}'", path.display()));
+      }
+      bumped_files.0.push((path.clone(), text));
+    }
+    log::info!("{} {}", colors::green("Updated"), path.display());
+  }
+
+  if bump_flags.dry_run {
+    log::warn!(
+      "{} Not updating the files due to --dry-run",
+      colors::yellow("Warning")
+    );
+  }
+  Ok(bumped_files)
+}
+
+/// The original contents of the files that bumping the versions updated.
+pub struct BumpedFiles(Vec<(PathBuf, String)>);
+
+impl BumpedFiles {
+  /// Writes the original contents back, for when publishing the new versions
+  /// failed.
+  pub fn restore(self) -> Result<(), AnyError> {
+    for (path, text) in self.0 {
+      std::fs::write(&path, text)
+        .with_context(|| format!("Failed writing '{}'", path.display()))?;
+      log::info!("{} {}", colors::green("Restored"), path.display());
+    }
+    Ok(())
+  }
+}
+
+/// Gets the new versions of the bumped workspace members by package name.
+fn bump_versions(
+  members: &[WorkspaceMemberConfig],
+  bump_flags: &VersionBumpFlags,
+) -> Result<HashMap<String, Version>, AnyError> {
+  let member_names = members
+    .iter()
+    .map(|member| member.package_name.as_str())
+    .collect::<HashSet<_>>();
+  for name in &bump_flags.packages {
+    if !member_names.contains(name.as_str()) {
+      bail!(
+        "'{}' isn't a member of the workspace. The members are: {}",
+        name,
+        members
+          .iter()
+          .map(|member| member.package_name.as_str())
+          .collect::<Vec<_>>()
+          .join(", ")
+      );
+    }
+  }
+
+  let mut new_versions = HashMap::new();
+  for member in members {
+    if !bump_flags.packages.is_empty()
+      && !bump_flags.packages.contains(&member.package_name)
+    {
+      continue;
+    }
+    let version = Version::parse_standard(&member.package_version)
+      .with_context(|| {
+        format!(
+          "Invalid version '{}' of {}",
+          member.package_version, member.package_name
+        )
+      })?;
+    let increment = match &bump_flags.increment {
+      VersionIncrement::Auto => match auto_increment(member, &version)? {
+        Some(increment) => increment,
+        None => {
+          log::info!(
+            "{} {}, no feat, fix or breaking commits since its last release",
+            colors::gray("Skip"),
+            member.package_name
+          );
+          continue;
+        }
+      },
+      increment => increment.clone(),
+    };
+    let new_version = bump_version(&version, &increment)?;
+    new_versions.insert(member.package_name.clone(), new_version);
+  }
+  Ok(new_versions)
+}
+
+fn bump_version(
+  version: &Version,
+  increment: &VersionIncrement,
+) -> Result<Version, AnyError> {
+  let (major, minor, patch) = (version.major, version.minor, version.patch);
+  let (major, minor, patch) = match increment {
+    // a prerelease is bumped to its release
+    VersionIncrement::Patch if !version.pre.is_empty() => (major, minor, patch),
+    VersionIncrement::Patch => (major, minor, patch + 1),
+    VersionIncrement::Minor => (major, minor + 1, 0),
+    VersionIncrement::Major => (major + 1, 0, 0),
+    VersionIncrement::Auto => {
+      unreachable!("resolved with auto_increment before")
+    }
+    VersionIncrement::Exact(text) => {
+      return Version::parse_standard(text)
+        .with_context(|| format!("Invalid version '{}'", text));
+    }
+  };
+  Ok(Version {
+    major,
+    minor,
+    patch,
+    pre: Vec::new(),
+    build: Vec::new(),
+  })
+}
+
+/// The increments that conventional commit messages call for, by
+/// significance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CommitBump {
+  Patch,
+  Minor,
+  Major,
+}
+
+/// Gets the increment of a workspace member from the conventional commit
+/// messages that touched its directory since its last release tag, or
+/// `None` when none of them call for a release.
+fn auto_increment(
+  member: &WorkspaceMemberConfig,
+  version: &Version,
+) -> Result<Option<VersionIncrement>, AnyError> {
+  let config_path = member.config_file.specifier.to_file_path().unwrap();
+  let dir = config_path.parent().unwrap();
+  let tag = find_release_tag(dir, &member.package_name, version)?;
+  let range = match &tag {
+    Some(tag) => format!("{}..HEAD", tag),
+    None => {
+      log::warn!(
+        "{} No release tag '{}@{}' or 'v{}' found for {}, using all of its commits",
+        colors::yellow("Warning"),
+        member.package_name,
+        version,
+        version,
+        member.package_name
+      );
+      "HEAD".to_string()
+    }
+  };
+  let log = git(dir, &["log", "--format=%B%x00", &range, "--", "."])?
+    .with_context(|| {
+      format!("Failed reading the git log of '{}'", dir.display())
+    })?;
+  let bump = log.split('\0').filter_map(commit_bump).max();
+  Ok(bump.map(|bump| match bump {
+    // breaking changes only bump the minor version before 1.0.0
+    CommitBump::Major if version.major == 0 => VersionIncrement::Minor,
+    CommitBump::Major => VersionIncrement::Major,
+    CommitBump::Minor => VersionIncrement::Minor,
+    CommitBump::Patch => VersionIncrement::Patch,
+  }))
+}
+
+/// Finds the tag of the release of a package, `@foo/bar@1.2.0` in
+/// workspaces or `v1.2.0` in repositories of a single package.
+fn find_release_tag(
+  dir: &Path,
+  name: &str,
+  version: &Version,
+) -> Result<Option<String>, AnyError> {
+  for tag in [format!("{}@{}", name, version), format!("v{}", version)] {
+    let tag_ref = format!("refs/tags/{}", tag);
+    if git(dir, &["rev-parse", "--quiet", "--verify", &tag_ref])?.is_some() {
+      return Ok(Some(tag));
+    }
+  }
+  Ok(None)
+}
+
+/// Runs git in a directory and returns its output, or `None` when it
+/// failed.
+fn git(dir: &Path, args: &[&str]) -> Result<Option<String>, AnyError> {
+  let output = std::process::Command::new("git")
+    .args(args)
+    .current_dir(dir)
+    .output()
+    .context("Failed running git, which is required for --bump auto")?;
+  if !output.status.success() {
+    log::debug!(
+      "git {} failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+    return Ok(None);
+  }
+  Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// Gets the increment a conventional commit message calls for, eg.
+/// `feat(parser): ...` or `fix!: ...`. Other types, like `docs` or `chore`,
+/// and messages that aren't conventional commits don't call for one.
+fn commit_bump(message: &str) -> Option<CommitBump> {
+  let message = message.trim();
+  let header = message.lines().next()?;
+  let (prefix, _) = header.split_once(':')?;
+  let (prefix, breaking) = match prefix.strip_suffix('!') {
+    Some(prefix) => (prefix, true),
+    None => (prefix, false),
+  };
+  let commit_type = match prefix.split_once('(') {
+    Some((commit_type, scope)) if scope.ends_with(')') => commit_type,
+    Some(_) => return None,
+    None => prefix,
+  };
+  if commit_type.is_empty()
+    || !commit_type
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-')
+  {
+    return None;
+  }
+  let breaking = breaking
+    || message.lines().skip(1).any(|line| {
+      line.starts_with("BREAKING CHANGE:")
+        || line.starts_with("BREAKING-CHANGE:")
+    });
+  if breaking {
+    return Some(CommitBump::Major);
+  }
+  match commit_type.to_ascii_lowercase().as_str() {
+    "feat" => Some(CommitBump::Minor),
+    "fix" | "perf" => Some(CommitBump::Patch),
+    _ => None,
+  }
+}
+
+/// Sets the `"version"` of a configuration file and updates the constraints
+/// of the `jsr:` imports of bumped packages in its `"imports"`. Returns
+/// `None` when nothing changed.
+fn update_json_text(
+  text: &str,
+  new_version: Option<&Version>,
+  new_versions: &HashMap<String, Version>,
+) -> Result<Option<String>, AnyError> {
+  let ast =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())?;
+  let Some(Value::Object(obj)) = ast.value else {
+    bail!("Expected an object");
+  };
+
+  let mut text_changes = Vec::new();
+  if let (
+    Some(new_version),
+    Some(ObjectProp {
+      value: Value::StringLit(lit),
+      ..
+    }),
+  ) = (new_version, obj.get("version"))
+  {
+    text_changes.push(TextChange {
+      range: lit.range.start..lit.range.end,
+      new_text: serde_json::to_string(&new_version.to_string())?,
+    });
+  }
+  if let Some(ObjectProp {
+    value: Value::Object(imports),
+    ..
+  }) = obj.get("imports")
+  {
+    for prop in &imports.properties {
+      let Value::StringLit(lit) = &prop.value else {
+        continue;
+      };
+      if let Some(new_value) = bump_jsr_specifier(&lit.value, new_versions) {
+        text_changes.push(TextChange {
+          range: lit.range.start..lit.range.end,
+          new_text: serde_json::to_string(&new_value)?,
+        });
+      }
+    }
+  }
+
+  if text_changes.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(deno_ast::apply_text_changes(text, text_changes)))
+}
+
+/// Updates the version constraint of a `jsr:` specifier of a bumped
+/// package, eg. `jsr:@foo/bar@^1.2.0/mod.ts` to `jsr:@foo/bar@^1.3.0/mod.ts`.
+fn bump_jsr_specifier(
+  specifier: &str,
+  new_versions: &HashMap<String, Version>,
+) -> Option<String> {
+  let rest = specifier.strip_prefix("jsr:")?;
+  let (name, new_version) = new_versions
+    .iter()
+    .find(|(name, _)| rest.starts_with(&format!("{}@", name)))?;
+  let rest = &rest[name.len() + 1..];
+  let (constraint, sub_path) = match rest.find('/') {
+    Some(index) => rest.split_at(index),
+    None => (rest, ""),
+  };
+  let new_constraint = bump_constraint(constraint, new_version)?;
+  Some(format!("jsr:{}@{}{}", name, new_constraint, sub_path))
+}
+
+/// Keeps the operator of a `^1.2.0`, `~1.2.0` or `1.2.0` constraint, and
+/// replaces more complex ones with a caret constraint. Unversioned
+/// constraints are kept.
+pub fn bump_constraint(
+  constraint: &str,
+  new_version: &Version,
+) -> Option<String> {
+  if constraint == "*" {
+    return None;
+  }
+  let (operator, version) = match constraint.strip_prefix(['^', '~', '=']) {
+    Some(version) => (&constraint[..1], version),
+    None => ("", constraint),
+  };
+  if Version::parse_standard(version).is_ok() {
+    Some(format!("{}{}", operator, new_version))
+  } else {
+    Some(format!("^{}", new_version))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn version(text: &str) -> Version {
+    Version::parse_standard(text).unwrap()
+  }
+
+  #[test]
+  fn bump_versions_by_increment() {
+    let bump = |text: &str, increment: VersionIncrement| {
+      bump_version(&version(text), &increment)
+        .unwrap()
+        .to_string()
+    };
+    assert_eq!(bump("1.2.3", VersionIncrement::Patch), "1.2.4");
+    assert_eq!(bump("1.2.3-beta.1", VersionIncrement::Patch), "1.2.3");
+    assert_eq!(bump("1.2.3", VersionIncrement::Minor), "1.3.0");
+    assert_eq!(bump("1.2.3", VersionIncrement::Major), "2.0.0");
+    assert_eq!(
+      bump("1.2.3", VersionIncrement::Exact("2.0.0-rc.1".to_string())),
+      "2.0.0-rc.1"
+    );
+    assert!(bump_version(
+      &version("1.2.3"),
+      &VersionIncrement::Exact("next".to_string())
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn conventional_commit_bumps() {
+    assert_eq!(commit_bump("feat: add parser"), Some(CommitBump::Minor));
+    assert_eq!(
+      commit_bump("feat(parser): support comments\n\nCloses #12"),
+      Some(CommitBump::Minor)
+    );
+    assert_eq!(commit_bump("fix: off by one"), Some(CommitBump::Patch));
+    assert_eq!(commit_bump("perf: cache lookups"), Some(CommitBump::Patch));
+    assert_eq!(
+      commit_bump("refactor!: drop Node 16"),
+      Some(CommitBump::Major)
+    );
+    assert_eq!(
+      commit_bump("fix: rename option\n\nBREAKING CHANGE: `foo` is `bar` now"),
+      Some(CommitBump::Major)
+    );
+    assert_eq!(commit_bump("docs: typo"), None);
+    assert_eq!(commit_bump("chore(deps): update"), None);
+    assert_eq!(commit_bump("Merge branch 'main'"), None);
+    assert_eq!(commit_bump("fix typo: in readme"), None);
+    assert_eq!(commit_bump(""), None);
+  }
+
+  #[test]
+  fn update_config_file_text() {
+    let new_versions = HashMap::from([
+      ("@foo/bar".to_string(), version("1.3.0")),
+      ("@foo/baz".to_string(), version("0.2.0")),
+    ]);
+    let text = r#"{
+  // the package
+  "name": "@foo/bar",
+  "version": "1.2.0",
+  "exports": "./mod.ts",
+  "imports": {
+    "@foo/baz": "jsr:@foo/baz@^0.1.0",
+    "@foo/baz/": "jsr:@foo/baz@~0.1.0/",
+    "@foo/baz-utils": "jsr:@foo/baz-utils@^0.1.0",
+    "@foo/bar": "jsr:@foo/bar@>=1.0.0 <2.0.0",
+    "@std/path": "jsr:@std/path@^0.220.0"
+  }
+}
+"#;
+    assert_eq!(
+      update_json_text(text, new_versions.get("@foo/bar"), &new_versions)
+        .unwrap()
+        .unwrap(),
+      r#"{
+  // the package
+  "name": "@foo/bar",
+  "version": "1.3.0",
+  "exports": "./mod.ts",
+  "imports": {
+    "@foo/baz": "jsr:@foo/baz@^0.2.0",
+    "@foo/baz/": "jsr:@foo/baz@~0.2.0/",
+    "@foo/baz-utils": "jsr:@foo/baz-utils@^0.1.0",
+    "@foo/bar": "jsr:@foo/bar@^1.3.0",
+    "@std/path": "jsr:@std/path@^0.220.0"
+  }
+}
+"#
+    );
+    assert!(update_json_text(
+      r#"{ "imports": { "@std/path": "jsr:@std/path@^0.220.0" } }"#,
+      None,
+      &new_versions
+    )
+    .unwrap()
+    .is_none());
+  }
+}
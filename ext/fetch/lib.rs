@@ -206,6 +206,7 @@ pub fn get_or_create_client_from_state(
         client_cert_chain_and_key: options.client_cert_chain_and_key.clone(),
         pool_max_idle_per_host: None,
         pool_idle_timeout: None,
+        http2_keep_alive_interval: None,
         http1: true,
         http2: true,
       },
@@ -868,6 +869,7 @@ where
           _ => Some(None),
         },
       ),
+      http2_keep_alive_interval: None,
       http1: args.http1,
       http2: args.http2,
     },
@@ -888,6 +890,7 @@ pub struct CreateHttpClientOptions {
   pub client_cert_chain_and_key: Option<(String, String)>,
   pub pool_max_idle_per_host: Option<usize>,
   pub pool_idle_timeout: Option<Option<u64>>,
+  pub http2_keep_alive_interval: Option<u64>,
   pub http1: bool,
   pub http2: bool,
 }
@@ -902,6 +905,7 @@ impl Default for CreateHttpClientOptions {
       client_cert_chain_and_key: None,
       pool_max_idle_per_host: None,
       pool_idle_timeout: None,
+      http2_keep_alive_interval: None,
       http1: true,
       http2: true,
     }
@@ -957,6 +961,14 @@ pub fn create_http_client(
     );
   }
 
+  if let Some(http2_keep_alive_interval) = options.http2_keep_alive_interval {
+    builder = builder
+      .http2_keep_alive_interval(std::time::Duration::from_millis(
+        http2_keep_alive_interval,
+      ))
+      .http2_keep_alive_while_idle(true);
+  }
+
   match (options.http1, options.http2) {
     (true, false) => builder = builder.http1_only(),
     (false, true) => builder = builder.http2_prior_knowledge(),
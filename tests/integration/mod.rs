@@ -54,6 +54,8 @@ mod npm;
 mod pm;
 #[path = "publish_tests.rs"]
 mod publish;
+#[path = "registry_tests.rs"]
+mod registry;
 
 #[path = "repl_tests.rs"]
 mod repl;
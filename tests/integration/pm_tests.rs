@@ -2,6 +2,7 @@
 
 use deno_core::serde_json::json;
 use test_util::assert_contains;
+use test_util::assert_not_contains;
 use test_util::env_vars_for_jsr_tests;
 // use test_util::env_vars_for_npm_tests;
 // use test_util::itest;
@@ -78,31 +79,347 @@ fn add_multiple() {
 }
 
 #[test]
-fn add_not_supported_npm() {
+fn add_dev() {
   let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+
+  let output = context.new_command().args("add --dev @denotest/add").run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Add @denotest/add");
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0"
+    },
+    "publish": {
+      "devImports": ["@denotest/add"]
+    }
+  }));
+
+  // other packages aren't dev imports
+  let output = context
+    .new_command()
+    .args("add @denotest/subset-type-graph")
+    .run();
+  output.assert_exit_code(0);
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/subset-type-graph": "jsr:@denotest/subset-type-graph@^0.1.0"
+    },
+    "publish": {
+      "devImports": ["@denotest/add"]
+    }
+  }));
+
+  // adding it again without --dev makes it a dependency of the package
+  let output = context.new_command().args("add @denotest/add").run();
+  output.assert_exit_code(0);
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/subset-type-graph": "jsr:@denotest/subset-type-graph@^0.1.0"
+    },
+    "publish": {
+      "devImports": []
+    }
+  }));
+}
+
+#[test]
+fn add_version_range() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "add": {
+      "versionRange": "tilde"
+    }
+  }));
+
+  let output = context
+    .new_command()
+    .args("add @denotest/subset-type-graph")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Add @denotest/subset-type-graph - jsr:@denotest/subset-type-graph@~0.1.0"
+  );
+
+  // the flags take precedence over the configuration file
+  let output = context
+    .new_command()
+    .args("add --exact @denotest/add npm:@denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "add": {
+      "versionRange": "tilde"
+    },
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@1.0.0",
+      "@denotest/esm-basic": "npm:@denotest/esm-basic@1.0.0",
+      "@denotest/subset-type-graph": "jsr:@denotest/subset-type-graph@~0.1.0"
+    }
+  }));
+}
+
+#[test]
+fn add_nothing_when_one_is_not_found() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
 
   let output = context
     .new_command()
-    .args("add @denotest/add npm:express")
+    .args("add @denotest/add @denotest/not-found @denotest/not-found-2")
     .run();
   output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "jsr:@denotest/not-found, jsr:@denotest/not-found-2 were not found, so no packages were added."
+  );
+  // the configuration file isn't created
+  assert!(!temp_dir.join("deno.json").exists());
+
+  let starting_deno_json = json!({
+    "imports": {
+      "@denotest/subset-type-graph": "jsr:@denotest/subset-type-graph@^0.1.0"
+    }
+  });
+  temp_dir.join("deno.json").write_json(&starting_deno_json);
+  let output = context
+    .new_command()
+    .args("add @denotest/add @denotest/not-found")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "jsr:@denotest/not-found was not found, so no packages were added."
+  );
+  temp_dir
+    .join("deno.json")
+    .assert_matches_json(starting_deno_json);
+}
+
+#[test]
+fn add_npm() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+
+  let output = context
+    .new_command()
+    .args("add @denotest/add npm:@denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
   let output = output.combined_output();
-  assert_contains!(output, "error: Adding npm: packages is currently not supported. Package: npm:express");
+  assert_contains!(output, "Add @denotest/esm-basic");
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/esm-basic": "npm:@denotest/esm-basic@^1.0.0"
+    }
+  }));
+  assert!(!temp_dir.join("package.json").exists());
 }
 
 #[test]
-fn add_not_supported_version_constraint() {
+fn add_npm_package_json() {
   let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("package.json").write_json(&json!({
+    "name": "foo",
+    "dependencies": {
+      "@denotest/breaking-change-between-versions": "^1.0.0"
+    }
+  }));
+
+  // the npm packages are added to package.json and the jsr packages to
+  // deno.json
+  let output = context
+    .new_command()
+    .args("add @denotest/add npm:@denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
+  temp_dir.join("package.json").assert_matches_json(json!({
+    "name": "foo",
+    "dependencies": {
+      "@denotest/breaking-change-between-versions": "^1.0.0",
+      "@denotest/esm-basic": "^1.0.0"
+    }
+  }));
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0"
+    }
+  }));
+
+  // adding a dependency with --dev moves it to the dev dependencies
+  let output = context
+    .new_command()
+    .args("add --dev npm:@denotest/breaking-change-between-versions")
+    .run();
+  output.assert_exit_code(0);
+  temp_dir.join("package.json").assert_matches_json(json!({
+    "name": "foo",
+    "dependencies": {
+      "@denotest/esm-basic": "^1.0.0"
+    },
+    "devDependencies": {
+      "@denotest/breaking-change-between-versions": "^2.0.0"
+    }
+  }));
+
+  let output = context
+    .new_command()
+    .args("add --target deno npm:@denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/esm-basic": "npm:@denotest/esm-basic@^1.0.0"
+    }
+  }));
+
+  let output = context
+    .new_command()
+    .args("add --target package-json @denotest/add")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "error: Only npm: packages can be added to package.json. Package: @denotest/add"
+  );
+}
+
+#[test]
+fn add_version_constraint() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+
+  let output = context.new_command().args("add @denotest/add@0").run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Add @denotest/add - jsr:@denotest/add@0.x"
+  );
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@0.x"
+    }
+  }));
 
-  let output = context.new_command().args("add @denotest/add@1").run();
+  let output = context.new_command().args("add @denotest/add@3").run();
   output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "jsr:@denotest/add@3 was not found, so no packages were added."
+  );
+}
+
+#[test]
+fn add_alias() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+
+  let output = context
+    .new_command()
+    .args(
+      "add npm:@denotest/breaking-change-between-versions@1 --alias breaking",
+    )
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Add breaking - npm:@denotest/breaking-change-between-versions@1.x"
+  );
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "breaking": "npm:@denotest/breaking-change-between-versions@1.x"
+    }
+  }));
+
+  let output = context
+    .new_command()
+    .args("add @denotest/add @denotest/subset-type-graph --alias add")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "error: --alias can only be used when adding a single package"
+  );
+}
+
+#[test]
+fn outdated_and_update() {
+  let context = pm_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/no_module_graph": "jsr:@denotest/no_module_graph@^0.1.0",
+      "local": "./local.ts"
+    }
+  }));
+
+  let output = context.new_command().args("outdated").run();
+  output.assert_exit_code(0);
   let output = output.combined_output();
-  assert_contains!(output, "error: Specifying version constraints is currently not supported. Package: jsr:@denotest/add@1");
+  assert_contains!(
+    output,
+    "jsr:@denotest/no_module_graph  -        0.1.1   0.2.0"
+  );
+  assert_not_contains!(output, "jsr:@denotest/add");
+
+  let output = context.new_command().args("update").run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Update @denotest/no_module_graph jsr:@denotest/no_module_graph@^0.1.0 -> jsr:@denotest/no_module_graph@^0.1.1"
+  );
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/no_module_graph": "jsr:@denotest/no_module_graph@^0.1.1",
+      "local": "./local.ts"
+    }
+  }));
+
+  let output = context.new_command().args("update --latest").run();
+  output.assert_exit_code(0);
+  temp_dir.join("deno.json").assert_matches_json(json!({
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@^1.0.0",
+      "@denotest/no_module_graph": "jsr:@denotest/no_module_graph@^0.2.0",
+      "local": "./local.ts"
+    }
+  }));
+
+  let output = context.new_command().args("outdated").run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "All dependencies are up to date."
+  );
 }
 
 fn pm_context_builder() -> TestContextBuilder {
   TestContextBuilder::new()
     .use_http_server()
     .envs(env_vars_for_jsr_tests())
+    .add_npm_env_vars()
     .use_temp_cwd()
 }
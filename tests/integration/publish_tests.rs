@@ -1,6 +1,8 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
 use test_util::assert_contains;
 use test_util::assert_not_contains;
 use test_util::env_vars_for_jsr_npm_tests;
@@ -165,6 +167,48 @@ itest!(successful {
   http_server: true,
 });
 
+itest!(missing_scope_permission {
+  args: "publish --token 'sadfasdf'",
+  output: "publish/missing_scope_permission.out",
+  cwd: Some("publish/missing_scope_permission"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+  exit_code: 1,
+});
+
+itest!(create_missing_scope_and_package {
+  args: "publish --token 'sadfasdf' --create",
+  output: "publish/create_missing.out",
+  cwd: Some("publish/create_missing"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(wait_for_available {
+  args: "publish --token 'sadfasdf' --wait-for-available",
+  output: "publish/wait_for_available.out",
+  cwd: Some("publish/wait_for_available"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(missing_publish_permission {
+  args: "publish --token 'sadfasdf'",
+  output: "publish/missing_publish_permission.out",
+  cwd: Some("publish/missing_publish_permission"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+  exit_code: 1,
+});
+
+itest!(auth_info {
+  args: "publish --auth-info --token 'sadfasdf'",
+  output: "publish/auth_info.out",
+  cwd: Some("publish/successful"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
 itest!(provenance {
   args: "publish",
   output: "publish/successful_provenance.out",
@@ -430,6 +474,714 @@ fn includes_dotenv() {
   assert_not_contains!(output, ".env");
 }
 
+#[test]
+fn json_module_imported_with_attributes() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./main.ts",
+  }));
+  temp_dir.join("main.ts").write(
+    "import data from \"./data.json\" with { type: \"json\" };\nexport const value: number = data.value;\n",
+  );
+  temp_dir
+    .join("data.json")
+    .write_json(&json!({ "value": 1 }));
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "data.json");
+
+  // excluding the module would break the published package
+  temp_dir.join(".gitignore").write("data.json\n");
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "error[excluded-module]");
+}
+
+#[test]
+fn dry_run_without_reachable_registry() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": {
+      // nothing listens on this port
+      "scopes": { "@foo": "http://127.0.0.1:1/" },
+    },
+  }));
+  temp_dir
+    .join("mod.ts")
+    .write("export const value: number = 1;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Warning Aborting due to --dry-run"
+  );
+}
+
+#[test]
+fn loaders_for_framework_files() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./main.ts",
+    "imports": { "lib": "./lib.ts" },
+  }));
+  temp_dir.join("main.ts").write(
+    "import \"./style.css\";\nexport { size } from \"./Button.svelte\";\n",
+  );
+  temp_dir
+    .join("lib.ts")
+    .write("export const size: number = 1;\n");
+  temp_dir.join("Button.svelte").write(
+    "<script context=\"module\" lang=\"ts\">\nexport { size } from \"lib\";\n</script>\n\n<button>{size}</button>\n",
+  );
+  temp_dir.join("style.css").write("button { color: red; }\n");
+
+  // the files can't be analyzed without a loader
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --no-check")
+    .run();
+  output.assert_exit_code(1);
+
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./main.ts",
+    "imports": { "lib": "./lib.ts" },
+    "publish": {
+      "loaders": { ".svelte": "script", ".css": "asset" },
+    },
+  }));
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --no-check")
+    .run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_contains!(output, "Button.svelte");
+  assert_contains!(output, "style.css");
+  assert_contains!(output, "lib.ts");
+}
+
+#[test]
+fn transpile_jsx() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./button.tsx",
+  }));
+  temp_dir.join("button.tsx").write(
+    "import { label } from \"./label.tsx\";\nexport function Button(): string {\n  return <button>{label}</button>;\n}\n",
+  );
+  temp_dir
+    .join("label.tsx")
+    .write("export const label: string = \"Click\";\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --no-check")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "unsupported-jsx-tsx");
+
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./button.tsx",
+    "publish": { "transpileJsx": true },
+  }));
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --no-check")
+    .run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_not_contains!(output, "unsupported-jsx-tsx");
+  assert_contains!(output, "button.tsx");
+  assert_contains!(output, "label.tsx");
+}
+
+#[test]
+fn dev_imports() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "imports": { "helpers": "./testing/helpers.ts" },
+    "publish": { "devImports": ["helpers"] },
+  }));
+  temp_dir
+    .join("mod.ts")
+    .write("export const value: number = 1;\n");
+  temp_dir.join("testing").create_dir_all();
+  temp_dir
+    .join("testing/helpers.ts")
+    .write("export function check(value: number) {}\n");
+  temp_dir.join("mod_test.ts").write(
+    "import { check } from \"helpers\";\nimport { value } from \"./mod.ts\";\ncheck(value);\n",
+  );
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(0);
+  assert_not_contains!(output.combined_output(), "dev-import");
+
+  // the exported modules can't use the dev-only dependencies
+  temp_dir.join("mod.ts").write(
+    "import { check } from \"helpers\";\nexport const value: number = 1;\ncheck(value);\n",
+  );
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "error[dev-import]");
+}
+
+#[test]
+fn check_lib() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write(
+    "export function env(key: string): string | undefined {\n  return undefined;\n}\nexport const perms: Deno.PermissionStatus | undefined = undefined;\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --check-lib=worker")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Checking the public API with the Deno worker libraries..."
+  );
+
+  // the `Deno` namespace doesn't exist in Node.js
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --check-lib=node")
+    .run();
+  output.assert_exit_code(1);
+  let output = output.combined_output();
+  assert_contains!(
+    output,
+    "The public API doesn't type check with the Node.js libraries."
+  );
+  assert_contains!(output, "Deno");
+}
+
+#[test]
+fn allow_slow_types_of_symbols() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": { "allowSlowTypes": ["./mod.ts#createClient"] },
+  }));
+  temp_dir.join("mod.ts").write(
+    "export function createClient() {\n  return { url: \"\" };\n}\nexport function add(a: number, b: number): number {\n  return a + b;\n}\n",
+  );
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "@foo/bar has 1 slow type(s) that \"publish.allowSlowTypes\" allows"
+  );
+
+  // the rest of the public API is still checked
+  temp_dir.join("mod.ts").write(
+    "export function createClient() {\n  return { url: \"\" };\n}\nexport function add(a: number, b: number) {\n  return a + b;\n}\n",
+  );
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run")
+    .run();
+  output.assert_exit_code(1);
+  let output = output.combined_output();
+  assert_contains!(output, "error[missing-explicit-return-type]");
+  assert_contains!(output, "Found 1 problem");
+}
+
+#[test]
+fn show_fast_check() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir
+    .join("mod.ts")
+    .write("export * from \"./add.ts\";\n");
+  temp_dir.join("add.ts").write(
+    "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --show-fast-check")
+    .run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_contains!(output, "Public API of @foo/bar@1.0.0");
+  assert_contains!(output, "// ./mod.ts (export)");
+  assert_contains!(output, "// ./add.ts");
+  assert_contains!(output, "export function add(a: number, b: number): number");
+  assert_not_contains!(output, "return a + b;");
+}
+
+#[test]
+fn writes_sbom() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "imports": {
+      "@denotest/add": "jsr:@denotest/add@1"
+    }
+  }));
+  temp_dir
+    .join("mod.ts")
+    .write("import { add } from '@denotest/add'; console.log(add(1, 2));");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --dry-run --sbom=sboms")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "SBOM written to");
+
+  let sbom = temp_dir
+    .join("sboms/foo_bar_1.0.0.cdx.json")
+    .read_json_value();
+  assert_eq!(sbom["bomFormat"], "CycloneDX");
+  assert_eq!(
+    sbom["metadata"]["component"]["purl"],
+    "pkg:jsr/@foo/bar@1.0.0"
+  );
+  let components = sbom["components"].as_array().unwrap();
+  assert_eq!(components.len(), 1);
+  assert_eq!(components[0]["purl"], "pkg:jsr/@denotest/add@1.0.0");
+  assert_eq!(components[0]["hashes"][0]["alg"], "SHA-256");
+  assert_eq!(
+    sbom["dependencies"][0],
+    json!({
+      "ref": "pkg:jsr/@foo/bar@1.0.0",
+      "dependsOn": ["pkg:jsr/@denotest/add@1.0.0"],
+    })
+  );
+}
+
+#[test]
+fn json_output_with_provenance() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .envs(env_vars_for_jsr_provenance_tests())
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write("export const a = 1;");
+
+  let output = context
+    .new_command()
+    .args("publish --json")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.stderr(), "Successfully published @foo/bar@1.0.0");
+  let json: Value = serde_json::from_str(output.stdout()).unwrap();
+  let package = &json["packages"][0];
+  assert_eq!(package["name"], "@foo/bar");
+  assert_eq!(package["version"], "1.0.0");
+  assert_eq!(package["published"], true);
+  let provenance = &package["provenance"];
+  assert_eq!(provenance["subject"]["name"], "pkg:jsr/@foo/bar@1.0.0");
+  assert!(provenance["subject"]["digest"]["sha256"].is_string());
+  assert!(provenance["logIndex"].is_u64());
+  assert_eq!(
+    provenance["bundle"],
+    format!(
+      "{}api/scopes/foo/packages/bar/versions/1.0.0/provenance",
+      test_util::jsr_registry_url()
+    )
+  );
+}
+
+#[test]
+fn workspace_member_without_provenance() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .envs(env_vars_for_jsr_provenance_tests())
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "workspaces": ["foo", "bar"],
+  }));
+  temp_dir.join("foo").create_dir_all();
+  temp_dir.join("foo/deno.json").write_json(&json!({
+    "name": "@foo/foo",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("foo/mod.ts").write("export const a = 1;");
+  temp_dir.join("bar").create_dir_all();
+  temp_dir.join("bar/deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": { "provenance": false },
+  }));
+  temp_dir.join("bar/mod.ts").write("export const b = 2;");
+
+  let output = context
+    .new_command()
+    .args("publish --json")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  let json: Value = serde_json::from_str(output.stdout()).unwrap();
+  let packages = json["packages"].as_array().unwrap();
+  assert_eq!(packages.len(), 2);
+  for package in packages {
+    let has_provenance = package["provenance"].is_object();
+    assert_eq!(has_provenance, package["name"] == "@foo/foo");
+  }
+}
+
+#[test]
+fn registry_with_private_ca() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  let registry_url = test_util::tls_jsr_registry_url();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write("export const a = 1;\n");
+  let root_ca = test_util::testdata_path().join("tls/RootCA.pem");
+
+  // the certificate of the registry isn't trusted without its CA
+  let output = context
+    .new_command()
+    .env("JSR_URL", &registry_url)
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    &format!("Failed to reach the registry {}", registry_url)
+  );
+
+  let output = context
+    .new_command()
+    .env("JSR_URL", &registry_url)
+    .env("DENO_REGISTRY_CERT", root_ca.as_path())
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.0"
+  );
+
+  temp_dir.join("certs").create_dir_all();
+  root_ca.copy(&temp_dir.join("certs/RootCA.pem"));
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": {
+      "exclude": ["certs/"],
+      "registries": {
+        registry_url.clone(): { "caFile": "./certs/RootCA.pem" },
+      },
+    },
+  }));
+  let output = context
+    .new_command()
+    .env("JSR_URL", &registry_url)
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.0"
+  );
+}
+
+#[test]
+fn registry_through_proxy() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  // the registry is only reachable through the proxy
+  let registry_url = "http://jsr.proxied.invalid/";
+  let proxy_url = test_util::jsr_registry_proxy_url();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write("export const a = 1;\n");
+
+  let output = context
+    .new_command()
+    .env("JSR_URL", registry_url)
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    &format!("Failed to reach the registry {}", registry_url)
+  );
+
+  // the proxy rejects requests without its credentials
+  let output = context
+    .new_command()
+    .env("JSR_URL", registry_url)
+    .env("HTTP_PROXY", "http://localhost:4254")
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(1);
+  assert_not_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.0"
+  );
+
+  let output = context
+    .new_command()
+    .env("JSR_URL", registry_url)
+    .env("HTTP_PROXY", &proxy_url)
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.0"
+  );
+}
+
+#[test]
+fn bump_restores_versions_on_failure() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/bar",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir
+    .join("mod.ts")
+    .write("export { a } from './missing.ts';\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --bump patch")
+    .run();
+  output.assert_exit_code(1);
+  assert_not_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.1"
+  );
+  let config = temp_dir.join("deno.json").read_json::<Value>();
+  assert_eq!(config["version"], "1.0.0");
+
+  temp_dir.join("mod.ts").write("export const a = 1;\n");
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf' --bump patch")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Successfully published @foo/bar@1.0.1"
+  );
+  let config = temp_dir.join("deno.json").read_json::<Value>();
+  assert_eq!(config["version"], "1.0.1");
+}
+
+#[test]
+fn reauthenticates_when_authorization_is_rejected() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .envs(env_vars_for_jsr_provenance_tests())
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "workspaces": ["upload", "status"],
+  }));
+  // the registry rejects the first upload of this package
+  temp_dir.join("upload").create_dir_all();
+  temp_dir.join("upload/deno.json").write_json(&json!({
+    "name": "@foo/unauthorized",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": { "provenance": false },
+  }));
+  temp_dir
+    .join("upload/mod.ts")
+    .write("export const a = 1;\n");
+  // and the first status request of the publishing task of this one
+  temp_dir.join("status").create_dir_all();
+  temp_dir.join("status/deno.json").write_json(&json!({
+    "name": "@foo/unauthorized-status",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+    "publish": { "provenance": false },
+  }));
+  temp_dir
+    .join("status/mod.ts")
+    .write("export const b = 2;\n");
+
+  let output = context.new_command().args("publish").run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_contains!(
+    output,
+    "The authorization for @foo/unauthorized@1.0.0 was rejected, it may have expired. Re-authenticating..."
+  );
+  assert_contains!(
+    output,
+    "The authorization for @foo/unauthorized-status@1.0.0 was rejected, it may have expired. Re-authenticating..."
+  );
+  assert_contains!(output, "Successfully published @foo/unauthorized@1.0.0");
+  assert_contains!(
+    output,
+    "Successfully published @foo/unauthorized-status@1.0.0"
+  );
+}
+
+#[test]
+fn verifies_published_manifest() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/verified",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write("export const value = 1;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_contains!(output, "Successfully published @foo/verified@1.0.0");
+  assert_not_contains!(output, "serves different files");
+}
+
+#[test]
+fn publishes_scope_to_another_registry() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "workspaces": ["foo", "other"],
+    "publish": {
+      "scopes": { "@other": test_util::second_jsr_registry_url() },
+    },
+  }));
+  temp_dir.join("foo").create_dir_all();
+  temp_dir.join("foo/deno.json").write_json(&json!({
+    "name": "@foo/scoped",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("foo/mod.ts").write("export const a = 1;\n");
+  temp_dir.join("other").create_dir_all();
+  temp_dir.join("other/deno.json").write_json(&json!({
+    "name": "@other/scoped",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("other/mod.ts").write("export const b = 2;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(0);
+  let output = output.combined_output();
+  assert_contains!(output, "Successfully published @foo/scoped@1.0.0");
+  assert_contains!(
+    output,
+    &format!("Publishing to {}", test_util::second_jsr_registry_url())
+  );
+  assert_contains!(output, "Successfully published @other/scoped@1.0.0");
+  // the manifest is only served by the registry it was uploaded to
+  assert_not_contains!(output, "Failed to get the version manifest");
+}
+
+#[test]
+fn published_manifest_mismatch() {
+  let context = publish_context_builder().build();
+  let temp_dir = context.temp_dir().path();
+  temp_dir.join("deno.json").write_json(&json!({
+    "name": "@foo/tampered",
+    "version": "1.0.0",
+    "exports": "./mod.ts",
+  }));
+  temp_dir.join("mod.ts").write("export const value = 1;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --token 'sadfasdf'")
+    .run();
+  output.assert_exit_code(1);
+  let output = output.combined_output();
+  assert_contains!(
+    output,
+    "@foo/tampered@1.0.0 was published, but the registry serves different files than were uploaded"
+  );
+  assert_contains!(output, "Checksum mismatch for /mod.ts");
+}
+
 fn publish_context_builder() -> TestContextBuilder {
   TestContextBuilder::new()
     .use_http_server()
@@ -0,0 +1,143 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use test_util::env_vars_for_jsr_tests;
+use test_util::itest;
+
+itest!(attach_provenance {
+  args: "registry attach-provenance attach_provenance/bundle.json @foo/bar@1.0.0 --token 'sadfasdf'",
+  output: "registry/attach_provenance.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(attach_provenance_mismatch {
+  args: "registry attach-provenance attach_provenance/bundle.json @foo/bar@2.0.0 --token 'sadfasdf'",
+  output: "registry/attach_provenance_mismatch.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+  exit_code: 1,
+});
+
+itest!(attach_provenance_attestations {
+  args: "registry attach-provenance attach_provenance/bundle.json @foo/bar@1.0.0 --attestations attach_provenance/attestations.json --token 'sadfasdf'",
+  output: "registry/attach_provenance.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(attach_provenance_attestations_mismatch {
+  args: "registry attach-provenance attach_provenance/bundle.json @foo/bar@1.0.0 --attestations attach_provenance/attestations_mismatch.json --token 'sadfasdf'",
+  output: "registry/attach_provenance_attestations_mismatch.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+  exit_code: 1,
+});
+
+itest!(info {
+  args: "registry info @foo/bar",
+  output: "registry/info.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(info_json {
+  args: "registry info @foo/bar --json",
+  output: "registry/info_json.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(search {
+  args: "registry search path",
+  output: "registry/search.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(search_json {
+  args: "registry search path --limit 1 --json",
+  output: "registry/search_json.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(search_paginated {
+  args: "registry search s --json",
+  output: "registry/search_paginated.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(ping {
+  args: "registry ping --token 'sadfasdf'",
+  output: "registry/ping.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(scopes {
+  args: "registry scopes --token 'sadfasdf'",
+  output_str: Some("@foo admin\n@member member\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(audit_log {
+  args: "registry audit-log @foo --token 'sadfasdf'",
+  output_str: Some("2024-01-02 10:00:00 package_version_publish @foo/bar by alice\n2024-01-01 09:00:00 token_create by alice\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(audit_log_package {
+  args: "registry audit-log @foo --token 'sadfasdf' --package @foo/bar --since 2024-01-02",
+  output_str: Some("2024-01-02 10:00:00 package_version_publish @foo/bar by alice\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(stats {
+  args: "registry stats @foo/bar",
+  output: "registry/stats.out",
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(status {
+  args: "registry status sdfwqer-qwer-qwerasdf",
+  output_str: Some("Publishing task sdfwqer-qwer-qwerasdf: success\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(status_rate_limited {
+  args: "registry status rate-limited",
+  output_str: Some("Warning Rate limited by the registry, retrying in 2 seconds\nPublishing task rate-limited: success\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+});
+
+itest!(status_failed {
+  args: "registry status failed-task",
+  output_str: Some("Publishing task failed-task of @foo/bar@1.0.0: failure\nerror: Publishing task failed-task failed: Failed to build the module graph (graphError)\n"),
+  cwd: Some("registry"),
+  envs: env_vars_for_jsr_tests(),
+  http_server: true,
+  exit_code: 1,
+});
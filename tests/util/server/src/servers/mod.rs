@@ -85,6 +85,9 @@ const H2_GRPC_PORT: u16 = 4246;
 const H2S_GRPC_PORT: u16 = 4247;
 const REGISTRY_SERVER_PORT: u16 = 4250;
 const PROVENANCE_MOCK_SERVER_PORT: u16 = 4251;
+const SECOND_REGISTRY_SERVER_PORT: u16 = 4252;
+const TLS_REGISTRY_SERVER_PORT: u16 = 4253;
+const REGISTRY_PROXY_SERVER_PORT: u16 = 4254;
 
 // Use the single-threaded scheduler. The hyper server is used as a point of
 // comparison for the (single-threaded!) benchmarks in cli/bench. We're not
@@ -128,6 +131,12 @@ pub async fn run_all_servers() {
   let h2_grpc_server_fut = grpc::h2_grpc_server(H2_GRPC_PORT, H2S_GRPC_PORT);
 
   let registry_server_fut = registry::registry_server(REGISTRY_SERVER_PORT);
+  let second_registry_server_fut =
+    registry::registry_server(SECOND_REGISTRY_SERVER_PORT);
+  let tls_registry_server_fut =
+    registry::registry_server_tls(TLS_REGISTRY_SERVER_PORT);
+  let registry_proxy_server_fut =
+    registry::registry_proxy_server(REGISTRY_PROXY_SERVER_PORT);
   let provenance_mock_server_fut =
     registry::provenance_mock_server(PROVENANCE_MOCK_SERVER_PORT);
 
@@ -157,6 +166,9 @@ pub async fn run_all_servers() {
       h2_only_server_fut,
       h2_grpc_server_fut,
       registry_server_fut,
+      second_registry_server_fut,
+      tls_registry_server_fut,
+      registry_proxy_server_fut,
       provenance_mock_server_fut,
     )
   }
@@ -1,14 +1,19 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::https::get_tls_listener_stream;
 use crate::testdata_path;
 
 use super::run_server;
+use super::run_server_with_acceptor;
 use super::ServerKind;
 use super::ServerOptions;
+use base64::engine::general_purpose::STANDARD;
 use base64::engine::general_purpose::STANDARD_NO_PAD;
 use base64::Engine as _;
 use bytes::Bytes;
+use futures::StreamExt;
 use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::BodyExt;
 use http_body_util::Empty;
 use http_body_util::Full;
 use hyper::body::Incoming;
@@ -17,11 +22,15 @@ use hyper::Response;
 use hyper::StatusCode;
 use once_cell::sync::Lazy;
 use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Mutex;
 
 pub async fn registry_server(port: u16) {
@@ -38,6 +47,79 @@ pub async fn registry_server(port: u16) {
   .await
 }
 
+/// The registry behind TLS with the certificate for `localhost` that is
+/// signed by `tls/RootCA.pem`, for registries with a private CA.
+pub async fn registry_server_tls(port: u16) {
+  let tls =
+    get_tls_listener_stream("registry (tls)", port, Default::default()).await;
+  run_server_with_acceptor(
+    tls.boxed_local(),
+    registry_server_handler,
+    "Registry TLS server error",
+    ServerKind::Auto,
+  )
+  .await
+}
+
+/// A proxy in front of the registry that requires the `denotest:password`
+/// credentials. It serves the registry for every proxied request, so that the
+/// registry is reachable at any host through it.
+pub async fn registry_proxy_server(port: u16) {
+  let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+  run_server(
+    ServerOptions {
+      addr,
+      error_msg: "Registry proxy server error",
+      kind: ServerKind::OnlyHttp1,
+    },
+    registry_proxy_server_handler,
+  )
+  .await
+}
+
+async fn registry_proxy_server_handler(
+  req: Request<Incoming>,
+) -> Result<Response<UnsyncBoxBody<Bytes, Infallible>>, anyhow::Error> {
+  // requests to a proxy have an absolute URI
+  if req.uri().scheme().is_none() {
+    let res = Response::builder()
+      .status(StatusCode::BAD_REQUEST)
+      .body(UnsyncBoxBody::new(Empty::new()))?;
+    return Ok(res);
+  }
+  let expected_auth = format!("Basic {}", STANDARD.encode("denotest:password"));
+  let authorized = req
+    .headers()
+    .get("proxy-authorization")
+    .is_some_and(|auth| auth.as_bytes() == expected_auth.as_bytes());
+  if !authorized {
+    let res = Response::builder()
+      .status(StatusCode::PROXY_AUTHENTICATION_REQUIRED)
+      .header("proxy-authenticate", "Basic realm=\"registry\"")
+      .body(UnsyncBoxBody::new(Empty::new()))?;
+    return Ok(res);
+  }
+  registry_server_handler(req).await
+}
+
+/// The version manifests of the published packages, by the host of the
+/// registry they were published to and `@scope/name/version`. When the same
+/// version is published again, the latest upload is served.
+static PUBLISHED_VERSIONS: Lazy<
+  Mutex<HashMap<(String, String), serde_json::Value>>,
+> = Lazy::new(Default::default);
+
+/// Every other upload of `@foo/unauthorized` and every other status request
+/// of the publishing task of `@foo/unauthorized-status` is rejected as
+/// unauthorized, so that publishing them re-authenticates and retries.
+static REJECTED_UPLOAD: AtomicBool = AtomicBool::new(false);
+static REJECTED_STATUS: AtomicBool = AtomicBool::new(false);
+
+/// The entries submitted to the mock transparency log, by log index.
+static REKOR_ENTRIES: Lazy<Mutex<HashMap<u64, Bytes>>> =
+  Lazy::new(Default::default);
+
 pub async fn provenance_mock_server(port: u16) {
   let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
@@ -92,14 +174,54 @@ async fn provenance_mock_server_handler(
 
   // Rekor
   if path.starts_with("/api/v1/log/entries") {
-    let body = serde_json::to_string_pretty(&json!({
-      "transparency_log_1": {
-        "logID": "test_log_id",
-        "logIndex": 42069,
-      }
-    }));
-    let res = Response::new(UnsyncBoxBody::new(Full::from(body.unwrap())));
-    return Ok(res);
+    if req.method() == hyper::Method::POST {
+      let entry = req.into_body().collect().await?.to_bytes();
+      let log_index = {
+        let mut entries = REKOR_ENTRIES.lock().unwrap();
+        let log_index = 42069 + entries.len() as u64;
+        entries.insert(log_index, entry);
+        log_index
+      };
+      let body = serde_json::to_string_pretty(&json!({
+        "transparency_log_1": {
+          "logID": "test_log_id",
+          "logIndex": log_index,
+        }
+      }));
+      let res = Response::new(UnsyncBoxBody::new(Full::from(body.unwrap())));
+      return Ok(res);
+    }
+
+    // the log only has the requested entry, so its inclusion proof is empty
+    let log_index = req
+      .uri()
+      .query()
+      .and_then(|query| query.strip_prefix("logIndex="))
+      .and_then(|log_index| log_index.parse::<u64>().ok());
+    let entry = log_index.and_then(|log_index| {
+      REKOR_ENTRIES.lock().unwrap().get(&log_index).cloned()
+    });
+    if let (Some(log_index), Some(entry)) = (log_index, entry) {
+      let mut leaf = vec![0];
+      leaf.extend_from_slice(&entry);
+      let body = serde_json::to_string_pretty(&json!({
+        "transparency_log_1": {
+          "body": STANDARD.encode(&entry),
+          "integratedTime": 1717200300,
+          "logIndex": log_index,
+          "verification": {
+            "inclusionProof": {
+              "logIndex": 0,
+              "rootHash": format!("{:x}", Sha256::digest(&leaf)),
+              "treeSize": 1,
+              "hashes": [],
+            },
+          },
+        }
+      }));
+      let res = Response::new(UnsyncBoxBody::new(Full::from(body.unwrap())));
+      return Ok(res);
+    }
   }
 
   let empty_body = UnsyncBoxBody::new(Empty::new());
@@ -109,16 +231,267 @@ async fn provenance_mock_server_handler(
   Ok(res)
 }
 
+fn registry_package_version(
+  scope: &str,
+  package: &str,
+  version: &str,
+  yanked: bool,
+) -> serde_json::Value {
+  json!({
+    "scope": scope,
+    "package": package,
+    "version": version,
+    "yanked": yanked,
+    "createdAt": "2024-01-01T00:00:00Z",
+  })
+}
+
 async fn registry_server_handler(
   req: Request<Incoming>,
 ) -> Result<Response<UnsyncBoxBody<Bytes, Infallible>>, anyhow::Error> {
   let path = req.uri().path();
+  // the registry servers only share the packages of the testdata directory
+  let host = req
+    .headers()
+    .get("host")
+    .and_then(|host| host.to_str().ok())
+    .unwrap_or_default()
+    .to_string();
 
   // TODO(bartlomieju): add a proper router here
-  if path.starts_with("/api/scope/") {
+  if path == "/api/user" {
+    let body = serde_json::to_string_pretty(&json!({
+      "name": "denotest",
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path == "/api/user/scopes" {
+    let body = serde_json::to_string_pretty(&json!([
+      { "scope": "member" },
+      { "scope": "foo" },
+    ]))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path.starts_with("/api/user/member/") {
+    let scope = path.trim_start_matches("/api/user/member/");
+    if scope == "nomember" {
+      let body = serde_json::to_string_pretty(&json!({
+        "code": "scopeMemberNotFound",
+        "message": "The requested scope member was not found."
+      }))
+      .unwrap();
+      let mut res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+      *res.status_mut() = StatusCode::NOT_FOUND;
+      return Ok(res);
+    }
+    let body = serde_json::to_string_pretty(&json!({
+      "scope": scope,
+      "isAdmin": scope != "member",
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path == "/api/packages" {
+    let param = |name: &str| {
+      req.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+          .find(|(key, _)| key == name)
+          .map(|(_, value)| value.to_string())
+      })
+    };
+    let query = param("query").unwrap_or_default();
+    // pages are at most 2 items long, to test following the pages
+    let limit = param("limit")
+      .and_then(|limit| limit.parse::<usize>().ok())
+      .unwrap_or(20)
+      .min(2);
+    let page = param("page")
+      .and_then(|page| page.parse::<usize>().ok())
+      .unwrap_or(1);
+    let matches = [
+      ("std", "path", "Utilities for working with file paths", 3),
+      ("std", "fs", "Helpers for working with the file system", 2),
+      ("std", "streams", "Utilities for working with streams", 1),
+      ("foo", "path_unpublished", "", 0),
+    ]
+    .into_iter()
+    .filter(|(_, name, _, _)| name.contains(&query))
+    .collect::<Vec<_>>();
+    let total = matches.len();
+    let items = matches
+      .into_iter()
+      .skip((page - 1) * limit)
+      .take(limit)
+      .map(|(scope, name, description, version_count)| {
+        json!({
+          "scope": scope,
+          "name": name,
+          "description": description,
+          "latestVersion": (version_count > 0).then_some("1.0.0"),
+          "versionCount": version_count,
+          "score": 100,
+        })
+      })
+      .collect::<Vec<_>>();
+    let body = serde_json::to_string_pretty(&json!({
+      "items": items,
+      "total": total,
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path.starts_with("/api/scope/") {
     let body = serde_json::to_string_pretty(&json!({})).unwrap();
     let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
     return Ok(res);
+  } else if (path.starts_with("/api/scopes/foo/packages/unauthorized/versions/")
+    && req.method() == "POST"
+    && !REJECTED_UPLOAD.fetch_xor(true, Ordering::SeqCst))
+    || (path == "/api/publish_status/unauthorized-status"
+      && !REJECTED_STATUS.fetch_xor(true, Ordering::SeqCst))
+  {
+    let body = serde_json::to_string_pretty(&json!({
+      "code": "invalidBearerToken",
+      "message": "The bearer token is expired."
+    }))
+    .unwrap();
+    let mut res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    *res.status_mut() = StatusCode::UNAUTHORIZED;
+    return Ok(res);
+  } else if path.starts_with("/api/scopes/foo/packages/readonly/versions/")
+    && req.method() == "POST"
+  {
+    let body = serde_json::to_string_pretty(&json!({
+      "code": "missingPermission",
+      "message": "The token does not have the required permission."
+    }))
+    .unwrap();
+    let mut res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    *res.status_mut() = StatusCode::FORBIDDEN;
+    return Ok(res);
+  } else if path.starts_with("/api/scopes/missing") && req.method() == "GET" {
+    // a scope that is created with `deno publish --create`
+    let body = serde_json::to_string_pretty(&json!({
+      "code": "scopeNotFound",
+      "message": "The requested scope was not found."
+    }))
+    .unwrap();
+    let mut res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    *res.status_mut() = StatusCode::NOT_FOUND;
+    return Ok(res);
+  } else if path == "/api/scopes" && req.method() == "POST" {
+    let body =
+      serde_json::to_string_pretty(&json!({ "scope": "missing" })).unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path == "/api/scopes/missing/packages" && req.method() == "POST" {
+    let body = serde_json::to_string_pretty(&json!({
+      "scope": "missing",
+      "name": "bar",
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if let Some(body) = path
+    .strip_prefix("/api/scopes/")
+    .filter(|_| req.method() == "GET")
+    .and_then(|path| match path.split('/').collect::<Vec<_>>()[..] {
+      [scope] => Some(json!({ "scope": scope })),
+      [scope, "packages", name] => Some(json!({
+        "scope": scope,
+        "name": name,
+        "description": "A test package",
+        "latestVersion": "1.0.0",
+        "versionCount": 2,
+        "score": 100,
+      })),
+      [scope, "packages", name, "versions"] => Some(json!([
+        registry_package_version(scope, name, "0.1.0", true),
+        registry_package_version(scope, name, "1.0.0", false),
+      ])),
+      [scope, "packages", name, "versions", version] => {
+        Some(registry_package_version(scope, name, version, false))
+      }
+      [_, "audit_log"] => Some(json!([
+        {
+          "action": "package_version_publish",
+          "user": { "name": "alice" },
+          "package": "bar",
+          "meta": { "version": "1.0.0" },
+          "createdAt": "2024-01-02T10:00:00Z",
+        },
+        {
+          "action": "token_create",
+          "user": { "name": "alice" },
+          "createdAt": "2024-01-01T09:00:00Z",
+        },
+      ])),
+      [_, "packages", _, "downloads"] => Some(json!({
+        "total": [
+          { "timeBucket": "2024-01-01T00:00:00Z", "count": 10 },
+          { "timeBucket": "2024-01-25T00:00:00Z", "count": 5 },
+        ],
+        "recentVersions": [
+          {
+            "version": "0.1.0",
+            "downloads": [
+              { "timeBucket": "2024-01-01T00:00:00Z", "count": 10 },
+            ],
+          },
+          {
+            "version": "1.0.0",
+            "downloads": [
+              { "timeBucket": "2024-01-25T00:00:00Z", "count": 5 },
+            ],
+          },
+        ],
+      })),
+      [_, "packages", _, "versions", _, "dependencies"] => Some(json!([
+        { "kind": "jsr", "name": "@std/path", "constraint": "^0.220.0", "path": "/mod.ts" },
+        { "kind": "jsr", "name": "@std/path", "constraint": "^0.220.0", "path": "/util.ts" },
+        { "kind": "npm", "name": "chalk", "constraint": "^5.0.0", "path": "/mod.ts" },
+      ])),
+      _ => None,
+    })
+  {
+    // every scope, package and version exists
+    let body = serde_json::to_string_pretty(&body).unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if let Some(key) =
+    published_version_key(path).filter(|_| req.method() == "POST")
+  {
+    // serve the manifest of the uploaded tarball, so that the published
+    // version can be verified
+    let is_gzip = req
+      .headers()
+      .get("content-encoding")
+      .is_some_and(|encoding| encoding == "gzip");
+    let config_path = req.uri().query().and_then(|query| {
+      url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "config")
+        .map(|(_, value)| value.to_string())
+    });
+    let tarball = req.into_body().collect().await?.to_bytes();
+    let mut meta =
+      version_meta_from_tarball(&tarball, is_gzip, config_path.as_deref())?;
+    // a package that the registry serves different files for
+    if key.starts_with("@foo/tampered/") {
+      meta["manifest"]["/mod.ts"]["checksum"] =
+        json!(format!("sha256-{}", get_checksum(b"tampered")));
+    }
+    // the publishing task of this package has to be polled
+    let task = if key.starts_with("@foo/unauthorized-status/") {
+      json!({ "id": "unauthorized-status", "status": "pending", "error": null })
+    } else {
+      json!({ "id": "sdfwqer-sffg-qwerasdf", "status": "success", "error": null })
+    };
+    PUBLISHED_VERSIONS.lock().unwrap().insert((host, key), meta);
+    let body = serde_json::to_string_pretty(&task).unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
   } else if path.starts_with("/api/scopes/") {
     let body = serde_json::to_string_pretty(&json!({
       "id": "sdfwqer-sffg-qwerasdf",
@@ -128,6 +501,40 @@ async fn registry_server_handler(
     .unwrap();
     let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
     return Ok(res);
+  } else if path == "/api/publish_status/rate-limited" {
+    // every other request is rate limited, so that the retry succeeds
+    static RATE_LIMITED: AtomicBool = AtomicBool::new(false);
+    if !RATE_LIMITED.fetch_xor(true, Ordering::SeqCst) {
+      let body = UnsyncBoxBody::new(Full::from("Too Many Requests"));
+      let res = Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header("retry-after", "2")
+        .body(body)?;
+      return Ok(res);
+    }
+    let body = serde_json::to_string_pretty(&json!({
+      "id": "rate-limited",
+      "status": "success",
+      "error": null,
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
+  } else if path == "/api/publish_status/failed-task" {
+    let body = serde_json::to_string_pretty(&json!({
+      "id": "failed-task",
+      "status": "failure",
+      "error": {
+        "code": "graphError",
+        "message": "Failed to build the module graph",
+      },
+      "packageScope": "foo",
+      "packageName": "bar",
+      "packageVersion": "1.0.0",
+    }))
+    .unwrap();
+    let res = Response::new(UnsyncBoxBody::new(Full::from(body)));
+    return Ok(res);
   } else if path.starts_with("/api/publish_status/") {
     let body = serde_json::to_string_pretty(&json!({
       "id": "sdfwqer-qwer-qwerasdf",
@@ -139,6 +546,15 @@ async fn registry_server_handler(
     return Ok(res);
   }
 
+  let published_meta = path.strip_suffix("_meta.json").and_then(|key| {
+    let key = (host, key[1..].to_string());
+    PUBLISHED_VERSIONS.lock().unwrap().get(&key).cloned()
+  });
+  if let Some(meta) = published_meta {
+    let body = serde_json::to_string(&meta).unwrap();
+    return Ok(Response::new(UnsyncBoxBody::new(Full::from(body))));
+  }
+
   // serve the registry package files
   let mut file_path =
     testdata_path().to_path_buf().join("jsr").join("registry");
@@ -182,6 +598,64 @@ async fn registry_server_handler(
   Ok(res)
 }
 
+/// Gets `@scope/name/version` from the URL a package version is uploaded to.
+fn published_version_key(path: &str) -> Option<String> {
+  let path = path.strip_prefix("/api/scopes/")?;
+  match path.split('/').collect::<Vec<_>>()[..] {
+    [scope, "packages", name, "versions", version] => {
+      Some(format!("@{}/{}/{}", scope, name, version))
+    }
+    _ => None,
+  }
+}
+
+/// Creates the version manifest of an uploaded tarball like the registry
+/// does, with the exports of its configuration file.
+fn version_meta_from_tarball(
+  tarball: &[u8],
+  is_gzip: bool,
+  config_path: Option<&str>,
+) -> Result<serde_json::Value, anyhow::Error> {
+  let reader: Box<dyn std::io::Read> = if is_gzip {
+    Box::new(flate2::read::GzDecoder::new(tarball))
+  } else {
+    Box::new(tarball)
+  };
+  let mut archive = tar::Archive::new(reader);
+  let mut manifest = BTreeMap::new();
+  let mut exports = json!({});
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    if !entry.header().entry_type().is_file() {
+      continue;
+    }
+    let path = entry.path()?.to_string_lossy().to_string();
+    let path = format!("/{}", path.trim_start_matches("./"));
+    let mut data = Vec::new();
+    std::io::Read::read_to_end(&mut entry, &mut data)?;
+    if Some(path.as_str()) == config_path {
+      // configuration files with comments aren't supported
+      let config = serde_json::from_slice::<serde_json::Value>(&data).ok();
+      exports = match config.as_ref().and_then(|config| config.get("exports")) {
+        Some(serde_json::Value::String(export)) => json!({ ".": export }),
+        Some(exports) => exports.clone(),
+        None => json!({}),
+      };
+    }
+    manifest.insert(
+      path,
+      json!({
+        "size": data.len(),
+        "checksum": format!("sha256-{}", get_checksum(&data)),
+      }),
+    );
+  }
+  Ok(json!({
+    "manifest": manifest,
+    "exports": exports,
+  }))
+}
+
 fn manifest_sorted(
   meta: serde_json::Value,
 ) -> BTreeMap<String, serde_json::Value> {